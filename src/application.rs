@@ -16,33 +16,56 @@
 //! The writers are there to handle the findings returned by the checkers. They
 //! can write them to standard output or in a file for example.
 
-use clap::{Parser, ValueEnum};
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
+use std::net::IpAddr;
+use std::time::Duration;
 
 use crate::checkers::angular::AngularChecker;
 use crate::checkers::angularjs::AngularJSChecker;
+use crate::checkers::aspnet::AspNetChecker;
 use crate::checkers::bootstrap::BootstrapChecker;
+use crate::checkers::caddy::CaddyChecker;
 use crate::checkers::ckeditor::CKEditorChecker;
+use crate::checkers::confluence::ConfluenceChecker;
+use crate::checkers::dirlisting::DirectoryListingChecker;
+use crate::checkers::dns::DnsChecker;
 use crate::checkers::dovecot::DovecotChecker;
 use crate::checkers::drupal::DrupalChecker;
 use crate::checkers::exim::EximChecker;
+use crate::checkers::exposure::ExposureChecker;
+use crate::checkers::external_resources::ExternalResourcesChecker;
+use crate::checkers::graphql::GraphQLChecker;
 use crate::checkers::gsap::GsapChecker;
 use crate::checkers::handlebars::HandlebarsChecker;
+use crate::checkers::haproxy::HAProxyChecker;
 use crate::checkers::highcharts::HighchartsChecker;
+use crate::checkers::hint::HintChecker;
 use crate::checkers::horde::HordeChecker;
+use crate::checkers::http2::Http2Checker;
+use crate::checkers::http_methods::HttpMethodsChecker;
 use crate::checkers::httpd::ApacheHttpdChecker;
+use crate::checkers::iis::IISChecker;
+use crate::checkers::infrastructure::InfrastructureChecker;
 use crate::checkers::jira::JiraChecker;
 use crate::checkers::jquery::JQueryChecker;
 use crate::checkers::jquerymobile::JQueryMobileChecker;
 use crate::checkers::jqueryui::JQueryUIChecker;
 use crate::checkers::knockout::KnockoutChecker;
+use crate::checkers::lighttpd::LighttpdChecker;
 use crate::checkers::lodash::LodashChecker;
+use crate::checkers::magento::MagentoChecker;
 use crate::checkers::mariadb::MariaDBChecker;
+use crate::checkers::matomo::MatomoChecker;
 //use crate::checkers::melis::MelisChecker;
+use crate::checkers::moodle::MoodleChecker;
+use crate::checkers::mssql::MSSQLChecker;
 use crate::checkers::mysql::MySQLChecker;
 use crate::checkers::nginx::NginxChecker;
+use crate::checkers::ntp::NtpChecker;
 use crate::checkers::openssh::OpenSSHChecker;
 use crate::checkers::openssl::OpenSSLChecker;
-use crate::checkers::os::OSChecker;
+use crate::checkers::os::{os_consensus, OSChecker};
 use crate::checkers::phonesystem_3cx::PhoneSystem3CXChecker;
 use crate::checkers::php::PHPChecker;
 use crate::checkers::phpmyadmin::PhpMyAdminChecker;
@@ -51,14 +74,21 @@ use crate::checkers::prestashop::PrestashopChecker;
 use crate::checkers::proftpd::ProFTPDChecker;
 use crate::checkers::pureftpd::PureFTPdChecker;
 use crate::checkers::reactjs::ReactJSChecker;
+use crate::checkers::session_cookie::SessionCookieChecker;
+use crate::checkers::snmp::SnmpChecker;
 use crate::checkers::squirrel_mail::SquirrelMailChecker;
 use crate::checkers::symfony::SymfonyChecker;
+use crate::checkers::telnet::TelnetChecker;
 use crate::checkers::tinymce::TinyMCEChecker;
+use crate::checkers::tls::TlsCertificateChecker;
 use crate::checkers::tomcat::TomcatChecker;
 use crate::checkers::twisted::TwistedChecker;
 use crate::checkers::twistedweb::TwistedWebChecker;
 use crate::checkers::typo3::Typo3Checker;
+use crate::checkers::versionfile::GenericVersionFileChecker;
+use crate::checkers::waf::WafChecker;
 use crate::checkers::wordpress::WordPressChecker;
+use crate::checkers::wordpress_api::WordPressApiChecker;
 use crate::checkers::wp_plugins::advanced_custom_fields::AdvancedCustomFieldsChecker;
 use crate::checkers::wp_plugins::akismet::AkismetChecker;
 use crate::checkers::wp_plugins::all_in_one_seo::AllInOneSEOChecker;
@@ -88,19 +118,39 @@ use crate::checkers::wp_plugins::wp_super_cache::WpSuperCacheChecker;
 use crate::checkers::wp_plugins::wpforms_lite::WpformsLiteChecker;
 use crate::checkers::wp_plugins::yoast_seo::YoastSEOChecker;
 use crate::checkers::wp_themes::divi::DiviChecker;
-use crate::checkers::{HttpChecker, TcpChecker};
-use crate::models::{reqres::UrlRequest, technology::Technology, Finding, ScanType, Writers};
-use crate::readers::http::HttpReader;
+use crate::checkers::wp_themes::generic::GenericThemeChecker;
+use crate::checkers::{HttpChecker, TcpChecker, UdpChecker};
+use crate::config::Config;
+use crate::models::{
+    reqres::{RequestBudget, UrlRequest, UrlRequestType, UrlResponse},
+    state::ScanState,
+    target::{parse_ports, Target},
+    technology::Technology,
+    Finding, ScanError, ScanMetadata, ScanResult, ScanType, Writers, SCHEMA_VERSION,
+};
+use crate::readers::export::{ExportReader, ExportedService};
+use crate::readers::http::{is_catch_all, HttpReader};
+use crate::readers::http_cache::HttpCache;
+use crate::readers::resolver::{AddressFamily, Resolver};
+use crate::readers::socks::SocksProxy;
 use crate::readers::tcp::TcpReader;
+use crate::readers::tls::TlsReader;
+use crate::readers::udp::UdpReader;
 use crate::vulnerabilities::cache_managers::{files::FileCacheManager, CacheManager};
-use crate::vulnerabilities::fetchers::{nvd::NVDFetcher, VulnFetcher};
+use crate::vulnerabilities::eol;
+use crate::vulnerabilities::fetchers::{
+    ghsa::GHSAFetcher, nvd::NVDFetcher, osv::OSVFetcher, VulnFetcher,
+};
 use crate::vulnerabilities::{CacheType, VulnSource};
+use crate::writers::cpe::CpeWriter;
 use crate::writers::csv::CsvWriter;
 use crate::writers::json::JsonWriter;
 use crate::writers::textstdout::TextStdoutWriter;
 use crate::writers::Writer;
 
-use log::{debug, error, info, trace};
+use log::{debug, error, info, trace, warn, LevelFilter};
+use reqwest::Method;
+use simple_logger::SimpleLogger;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -108,8 +158,14 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub struct Application {
     /// The list of TCP checkers available to the application.
     tcp_checkers: Vec<Box<dyn TcpChecker>>,
+    /// The list of UDP checkers available to the application.
+    udp_checkers: Vec<Box<dyn UdpChecker>>,
     /// The list of HTTP checkers available to the application.
     http_checkers: Vec<Box<dyn HttpChecker>>,
+    /// The checker of the version files given with --version-files, if any.
+    version_file_checker: Option<GenericVersionFileChecker>,
+    /// The resolver shared by the readers, to resolve each hostname once.
+    resolver: Resolver,
     /// The arguments given on the command line.
     argv: Option<Args>,
 }
@@ -130,14 +186,36 @@ impl Application {
             Box::new(DovecotChecker::new()),
             Box::new(MySQLChecker::new()),
             Box::new(MariaDBChecker::new()),
+            Box::new(MSSQLChecker::new()),
+            Box::new(TelnetChecker::new()),
+        ];
+
+        trace!("About to create udp_checkers list");
+        let udp_checkers: Vec<Box<dyn UdpChecker>> = vec![
+            Box::new(DnsChecker::new()),
+            Box::new(NtpChecker::new()),
+            Box::new(SnmpChecker::new()),
         ];
 
         trace!("About to create http_checkers list");
         let http_checkers: Vec<Box<dyn HttpChecker>> = vec![
             Box::new(OSChecker::new()),
             Box::new(ApacheHttpdChecker::new()),
+            Box::new(AspNetChecker::new()),
+            Box::new(CaddyChecker::new()),
+            Box::new(ConfluenceChecker::new()),
+            Box::new(DirectoryListingChecker::new()),
+            Box::new(GraphQLChecker::new()),
+            Box::new(HAProxyChecker::new()),
             Box::new(HordeChecker::new()),
+            Box::new(IISChecker::new()),
+            Box::new(InfrastructureChecker::new()),
+            Box::new(ExternalResourcesChecker::new()),
             Box::new(JiraChecker::new()),
+            Box::new(LighttpdChecker::new()),
+            Box::new(MagentoChecker::new()),
+            Box::new(MatomoChecker::new()),
+            Box::new(MoodleChecker::new()),
             // Disable Melis because there is a confusion between
             // Melis Platform & Melis CMS.
             //Box::new(MelisChecker::new()),
@@ -148,14 +226,20 @@ impl Application {
             Box::new(PhpMyAdminChecker::new()),
             Box::new(PleskChecker::new()),
             Box::new(SquirrelMailChecker::new()),
+            Box::new(SessionCookieChecker::new(Technology::Laravel)),
+            Box::new(SessionCookieChecker::new(Technology::CodeIgniter)),
+            Box::new(SessionCookieChecker::new(Technology::Express)),
+            Box::new(SessionCookieChecker::new(Technology::JavaServlet)),
             Box::new(SymfonyChecker::new()),
             Box::new(TomcatChecker::new()),
             Box::new(TwistedChecker::new()),
             Box::new(TwistedWebChecker::new()),
+            Box::new(WafChecker::new()),
             Box::new(DrupalChecker::new()),
             Box::new(PrestashopChecker::new()),
             Box::new(Typo3Checker::new()),
             Box::new(WordPressChecker::new()),
+            Box::new(WordPressApiChecker::new()),
             Box::new(AdvancedCustomFieldsChecker::new()),
             Box::new(AkismetChecker::new()),
             Box::new(AllInOneWpMigrationChecker::new()),
@@ -168,6 +252,7 @@ impl Application {
             Box::new(EmailSubscribersChecker::new()),
             Box::new(ForminatorChecker::new()),
             Box::new(DiviChecker::new()),
+            Box::new(GenericThemeChecker::new()),
             Box::new(GTranslateChecker::new()),
             Box::new(HealthCheckChecker::new()),
             Box::new(JetpackChecker::new()),
@@ -204,19 +289,100 @@ impl Application {
         trace!("Returning the Application");
         Application {
             tcp_checkers,
+            udp_checkers,
             http_checkers,
+            version_file_checker: None,
+            resolver: Resolver::new(None),
+            argv: None,
+        }
+    }
+
+    /// Creates an application without the built-in checkers, only the
+    /// registered ones are used.
+    pub fn without_checkers() -> Self {
+        Application {
+            tcp_checkers: Vec::new(),
+            udp_checkers: Vec::new(),
+            http_checkers: Vec::new(),
+            version_file_checker: None,
+            resolver: Resolver::new(None),
             argv: None,
         }
     }
 
+    /// Registers a TCP checker, used along with the other ones.
+    /// It's only used if its technology is selected.
+    pub fn register_tcp_checker(&mut self, checker: Box<dyn TcpChecker>) {
+        debug!("Registering the TCP checker {:?}", checker.get_technology());
+        self.tcp_checkers.push(checker);
+    }
+
+    /// Registers a UDP checker, used along with the other ones.
+    /// It's only used if its technology is selected.
+    pub fn register_udp_checker(&mut self, checker: Box<dyn UdpChecker>) {
+        debug!("Registering the UDP checker {:?}", checker.get_technology());
+        self.udp_checkers.push(checker);
+    }
+
+    /// Registers a HTTP checker, used along with the other ones.
+    /// It's only used if its technology is selected, the requests sent
+    /// are the ones of the technology.
+    pub fn register_http_checker(&mut self, checker: Box<dyn HttpChecker>) {
+        debug!(
+            "Registering the HTTP checker {:?}",
+            checker.get_technology()
+        );
+        self.http_checkers.push(checker);
+    }
+
     /// Read argv to get the arguments before running the application
     pub fn read_argv(&mut self) {
         trace!("In Application::read_argv()");
-        let mut args = Args::parse();
+        // The matches are kept to know which arguments have been given on
+        // the command line, they take precedence over the config file
+        let matches = Args::command().get_matches();
+        let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        if let Some(config_path) = args.config.clone() {
+            debug!("Reading the config file {}", config_path);
+            let config_result = Config::from_file(&config_path)
+                .and_then(|config| config.apply(&mut args, &matches));
+            if let Err(e) = config_result {
+                error!("Invalid config file: {}", e);
+                println!("{}", e);
+                panic!("Unable to use the config file {}.", config_path);
+            }
+        }
+        // The writer is given by the extension of the output file, unless
+        // it's set explicitly
+        let writer_given = matches.value_source("writer") == Some(ValueSource::CommandLine)
+            || args.writer != Writers::TextStdout;
+        if let Err(e) = args.infer_writer(writer_given) {
+            error!("Invalid writer: {}", e);
+            println!("{}", e);
+            panic!("Unable to choose the writer.");
+        }
+        self.set_args(args);
+    }
+
+    /// Checks the arguments and prepares the application to use them
+    /// It's used to build the arguments without the command line, e.g. with
+    /// Args::parse_from(), when sanca is used as a library
+    pub fn set_args(&mut self, mut args: Args) {
+        trace!("In Application::set_args()");
         // For a TCP or UDP scan these two arguments are required
         // TODO: manage this with clap
-        if (args.scan_type == ScanType::Tcp || args.scan_type == ScanType::Udp)
+        if args.import.is_some() && args.scan_type == ScanType::Udp {
+            error!("Invalid parameters, the UDP services can't be imported");
+            println!("Invalid parameters provided. Use sanca --help");
+            panic!("The export can only be imported by a TCP or HTTP scan.");
+        } else if args.import.is_some() {
+            debug!("Importing {:?}, no target is needed", args.import);
+            if args.technologies.is_none() {
+                args.technologies = Some(Technology::value_variants().to_vec());
+            }
+        } else if (args.scan_type == ScanType::Tcp || args.scan_type == ScanType::Udp)
             && (args.ip_hostname.is_none() || args.port.is_none())
+            && args.targets_file.is_none()
         {
             error!("Invalid parameters");
             error!(
@@ -224,17 +390,38 @@ impl Application {
                 args.scan_type, args.ip_hostname, args.port
             );
             println!("Invalid parameters provided. Use sanca --help");
-            panic!("To perform a TCP or UDP scan, the scan type, ip or hostname, and the port are required.");
-        } else if args.scan_type == ScanType::Http && args.url.is_none() {
+            panic!("To perform a TCP or UDP scan, the scan type, ip or hostname, and the port, or the targets file are required.");
+        } else if args.scan_type == ScanType::Http
+            && args.url.is_none()
+            && args.targets_file.is_none()
+        {
             error!("Invalid parameters, scan type is HTTP but no URL has been provided");
             // If a HTTP scan is asked but no URL has been provided
             println!("Invalid parameters provided. Use sanca --help");
-            panic!("To perform a HTTP scan, the url is required.");
+            panic!("To perform a HTTP scan, the url or the targets file is required.");
         } else if args.technologies.is_none() {
             debug!("No technologies specified, default to all");
             // If no technologies are provided, check for all
             args.technologies = Some(Technology::value_variants().to_vec());
         }
+        if matches!(args.vuln_source, Some(VulnSource::GHSA)) && args.github_token.is_none() {
+            error!("Invalid parameters, the GHSA requires a GitHub token");
+            println!("Invalid parameters provided. Use sanca --help");
+            panic!("To use the GHSA vuln-source, the github-token is required.");
+        }
+        if args.only_vulnerable && args.vuln_source.is_none() {
+            warn!("No vuln-source given, --only-vulnerable is ignored");
+        }
+        if let Err(e) = args.apply_exclusions() {
+            error!("Invalid exclusion: {}", e);
+            println!("{}", e);
+            panic!("Unable to exclude the technologies.");
+        }
+        if let Err(e) = args.check_proxy() {
+            error!("Invalid proxy: {}", e);
+            println!("{}", e);
+            panic!("Unable to use the proxy.");
+        }
         // Filter on technologies supporting the given type of scan
         // It's not needed to check for Exim or ProFTPd in a HTTP scan
         debug!("Filtering technologies based on scan type");
@@ -253,77 +440,625 @@ impl Application {
             "These technologies are selected: {:?}",
             args.technologies.as_ref().unwrap()
         );
+        if let Some(version_files) = &args.version_files {
+            match GenericVersionFileChecker::from_file(version_files) {
+                Ok(checker) => self.version_file_checker = Some(checker),
+                Err(e) => {
+                    error!("Invalid version files: {}", e);
+                    println!("{}", e);
+                    panic!("Unable to use the version files.");
+                }
+            }
+        }
+        if let Some(resolver) = args.resolver {
+            debug!("Using the DNS server {}", resolver);
+            self.resolver = Resolver::new(Some(resolver));
+        }
+        if args.prefer_ipv6 {
+            self.resolver = self
+                .resolver
+                .clone()
+                .with_address_family(AddressFamily::PreferIpv6);
+        } else if args.ipv4_only {
+            self.resolver = self
+                .resolver
+                .clone()
+                .with_address_family(AddressFamily::Ipv4Only);
+        }
+        match args.get_resolve_overrides() {
+            Ok(overrides) => {
+                for (hostname, ip) in overrides {
+                    self.resolver.add_override(&hostname, ip);
+                }
+            }
+            Err(e) => {
+                error!("Invalid --resolve: {}", e);
+                println!("{}", e);
+                panic!("Unable to use the forced addresses.");
+            }
+        }
+        if let Err(e) = args.get_headers() {
+            error!("Invalid header: {}", e);
+            println!("{}", e);
+            panic!("Unable to use the headers.");
+        }
+        if let Err(e) = args.resolve_targets() {
+            error!("Invalid targets: {}", e);
+            println!("{}", e);
+            panic!("Unable to read the targets.");
+        }
 
         self.argv = Some(args);
     }
 
     /// Executes a TCP or UDP scan
+    /// Returns the findings, and the errors of the failed probes.
     pub fn tcp_udp_scan(
         &self,
         ip_hostname: &str,
         port: u16,
         scan_type: ScanType,
         technologies: &[Technology],
-    ) -> Vec<Finding> {
+    ) -> (Vec<Finding>, Vec<ScanError>) {
         trace!("In Application::tcp_udp_scan()");
         let mut findings: Vec<Finding> = Vec::new();
+        let mut scan_errors: Vec<ScanError> = Vec::new();
+        let target = format!("{}:{}", ip_hostname, port);
         if scan_type == ScanType::Tcp {
             debug!("Starting a TCP scan");
-            let tcp_reader = TcpReader::new(ip_hostname, port);
-            let banner_result = tcp_reader.read(200);
-
-            if let Err(e) = banner_result {
-                error!("Unable to read the TCP banner: {:?}", e);
-                panic!("Unable to read. {:?}", e);
-            } else {
-                let banner = banner_result.unwrap();
-                info!("Here is the banner: {}", banner);
-                for tcp_checker in &self.tcp_checkers {
-                    // Use the current checker only if it supports one of the
-                    // technologies we're looking for
-                    if technologies.contains(&tcp_checker.get_technology()) {
-                        let option_finding = tcp_checker.check_tcp(&[banner.clone()]);
-                        if option_finding.is_some() {
-                            findings.push(option_finding.unwrap());
+            let mut tcp_reader =
+                TcpReader::new(ip_hostname, port).with_resolver(self.resolver.clone());
+            let proxy = self.argv.as_ref().and_then(|args| args.proxy.as_deref());
+            if let Some(proxy) = proxy.and_then(SocksProxy::from_url) {
+                debug!("Using the SOCKS proxy {:?}", proxy);
+                tcp_reader = tcp_reader.with_proxy(proxy);
+            }
+            if let Some(timeout) = self.argv.as_ref().and_then(|args| args.get_timeout()) {
+                tcp_reader = tcp_reader.with_timeout(timeout);
+            }
+            let banner_result = tcp_reader.read_raw(200);
+
+            // Some services (e.g. MSSQL) don't send a banner but answer to
+            // a probe. Failing to read the banner is a scan error, the
+            // other targets are still scanned.
+            match &banner_result {
+                Err(e) => {
+                    warn!("Unable to read the TCP banner: {:?}", e);
+                    scan_errors.push(ScanError::from_io_error(&target, e));
+                }
+                Ok(banner) => info!("Here is the banner: {}", String::from_utf8_lossy(banner)),
+            }
+
+            for tcp_checker in &self.tcp_checkers {
+                // Use the current checker only if it supports one of the
+                // technologies we're looking for
+                if !technologies.contains(&tcp_checker.get_technology()) {
+                    continue;
+                }
+                let option_finding = match (tcp_checker.get_tcp_probe(), &banner_result) {
+                    // The responses to the probes can be longer than a
+                    // banner (e.g. the replies to FEAT)
+                    (Some(probe), _) => match tcp_reader.probe(&probe, 4096) {
+                        Ok(response) => tcp_checker.check_tcp_probe_response(&response),
+                        Err(e) => {
+                            warn!(
+                                "Unable to probe for {:?}: {:?}",
+                                tcp_checker.get_technology(),
+                                e
+                            );
+                            scan_errors.push(ScanError::from_io_error(&target, &e));
+                            None
                         }
-                    }
+                    },
+                    (None, Ok(banner)) => tcp_checker.check_tcp_banner(banner),
+                    (None, Err(_)) => None,
+                };
+                if let Some(finding) = option_finding {
+                    findings.push(finding);
                 }
             }
         } else if scan_type == ScanType::Udp {
             debug!("Starting a UDP scan");
-            panic!("UDP is not supported yet.");
+            let mut udp_reader = UdpReader::new(ip_hostname, port);
+            if let Some(timeout) = self.argv.as_ref().and_then(|args| args.get_timeout()) {
+                udp_reader = udp_reader.with_timeout(timeout);
+            }
+            for udp_checker in &self.udp_checkers {
+                // Use the current checker only if it supports one of the
+                // technologies we're looking for
+                if !technologies.contains(&udp_checker.get_technology()) {
+                    continue;
+                }
+                // No response is not an error, the service may simply
+                // not be there
+                match udp_reader.read(&udp_checker.probe_payload(), 1500) {
+                    Ok(response) => {
+                        if let Some(finding) = udp_checker.check_udp(&response) {
+                            findings.push(finding);
+                        }
+                    }
+                    Err(e) => warn!(
+                        "No UDP response for {:?}: {:?}",
+                        udp_checker.get_technology(),
+                        e
+                    ),
+                }
+            }
         }
-        return findings;
+        return (findings, scan_errors);
     }
 
     /// Performs a HTTP scan on a given set of UrlRequest
+    /// Returns the findings, and the errors of the failed requests.
     fn http_scan(
         &self,
         url_requests: &[UrlRequest],
+        main_url: &str,
         technologies: &[Technology],
         user_agent: &str,
-    ) -> Vec<Finding> {
+        use_http_cache: bool,
+    ) -> (Vec<Finding>, Vec<ScanError>) {
         trace!("Performing a HTTP scan");
         let tk_runtime = tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()
             .unwrap();
 
-        let http_reader = HttpReader::new();
+        let mut http_reader = HttpReader::new().with_resolver(self.resolver.clone());
+        if use_http_cache {
+            debug!("Using the HTTP cache");
+            http_reader = http_reader.with_http_cache(HttpCache::new());
+        }
+        // The proxy has been checked by set_args()
+        if let Some(Ok(proxy)) = self
+            .argv
+            .as_ref()
+            .and_then(|args| args.proxy.as_deref())
+            .map(HttpReader::parse_proxy)
+        {
+            http_reader = http_reader.with_proxy(proxy);
+        }
+        if let Some(timeout) = self.argv.as_ref().and_then(|args| args.get_timeout()) {
+            http_reader = http_reader.with_timeout(timeout);
+        }
+        if let Some(args) = self.argv.as_ref() {
+            http_reader = http_reader
+                .with_max_redirects(args.max_redirects)
+                .with_retries(args.retries);
+        }
+        // The headers have been checked by set_args()
+        if let Some(Ok(headers)) = self.argv.as_ref().map(|args| args.get_headers()) {
+            http_reader = http_reader.with_headers(&headers);
+        }
+        // Log in first, the session is kept in the cookie jar
+        if let Some(args) = self.argv.as_ref() {
+            if let (Some(login_url), Some(login_data)) = (&args.login_url, &args.login_data) {
+                http_reader = http_reader.with_cookie_jar();
+                let login_result = tk_runtime.block_on(http_reader.login(
+                    login_url,
+                    login_data,
+                    args.login_csrf_field.as_deref(),
+                    user_agent,
+                ));
+                match login_result {
+                    Ok(status_code) if status_code >= 400 => {
+                        warn!("The login may have failed, status code {}", status_code)
+                    }
+                    Ok(_) => info!("Logged in on {}", login_url),
+                    Err(e) => error!("{}. The scan continues without session", e),
+                }
+            }
+        }
         trace!("Sending the HTTP requests...");
         // Wait for all the HTTP requests to be finished
-        let url_responses = tk_runtime.block_on(http_reader.read(&url_requests, user_agent));
+        let (mut url_responses, mut scan_errors) =
+            tk_runtime.block_on(http_reader.read(&url_requests, user_agent));
+
+        // A host answering the same response to all the paths (a
+        // catch-all or a honeypot) would give dozens of bogus findings,
+        // only its main page is checked
+        let normalized_main_url = UrlRequest::normalize_url(main_url);
+        let detect_catch_all = self.argv.as_ref().is_some_and(|args| args.detect_catch_all);
+        let catch_all = detect_catch_all && is_catch_all(&normalized_main_url, &url_responses);
+        if catch_all {
+            warn!(
+                "All the paths of {} get the same response, it may be a catch-all or a honeypot. The scan is unreliable, only the main page is checked",
+                main_url
+            );
+            url_responses.retain(|url_response| {
+                url_response.url == normalized_main_url
+                    || url_response.request_type != UrlRequestType::Default
+            });
+        }
+
+        // The missing pages answered with a success status code (soft 404)
+        // would be taken for the files probed by the checkers. Only needed
+        // if other pages than the main one are requested.
+        let aggressive = self.argv.as_ref().is_some_and(|args| args.aggressive);
+        let mut soft_404_fingerprint = None;
+        if !catch_all
+            && (aggressive
+                || url_requests
+                    .iter()
+                    .any(|url_request| url_request.url != normalized_main_url))
+        {
+            let probe = tk_runtime.block_on(http_reader.probe_soft_404(main_url, user_agent));
+            if let Some((missing_page, Some(fingerprint))) = probe {
+                let count = url_responses.len();
+                url_responses.retain(|url_response| {
+                    url_response.url == normalized_main_url
+                        || url_response.request_type != UrlRequestType::Default
+                        || !fingerprint.matches(url_response)
+                });
+                info!(
+                    "{} responses are soft 404, they are ignored",
+                    count - url_responses.len()
+                );
+                // The missing page can still reveal technologies
+                url_responses.push(missing_page);
+                soft_404_fingerprint = Some(fingerprint);
+            }
+        }
 
         trace!("HTTP requests sent");
+        let mut findings = self.check_url_responses(&url_responses, technologies);
+        if let Some(version_file_checker) = &self.version_file_checker {
+            findings.extend(version_file_checker.check_http(&url_responses));
+        }
+
+        // The hints have a low confidence, they are only given when no
+        // technology has been identified, the delivery path isn't one
+        let use_hints = self.argv.as_ref().is_some_and(|args| args.hints);
+        if use_hints
+            && findings.iter().all(|finding| {
+                matches!(
+                    finding.technology,
+                    Technology::Infrastructure
+                        | Technology::Cloudflare
+                        | Technology::Akamai
+                        | Technology::Sucuri
+                )
+            })
+        {
+            debug!("No technology identified, looking for hints");
+            findings.extend(HintChecker::new().check_http(&url_responses));
+        }
+
+        // The aggressive probes depend on the detected technologies
+        if aggressive && catch_all {
+            warn!("The scan is unreliable, the aggressive probes are not sent");
+        } else if aggressive {
+            let detected: Vec<Technology> = findings
+                .iter()
+                .map(|finding| finding.technology.clone())
+                .collect();
+            let aggressive_url_requests = Self::get_aggressive_url_requests(
+                main_url,
+                &detected,
+                url_requests,
+                self.argv.as_ref(),
+            );
+            debug!("Aggressive URL requests: {:?}", aggressive_url_requests);
+            let (mut aggressive_responses, aggressive_errors) =
+                tk_runtime.block_on(http_reader.read(&aggressive_url_requests, user_agent));
+            scan_errors.extend(aggressive_errors);
+            if let Some(fingerprint) = &soft_404_fingerprint {
+                aggressive_responses.retain(|url_response| !fingerprint.matches(url_response));
+            }
+            findings.extend(ExposureChecker::new().check_http(&aggressive_responses));
+            findings.extend(DirectoryListingChecker::new().check_http(&aggressive_responses));
+        }
+
+        // The HTTP/2 probe sends an additional request, only on demand
+        if self.argv.as_ref().is_some_and(|args| args.http2) {
+            debug!("Probing the support of HTTP/2");
+            let probe_responses: Vec<UrlResponse> = tk_runtime
+                .block_on(http_reader.probe_http2(main_url, user_agent))
+                .into_iter()
+                .collect();
+            findings.extend(Http2Checker::new().check_http(&probe_responses));
+        }
+
+        // The certificate is read with a TLS handshake, over HTTPS only
+        if technologies.contains(&Technology::TlsCertificate)
+            && main_url.to_lowercase().starts_with("https://")
+        {
+            let (tls_findings, tls_errors) = self.read_tls_certificate(main_url);
+            findings.extend(tls_findings);
+            scan_errors.extend(tls_errors);
+        }
+
+        // The OPTIONS probe sends an additional request, only on demand
+        if self.argv.as_ref().is_some_and(|args| args.http_methods) {
+            debug!("Probing the HTTP methods allowed");
+            let options_request = UrlRequest::new(main_url, false).with_method(Method::OPTIONS);
+            let (probe_responses, probe_errors) =
+                tk_runtime.block_on(http_reader.read(&[options_request], user_agent));
+            scan_errors.extend(probe_errors);
+            findings.extend(HttpMethodsChecker::new().check_http(&probe_responses));
+        }
+        (findings, scan_errors)
+    }
+
+    /// Reads the certificate of the HTTPS server of the main URL, through
+    /// the SOCKS proxy if any. It isn't read when another proxy is used,
+    /// not to bypass it.
+    fn read_tls_certificate(&self, main_url: &str) -> (Vec<Finding>, Vec<ScanError>) {
+        trace!("Reading the TLS certificate of {}", main_url);
+        let (hostname, port) = UrlRequest::new(main_url, false).get_hostname_port();
+        let target = format!("{}:{}", hostname, port);
+        let mut tcp_reader = TcpReader::new(&hostname, port).with_resolver(self.resolver.clone());
+        if let Some(proxy) = self.argv.as_ref().and_then(|args| args.proxy.as_deref()) {
+            match SocksProxy::from_url(proxy) {
+                Some(proxy) => tcp_reader = tcp_reader.with_proxy(proxy),
+                None => {
+                    warn!(
+                        "The TLS certificate of {} isn't read through the proxy {}",
+                        target, proxy
+                    );
+                    return (Vec::new(), Vec::new());
+                }
+            }
+        }
+        if let Some(timeout) = self.argv.as_ref().and_then(|args| args.get_timeout()) {
+            tcp_reader = tcp_reader.with_timeout(timeout);
+        }
+        match TlsReader::new(tcp_reader).read_certificate() {
+            Ok(certificate) => (
+                TlsCertificateChecker::new().check_certificate(&certificate, main_url),
+                Vec::new(),
+            ),
+            Err(e) => {
+                warn!("Unable to read the TLS certificate of {}: {:?}", target, e);
+                (Vec::new(), vec![ScanError::from_io_error(&target, &e)])
+            }
+        }
+    }
+
+    /// Returns the requests of a HTTP scan of the main URL: the ones of
+    /// the technologies, and the ones of the version files, within the
+    /// request budget.
+    /// With --only-main-requests, the JavaScript files and the stylesheets
+    /// are never fetched.
+    fn get_url_requests(
+        &self,
+        main_url: &str,
+        technologies: &[Technology],
+        args: &Args,
+    ) -> Vec<UrlRequest> {
+        let path_prefix = args.path_prefix.as_deref();
+        let budget = RequestBudget {
+            per_technology: args.max_requests_per_tech,
+            total: args.max_requests,
+        };
+        let mut url_requests =
+            UrlRequest::from_technologies_with_budget(main_url, technologies, path_prefix, &budget);
+        if args.only_main_requests {
+            debug!("Only the main requests are sent, no JavaScript file");
+            for url_request in url_requests.iter_mut() {
+                url_request.fetch_js = false;
+                url_request.fetch_css = false;
+            }
+        }
+        if let Some(version_file_checker) = &self.version_file_checker {
+            for mut url_request in version_file_checker.get_url_requests(main_url) {
+                if let Some(prefix) = path_prefix {
+                    url_request.add_path_prefix(prefix);
+                }
+                if url_requests
+                    .iter()
+                    .any(|existing| existing.url == url_request.url)
+                {
+                    continue;
+                }
+                let count = url_requests
+                    .iter()
+                    .filter(|existing| existing.url != UrlRequest::normalize_url(main_url))
+                    .count();
+                if !budget.allows(count) {
+                    warn!(
+                        "The request budget is reached, {} is skipped",
+                        url_request.url
+                    );
+                    continue;
+                }
+                url_requests.push(url_request);
+            }
+        }
+        url_requests
+    }
+
+    /// Returns the aggressive probes of the detected technologies that
+    /// haven't been requested yet, within the request budget left.
+    fn get_aggressive_url_requests(
+        main_url: &str,
+        detected: &[Technology],
+        url_requests: &[UrlRequest],
+        args: Option<&Args>,
+    ) -> Vec<UrlRequest> {
+        let normalized_main_url = UrlRequest::normalize_url(main_url);
+        let mut aggressive_url_requests: Vec<UrlRequest> =
+            ExposureChecker::get_url_requests(main_url, detected)
+                .into_iter()
+                .map(|mut url_request| {
+                    if let Some(prefix) = args.and_then(|args| args.path_prefix.as_deref()) {
+                        url_request.add_path_prefix(prefix);
+                    }
+                    url_request
+                })
+                .filter(|url_request| {
+                    !url_requests
+                        .iter()
+                        .any(|existing| existing.url == url_request.url)
+                })
+                .collect();
+        if let Some(max_requests) = args.and_then(|args| args.max_requests) {
+            let count = url_requests
+                .iter()
+                .filter(|url_request| url_request.url != normalized_main_url)
+                .count();
+            let left = max_requests.saturating_sub(count);
+            if aggressive_url_requests.len() > left {
+                warn!(
+                    "The request budget is reached, {} aggressive probes are skipped",
+                    aggressive_url_requests.len() - left
+                );
+                aggressive_url_requests.truncate(left);
+            }
+        }
+        aggressive_url_requests
+    }
+
+    /// Completes the findings before writing them: the best guess of the
+    /// OS, the end of life, the length of the evidences and the CVEs.
+    fn complete_findings(args: &Args, findings: Vec<Finding>) -> Vec<Finding> {
+        // Several services can give a hint about the OS, keep a single
+        // best guess
+        let mut findings = os_consensus(findings);
+
+        // Flag the versions past their end of life
+        eol::complete_findings(&mut findings);
+
+        // Override the truncation of the evidences chosen by the checkers
+        if args.full_evidence || args.evidence_length.is_some() {
+            debug!("Setting the evidence length to {:?}", args.evidence_length);
+            for finding in findings.iter_mut() {
+                finding.set_evidence_length(args.evidence_length);
+            }
+        }
+
+        // Complete the findings with their vulnerabilities
+        if let Some(vuln_source) = &args.vuln_source {
+            info!("Completing findings with CVEs");
+            let cache_manager: Option<Box<dyn CacheManager>> = match &args.vuln_cache {
+                Some(CacheType::Files) => {
+                    let source = vuln_source
+                        .to_possible_value()
+                        .expect("The vuln-source has a name");
+                    let mut file_cache_manager = FileCacheManager::new(source.get_name());
+                    if let Some(days) = args.cve_cache_ttl {
+                        debug!("The CVEs cached for {} days are downloaded again", days);
+                        file_cache_manager =
+                            file_cache_manager.with_ttl(Duration::from_secs(days * 86400));
+                    }
+                    Some(Box::new(file_cache_manager))
+                }
+                None => None,
+            };
+            match vuln_source {
+                VulnSource::NVD => {
+                    let mut nvd_fetcher = NVDFetcher::new(cache_manager);
+                    if let Some(api_key) = &args.nvd_api_key {
+                        nvd_fetcher = nvd_fetcher.with_api_key(api_key);
+                    }
+                    nvd_fetcher.complete_findings(&mut findings);
+                }
+                VulnSource::GHSA => {
+                    let mut ghsa_fetcher = GHSAFetcher::new(cache_manager);
+                    if let Some(token) = &args.github_token {
+                        ghsa_fetcher = ghsa_fetcher.with_token(token);
+                    }
+                    ghsa_fetcher.complete_findings(&mut findings);
+                }
+                VulnSource::OSV => {
+                    OSVFetcher::new(cache_manager).complete_findings(&mut findings);
+                }
+            }
+        }
+
+        let findings = match args.min_cvss {
+            Some(min_cvss) => Self::filter_by_cvss(findings, min_cvss, args.keep_unscored),
+            None => findings,
+        };
+
+        // Without vuln-source, no finding has CVEs: keep them all rather
+        // than writing an empty report
+        if args.only_vulnerable && args.vuln_source.is_some() {
+            Self::filter_vulnerable(findings)
+        } else {
+            findings
+        }
+    }
+
+    /// Keeps the findings with at least one CVE.
+    fn filter_vulnerable(findings: Vec<Finding>) -> Vec<Finding> {
+        debug!("Keeping the findings with CVEs");
+        findings
+            .into_iter()
+            .filter(|finding| !finding.vulnerabilities.is_empty())
+            .collect()
+    }
+
+    /// Keeps the findings with a CVE whose base score is at least the
+    /// given one. The findings without CVE are kept only if keep_unscored
+    /// is true.
+    fn filter_by_cvss(findings: Vec<Finding>, min_cvss: f32, keep_unscored: bool) -> Vec<Finding> {
+        debug!("Keeping the findings with a CVSS of at least {}", min_cvss);
+        findings
+            .into_iter()
+            .filter(|finding| {
+                if finding.vulnerabilities.is_empty() {
+                    return keep_unscored;
+                }
+                // The scores are compared in f32, so a 9.8 is at least 9.8
+                finding
+                    .vulnerabilities
+                    .iter()
+                    .any(|vuln| vuln.base_score as f32 >= min_cvss)
+            })
+            .collect()
+    }
+
+    /// Completes the findings and writes them at once, for --stream.
+    /// The completed findings are returned to be given to the writer at
+    /// the end of the scan too.
+    fn stream_findings(args: &Args, findings: Vec<Finding>, writer: &dyn Writer) -> Vec<Finding> {
+        let findings = Self::complete_findings(args, findings);
+        for finding in &findings {
+            if let Err(e) = writer.write_one(finding) {
+                error!("Unable to write the finding: {:?}", e);
+                panic!("Unable to write the finding. {:?}", e);
+            }
+        }
+        findings
+    }
+
+    /// Returns the technologies checked without any finding, neither of
+    /// their own nor by their checker.
+    fn get_absent_technologies(
+        technologies: &[Technology],
+        findings: &[Finding],
+    ) -> Vec<Technology> {
+        technologies
+            .iter()
+            .filter(|technology| {
+                !findings.iter().any(|finding| {
+                    finding.technology == **technology
+                        || finding.checker_technology.as_ref() == Some(*technology)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Runs the HTTP checkers of the technologies on the responses.
+    fn check_url_responses(
+        &self,
+        url_responses: &[UrlResponse],
+        technologies: &[Technology],
+    ) -> Vec<Finding> {
         trace!("Looping over all HTTP checkers");
-        let mut findings = Vec::new();
+        let mut findings: Vec<Finding> = Vec::new();
         for http_checker in &self.http_checkers {
             trace!("HTTP checker -> {:?}", http_checker.get_technology());
             // Only use the current checker if it checks for one of the
             // technologies we're looking for
             if technologies.contains(&http_checker.get_technology()) {
                 debug!("Using HTTP checker {:?}", http_checker.get_technology());
-                let found_findings = http_checker.check_http(&url_responses);
+                let mut found_findings = http_checker.check_http(url_responses);
+                for found_finding in found_findings.iter_mut() {
+                    found_finding.checker_technology = Some(http_checker.get_technology());
+                }
                 if !found_findings.is_empty() {
                     info!(
                         "HTTP checker {:?} found finding(s)",
@@ -332,8 +1067,24 @@ impl Application {
                     // Avoid storing duplicate findings
                     // That's especially for JavaScript libraries with plugins,
                     // that could be detected several times in different files.
+                    // The misconfigurations and the infrastructure have no
+                    // version: the ones of a same checker are all kept, and
+                    // they are duplicates of the ones of the other checkers
+                    // only if they have the same evidence text.
+                    let previous_count = findings.len();
                     for found_finding in found_findings {
-                        if !findings.contains(&found_finding) {
+                        let is_duplicate = if matches!(
+                            found_finding.technology,
+                            Technology::Misconfiguration | Technology::Infrastructure
+                        ) {
+                            findings[..previous_count].iter().any(|finding| {
+                                *finding == found_finding
+                                    && finding.evidence_text == found_finding.evidence_text
+                            })
+                        } else {
+                            findings.contains(&found_finding)
+                        };
+                        if !is_duplicate {
                             findings.push(found_finding);
                         }
                     }
@@ -343,84 +1094,443 @@ impl Application {
         findings
     }
 
+    /// Runs the checkers on the services of a Shodan or Censys export,
+    /// without sending anything. The TCP banners are checked by a TCP
+    /// scan, the HTTP responses by a HTTP scan.
+    /// The findings of the banners are attributed to tcp://host:port.
+    fn import_scan(
+        &self,
+        services: &[ExportedService],
+        scan_type: ScanType,
+        technologies: &[Technology],
+    ) -> Vec<Finding> {
+        trace!("In Application::import_scan()");
+        let mut findings = Vec::new();
+        for service in services {
+            match (service, scan_type) {
+                (
+                    ExportedService::Banner {
+                        ip_hostname,
+                        port,
+                        banner,
+                    },
+                    ScanType::Tcp,
+                ) => {
+                    debug!("Checking the banner of {}:{}", ip_hostname, port);
+                    let url_of_finding = format!("tcp://{}:{}", ip_hostname, port);
+                    for tcp_checker in &self.tcp_checkers {
+                        if !technologies.contains(&tcp_checker.get_technology()) {
+                            continue;
+                        }
+                        if let Some(mut finding) = tcp_checker.check_tcp_banner(banner) {
+                            finding.url_of_finding = Some(url_of_finding.clone());
+                            findings.push(finding);
+                        }
+                    }
+                }
+                (ExportedService::Http(url_response), ScanType::Http) => {
+                    debug!("Checking the HTTP response of {}", url_response.url);
+                    findings.extend(
+                        self.check_url_responses(std::slice::from_ref(url_response), technologies),
+                    );
+                }
+                _ => {}
+            }
+        }
+        findings
+    }
+
+    /// Lists the requests and probes a scan would send, without sending
+    /// them. The JavaScript files found in the responses and the
+    /// vulnerabilities can't be known in advance, they are not listed.
+    pub fn get_planned_requests(&self, args: &Args) -> Vec<String> {
+        trace!("Running Application::get_planned_requests()");
+        let technologies = args.technologies.clone().unwrap_or_default();
+        let mut lines = Vec::new();
+        if let Some(import) = &args.import {
+            lines.push(format!("No request, the export {} is imported", import));
+            return lines;
+        }
+        match args.scan_type {
+            ScanType::Tcp => {
+                for target in &args.targets {
+                    let target_technologies = target.technologies.as_ref().unwrap_or(&technologies);
+                    lines.push(format!("TCP {} read the banner", target.url));
+                    for tcp_checker in &self.tcp_checkers {
+                        if !target_technologies.contains(&tcp_checker.get_technology()) {
+                            continue;
+                        }
+                        if let Some(probe) = tcp_checker.get_tcp_probe() {
+                            lines.push(format!(
+                                "TCP {} probe for {}: {}",
+                                target.url,
+                                tcp_checker.get_technology(),
+                                escape_bytes(&probe)
+                            ));
+                        }
+                    }
+                }
+            }
+            ScanType::Udp => {
+                for target in &args.targets {
+                    let target_technologies = target.technologies.as_ref().unwrap_or(&technologies);
+                    for udp_checker in &self.udp_checkers {
+                        if target_technologies.contains(&udp_checker.get_technology()) {
+                            lines.push(format!(
+                                "UDP {} probe for {}: {}",
+                                target.url,
+                                udp_checker.get_technology(),
+                                escape_bytes(&udp_checker.probe_payload())
+                            ));
+                        }
+                    }
+                }
+            }
+            ScanType::Http => {
+                if let Some(login_url) = &args.login_url {
+                    if args.login_csrf_field.is_some() {
+                        lines.push(format!("GET {} (CSRF token)", login_url));
+                    }
+                    lines.push(format!("POST {} (login)", login_url));
+                }
+                for target in &args.targets {
+                    let target_technologies = target.technologies.as_ref().unwrap_or(&technologies);
+                    let url_requests =
+                        self.get_url_requests(&target.url, target_technologies, args);
+                    let normalized_main_url = UrlRequest::normalize_url(&target.url);
+                    let has_other_pages = url_requests
+                        .iter()
+                        .any(|url_request| url_request.url != normalized_main_url);
+                    for url_request in &url_requests {
+                        let included = match (url_request.fetch_js, url_request.fetch_css) {
+                            (true, true) => {
+                                " (and the JavaScript files and stylesheets it includes)"
+                            }
+                            (true, false) => " (and the JavaScript files it includes)",
+                            (false, true) => " (and the stylesheets it includes)",
+                            (false, false) => "",
+                        };
+                        lines.push(format!(
+                            "{} {}{}",
+                            url_request.method, url_request.url, included
+                        ));
+                    }
+                    if has_other_pages || args.aggressive {
+                        let probe = UrlRequest::from_path(&target.url, "/sanca-<random>", false);
+                        lines.push(format!("GET {} (soft 404 probe)", probe.url));
+                    }
+                    if args.aggressive {
+                        // The other probes depend on the detected technologies
+                        for url_request in Self::get_aggressive_url_requests(
+                            &target.url,
+                            &[],
+                            &url_requests,
+                            Some(args),
+                        ) {
+                            lines.push(format!("GET {} (aggressive)", url_request.url));
+                        }
+                        lines.push(
+                            "GET the backup files, administration panels and directories of the detected technologies (aggressive)".to_string(),
+                        );
+                    }
+                    if args.http2 {
+                        lines.push(format!("GET {} (HTTP/2 probe)", target.url));
+                    }
+                    if args.http_methods {
+                        lines.push(format!("OPTIONS {} (HTTP methods probe)", target.url));
+                    }
+                }
+            }
+        }
+        lines
+    }
+
     /// Prints the header of the program.
     pub fn print_header(&self) {
         println!("Sanca software v{} - https://www.sanca.io\n", VERSION);
     }
 
-    /// Runs the global application
+    /// Initializes the logger, the logs are written on STDERR to keep
+    /// STDOUT for the findings.
     /// read_argv() MUST have been called before
-    pub fn run(&self) {
-        trace!("Running Application::run()");
-
+    pub fn init_logger(&self) {
         let args = self
             .argv
             .as_ref()
             .expect("CLI arguments haven't been read.");
+        SimpleLogger::new()
+            .with_level(args.get_log_level())
+            .init()
+            .expect("Unable to initialize the logger");
+    }
 
-        if !args.hide_header && Writers::TextStdout == args.writer {
-            trace!("Showing header");
-            self.print_header();
+    /// Scans the targets of the arguments and returns the findings
+    /// No writer is used, the arguments must have been given to
+    /// Application::read_argv() or Application::set_args() before: they
+    /// also set up the resolver, the proxy and the HTTP options, so the
+    /// arguments aren't given again here.
+    /// Application::run() shares its scan, Application::scan_targets(),
+    /// since it also needs the failed requests and the stream writer.
+    pub fn scan(&self) -> Vec<Finding> {
+        trace!("Running Application::scan()");
+        let args = self
+            .argv
+            .as_ref()
+            .expect("CLI arguments haven't been read.");
+        let (findings, scan_errors) = self.scan_targets(args, None);
+        if !scan_errors.is_empty() {
+            warn!(
+                "{} requests failed, the findings can be incomplete",
+                scan_errors.len()
+            );
         }
+        findings
+    }
+
+    /// Scans the targets, or imports the export, and completes the findings
+    /// With a stream writer, the findings are written as soon as a target
+    /// has been scanned.
+    /// Returns the findings, and the errors of the failed probes.
+    fn scan_targets(
+        &self,
+        args: &Args,
+        stream_writer: Option<&dyn Writer>,
+    ) -> (Vec<Finding>, Vec<ScanError>) {
+        // Completes and writes the findings found so far, with --stream
+        let stream = |findings: Vec<Finding>| match stream_writer {
+            Some(writer) => Self::stream_findings(args, findings, writer),
+            None => findings,
+        };
 
         trace!("Checking args.scan_type");
-        let mut findings: Vec<Finding> = match args.scan_type {
-            ScanType::Tcp | ScanType::Udp => {
-                info!("Scan type is TCP or UDP");
-                let ip_hostname = &args.ip_hostname.clone().unwrap();
-                let port = args.port.clone().unwrap();
-                let scan_type = args.scan_type.clone();
-                trace!(
-                    "ip_hostname = {}, port = {}, scan_type = {:?}",
-                    ip_hostname,
-                    port,
-                    scan_type
-                );
-                self.tcp_udp_scan(
-                    &ip_hostname,
-                    port,
-                    scan_type,
-                    &args.technologies.as_ref().unwrap(),
-                )
+        let (findings, scan_errors) = match (&args.import, args.scan_type) {
+            (Some(import), scan_type) => {
+                info!("Importing the export {}", import);
+                let services = ExportReader::new(import).read().unwrap_or_else(|e| {
+                    error!("Unable to import the export: {}", e);
+                    panic!("Unable to import {}. {}", import, e);
+                });
+                let findings =
+                    self.import_scan(&services, scan_type, args.technologies.as_ref().unwrap());
+                // Nothing is sent, nothing can fail
+                (stream(findings), Vec::new())
+            }
+            (None, scan_type) => {
+                info!("Scan type is {:?}", scan_type);
+                let mut findings = Vec::new();
+                let mut scan_errors = Vec::new();
+                let mut state = args.state_file.as_ref().map(|state_file| {
+                    ScanState::from_file(state_file).unwrap_or_else(|e| {
+                        error!("Unable to resume the scan: {}", e);
+                        panic!("Unable to resume the scan. {}", e);
+                    })
+                });
+                // The findings of the targets finished by a previous run
+                // haven't been streamed yet
+                let resumed_count = state.as_ref().map_or(0, ScanState::completed_count);
+                let pending_targets: Vec<&Target> = args
+                    .targets
+                    .iter()
+                    .filter(|target| {
+                        let completed = state
+                            .as_ref()
+                            .is_some_and(|state| state.is_completed(&target.url));
+                        if completed {
+                            info!("{} has already been scanned", target.url);
+                        }
+                        !completed
+                    })
+                    .collect();
+                // The TCP and UDP targets are scanned by batches, the HTTP
+                // requests of a target are already sent concurrently
+                let batch_size = match scan_type {
+                    ScanType::Http => 1,
+                    ScanType::Tcp | ScanType::Udp => usize::from(args.concurrency),
+                };
+                for batch in pending_targets.chunks(batch_size) {
+                    let results = self.scan_batch(batch, scan_type, args);
+                    for (target, (mut target_findings, target_scan_errors)) in
+                        batch.iter().zip(results)
+                    {
+                        // Tell the targets apart in the output
+                        if args.targets_file.is_some() || args.targets.len() > 1 {
+                            for finding in target_findings.iter_mut() {
+                                finding.target = Some(target.url.clone());
+                            }
+                        }
+                        let target_findings = stream(target_findings);
+                        // The targets with scan errors are scanned again
+                        // when the scan is resumed
+                        match state.as_mut() {
+                            Some(state) if target_scan_errors.is_empty() => {
+                                if let Err(e) = state.complete(&target.url, target_findings) {
+                                    error!("Unable to save the state of the scan: {}", e);
+                                    panic!("Unable to save the state of the scan. {}", e);
+                                }
+                            }
+                            _ => findings.extend(target_findings),
+                        }
+                        scan_errors.extend(target_scan_errors);
+                    }
+                }
+                if let Some(state) = state {
+                    let (resumed_findings, new_findings) = state.into_findings(resumed_count);
+                    findings.extend(stream(resumed_findings));
+                    findings.extend(new_findings);
+                }
+                (findings, scan_errors)
             }
+        };
+
+        // The streamed findings have already been completed
+        match stream_writer {
+            Some(_) => (findings, scan_errors),
+            None => (Self::complete_findings(args, findings), scan_errors),
+        }
+    }
+
+    /// Scans the targets of the batch at the same time, one thread each.
+    /// The results are returned in the order of the targets.
+    fn scan_batch(
+        &self,
+        batch: &[&Target],
+        scan_type: ScanType,
+        args: &Args,
+    ) -> Vec<(Vec<Finding>, Vec<ScanError>)> {
+        if let [target] = batch {
+            return vec![self.scan_target(target, scan_type, args)];
+        }
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|target| scope.spawn(move || self.scan_target(target, scan_type, args)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("The scan of a target panicked."))
+                .collect()
+        })
+    }
+
+    /// Scans one target, with the technologies of its profile if any.
+    fn scan_target(
+        &self,
+        target: &Target,
+        scan_type: ScanType,
+        args: &Args,
+    ) -> (Vec<Finding>, Vec<ScanError>) {
+        // The profile of the target, if any, replaces the technologies of
+        // the scan
+        let technologies = target
+            .technologies
+            .as_ref()
+            .unwrap_or_else(|| args.technologies.as_ref().unwrap());
+        info!("Scanning {} for {:?}", target.url, technologies);
+        match scan_type {
             ScanType::Http => {
-                info!("Scan type is HTTP");
-                let url_requests = UrlRequest::from_technologies(
-                    &args.url.as_ref().unwrap(),
-                    &args.technologies.as_ref().unwrap(),
-                );
+                let url_requests = self.get_url_requests(&target.url, technologies, args);
                 debug!("URL requests: {:?}", url_requests);
                 self.http_scan(
                     &url_requests,
-                    &args.technologies.as_ref().unwrap(),
+                    &target.url,
+                    technologies,
                     &args.user_agent,
+                    args.http_cache,
                 )
             }
-        };
+            // The targets have been checked by resolve_targets()
+            ScanType::Tcp | ScanType::Udp => {
+                let (ip_hostname, port) = target.get_host_port().unwrap();
+                self.tcp_udp_scan(&ip_hostname, port, scan_type, technologies)
+            }
+        }
+    }
 
-        // Complete the findings with their vulnerabilities
-        if let Some(vuln_source) = &args.vuln_source {
-            info!("Completing findings with CVEs");
-            let cache_manager: Option<Box<dyn CacheManager>> = match &args.vuln_cache {
-                Some(CacheType::Files) => Some(Box::new(FileCacheManager::new())),
-                None => None,
-            };
-            let vuln_fetcher = match vuln_source {
-                VulnSource::NVD => NVDFetcher::new(cache_manager),
-            };
-            vuln_fetcher.complete_findings(&mut findings);
+    /// Runs the global application and writes the findings
+    /// read_argv() or set_args() MUST have been called before
+    pub fn run(&self) {
+        trace!("Running Application::run()");
+
+        let args = self
+            .argv
+            .as_ref()
+            .expect("CLI arguments haven't been read.");
+
+        if !args.hide_header && !args.quiet && Writers::TextStdout == args.writer {
+            trace!("Showing header");
+            self.print_header();
         }
 
-        info!("Scan finished, writing output");
+        if args.dry_run {
+            info!("Dry run, only listing the requests");
+            for line in self.get_planned_requests(args) {
+                println!("{}", line);
+            }
+            return;
+        }
+
+        let args_list: Vec<String> = std::env::args().collect();
+        let mut metadata = ScanMetadata::new(VERSION, &args_list);
         let writer: Box<dyn Writer> = match args.writer {
             Writers::TextStdout => Box::new(TextStdoutWriter::new(args)),
             Writers::Csv => Box::new(CsvWriter::new(args)),
             Writers::Json => Box::new(JsonWriter::new(args)),
+            Writers::Cpe => Box::new(CpeWriter::new(args)),
+        };
+        // The structured formats are written at once at the end
+        let stream_writer = if args.stream && writer.supports_streaming() {
+            Some(writer.as_ref())
+        } else {
+            None
+        };
+        if args.stream && stream_writer.is_none() {
+            warn!("The writer doesn't support streaming, the findings are written at the end");
+        }
+        let (findings, scan_errors) = self.scan_targets(args, stream_writer);
+
+        info!("Scan finished, writing output");
+        metadata.finish();
+        if !scan_errors.is_empty() {
+            warn!(
+                "{} requests failed, the findings can be incomplete",
+                scan_errors.len()
+            );
+        }
+        // The technologies of the profiles of the targets are checked too
+        let absent_technologies = args.report_absent.then(|| {
+            let mut technologies = args.technologies.clone().unwrap_or_default();
+            for target in &args.targets {
+                for technology in target.technologies.iter().flatten() {
+                    if !technologies.contains(technology) {
+                        technologies.push(technology.clone());
+                    }
+                }
+            }
+            Self::get_absent_technologies(&technologies, &findings)
+        });
+        let scan_result = ScanResult {
+            schema_version: SCHEMA_VERSION,
+            metadata,
+            findings,
+            scan_errors,
+            absent_technologies,
         };
-        writer.write(findings);
+        if let Err(e) = writer.write(scan_result) {
+            error!("Unable to write the findings: {:?}", e);
+            panic!("Unable to write the findings. {:?}", e);
+        }
     }
 }
 
+/// Escapes the bytes of a probe to print them.
+/// Example: SYST\r\n
+fn escape_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| std::ascii::escape_default(*byte).to_string())
+        .collect()
+}
+
 /// Represents the CLI arguments accepted by Sanca
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -428,33 +1538,1271 @@ pub struct Args {
     /// The URL where to send an HTTP request
     #[arg(short, long, value_name = "URL")]
     pub url: Option<String>,
+    /// A file listing the targets to scan, one per line: the URLs for a
+    /// HTTP scan, the host:port for a TCP or UDP scan. Each target can be
+    /// followed by the technologies to check for it, separated by a |
+    /// (e.g. https://shop.example.com | prestashop,php).
+    #[arg(
+        long,
+        visible_alias = "input-file",
+        value_name = "TARGETS_FILE",
+        conflicts_with_all = ["url", "ip_hostname"]
+    )]
+    pub targets_file: Option<String>,
+    /// A file recording the targets scanned, to resume an interrupted
+    /// scan of the targets file or of the ports. The targets already
+    /// scanned are skipped, their findings are read from the file. Remove
+    /// it to scan all the targets again.
+    #[arg(long, value_name = "STATE_FILE")]
+    pub state_file: Option<String>,
+    /// A Shodan or Censys host export (JSON) to check instead of scanning.
+    /// The TCP banners are checked by a TCP scan and the HTTP responses by
+    /// a HTTP scan, nothing is sent.
+    #[arg(long, value_name = "EXPORT_FILE", conflicts_with_all = ["url", "targets_file", "ip_hostname"])]
+    pub import: Option<String>,
     /// The IP or hostname to connect on
     #[arg(short, long, value_name = "IP_HOSTNAME")]
     pub ip_hostname: Option<String>,
-    /// The port to connect on
-    #[arg(short, long, value_name = "PORT")]
-    pub port: Option<u16>,
+    /// The port to connect on, or several ports and ranges separated by
+    /// commas (e.g. 21,22,80-90)
+    #[arg(short, long, value_name = "PORTS")]
+    pub port: Option<String>,
     /// The type of scan
     #[arg(short, long, value_name = "SCAN_TYPE")]
     pub scan_type: ScanType,
     /// The technologies to check
     #[arg(short, long, value_name = "TECHNOLOGIES")]
     pub technologies: Option<Vec<Technology>>,
+    /// The technologies not to check, it can be repeated. The groups
+    /// wp-plugins, wp-themes, js-libraries and os are accepted too.
+    #[arg(short('x'), long, value_name = "TECHNOLOGY_OR_GROUP")]
+    pub exclude: Option<Vec<String>>,
     /// The writer to use
     #[arg(short, long, value_name = "WRITER", default_value = "textstdout")]
     pub writer: Writers,
+    /// The file where to write the output, STDOUT is used if not given.
+    /// Without --writer, the extension .json or .csv selects the writer
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<String>,
     /// The user agent
     #[arg(short('a'), long, value_name = "USER_AGENT", default_value = "Sanca")]
     pub user_agent: String,
+    /// A path prefix to prepend to the paths requested in HTTP scans.
+    /// Useful when the application is installed in a non-standard
+    /// directory (e.g. /cms/).
+    #[arg(long, value_name = "PATH_PREFIX")]
+    pub path_prefix: Option<String>,
     /// Hide the header with the URL to the Sanca's website
     #[arg(short('e'), long)]
     pub hide_header: bool,
+    /// Increase the verbosity of the logs written on STDERR.
+    /// Can be repeated up to 3 times (-vvv)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+    /// Only output the findings: hide the header and log only the errors.
+    /// Takes precedence over --verbose
+    #[arg(short, long)]
+    pub quiet: bool,
     /// The source where download the CVEs to match the findings against.
     /// Only the technology and the version are transmitted.
     #[arg(long("vuln-source"))]
     pub vuln_source: Option<VulnSource>,
+    /// Only reports the findings with a CVE whose CVSS base score is at
+    /// least the given one (e.g. 7.0). The findings without CVE are
+    /// dropped, unless keep-unscored is given.
+    #[arg(long("min-cvss"), value_name = "SCORE", requires = "vuln_source")]
+    pub min_cvss: Option<f32>,
+    /// Keeps the findings without CVE when min-cvss is given.
+    #[arg(long("keep-unscored"), requires = "min_cvss")]
+    pub keep_unscored: bool,
+    /// Only reports the findings with at least one CVE. Ignored without
+    /// vuln-source.
+    #[arg(long)]
+    pub only_vulnerable: bool,
+    /// When no technology is identified by a HTTP scan, report the page
+    /// title, the generator and the "powered by" mentions as hints.
+    #[arg(long)]
+    pub hints: bool,
+    /// Never fetch the JavaScript files and stylesheets included by the
+    /// pages, to only get the server-side fingerprints with fewer
+    /// requests.
+    #[arg(long)]
+    pub only_main_requests: bool,
+    /// The maximum number of requests of each technology for each
+    /// target, the remaining ones are skipped.
+    #[arg(long, value_name = "N")]
+    pub max_requests_per_tech: Option<usize>,
+    /// The maximum number of requests for each target, the remaining
+    /// ones are skipped. The main URL is always requested, the JavaScript
+    /// files found in the pages are not counted.
+    #[arg(long, value_name = "N")]
+    pub max_requests: Option<usize>,
+    /// Sends intrusive probes after the scan: the backup files (.bak, ~,
+    /// .swp), the administration panels and the directory listings of the
+    /// detected technologies. They can trigger the alerts of the target,
+    /// only use it with the approval of its owner.
+    #[arg(long)]
+    pub aggressive: bool,
+    /// Checks whether all the paths get the same response (a catch-all
+    /// or a honeypot). If so, only the main page is checked to avoid
+    /// bogus findings.
+    #[arg(long)]
+    pub detect_catch_all: bool,
+    /// Probes the support of HTTP/2 by the HTTP server (h2 with ALPN,
+    /// or h2c). It sends an additional request.
+    #[arg(long)]
+    pub http2: bool,
+    /// Lists the HTTP methods allowed by the HTTP server with an OPTIONS
+    /// request, and reports the dangerous ones (e.g. TRACE, PUT, DELETE).
+    #[arg(long)]
+    pub http_methods: bool,
+    /// Cut the evidences longer than EVIDENCE_LENGTH chars in the middle,
+    /// instead of using the length chosen by each checker.
+    #[arg(long, value_name = "EVIDENCE_LENGTH", conflicts_with = "full_evidence")]
+    pub evidence_length: Option<usize>,
+    /// Never truncate the evidences, to verify them manually.
+    #[arg(long)]
+    pub full_evidence: bool,
+    /// Lists the requests and probes the scan would send, then exits
+    /// without sending anything.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// A TOML file listing version files (e.g. /VERSION, /api/version)
+    /// to request, with the regex extracting the version of each.
+    #[arg(long, value_name = "VERSION_FILES")]
+    pub version_files: Option<String>,
+    /// The URL of the login form, to log in before the HTTP scan. The
+    /// session cookies are reused by all the requests of the scan.
+    #[arg(long, value_name = "LOGIN_URL", requires = "login_data")]
+    pub login_url: Option<String>,
+    /// The URL-encoded form data posted to the login URL
+    /// (e.g. user=admin&password=secret).
+    #[arg(long, value_name = "LOGIN_DATA", requires = "login_url")]
+    pub login_data: Option<String>,
+    /// The hidden field of the login form holding a CSRF token. The login
+    /// page is fetched first to add the token to the form data.
+    #[arg(long, value_name = "FIELD", requires = "login_url")]
+    pub login_csrf_field: Option<String>,
+    /// A header sent with all the HTTP requests (e.g. "Authorization:
+    /// Bearer 0a1b2c"). Can be given several times. A User-Agent header
+    /// replaces the user-agent argument.
+    #[arg(short('H'), long, value_name = "NAME: VALUE")]
+    pub header: Vec<String>,
+    /// The cookies sent with all the HTTP requests (e.g.
+    /// "session=0a1b2c; lang=en"), to scan with an existing session.
+    #[arg(long, value_name = "COOKIE", conflicts_with = "login_url")]
+    pub cookie: Option<String>,
+    /// Cache the HTTP responses on disk, and revalidate them with
+    /// conditional requests on the next scans.
+    #[arg(long("http-cache"))]
+    pub http_cache: bool,
+    /// Writes the findings as soon as they're found (after each target),
+    /// instead of at the end of the scan. Only the text writer supports
+    /// it, the findings are then not sorted by severity.
+    #[arg(long)]
+    pub stream: bool,
+    /// Lists in the JSON output the technologies checked without any
+    /// finding, to tell them from the technologies not checked.
+    #[arg(long("report-absent"))]
+    pub report_absent: bool,
     /// The type of cache to use to store the downloaded vulnerabilities.
     /// Can be used only if vuln-source is given.
     #[arg(long("vuln-cache"))]
     pub vuln_cache: Option<CacheType>,
+    /// The number of days after which the cached vulnerabilities are
+    /// downloaded again, to get the new CVEs. They're kept forever by
+    /// default.
+    #[arg(long("cve-cache-ttl"), value_name = "DAYS", requires = "vuln_cache")]
+    pub cve_cache_ttl: Option<u64>,
+    /// The API key to use with the NVD, it raises the rate limit.
+    /// Can be used only if vuln-source is nvd.
+    #[arg(long("nvd-api-key"), value_name = "NVD_API_KEY")]
+    pub nvd_api_key: Option<String>,
+    /// The proxy to send the requests through. With socks5h://, the
+    /// hostnames are resolved by the proxy, as needed for the .onion
+    /// addresses. TCP scans only support SOCKS5 proxies, UDP scans
+    /// don't support any proxy.
+    #[arg(long, value_name = "PROXY")]
+    pub proxy: Option<String>,
+    /// The timeout of the connections and the reads, in milliseconds. By
+    /// default 1 second for the TCP banners, 2 seconds for the UDP
+    /// responses and none for the HTTP requests. 0 waits indefinitely.
+    #[arg(short('T'), long, value_name = "TIMEOUT_MS")]
+    pub timeout: Option<u64>,
+    /// The maximum number of HTTP redirections followed for each request.
+    /// Beyond, the response of the last redirection is checked.
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub max_redirects: usize,
+    /// The number of times an HTTP request is sent again when the
+    /// connection failed or timed out, with an increasing delay. The
+    /// timeout bounds the request with its retries.
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    pub retries: usize,
+    /// The maximum number of ports or TCP and UDP targets scanned at the
+    /// same time.
+    #[arg(long, value_name = "N", default_value_t = 10, value_parser = clap::value_parser!(u16).range(1..))]
+    pub concurrency: u16,
+    /// The DNS server used to resolve the hostnames, instead of the
+    /// system resolver. The hostnames are resolved once per scan either
+    /// way.
+    #[arg(long, value_name = "IP")]
+    pub resolver: Option<IpAddr>,
+    /// Connects to the given IP for the hostname, while the Host header
+    /// and the SNI are still the hostname (e.g.
+    /// www.example.com:203.0.113.7). Can be given several times. It's
+    /// ignored by the proxies resolving the hostnames (socks5h://).
+    #[arg(long, value_name = "HOSTNAME:IP")]
+    pub resolve: Vec<String>,
+    /// Tries the IPv6 addresses of the hostnames before the IPv4 ones.
+    #[arg(long, conflicts_with = "ipv4_only")]
+    pub prefer_ipv6: bool,
+    /// Connects to the IPv4 addresses of the hostnames only.
+    #[arg(long)]
+    pub ipv4_only: bool,
+    /// The GitHub token, required by the GHSA vuln-source.
+    #[arg(long("github-token"), value_name = "GITHUB_TOKEN")]
+    pub github_token: Option<String>,
+    /// A TOML file setting the default values of user-agent,
+    /// technologies, exclude, writer, output, proxy, concurrency,
+    /// nvd-api-key and github-token. The values given on the command line
+    /// take precedence.
+    #[arg(short('c'), long, value_name = "CONFIG")]
+    pub config: Option<String>,
+    /// The targets of the HTTP scan, from --url or --targets-file
+    #[arg(skip)]
+    pub targets: Vec<Target>,
+}
+
+impl Args {
+    /// Removes the excluded technologies and groups from the technologies
+    /// to check.
+    pub fn apply_exclusions(&mut self) -> Result<(), String> {
+        let excluded = self.get_excluded_technologies()?;
+        if excluded.is_empty() {
+            return Ok(());
+        }
+        debug!("Excluding the technologies {:?}", excluded);
+        if let Some(technologies) = self.technologies.as_mut() {
+            technologies.retain(|technology| !excluded.contains(technology));
+        }
+        Ok(())
+    }
+
+    /// Returns the technologies excluded with --exclude.
+    fn get_excluded_technologies(&self) -> Result<Vec<Technology>, String> {
+        let mut excluded = Vec::new();
+        for selector in self.exclude.iter().flatten() {
+            excluded.extend(Technology::from_selector(selector)?);
+        }
+        Ok(excluded)
+    }
+
+    /// Infers the writer from the extension of --output (e.g. .json or
+    /// .csv). If the writer has been given too, it must match the
+    /// extension. An unknown extension keeps the writer.
+    pub fn infer_writer(&mut self, writer_given: bool) -> Result<(), String> {
+        let output = match &self.output {
+            Some(output) => output,
+            None => return Ok(()),
+        };
+        match Writers::from_extension(output) {
+            Some(writer) if !writer_given => {
+                debug!("Using the writer {:?} from the output {}", writer, output);
+                self.writer = writer;
+                Ok(())
+            }
+            Some(writer) if writer != self.writer => Err(format!(
+                "The writer {:?} doesn't match the extension of the output {}",
+                self.writer, output
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Sets the targets of the scan, from --url, --ip-hostname and --port,
+    /// or --targets-file. The targets of the TCP and UDP scans must be
+    /// host:port. The technologies of the profiles are restricted to the
+    /// ones of the scan type, and the exclusions apply to them too.
+    pub fn resolve_targets(&mut self) -> Result<(), String> {
+        // The targets are in the export
+        if self.import.is_some() {
+            return Ok(());
+        }
+        let mut targets = match (&self.targets_file, self.scan_type) {
+            (Some(targets_file), _) => Target::from_file(targets_file)?,
+            (None, ScanType::Http) => self.url.iter().map(|url| Target::new(url, None)).collect(),
+            (None, ScanType::Tcp | ScanType::Udp) => match &self.ip_hostname {
+                Some(ip_hostname) => self
+                    .get_ports()?
+                    .into_iter()
+                    .map(|port| Target::from_host_port(ip_hostname, port))
+                    .collect(),
+                None => Vec::new(),
+            },
+        };
+        if targets.is_empty() {
+            return Err("No target to scan".to_string());
+        }
+        if self.scan_type != ScanType::Http {
+            for target in &targets {
+                target.get_host_port()?;
+            }
+        }
+        let excluded = self.get_excluded_technologies()?;
+        for target in targets.iter_mut() {
+            if let Some(technologies) = target.technologies.as_mut() {
+                technologies.retain(|technology| {
+                    technology.supports_scan(self.scan_type) && !excluded.contains(technology)
+                });
+            }
+        }
+        debug!("Targets: {:?}", targets);
+        self.targets = targets;
+        Ok(())
+    }
+
+    /// Returns the ports given with --port, none if it isn't given.
+    pub fn get_ports(&self) -> Result<Vec<u16>, String> {
+        match &self.port {
+            Some(port) => parse_ports(port),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns the port given with --port, if a single one is given.
+    pub fn get_port(&self) -> Option<u16> {
+        match self.get_ports().as_deref() {
+            Ok([port]) => Some(*port),
+            _ => None,
+        }
+    }
+
+    /// Returns the timeout given with --timeout, if any.
+    /// A zero duration means no timeout.
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout.map(Duration::from_millis)
+    }
+
+    /// Returns the addresses forced with --resolve, by hostname.
+    pub fn get_resolve_overrides(&self) -> Result<Vec<(String, IpAddr)>, String> {
+        self.resolve
+            .iter()
+            .map(|resolve| {
+                // The IPv6 addresses contain colons, the hostnames don't
+                let (hostname, ip) = resolve
+                    .split_once(':')
+                    .filter(|(hostname, _)| !hostname.is_empty())
+                    .ok_or_else(|| format!("Expected HOSTNAME:IP, got {}", resolve))?;
+                let ip = ip.trim_start_matches('[').trim_end_matches(']');
+                let ip = ip
+                    .parse::<IpAddr>()
+                    .map_err(|_| format!("Invalid IP address in {}", resolve))?;
+                Ok((hostname.to_string(), ip))
+            })
+            .collect()
+    }
+
+    /// Returns the headers given with --header and --cookie, by name.
+    pub fn get_headers(&self) -> Result<Vec<(String, String)>, String> {
+        let mut headers = self
+            .header
+            .iter()
+            .map(|header| {
+                let (name, value) = header
+                    .split_once(':')
+                    .ok_or_else(|| format!("Expected NAME: VALUE, got {}", header))?;
+                let (name, value) = (name.trim(), value.trim());
+                if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err()
+                    || reqwest::header::HeaderValue::from_str(value).is_err()
+                {
+                    return Err(format!("Invalid header {}", header));
+                }
+                Ok((name.to_string(), value.to_string()))
+            })
+            .collect::<Result<Vec<(String, String)>, String>>()?;
+        if let Some(cookie) = &self.cookie {
+            if reqwest::header::HeaderValue::from_str(cookie).is_err() {
+                return Err(format!("Invalid cookie {}", cookie));
+            }
+            headers.push(("Cookie".to_string(), cookie.to_string()));
+        }
+        Ok(headers)
+    }
+
+    /// Checks whether the proxy can be used for the scan type.
+    pub fn check_proxy(&self) -> Result<(), String> {
+        let proxy = match &self.proxy {
+            Some(proxy) => proxy,
+            None => return Ok(()),
+        };
+        let is_socks = SocksProxy::from_url(proxy).is_some();
+        match self.scan_type {
+            ScanType::Udp => Err("UDP scans can't go through a proxy".to_string()),
+            ScanType::Tcp if !is_socks => Err(format!(
+                "TCP scans only support SOCKS5 proxies (socks5:// or socks5h://), got {}",
+                proxy
+            )),
+            ScanType::Http if is_socks && !cfg!(feature = "socks") => Err(format!(
+                "Sanca has been built without SOCKS support, rebuild it with --features socks to use {}",
+                proxy
+            )),
+            ScanType::Http => HttpReader::parse_proxy(proxy).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the log level based on --verbose and --quiet.
+    /// Only the warnings and errors are logged by default, --quiet
+    /// restricts it to the errors whatever the verbosity.
+    pub fn get_log_level(&self) -> LevelFilter {
+        if self.quiet {
+            return LevelFilter::Error;
+        }
+        match self.verbose {
+            0 => LevelFilter::Warn,
+            1 => LevelFilter::Info,
+            2 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::Checker;
+    use crate::models::CVE;
+    use std::collections::HashMap;
+
+    #[test]
+    fn get_resolve_overrides_works() {
+        let args = Args::parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "--resolve",
+            "www.example.com:203.0.113.7",
+            "--resolve",
+            "staging.example.com:[2001:db8::7]",
+        ]);
+        assert_eq!(
+            Ok(vec![
+                (
+                    "www.example.com".to_string(),
+                    "203.0.113.7".parse::<IpAddr>().unwrap()
+                ),
+                (
+                    "staging.example.com".to_string(),
+                    "2001:db8::7".parse::<IpAddr>().unwrap()
+                ),
+            ]),
+            args.get_resolve_overrides()
+        );
+        let args = Args::parse_from(["sanca", "-s", "http", "--resolve", "www.example.com"]);
+        assert!(args.get_resolve_overrides().is_err());
+        let args = Args::parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "--resolve",
+            "www.example.com:example.org",
+        ]);
+        assert!(args.get_resolve_overrides().is_err());
+    }
+
+    #[test]
+    fn get_headers_works() {
+        let args = Args::parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "-H",
+            "Authorization: Bearer 0a1b2c",
+            "--header",
+            "X-Api-Key:3d4e5f",
+            "--cookie",
+            "session=6a7b8c; lang=en",
+        ]);
+        assert_eq!(
+            Ok(vec![
+                ("Authorization".to_string(), "Bearer 0a1b2c".to_string()),
+                ("X-Api-Key".to_string(), "3d4e5f".to_string()),
+                ("Cookie".to_string(), "session=6a7b8c; lang=en".to_string()),
+            ]),
+            args.get_headers()
+        );
+        let args = Args::parse_from(["sanca", "-s", "http", "-H", "Authorization"]);
+        assert!(args.get_headers().is_err());
+        let args = Args::parse_from(["sanca", "-s", "http", "-H", "Invalid name: value"]);
+        assert!(args.get_headers().is_err());
+
+        let args = Args::try_parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "--cookie",
+            "session=6a7b8c",
+            "--login-url",
+            "https://www.example.com/login",
+            "--login-data",
+            "user=admin",
+        ]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn check_proxy_works() {
+        let get_args = |command_line: &[&str]| Args::parse_from(command_line);
+
+        let args = get_args(&["sanca", "-s", "tcp", "--proxy", "socks5h://127.0.0.1:9050"]);
+        assert!(args.check_proxy().is_ok());
+        let args = get_args(&["sanca", "-s", "http", "--proxy", "socks5h://127.0.0.1:9050"]);
+        assert_eq!(cfg!(feature = "socks"), args.check_proxy().is_ok());
+        let args = get_args(&["sanca", "-s", "http", "--proxy", "http://127.0.0.1:3128"]);
+        assert!(args.check_proxy().is_ok());
+        let args = get_args(&["sanca", "-s", "http"]);
+        assert!(args.check_proxy().is_ok());
+
+        let args = get_args(&["sanca", "-s", "tcp", "--proxy", "http://127.0.0.1:3128"]);
+        assert!(args.check_proxy().is_err());
+        let args = get_args(&["sanca", "-s", "udp", "--proxy", "socks5h://127.0.0.1:9050"]);
+        assert!(args.check_proxy().is_err());
+        let args = get_args(&["sanca", "-s", "http", "--proxy", "ftp://127.0.0.1:21"]);
+        assert!(args.check_proxy().is_err());
+    }
+
+    #[test]
+    fn evidence_length_conflicts_with_full_evidence() {
+        let args = Args::try_parse_from(["sanca", "-s", "http", "--evidence-length", "20"]);
+        assert_eq!(Some(20), args.unwrap().evidence_length);
+        let args = Args::try_parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "--evidence-length",
+            "20",
+            "--full-evidence",
+        ]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn get_timeout_works() {
+        let args = Args::parse_from(["sanca", "-s", "tcp", "-T", "5000"]);
+        assert_eq!(Some(Duration::from_secs(5)), args.get_timeout());
+        let args = Args::parse_from(["sanca", "-s", "tcp", "--timeout", "0"]);
+        assert!(args.get_timeout().is_some_and(|timeout| timeout.is_zero()));
+        let args = Args::parse_from(["sanca", "-s", "tcp"]);
+        assert_eq!(None, args.get_timeout());
+    }
+
+    #[test]
+    fn prefer_ipv6_conflicts_with_ipv4_only() {
+        let args = Args::try_parse_from(["sanca", "-s", "tcp", "--prefer-ipv6"]);
+        assert!(args.unwrap().prefer_ipv6);
+        let args = Args::try_parse_from(["sanca", "-s", "tcp", "--prefer-ipv6", "--ipv4-only"]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn apply_exclusions_works() {
+        let mut args = Args::parse_from(["sanca", "-s", "http", "-x", "wp-plugins", "-x", "PHP"]);
+        args.technologies = Some(Technology::value_variants().to_vec());
+        assert!(args.apply_exclusions().is_ok());
+        let technologies = args.technologies.unwrap();
+        assert!(!technologies.contains(&Technology::PHP));
+        assert!(!technologies.contains(&Technology::WPPAkismet));
+        assert!(technologies.contains(&Technology::WordPress));
+        assert!(technologies.contains(&Technology::WPTDivi));
+
+        let mut args = Args::parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "-t",
+            "php",
+            "-t",
+            "jquery",
+            "-x",
+            "js-libraries",
+        ]);
+        assert!(args.apply_exclusions().is_ok());
+        assert_eq!(Some(vec![Technology::PHP]), args.technologies);
+
+        let mut args = Args::parse_from(["sanca", "-s", "http", "-x", "notagroup"]);
+        assert!(args.apply_exclusions().is_err());
+    }
+
+    #[test]
+    fn ports_give_one_target_each() {
+        let mut args = Args::parse_from(["sanca", "-s", "tcp", "-i", "10.0.0.1", "-p", "21,80-81"]);
+        assert!(args.resolve_targets().is_ok());
+        assert_eq!(
+            vec![
+                Target::new("10.0.0.1:21", None),
+                Target::new("10.0.0.1:80", None),
+                Target::new("10.0.0.1:81", None),
+            ],
+            args.targets
+        );
+        assert_eq!(None, args.get_port());
+        assert_eq!(10, args.concurrency);
+
+        let args = Args::parse_from(["sanca", "-s", "tcp", "-i", "10.0.0.1", "-p", "22"]);
+        assert_eq!(Some(22), args.get_port());
+
+        let mut args = Args::parse_from(["sanca", "-s", "tcp", "-i", "10.0.0.1", "-p", "90-80"]);
+        assert!(args.resolve_targets().is_err());
+        let args = Args::try_parse_from(["sanca", "-s", "tcp", "--concurrency", "0"]);
+        assert!(args.is_err());
+
+        // The ports can be resumed, as the targets of a file
+        let args = Args::try_parse_from([
+            "sanca",
+            "-s",
+            "tcp",
+            "-i",
+            "10.0.0.1",
+            "-p",
+            "80-81",
+            "--state-file",
+            "state.json",
+        ]);
+        assert!(args.is_ok_and(|args| args.state_file == Some("state.json".to_string())));
+    }
+
+    #[test]
+    fn resolve_targets_works() {
+        let mut args = Args::parse_from(["sanca", "-s", "http", "-u", "https://example.com/"]);
+        assert!(args.resolve_targets().is_ok());
+        assert_eq!(
+            vec![Target::new("https://example.com/", None)],
+            args.targets
+        );
+
+        let path = std::env::temp_dir().join(format!("sanca-targets-{}.txt", std::process::id()));
+        std::fs::write(
+            &path,
+            "https://shop.example.com | prestashop,php,openssh\nhttps://www.example.com\n",
+        )
+        .unwrap();
+        let path_str = path.to_str().unwrap();
+        let mut args = Args::parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "--targets-file",
+            path_str,
+            "-x",
+            "php",
+        ]);
+        assert!(args.resolve_targets().is_ok());
+        // OpenSSH isn't checked by HTTP scans, PHP is excluded
+        assert_eq!(
+            vec![
+                Target::new(
+                    "https://shop.example.com",
+                    Some(vec![Technology::Prestashop])
+                ),
+                Target::new("https://www.example.com", None),
+            ],
+            args.targets
+        );
+
+        let mut args = Args::parse_from(["sanca", "-s", "tcp", "--targets-file", path_str]);
+        assert!(args.resolve_targets().is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        // The TCP targets are host:port
+        std::fs::write(&path, "# SSH\n203.0.113.10:22 | openssh, php\n\n[::1]:25\n").unwrap();
+        let mut args = Args::parse_from(["sanca", "-s", "tcp", "--input-file", path_str]);
+        assert!(args.resolve_targets().is_ok());
+        assert_eq!(
+            vec![
+                Target::new("203.0.113.10:22", Some(vec![Technology::OpenSSH])),
+                Target::new("[::1]:25", None),
+            ],
+            args.targets
+        );
+        std::fs::remove_file(&path).unwrap();
+        let mut args = Args::parse_from(["sanca", "-s", "udp", "-i", "::1", "-p", "161"]);
+        assert!(args.resolve_targets().is_ok());
+        assert_eq!(vec![Target::new("[::1]:161", None)], args.targets);
+
+        let args = Args::try_parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "-u",
+            "https://example.com/",
+            "--targets-file",
+            "targets.txt",
+        ]);
+        assert!(args.is_err());
+    }
+
+    /// A checker given by a library user
+    struct ExternalChecker;
+
+    impl Checker for ExternalChecker {}
+
+    impl HttpChecker for ExternalChecker {
+        fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+            data.iter()
+                .filter(|url_response| url_response.body.contains("X-Sanca-Test"))
+                .map(|url_response| {
+                    Finding::new(
+                        Technology::Hint,
+                        None,
+                        "X-Sanca-Test",
+                        "X-Sanca-Test",
+                        Some(&url_response.url),
+                    )
+                })
+                .collect()
+        }
+
+        fn get_technology(&self) -> Technology {
+            Technology::Hint
+        }
+    }
+
+    #[test]
+    fn register_http_checker_works() {
+        let url_responses = [UrlResponse::new(
+            "https://www.example.com/",
+            HashMap::new(),
+            "<p>X-Sanca-Test</p>",
+            UrlRequestType::Default,
+            200,
+        )];
+        let mut application = Application::without_checkers();
+        assert!(application
+            .check_url_responses(&url_responses, &[Technology::Hint])
+            .is_empty());
+        application.register_http_checker(Box::new(ExternalChecker));
+        let findings = application.check_url_responses(&url_responses, &[Technology::Hint]);
+        assert_eq!(1, findings.len());
+        assert_eq!("X-Sanca-Test", findings[0].evidence);
+        // Only used if its technology is selected
+        assert!(application
+            .check_url_responses(&url_responses, &[Technology::JQuery])
+            .is_empty());
+    }
+
+    #[test]
+    fn infrastructure_findings_are_kept() {
+        let mut headers = HashMap::new();
+        headers.insert("X-cache".to_string(), "HIT".to_string());
+        let url_responses = [UrlResponse::new(
+            "https://www.example.com/",
+            headers,
+            r#"<script src="https://cdn.example.net/app.js"></script>"#,
+            UrlRequestType::Default,
+            200,
+        )];
+        // The findings of the two checkers have no version
+        let findings =
+            Application::new().check_url_responses(&url_responses, &[Technology::Infrastructure]);
+        assert_eq!(2, findings.len());
+        assert_eq!("X-cache: HIT", findings[0].evidence);
+        assert_eq!("https://cdn.example.net", findings[1].evidence);
+    }
+
+    #[test]
+    fn cloudflare_is_found_once() {
+        let mut headers = HashMap::new();
+        headers.insert("Server".to_string(), "cloudflare".to_string());
+        headers.insert("Cf-ray".to_string(), "8a1b2c3d4e5f6a7b-CDG".to_string());
+        headers.insert("Cf-cache-status".to_string(), "HIT".to_string());
+        let url_responses = [UrlResponse::new(
+            "https://www.example.com/",
+            headers,
+            "",
+            UrlRequestType::Default,
+            200,
+        )];
+        let findings = Application::new().check_url_responses(
+            &url_responses,
+            &[Technology::Infrastructure, Technology::Waf],
+        );
+        assert_eq!(1, findings.len());
+        assert_eq!(Technology::Cloudflare, findings[0].technology);
+    }
+
+    #[test]
+    fn same_library_in_several_files_is_merged() {
+        let body =
+            "/*! jQuery v3.5.1 | (c) JS Foundation and other contributors | jquery.org/license */";
+        let url_responses = [
+            UrlResponse::new(
+                "https://www.example.com/js/jquery.min.js",
+                HashMap::new(),
+                body,
+                UrlRequestType::JavaScript,
+                200,
+            ),
+            UrlResponse::new(
+                "https://www.example.com/js/vendor.js",
+                HashMap::new(),
+                body,
+                UrlRequestType::JavaScript,
+                200,
+            ),
+        ];
+        let findings =
+            Application::new().check_url_responses(&url_responses, &[Technology::JQuery]);
+        assert_eq!(1, findings.len());
+        assert_eq!(Technology::JQuery, findings[0].technology);
+        assert_eq!(Some("3.5.1".to_string()), findings[0].version);
+    }
+
+    #[test]
+    fn get_absent_technologies_works() {
+        let url_responses = [UrlResponse::new(
+            "https://www.example.com/uploads/",
+            HashMap::new(),
+            "<html><head><title>Index of /uploads</title></head><body><h1>Index of /uploads</h1><a href=\"/\">Parent Directory</a></body></html>",
+            UrlRequestType::Default,
+            200,
+        )];
+        let technologies = [
+            Technology::DirectoryListing,
+            Technology::WordPress,
+            Technology::PHP,
+        ];
+        let mut findings = Application::new().check_url_responses(&url_responses, &technologies);
+        findings.push(Finding::new(Technology::PHP, Some("8.2.7"), "", "", None));
+        // The directory listing is a misconfiguration found by its checker
+        assert_eq!(
+            vec![Technology::WordPress],
+            Application::get_absent_technologies(&technologies, &findings)
+        );
+    }
+
+    #[test]
+    fn filter_by_cvss_works() {
+        let finding = |technology: Technology, scores: &[f64]| {
+            let mut finding = Finding::new(technology, Some("1.0.0"), "", "", None);
+            for (i, score) in scores.iter().enumerate() {
+                finding.vulnerabilities.push(CVE {
+                    cve_id: format!("CVE-2024-000{}", i),
+                    base_score: *score,
+                    cvss_version: "3.1".to_string(),
+                    version_ranges: Vec::new(),
+                });
+            }
+            finding
+        };
+        let findings = || {
+            vec![
+                finding(Technology::PHP, &[9.8]),
+                finding(Technology::Httpd, &[5.3, 7.0]),
+                finding(Technology::JQuery, &[6.9]),
+                finding(Technology::Nginx, &[]),
+            ]
+        };
+        let technologies = |findings: Vec<Finding>| -> Vec<Technology> {
+            findings.into_iter().map(|f| f.technology).collect()
+        };
+
+        // A score equal to the minimum is kept
+        assert_eq!(
+            vec![Technology::PHP, Technology::Httpd],
+            technologies(Application::filter_by_cvss(findings(), 7.0, false))
+        );
+        assert_eq!(
+            vec![Technology::PHP],
+            technologies(Application::filter_by_cvss(findings(), 9.8, false))
+        );
+        assert_eq!(
+            vec![Technology::PHP, Technology::Httpd, Technology::Nginx],
+            technologies(Application::filter_by_cvss(findings(), 7.0, true))
+        );
+        assert!(Application::filter_by_cvss(findings(), 10.0, false).is_empty());
+
+        let args = Args::try_parse_from(["sanca", "-s", "http", "--min-cvss", "7.0"]);
+        assert!(args.is_err());
+        let args = Args::try_parse_from(["sanca", "-s", "http", "--keep-unscored"]);
+        assert!(args.is_err());
+        let args = Args::try_parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "--vuln-source",
+            "nvd",
+            "--min-cvss",
+            "7.0",
+            "--keep-unscored",
+        ]);
+        assert_eq!(Some(7.0), args.unwrap().min_cvss);
+    }
+
+    #[test]
+    fn filter_vulnerable_works() {
+        let findings = || {
+            let mut php = Finding::new(Technology::PHP, Some("8.1.0"), "", "", None);
+            php.vulnerabilities.push(CVE {
+                cve_id: "CVE-2024-0001".to_string(),
+                base_score: 9.8,
+                cvss_version: "3.1".to_string(),
+                version_ranges: Vec::new(),
+            });
+            vec![
+                Finding::new(Technology::Nginx, Some("1.25.3"), "", "", None),
+                php,
+                Finding::new(Technology::JQuery, None, "", "", None),
+            ]
+        };
+        let filtered = Application::filter_vulnerable(findings());
+        assert_eq!(1, filtered.len());
+        assert_eq!(Technology::PHP, filtered[0].technology);
+
+        // Without vuln-source, the findings are all kept
+        let args = Args::parse_from(["sanca", "-s", "http", "--only-vulnerable"]);
+        assert!(args.only_vulnerable);
+        assert_eq!(3, Application::complete_findings(&args, findings()).len());
+    }
+
+    #[test]
+    fn infer_writer_works() {
+        let get_args = |command_line: &[&str]| Args::parse_from(command_line);
+
+        let mut args = get_args(&["sanca", "-s", "http", "-o", "results.json"]);
+        assert!(args.infer_writer(false).is_ok());
+        assert_eq!(Writers::Json, args.writer);
+        let mut args = get_args(&["sanca", "-s", "http", "-o", "/tmp/Results.CSV"]);
+        assert!(args.infer_writer(false).is_ok());
+        assert_eq!(Writers::Csv, args.writer);
+        let mut args = get_args(&["sanca", "-s", "http", "-o", "results.txt"]);
+        assert!(args.infer_writer(false).is_ok());
+        assert_eq!(Writers::TextStdout, args.writer);
+
+        let mut args = get_args(&["sanca", "-s", "http", "-w", "json", "-o", "results.json"]);
+        assert!(args.infer_writer(true).is_ok());
+        let mut args = get_args(&["sanca", "-s", "http", "-w", "csv", "-o", "results.json"]);
+        assert!(args.infer_writer(true).is_err());
+        let mut args = get_args(&["sanca", "-s", "http", "-w", "textstdout", "-o", "out.csv"]);
+        assert!(args.infer_writer(true).is_err());
+    }
+
+    #[test]
+    fn get_planned_requests_works() {
+        let application = Application::new();
+        let mut args = Args::parse_from(["sanca", "-s", "tcp", "-i", "10.0.0.1", "-p", "21"]);
+        args.technologies = Some(vec![Technology::ProFTPD, Technology::OpenSSH]);
+        args.resolve_targets().unwrap();
+        let lines = application.get_planned_requests(&args);
+        assert_eq!(
+            vec![
+                "TCP 10.0.0.1:21 read the banner",
+                "TCP 10.0.0.1:21 probe for ProFTPD: SYST\\r\\nFEAT\\r\\nSTAT\\r\\nQUIT\\r\\n",
+                "TCP 10.0.0.1:21 probe for OpenSSH: SSH-2.0-Sanca\\r\\n",
+            ],
+            lines
+        );
+
+        let mut args = Args::parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "-u",
+            "https://example.com/",
+            "--http2",
+            "--http-methods",
+        ]);
+        args.technologies = Some(vec![Technology::JQuery]);
+        args.resolve_targets().unwrap();
+        let lines = application.get_planned_requests(&args);
+        assert_eq!(
+            vec![
+                "GET https://example.com/ (and the JavaScript files it includes)",
+                "GET https://example.com/ (HTTP/2 probe)",
+                "OPTIONS https://example.com/ (HTTP methods probe)",
+            ],
+            lines
+        );
+
+        let mut args = Args::parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "-u",
+            "https://example.com/",
+            "--login-url",
+            "https://example.com/login",
+            "--login-data",
+            "user=admin&password=secret",
+            "--login-csrf-field",
+            "_token",
+        ]);
+        args.technologies = Some(vec![Technology::JQuery]);
+        args.resolve_targets().unwrap();
+        let lines = application.get_planned_requests(&args);
+        assert_eq!(
+            vec![
+                "GET https://example.com/login (CSRF token)",
+                "POST https://example.com/login (login)",
+                "GET https://example.com/ (and the JavaScript files it includes)",
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn login_requires_its_data() {
+        let args = Args::try_parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "-u",
+            "https://example.com/",
+            "--login-url",
+            "https://example.com/login",
+        ]);
+        assert!(args.is_err());
+        let args = Args::try_parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "-u",
+            "https://example.com/",
+            "--login-csrf-field",
+            "_token",
+        ]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn import_scan_works() {
+        let application = Application::new();
+        let content = r#"{"ip_str": "203.0.113.10", "data": [
+            {"ip_str": "203.0.113.10", "port": 22, "transport": "tcp", "data": "SSH-2.0-OpenSSH_7.4\r\n"},
+            {"ip_str": "203.0.113.10", "port": 80, "transport": "tcp",
+                "data": "HTTP/1.1 200 OK\r\nServer: nginx/1.18.0\r\n\r\n", "http": {"html": ""}}
+        ]}"#;
+        let services = crate::readers::export::parse_export(content).unwrap();
+
+        let findings = application.import_scan(
+            &services,
+            ScanType::Tcp,
+            &[Technology::OpenSSH, Technology::Nginx],
+        );
+        assert_eq!(1, findings.len());
+        assert_eq!(Technology::OpenSSH, findings[0].technology);
+        assert_eq!(
+            Some("tcp://203.0.113.10:22".to_string()),
+            findings[0].url_of_finding
+        );
+
+        let findings = application.import_scan(
+            &services,
+            ScanType::Http,
+            &[Technology::OpenSSH, Technology::Nginx],
+        );
+        assert_eq!(1, findings.len());
+        assert_eq!(Technology::Nginx, findings[0].technology);
+        assert_eq!(Some("1.18.0".to_string()), findings[0].version);
+    }
+
+    #[test]
+    fn version_file_requests_are_planned() {
+        let mut application = Application::new();
+        application.version_file_checker = Some(
+            GenericVersionFileChecker::parse(
+                r#"
+                [[version_files]]
+                name = "App"
+                paths = ["/VERSION", "/"]
+                regex = '^(?P<version1>\d+\.\d+)'
+                "#,
+            )
+            .unwrap(),
+        );
+        let mut args = Args::parse_from(["sanca", "-s", "http", "-u", "https://example.com/"]);
+        args.technologies = Some(vec![Technology::JQuery]);
+        args.resolve_targets().unwrap();
+        let lines = application.get_planned_requests(&args);
+        assert_eq!(
+            vec![
+                "GET https://example.com/ (and the JavaScript files it includes)",
+                "GET https://example.com/VERSION",
+                "GET https://example.com/sanca-<random> (soft 404 probe)",
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn aggressive_requests_are_planned() {
+        let application = Application::new();
+        let mut args = Args::parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "-u",
+            "https://example.com/",
+            "--aggressive",
+            "--max-requests",
+            "3",
+        ]);
+        args.technologies = Some(vec![Technology::JQuery]);
+        args.resolve_targets().unwrap();
+        let lines = application.get_planned_requests(&args);
+        assert_eq!(
+            vec![
+                "GET https://example.com/ (and the JavaScript files it includes)",
+                "GET https://example.com/sanca-<random> (soft 404 probe)",
+                "GET https://example.com/index.php.bak (aggressive)",
+                "GET https://example.com/index.php~ (aggressive)",
+                "GET https://example.com/index.php.old (aggressive)",
+                "GET the backup files, administration panels and directories of the detected technologies (aggressive)",
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn only_main_requests_works() {
+        let application = Application::new();
+        let mut args = Args::parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "-u",
+            "https://example.com/",
+            "--only-main-requests",
+        ]);
+        args.technologies = Some(vec![Technology::JQuery, Technology::Lodash]);
+        args.resolve_targets().unwrap();
+        let lines = application.get_planned_requests(&args);
+        assert_eq!(vec!["GET https://example.com/"], lines);
+    }
+
+    #[test]
+    fn request_budget_works() {
+        let mut application = Application::new();
+        application.version_file_checker = Some(
+            GenericVersionFileChecker::parse(
+                r#"
+                [[version_files]]
+                name = "App"
+                paths = ["/VERSION"]
+                regex = '(?P<version1>\d+\.\d+)'
+                "#,
+            )
+            .unwrap(),
+        );
+        let mut args = Args::parse_from([
+            "sanca",
+            "-s",
+            "http",
+            "-u",
+            "https://example.com/",
+            "--max-requests-per-tech",
+            "1",
+            "--max-requests",
+            "2",
+        ]);
+        args.technologies = Some(vec![
+            Technology::PhpMyAdmin,
+            Technology::WordPress,
+            Technology::Typo3,
+        ]);
+        let technologies = args.technologies.clone().unwrap();
+        let urls: Vec<String> = application
+            .get_url_requests("https://example.com/", &technologies, &args)
+            .into_iter()
+            .map(|url_request| url_request.url)
+            .collect();
+        assert_eq!(
+            vec![
+                "https://example.com/",
+                "https://example.com/doc/html/index.html",
+                "https://example.com/wp-admin/install.php",
+            ],
+            urls
+        );
+    }
+
+    /// A writer keeping the versions of the findings written with --stream
+    struct StreamRecorder {
+        versions: std::cell::RefCell<Vec<Option<String>>>,
+    }
+
+    impl Writer for StreamRecorder {
+        fn new(_argv: &Args) -> Self {
+            Self {
+                versions: std::cell::RefCell::new(Vec::new()),
+            }
+        }
+
+        fn write(&self, _scan_result: ScanResult) -> Result<(), std::io::Error> {
+            Ok(())
+        }
+
+        fn supports_streaming(&self) -> bool {
+            true
+        }
+
+        fn write_one(&self, finding: &Finding) -> Result<(), std::io::Error> {
+            self.versions.borrow_mut().push(finding.version.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn resumed_scan_is_streamed_once() {
+        use std::io::Write;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let _ = stream.write_all(b"SSH-2.0-OpenSSH_9.2p1 Debian-2+deb12u2\r\n");
+            }
+        });
+        // The first target has been scanned by a previous run
+        let state_file = std::env::temp_dir()
+            .join(format!("sanca-stream-state-{}.json", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let mut state = ScanState::from_file(&state_file).unwrap();
+        let finding = Finding::new(
+            Technology::OpenSSH,
+            Some("8.4p1"),
+            "SSH-2.0-OpenSSH_8.4p1",
+            "OpenSSH 8.4p1 has been identified",
+            None,
+        );
+        state.complete("127.0.0.1:1", vec![finding]).unwrap();
+
+        let args = Args::parse_from([
+            "sanca",
+            "-s",
+            "tcp",
+            "-i",
+            "127.0.0.1",
+            "-p",
+            &format!("1,{}", port),
+            "-t",
+            "openssh",
+            "--state-file",
+            &state_file,
+        ]);
+        let mut application = Application::new();
+        application.set_args(args);
+        let writer = StreamRecorder::new(application.argv.as_ref().unwrap());
+        let (findings, scan_errors) =
+            application.scan_targets(application.argv.as_ref().unwrap(), Some(&writer));
+        std::fs::remove_file(&state_file).unwrap();
+
+        assert!(scan_errors.is_empty());
+        assert_eq!(2, findings.len());
+        assert_eq!(
+            vec![Some("9.2p1".to_string()), Some("8.4p1".to_string())],
+            *writer.versions.borrow()
+        );
+    }
 }
@@ -6,7 +6,8 @@
 use std::collections::HashMap;
 
 use super::{Checker, HttpChecker};
-use crate::models::{reqres::UrlResponse, technology::Technology, Finding};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
 use log::{info, trace};
 use regex::Regex;
 
@@ -32,7 +33,16 @@ impl<'a> AngularChecker<'a> {
         )
         .unwrap();
 
+        // Example: <app-root ng-version="17.0.5">
+        // Angular adds this attribute to the root component, it's found in
+        // the pages rendered on the server side.
+        let ng_version_regex = Regex::new(
+            r#"<[a-zA-Z][a-zA-Z0-9-]*\s[^>]*(?P<wholematch>ng-version=['"](?P<version1>\d+\.\d+\.\d+)['"])"#,
+        )
+        .unwrap();
+
         regexes.insert("http-body-source", (source_code_regex, 15, 21));
+        regexes.insert("http-body-ng-version", (ng_version_regex, 30, 30));
         Self { regexes: regexes }
     }
 
@@ -43,15 +53,23 @@ impl<'a> AngularChecker<'a> {
             url_response.url
         );
 
-        // Loop over each regex to try to detect the technology
-        for (regex_name, (regex, keep_left, keep_right)) in &self.regexes {
-            let caps_result = regex.captures(&url_response.body);
+        // The ng-version attribute is the most reliable source, but it's
+        // only relevant in the HTML pages
+        let regex_names = if url_response.request_type == UrlRequestType::Default {
+            vec!["http-body-ng-version", "http-body-source"]
+        } else {
+            vec!["http-body-source"]
+        };
+        for regex_name in regex_names {
+            let (regex, keep_left, keep_right) = self
+                .regexes
+                .get(regex_name)
+                .unwrap_or_else(|| panic!("Regex Angular/{} not found", regex_name));
             // The regex matches
-            if caps_result.is_some() {
+            if let Some(caps) = regex.captures(&url_response.body) {
                 info!("Regex Angular/{} matches", regex_name);
-                let caps = caps_result.unwrap();
                 return Some(self.extract_finding_from_captures(
-                caps,
+                    caps,
                     Some(url_response),
                     keep_left.to_owned(),
                     keep_right.to_owned(),
@@ -90,7 +108,6 @@ impl<'a> HttpChecker for AngularChecker<'a> {
 mod tests {
     use super::*;
     use crate::checkers::check_finding_fields;
-    use crate::models::reqres::UrlRequestType;
 
     #[test]
     fn source_code_matches() {
@@ -111,6 +128,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ng_version_matches() {
+        let checker = AngularChecker::new();
+        let body1 = r#"<body><app-root _nghost-ng-c123="" ng-version="17.0.5"><div>"#;
+        let url1 = "https://www.example.com/";
+        let url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "ng-version=\"17.0.5\"",
+            Technology::Angular,
+            Some("17.0.5"),
+            Some(url1),
+        );
+
+        // Only checked in the HTML pages
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/main.3f2a1c.js",
+            HashMap::new(),
+            body1,
+            UrlRequestType::JavaScript,
+            200,
+        );
+        assert!(checker.check_http_body(&url_response_invalid).is_none());
+    }
+
     #[test]
     fn source_code_doesnt_match() {
         let checker = AngularChecker::new();
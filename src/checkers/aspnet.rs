@@ -0,0 +1,315 @@
+//! The ASP.NET checker.
+//! This module contains the checker used to determine if ASP.NET is
+//! used by the asset.
+//! https://dotnet.microsoft.com/apps/aspnet
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The ASP.NET checker
+pub struct AspNetChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> AspNetChecker<'a> {
+    /// Creates the checker.
+    /// By doing so, the regex will is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: X-AspNet-Version: 4.0.30319
+        let version_header_regex =
+            Regex::new(r"^(?P<wholematch>(?P<version1>\d+\.\d+(\.\d+(\.\d+)?)?))$").unwrap();
+        // The version of ASP.NET MVC isn't the one of the framework
+        // Example: X-AspNetMvc-Version: 5.2
+        let mvc_header_regex = Regex::new(r"^(?P<wholematch>\d+\.\d+)$").unwrap();
+        // Example: X-Powered-By: ASP.NET
+        let powered_by_header_regex = Regex::new(r"(?P<wholematch>ASP\.NET)").unwrap();
+        // Example: <input type="hidden" name="__VIEWSTATE" id="__VIEWSTATE" value="/wEPDwUK..." />
+        let viewstate_body_regex =
+            Regex::new(r#"(?P<wholematch><input [^>]*name=["']__VIEWSTATE["'])"#).unwrap();
+        // Example: <form method="post" action="./Default.aspx" id="form1">
+        let aspx_body_regex = Regex::new(
+            r#"(?i)(?P<wholematch>(href|action|src)\s*=\s*["'][^"'?#\s]+\.aspx)(["'?#])"#,
+        )
+        .unwrap();
+
+        regexes.insert("http-header-version", (version_header_regex, 30, 30));
+        regexes.insert("http-header-mvc", (mvc_header_regex, 30, 30));
+        regexes.insert("http-header-powered-by", (powered_by_header_regex, 45, 45));
+        regexes.insert("http-body-viewstate", (viewstate_body_regex, 30, 30));
+        regexes.insert("http-body-aspx", (aspx_body_regex, 30, 30));
+        Self { regexes }
+    }
+
+    /// Check in the HTTP headers.
+    fn check_http_headers(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running AspNetChecker::check_http_headers() on {}",
+            url_response.url
+        );
+        // The headers are sorted by relevance, the version is preferred
+        let headers_to_check = [
+            ("X-aspnet-version", "http-header-version"),
+            ("X-aspnetmvc-version", "http-header-mvc"),
+            ("X-powered-by", "http-header-powered-by"),
+        ];
+
+        for (header_name, regex_name) in headers_to_check {
+            let header_value = match url_response.headers.get(header_name) {
+                Some(header_value) => header_value,
+                None => continue,
+            };
+            trace!("Checking header: {} / {}", header_name, header_value);
+            let (regex, keep_left, keep_right) = self
+                .regexes
+                .get(regex_name)
+                .unwrap_or_else(|| panic!("Regex AspNet/{} not found", regex_name));
+
+            // The regex matches
+            if let Some(caps) = regex.captures(header_value) {
+                info!("Regex AspNet/{} matches", regex_name);
+                return Some(self.extract_finding_from_captures(
+                    caps,
+                    Some(url_response),
+                    keep_left.to_owned(),
+                    keep_right.to_owned(),
+                    Technology::AspNet,
+                    &format!("$techno_name$$techno_version$ has been identified using the HTTP header \"{}: $evidence$\" returned at the following URL: $url_of_finding$", header_name),
+                ));
+            }
+        }
+        None
+    }
+
+    /// Check for the technology in the body
+    fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running AspNetChecker::check_http_body() on {}",
+            url_response.url
+        );
+        let regexes = [
+            (
+                "http-body-viewstate",
+                "$techno_name$$techno_version$ has been identified because the page contains the Web Forms state \"$evidence$\" at this page: $url_of_finding$",
+            ),
+            (
+                "http-body-aspx",
+                "$techno_name$$techno_version$ has been identified because the page links to an ASP.NET page \"$evidence$\" at this page: $url_of_finding$",
+            ),
+        ];
+
+        for (regex_name, template) in regexes {
+            let (regex, keep_left, keep_right) = self
+                .regexes
+                .get(regex_name)
+                .unwrap_or_else(|| panic!("Regex AspNet/{} not found", regex_name));
+            // The regex matches
+            if let Some(caps) = regex.captures(&url_response.body) {
+                info!("Regex AspNet/{} matches", regex_name);
+                return Some(self.extract_finding_from_captures(
+                    caps,
+                    Some(url_response),
+                    keep_left.to_owned(),
+                    keep_right.to_owned(),
+                    Technology::AspNet,
+                    template,
+                ));
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Checker for AspNetChecker<'a> {}
+
+impl<'a> HttpChecker for AspNetChecker<'a> {
+    /// Check if the asset is running ASP.NET.
+    /// It looks in the following HTTP headers:
+    /// - X-AspNet-Version
+    /// - X-AspNetMvc-Version
+    /// - X-Powered-By
+    ///
+    /// and in the page content for the __VIEWSTATE field and the links
+    /// to .aspx pages.
+    ///
+    /// Returns only one finding, otherwise findings would be duplicated each
+    /// time it's found.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running AspNetChecker::check_http()");
+
+        // The headers give the version, check them in all the responses
+        // before the bodies
+        let main_responses: Vec<&UrlResponse> = data
+            .iter()
+            // JavaScript files could be hosted on a different server
+            // Don't check the JavaScript files to avoid false positive,
+            // Check only the "main" requests.
+            .filter(|url_response| url_response.request_type == UrlRequestType::Default)
+            .collect();
+        for url_response in &main_responses {
+            if let Some(finding) = self.check_http_headers(url_response) {
+                return vec![finding];
+            }
+        }
+        for url_response in &main_responses {
+            if let Some(finding) = self.check_http_body(url_response) {
+                return vec![finding];
+            }
+        }
+        Vec::new()
+    }
+
+    /// This checker supports ASP.NET
+    fn get_technology(&self) -> Technology {
+        Technology::AspNet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn header_matches() {
+        let checker = AspNetChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("X-powered-by".to_string(), "ASP.NET".to_string());
+        headers1.insert("X-aspnet-version".to_string(), "4.0.30319".to_string());
+        let url1 = "https://www.example.com/Default.aspx";
+        let mut url_response_valid =
+            UrlResponse::new(url1, headers1, "the body", UrlRequestType::Default, 200);
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "4.0.30319",
+            Technology::AspNet,
+            Some("4.0.30319"),
+            Some(url1),
+        );
+
+        // The MVC version isn't the framework's one
+        let mut headers2 = HashMap::new();
+        headers2.insert("X-aspnetmvc-version".to_string(), "5.2".to_string());
+        url_response_valid.headers = headers2;
+        let finding = checker.check_http_headers(&url_response_valid).unwrap();
+        check_finding_fields(&finding, "5.2", Technology::AspNet, None, Some(url1));
+        assert!(finding.version.is_none());
+        assert!(finding.evidence_text.contains("X-aspnetmvc-version: 5.2"));
+
+        let mut headers3 = HashMap::new();
+        headers3.insert("X-powered-by".to_string(), "ASP.NET".to_string());
+        url_response_valid.headers = headers3;
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "ASP.NET",
+            Technology::AspNet,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn header_doesnt_match() {
+        let checker = AspNetChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("X-powered-by".to_string(), "PHP/8.2.1".to_string());
+        headers1.insert("Server".to_string(), "Microsoft-IIS/10.0".to_string());
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/",
+            headers1,
+            "the body",
+            UrlRequestType::Default,
+            200,
+        );
+        assert!(checker.check_http_headers(&url_response_invalid).is_none());
+    }
+
+    #[test]
+    fn source_code_matches() {
+        let checker = AspNetChecker::new();
+        let body1 = r#"<form method="post" action="./Default.aspx" id="form1">
+<div class="aspNetHidden">
+<input type="hidden" name="__VIEWSTATE" id="__VIEWSTATE" value="/wEPDwUKLTE2MTY2ODcyOWRkYt7Ft5kY4RBQ6hhgKkI1YQ==" />
+</div>"#;
+        let url1 = "https://www.example.com/";
+        let mut url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "name=\"__VIEWSTATE\"",
+            Technology::AspNet,
+            None,
+            Some(url1),
+        );
+
+        let body2 = r#"<a href="/products/list.aspx?page=2">Next</a>"#;
+        url_response_valid.body = body2.to_string();
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "href=\"/products/list.aspx",
+            Technology::AspNet,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn source_code_doesnt_match() {
+        let checker = AspNetChecker::new();
+        let body = r#"<p>Our old site used pages like list.aspx and the __VIEWSTATE field.</p><a href="/aspx-guide.html">Guide</a>"#;
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/",
+            HashMap::new(),
+            body,
+            UrlRequestType::Default,
+            200,
+        );
+        assert!(checker.check_http_body(&url_response_invalid).is_none());
+    }
+
+    #[test]
+    fn finds_match_in_url_responses() {
+        let checker = AspNetChecker::new();
+        let url1 = "https://www.example.com/";
+        let url_response_body = UrlResponse::new(
+            url1,
+            HashMap::new(),
+            r#"<input type="hidden" name="__VIEWSTATE" value="abc" />"#,
+            UrlRequestType::Default,
+            200,
+        );
+        let mut headers = HashMap::new();
+        headers.insert("X-aspnet-version".to_string(), "2.0.50727".to_string());
+        let url2 = "https://www.example.com/pageNotFoundNotFound";
+        let url_response_header =
+            UrlResponse::new(url2, headers, "Not found", UrlRequestType::Default, 404);
+        // The version is preferred, even if the body matches first
+        let findings = checker.check_http(&[url_response_body, url_response_header]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "2.0.50727",
+            Technology::AspNet,
+            Some("2.0.50727"),
+            Some(url2),
+        );
+    }
+}
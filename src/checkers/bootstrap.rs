@@ -1,6 +1,8 @@
 //! The Bootstrap checker.
 //! This module contains the checker used to determine if Bootstrap is
 //! used by the asset.
+//! The version is found in the JavaScript files, and in the banner of
+//! the stylesheets.
 //! https://getbootstrap.com
 
 use std::collections::HashMap;
@@ -177,6 +179,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn css_banner_matches() {
+        let checker = BootstrapChecker::new();
+        let body = "@charset \"UTF-8\";/*!\n * Bootstrap  v5.3.0 (https://getbootstrap.com/)\n * Copyright 2011-2023 The Bootstrap Authors\n * Licensed under MIT (https://github.com/twbs/bootstrap/blob/main/LICENSE)\n */:root,[data-bs-theme=light]{--bs-blue:#0d6efd}";
+        let url = "https://www.example.com/css/bootstrap.min.css?v=5";
+        let url_response = UrlResponse::new(url, HashMap::new(), body, UrlRequestType::Css, 200);
+        let findings = checker.check_http(&[url_response]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Bootstrap  v5.3.0",
+            Technology::Bootstrap,
+            Some("5.3.0"),
+            Some(url),
+        );
+    }
+
     #[test]
     fn comment_doesnt_match() {
         let checker = BootstrapChecker::new();
@@ -0,0 +1,269 @@
+//! The Caddy checker.
+//! This module contains the checker used to determine if Caddy is
+//! used by the asset.
+//! https://caddyserver.com
+//!
+//! Caddy doesn't give its version, neither in the headers nor in its pages,
+//! so the findings are usually versionless.
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The Caddy checker
+pub struct CaddyChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> CaddyChecker<'a> {
+    /// Creates a new CaddyChecker.
+    /// By doing so, the regex will is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: Caddy
+        // The version is only given by some custom builds.
+        let header_regex =
+            Regex::new(r"^(?P<wholematch>Caddy(\/v?(?P<version1>\d+(\.\d+(\.\d+)?)?))?)\b")
+                .unwrap();
+        // The directory listings of the file server have a footer
+        // Example: Served with <a rel="noopener noreferrer" href="https://caddyserver.com">Caddy</a>
+        let body_listing_regex = Regex::new(
+            r#"(?P<wholematch>Served with\s*<a [^>]*href="https://caddyserver\.com/?">Caddy</a>)"#,
+        )
+        .unwrap();
+        // The default page installed with the packages
+        // Example: <title>Caddy works!</title>
+        let body_default_page_regex =
+            Regex::new(r"(?i)<title>(?P<wholematch>Caddy works!)</title>").unwrap();
+
+        regexes.insert("http-header", (header_regex, 45, 45));
+        regexes.insert("http-body-listing", (body_listing_regex, 50, 50));
+        regexes.insert("http-body-default-page", (body_default_page_regex, 30, 30));
+        Self { regexes }
+    }
+
+    /// Check for the technology in HTTP headers.
+    fn check_http_headers(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running CaddyChecker::check_http_headers() on {}",
+            url_response.url
+        );
+        // Check the HTTP headers of each UrlResponse
+        let headers_to_check = url_response.get_headers(&["Server".to_string()]);
+        let (regex_header, keep_left_header, keep_right_header) = self
+            .regexes
+            .get("http-header")
+            .expect("Regex Caddy/http-header not found");
+
+        // Check in the headers to check present in this UrlResponse
+        for (header_name, header_value) in headers_to_check {
+            trace!("Checking header: {} / {}", header_name, header_value);
+            // The regex matches
+            if let Some(caps) = regex_header.captures(&header_value) {
+                info!("Regex Caddy/http-header matches");
+                return Some(self.extract_finding_from_captures(
+                    caps,
+                    Some(url_response),
+                    keep_left_header.to_owned(),
+                    keep_right_header.to_owned(),
+                    Technology::Caddy,
+                    &format!("$techno_name$$techno_version$ has been identified using the HTTP header \"{}: $evidence$\" returned at the following URL: $url_of_finding$", header_name),
+                ));
+            }
+        }
+        None
+    }
+
+    /// Check for the technology in the body.
+    fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running CaddyChecker::check_http_body() on {}",
+            url_response.url
+        );
+        let regexes = [
+            (
+                "http-body-listing",
+                "$techno_name$$techno_version$ has been identified by looking at its signature \"$evidence$\" at this page: $url_of_finding$",
+            ),
+            (
+                "http-body-default-page",
+                "$techno_name$$techno_version$ has been identified because its default page \"$evidence$\" has been returned at this page: $url_of_finding$",
+            ),
+        ];
+
+        for (regex_name, template) in regexes {
+            let (regex, keep_left, keep_right) = self
+                .regexes
+                .get(regex_name)
+                .unwrap_or_else(|| panic!("Regex Caddy/{} not found", regex_name));
+            // The regex matches
+            if let Some(caps) = regex.captures(&url_response.body) {
+                info!("Regex Caddy/{} matches", regex_name);
+                return Some(self.extract_finding_from_captures(
+                    caps,
+                    Some(url_response),
+                    keep_left.to_owned(),
+                    keep_right.to_owned(),
+                    Technology::Caddy,
+                    template,
+                ));
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Checker for CaddyChecker<'a> {}
+
+impl<'a> HttpChecker for CaddyChecker<'a> {
+    /// Check if the asset is running Caddy.
+    /// It looks in the Server HTTP header, and in the directory listings
+    /// and default page.
+    ///
+    /// Returns only one finding, otherwise findings would be duplicated each
+    /// time it's found.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running CaddyChecker::check_http()");
+
+        for url_response in data {
+            // JavaScript files could be hosted on a different server
+            // Don't check the JavaScript files to avoid false positive,
+            // Check only the "main" requests.
+            if url_response.request_type != UrlRequestType::Default {
+                continue;
+            }
+
+            // Check in HTTP headers first
+            if let Some(finding) = self.check_http_headers(url_response) {
+                return vec![finding];
+            }
+            // Check in response body then
+            if let Some(finding) = self.check_http_body(url_response) {
+                return vec![finding];
+            }
+        }
+        Vec::new()
+    }
+
+    /// This checker supports Caddy
+    fn get_technology(&self) -> Technology {
+        Technology::Caddy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn header_matches() {
+        let checker = CaddyChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("Accept".to_string(), "text/html".to_string());
+        headers1.insert("Server".to_string(), "Caddy".to_string());
+        let url1 = "https://www.example.com/";
+        let mut url_response_valid =
+            UrlResponse::new(url1, headers1, "the body", UrlRequestType::Default, 200);
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        let finding = finding.unwrap();
+        check_finding_fields(&finding, "Caddy", Technology::Caddy, None, Some(url1));
+        assert!(finding.version.is_none());
+
+        let mut headers2 = HashMap::new();
+        headers2.insert("Server".to_string(), "Caddy/v2.7.6".to_string());
+        url_response_valid.headers = headers2;
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "Caddy/v2.7.6",
+            Technology::Caddy,
+            Some("2.7.6"),
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn header_doesnt_match() {
+        let checker = CaddyChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("Server".to_string(), "CaddyShack".to_string());
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/",
+            headers1,
+            "the body",
+            UrlRequestType::Default,
+            200,
+        );
+        let finding = checker.check_http_headers(&url_response_invalid);
+        assert!(finding.is_none());
+    }
+
+    #[test]
+    fn source_code_matches() {
+        let checker = CaddyChecker::new();
+        let body1 = r#"<footer>Served with <a rel="noopener noreferrer" href="https://caddyserver.com">Caddy</a></footer>"#;
+        let url1 = "https://www.example.com/files/";
+        let mut url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "https://caddyserver.com\">Caddy</a>",
+            Technology::Caddy,
+            None,
+            Some(url1),
+        );
+
+        let body2 = r#"<head><title>Caddy works!</title></head>"#;
+        url_response_valid.body = body2.to_string();
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "Caddy works!",
+            Technology::Caddy,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn doesnt_find_match_in_url_responses() {
+        let checker = CaddyChecker::new();
+        let body1 = r#"We migrated to Caddy, it works!"#;
+        let url_response_invalid1 = UrlResponse::new(
+            "https://www.example.com/blog",
+            HashMap::new(),
+            body1,
+            UrlRequestType::Default,
+            200,
+        );
+        let mut headers1 = HashMap::new();
+        headers1.insert("Server".to_string(), "Caddy".to_string());
+        let url_response_invalid2 = UrlResponse::new(
+            "https://cdn.example.com/app.js",
+            headers1,
+            "the body",
+            UrlRequestType::JavaScript,
+            200,
+        );
+        let findings = checker.check_http(&[url_response_invalid1, url_response_invalid2]);
+        assert!(
+            findings.is_empty(),
+            "Caddy must not be detected against JavaScript URLs to avoid false positive"
+        );
+    }
+}
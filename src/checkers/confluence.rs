@@ -0,0 +1,271 @@
+//! The Confluence checker.
+//! This module contains the checker used to determine if Confluence is
+//! used by the asset.
+//! https://www.atlassian.com/software/confluence
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The checker
+pub struct ConfluenceChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> ConfluenceChecker<'a> {
+    /// Creates the checker.
+    /// By doing so, the regex will is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: <meta name="ajs-version-number" content="7.19.16">
+        let body_meta_regex = Regex::new(r#"(?P<wholematch><meta\s+name\s*=\s*['"]ajs-version-number['"]\s+content\s*=\s*['"](?P<version1>\d+\.\d+\.\d+(\.\d+)?)['"])"#).unwrap();
+        // Example: Powered by <a href="https://www.atlassian.com/software/confluence" class="hover-footer-link">Atlassian Confluence</a> <span id='footer-build-information'>7.13.0</span>
+        let body_footer_regex = Regex::new(r#"(?P<wholematch>Atlassian Confluence</a>\s*<span\s+id\s*=\s*['"]footer-build-information['"]\s*>(?P<version1>\d+\.\d+\.\d+(\.\d+)?)</span>)"#).unwrap();
+        // Example: X-Confluence-Request-Time: 1712755297371
+        let header_regex = Regex::new(r"^(?P<wholematch>\d+)$").unwrap();
+        regexes.insert("http-body-meta", (body_meta_regex, 50, 50));
+        regexes.insert("http-body-footer", (body_footer_regex, 50, 50));
+        regexes.insert("http-header", (header_regex, 20, 20));
+        Self { regexes }
+    }
+
+    /// Checks in HTTP response body.
+    /// The meta tag and the footer both give the exact version, the meta tag
+    /// is checked first since it's present on most pages.
+    fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running ConfluenceChecker::check_http_body() on {}",
+            url_response.url
+        );
+
+        for regex_name in ["http-body-meta", "http-body-footer"] {
+            let (regex, keep_left, keep_right) = self
+                .regexes
+                .get(regex_name)
+                .unwrap_or_else(|| panic!("Regex Confluence/{} not found", regex_name));
+            // The regex matches
+            if let Some(caps) = regex.captures(&url_response.body) {
+                info!("Regex Confluence/{} matches", regex_name);
+                return Some(self.extract_finding_from_captures(
+		    caps,
+		    Some(url_response),
+		    keep_left.to_owned(),
+		    keep_right.to_owned(),
+		    Technology::Confluence,
+		    "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$"));
+            }
+        }
+        None
+    }
+
+    /// Checks in HTTP headers.
+    /// The X-Confluence-Request-Time header doesn't give the version, it's
+    /// only used to confirm Confluence is there.
+    fn check_http_headers(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running ConfluenceChecker::check_http_headers() on {}",
+            url_response.url
+        );
+        let headers_to_check = url_response.get_headers(&["X-confluence-request-time".to_string()]);
+        let (regex_header, keep_left_header, keep_right_header) = self
+            .regexes
+            .get("http-header")
+            .expect("Regex Confluence/http-header not found");
+
+        for (header_name, header_value) in headers_to_check {
+            trace!("Checking header: {} / {}", header_name, header_value);
+            // The regex matches
+            if let Some(caps) = regex_header.captures(&header_value) {
+                info!("Regex Confluence/http-header matches");
+                return Some(self.extract_finding_from_captures(
+		    caps,
+		    Some(url_response),
+		    keep_left_header.to_owned(),
+		    keep_right_header.to_owned(),
+		    Technology::Confluence,
+		    &format!("$techno_name$$techno_version$ has been identified using the HTTP header \"{}: $evidence$\" returned at the following URL: $url_of_finding$", header_name)
+		));
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Checker for ConfluenceChecker<'a> {}
+
+impl<'a> HttpChecker for ConfluenceChecker<'a> {
+    /// Check for a HTTP scan.
+    ///
+    /// Returns only one finding, otherwise findings would be duplicated each
+    /// time it's found.
+    /// Since Confluence versions matter a lot for the CVEs, a finding with
+    /// a version found in the body is always preferred over the versionless
+    /// finding from the headers.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running ConfluenceChecker::check_http()");
+
+        let mut header_finding = None;
+        for url_response in data {
+            // JavaScript files could be hosted on a different server
+            // Don't check the JavaScript files to avoid false positive,
+            // Check only the "main" requests.
+            if url_response.request_type != UrlRequestType::Default {
+                continue;
+            }
+
+            if let Some(body_finding) = self.check_http_body(url_response) {
+                return vec![body_finding];
+            }
+
+            if header_finding.is_none() {
+                header_finding = self.check_http_headers(url_response);
+            }
+        }
+
+        if let Some(finding) = header_finding {
+            return vec![finding];
+        }
+        Vec::new()
+    }
+
+    /// The technology supported by the checker
+    fn get_technology(&self) -> Technology {
+        Technology::Confluence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn source_code_matches() {
+        let checker = ConfluenceChecker::new();
+        let body1 = r#"<meta name="ajs-version-number" content="7.19.16"> <meta name="ajs-build-number" content="8804">"#;
+        let url1 = "https://www.example.com/wiki/login.action";
+        let mut url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "content=\"7.19.16\"",
+            Technology::Confluence,
+            Some("7.19.16"),
+            Some(url1),
+        );
+
+        let body2 = r#"Powered by <a href="https://www.atlassian.com/software/confluence" class="hover-footer-link" rel="nofollow">Atlassian Confluence</a> <span id='footer-build-information'>7.13.0</span>"#;
+        url_response_valid.body = body2.to_string();
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "<span id='footer-build-information'>7.13.0</span>",
+            Technology::Confluence,
+            Some("7.13.0"),
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn source_code_doesnt_match() {
+        let checker = ConfluenceChecker::new();
+        let body = r#"<h1>Atlassian Confluence 7.13.0</h1>"#;
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/login.action",
+            HashMap::new(),
+            body,
+            UrlRequestType::Default,
+            200,
+        );
+        let finding = checker.check_http_body(&url_response_invalid);
+        assert!(finding.is_none());
+    }
+
+    #[test]
+    fn header_matches() {
+        let checker = ConfluenceChecker::new();
+        let mut headers = HashMap::new();
+        headers.insert(
+            "X-confluence-request-time".to_string(),
+            "1712755297371".to_string(),
+        );
+        let url1 = "https://www.example.com/";
+        let url_response_valid =
+            UrlResponse::new(url1, headers, "the body", UrlRequestType::Default, 200);
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "1712755297371",
+            Technology::Confluence,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn finds_match_in_url_responses() {
+        let checker = ConfluenceChecker::new();
+        let mut headers = HashMap::new();
+        headers.insert(
+            "X-confluence-request-time".to_string(),
+            "1712755297371".to_string(),
+        );
+        let url_response_header = UrlResponse::new(
+            "https://www.example.com/",
+            headers,
+            "nothing to find in body",
+            UrlRequestType::Default,
+            200,
+        );
+        let body1 = r#"<meta name = "ajs-version-number"  content= '8.5.4'>"#;
+        let url1 = "https://www.example.com/login.action";
+        let url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+
+        // The version from the body is preferred over the header
+        let findings = checker.check_http(&[url_response_header, url_response_valid]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "content= '8.5.4'",
+            Technology::Confluence,
+            Some("8.5.4"),
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn doesnt_find_match_in_url_responses() {
+        let checker = ConfluenceChecker::new();
+        let body1 = r#"<meta name="ajs-version-number" content="8.5.4">"#;
+        let url_response_invalid1 = UrlResponse::new(
+            "https://cdn.example.com/confluence.js",
+            HashMap::new(),
+            body1,
+            UrlRequestType::JavaScript,
+            200,
+        );
+        let url_response_invalid2 = UrlResponse::new(
+            "https://www.example.com/login.action",
+            HashMap::new(),
+            "Confluence version: 8.5.4",
+            UrlRequestType::Default,
+            200,
+        );
+        let findings = checker.check_http(&[url_response_invalid1, url_response_invalid2]);
+        assert!(findings.is_empty());
+    }
+}
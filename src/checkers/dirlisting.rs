@@ -0,0 +1,183 @@
+//! The directory listing checker.
+//! This module contains the checker used to report the directory listings
+//! (autoindex) exposed by the asset, in any of the responses: the ones of
+//! its own paths and the ones of the other technologies.
+//!
+//! Apache and nginx title their listings "Index of /path", Apache shows
+//! the [PARENTDIR] icon and IIS the [To Parent Directory] link.
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The checker
+pub struct DirectoryListingChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> DirectoryListingChecker<'a> {
+    /// Creates the checker.
+    /// By doing so, the regex is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: <title>Index of /backup</title>
+        // Example: <h1>Index of /uploads/</h1>
+        let title_regex =
+            Regex::new(r"(?i)<(title|h1)>\s*(?P<wholematch>Index of /[^<]*)</(title|h1)>").unwrap();
+        // Example: <img src="/icons/back.gif" alt="[PARENTDIR]">
+        // Example: <A HREF="/">[To Parent Directory]</A>
+        let parent_regex =
+            Regex::new(r"(?P<wholematch>alt=.\[PARENTDIR\].|\[To Parent Directory\])").unwrap();
+        regexes.insert("http-body-title", (title_regex, 40, 0));
+        regexes.insert("http-body-parent", (parent_regex, 30, 0));
+        Self { regexes }
+    }
+
+    /// Checks whether the response is a directory listing.
+    /// The title gives the path listed, the parent directory link is
+    /// enough for the servers without the "Index of" title.
+    fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running DirectoryListingChecker::check_http_body() on {}",
+            url_response.url
+        );
+        if url_response.status_code != 200 {
+            return None;
+        }
+        for regex_name in ["http-body-title", "http-body-parent"] {
+            let (regex, keep_left, keep_right) = self
+                .regexes
+                .get(regex_name)
+                .unwrap_or_else(|| panic!("Regex DirectoryListing/{} not found", regex_name));
+            if let Some(caps) = regex.captures(&url_response.body) {
+                info!("Regex DirectoryListing/{} matches", regex_name);
+                return Some(self.extract_finding_from_captures(
+                    caps,
+                    Some(url_response),
+                    keep_left.to_owned(),
+                    keep_right.to_owned(),
+                    Technology::Misconfiguration,
+                    "$techno_name$: a directory listing is exposed, it reveals the files of the directory. We found \"$evidence$\" at this url: $url_of_finding$",
+                ));
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Checker for DirectoryListingChecker<'a> {}
+
+impl<'a> HttpChecker for DirectoryListingChecker<'a> {
+    /// Check for a HTTP scan.
+    /// Each directory listed gives a finding.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running DirectoryListingChecker::check_http()");
+        let mut findings: Vec<Finding> = Vec::new();
+        for url_response in data {
+            if url_response.request_type != UrlRequestType::Default {
+                continue;
+            }
+            if let Some(finding) = self.check_http_body(url_response) {
+                // The same directory can be reached by several URLs
+                if !findings.iter().any(|f| f.evidence == finding.evidence) {
+                    findings.push(finding);
+                }
+            }
+        }
+        findings
+    }
+
+    /// The technology supported by the checker
+    fn get_technology(&self) -> Technology {
+        Technology::DirectoryListing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn listings_match() {
+        let checker = DirectoryListingChecker::new();
+        // Apache
+        let url1 = "https://www.example.com/backup/";
+        let body1 = r#"<html><head><title>Index of /backup</title></head><body>
+<h1>Index of /backup</h1><table><tr><td valign="top"><img src="/icons/back.gif" alt="[PARENTDIR]"></td>"#;
+        // nginx
+        let url2 = "https://www.example.com/uploads/";
+        let body2 = r#"<html><head><title>Index of /uploads/</title></head><body><h1>Index of /uploads/</h1><hr><pre><a href="../">../</a>"#;
+        // IIS
+        let url3 = "https://www.example.com/images/";
+        let body3 = r#"<html><head><title>www.example.com - /images/</title></head><body><H1>www.example.com - /images/</H1><hr><pre><A HREF="/">[To Parent Directory]</A><br><br>"#;
+        let url_responses = [
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200),
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 200),
+            UrlResponse::new(url3, HashMap::new(), body3, UrlRequestType::Default, 200),
+        ];
+
+        let findings = checker.check_http(&url_responses);
+        assert_eq!(3, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Index of /backup",
+            Technology::Misconfiguration,
+            None,
+            Some(url1),
+        );
+        check_finding_fields(
+            &findings[1],
+            "Index of /uploads/",
+            Technology::Misconfiguration,
+            None,
+            Some(url2),
+        );
+        check_finding_fields(
+            &findings[2],
+            "[To Parent Directory]",
+            Technology::Misconfiguration,
+            None,
+            Some(url3),
+        );
+        assert!(findings[2].evidence_text.contains("a directory listing"));
+    }
+
+    #[test]
+    fn listings_dont_match() {
+        let checker = DirectoryListingChecker::new();
+        let url_responses = [
+            UrlResponse::new(
+                "https://www.example.com/about/",
+                HashMap::new(),
+                "<h2>Index of our products</h2><title>Index of products</title>",
+                UrlRequestType::Default,
+                200,
+            ),
+            UrlResponse::new(
+                "https://www.example.com/backup/",
+                HashMap::new(),
+                "<h1>Index of /backup</h1>",
+                UrlRequestType::Default,
+                403,
+            ),
+            UrlResponse::new(
+                "https://cdn.example.com/js/",
+                HashMap::new(),
+                "<title>Index of /js</title>",
+                UrlRequestType::JavaScript,
+                200,
+            ),
+        ];
+        assert!(checker.check_http(&url_responses).is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+//! The DNS server checker.
+//! This module contains the checker used to determine which DNS server
+//! is exposed by the asset.
+//!
+//! It sends a "version.bind CHAOS TXT" query. Many servers answer it with
+//! their name and version (BIND, PowerDNS, dnsmasq), the others refuse it
+//! or give a custom text.
+
+use std::collections::HashMap;
+
+use super::{Checker, UdpChecker};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The ID of the query, to recognize the response
+const QUERY_ID: u16 = 0x5341;
+/// The length of the header of a DNS message
+const HEADER_LENGTH: usize = 12;
+/// The type of the TXT records
+const TYPE_TXT: u16 = 16;
+/// The CHAOS class
+const CLASS_CHAOS: u16 = 3;
+/// The maximum number of chars of the text kept in the evidence
+const MAX_TEXT_LENGTH: usize = 100;
+
+/// The DNS server checker
+pub struct DnsChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> Checker for DnsChecker<'a> {}
+
+impl<'a> DnsChecker<'a> {
+    /// Creates a new DnsChecker.
+    /// By doing so, the regexes are compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: 9.18.24-1ubuntu1.4-Ubuntu
+        // Example: BIND 9.16.1
+        let regex =
+            Regex::new(r"^(?P<wholematch>(BIND )?(?P<version1>9\.\d+\.\d+)(-[PS]\d+)?\b)").unwrap();
+        regexes.insert("dns-bind", (regex, 30, 30));
+        // Example: PowerDNS Authoritative Server 4.8.3 (built Nov 21 2023 11:19:17 by root@localhost)
+        let regex = Regex::new(
+            r"^(?P<wholematch>PowerDNS Authoritative Server (?P<version1>\d+\.\d+\.\d+))",
+        )
+        .unwrap();
+        regexes.insert("dns-powerdns", (regex, 40, 20));
+        // Example: PowerDNS Recursor 4.9.2 (built Nov 21 2023 11:19:17 by root@localhost)
+        let regex =
+            Regex::new(r"^(?P<wholematch>PowerDNS Recursor (?P<version1>\d+\.\d+\.\d+))").unwrap();
+        regexes.insert("dns-powerdns-recursor", (regex, 30, 30));
+        // Example: dnsmasq-2.80
+        let regex = Regex::new(r"^(?P<wholematch>dnsmasq-(?P<version1>\d+\.\d+(\.\d+)?))").unwrap();
+        regexes.insert("dns-dnsmasq", (regex, 30, 30));
+        Self { regexes }
+    }
+
+    /// Returns the position following the name starting at pos.
+    /// The name is either a list of labels ending with an empty one, or
+    /// ends with a compression pointer.
+    fn skip_name(data: &[u8], mut pos: usize) -> Option<usize> {
+        loop {
+            let length = *data.get(pos)? as usize;
+            match length {
+                0 => return Some(pos + 1),
+                // A pointer takes 2 bytes and ends the name
+                length if length & 0xC0 == 0xC0 => return Some(pos + 2),
+                length => pos += 1 + length,
+            }
+        }
+    }
+
+    /// Parses a response to the query.
+    /// Returns the response code and the text of the first TXT record.
+    fn parse_response(data: &[u8]) -> Option<(u8, Option<String>)> {
+        let header = data.get(..HEADER_LENGTH)?;
+        let id = u16::from_be_bytes([header[0], header[1]]);
+        let flags = u16::from_be_bytes([header[2], header[3]]);
+        // The QR bit is set in the responses
+        if id != QUERY_ID || flags & 0x8000 == 0 {
+            return None;
+        }
+        let response_code = (flags & 0x000F) as u8;
+        let question_count = u16::from_be_bytes([header[4], header[5]]);
+        let answer_count = u16::from_be_bytes([header[6], header[7]]);
+
+        let mut pos = HEADER_LENGTH;
+        for _ in 0..question_count {
+            // The name, then the type and the class
+            pos = Self::skip_name(data, pos)? + 4;
+        }
+        for _ in 0..answer_count {
+            pos = Self::skip_name(data, pos)?;
+            let record = data.get(pos..pos + 10)?;
+            let record_type = u16::from_be_bytes([record[0], record[1]]);
+            let data_length = u16::from_be_bytes([record[8], record[9]]) as usize;
+            let record_data = data.get(pos + 10..pos + 10 + data_length)?;
+            pos += 10 + data_length;
+            if record_type != TYPE_TXT {
+                continue;
+            }
+            // The TXT data is a list of length-prefixed strings
+            let mut text = String::new();
+            let mut string_pos = 0;
+            while let Some(length) = record_data.get(string_pos) {
+                let string_end = (string_pos + 1 + *length as usize).min(record_data.len());
+                text.push_str(&String::from_utf8_lossy(
+                    &record_data[string_pos + 1..string_end],
+                ));
+                string_pos = string_end;
+            }
+            return Some((response_code, Some(text.trim().to_string())));
+        }
+        Some((response_code, None))
+    }
+
+    /// Returns the name of the response code.
+    fn get_response_code_name(response_code: u8) -> String {
+        match response_code {
+            1 => "FORMERR".to_string(),
+            2 => "SERVFAIL".to_string(),
+            3 => "NXDOMAIN".to_string(),
+            4 => "NOTIMP".to_string(),
+            5 => "REFUSED".to_string(),
+            _ => format!("RCODE {}", response_code),
+        }
+    }
+}
+
+impl<'a> UdpChecker for DnsChecker<'a> {
+    /// Returns a "version.bind CHAOS TXT" query
+    fn probe_payload(&self) -> Vec<u8> {
+        let mut query = QUERY_ID.to_be_bytes().to_vec();
+        // No flags (no recursion), one question
+        query.extend_from_slice(&[0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        for label in ["version", "bind"] {
+            query.push(label.len() as u8);
+            query.extend_from_slice(label.as_bytes());
+        }
+        query.push(0);
+        query.extend_from_slice(&TYPE_TXT.to_be_bytes());
+        query.extend_from_slice(&CLASS_CHAOS.to_be_bytes());
+        query
+    }
+
+    /// Check which DNS server is exposed by the asset.
+    /// The text of version.bind gives the software and its version. If
+    /// it's not recognized or not given, a DNS server is still reported
+    /// since the query has been answered.
+    fn check_udp(&self, data: &[u8]) -> Option<Finding> {
+        trace!("Running DnsChecker::check_udp()");
+        let (response_code, text) = Self::parse_response(data)?;
+        info!("Got a DNS response with the text {:?}", text);
+
+        let text = match text {
+            Some(text) => text,
+            None => {
+                let evidence = Self::get_response_code_name(response_code);
+                let evidence_text = format!(
+                    "A {} has been identified because it answered the query for version.bind ({})",
+                    Technology::DnsServer,
+                    evidence
+                );
+                return Some(Finding::new(
+                    Technology::DnsServer,
+                    None,
+                    &evidence,
+                    &evidence_text,
+                    None,
+                ));
+            }
+        };
+
+        let regexes = [
+            ("dns-bind", Technology::Bind),
+            ("dns-powerdns", Technology::PowerDNS),
+            ("dns-powerdns-recursor", Technology::PowerDNSRecursor),
+            ("dns-dnsmasq", Technology::Dnsmasq),
+        ];
+        for (regex_name, technology) in regexes {
+            let (regex, keep_left, keep_right) = self
+                .regexes
+                .get(regex_name)
+                .unwrap_or_else(|| panic!("Regex DNS/{} not found", regex_name));
+            if let Some(caps) = regex.captures(&text) {
+                info!("Regex DNS/{} matches", regex_name);
+                return Some(self.extract_finding_from_captures(
+                    caps,
+                    None,
+                    keep_left.to_owned(),
+                    keep_right.to_owned(),
+                    technology,
+                    "$techno_name$$techno_version$ has been identified because version.bind is \"$evidence$\"",
+                ));
+            }
+        }
+
+        // A custom text, often set to hide the version
+        let evidence: String = text.chars().take(MAX_TEXT_LENGTH).collect();
+        let evidence_text = format!(
+            "A {} has been identified because version.bind is \"{}\"",
+            Technology::DnsServer,
+            evidence
+        );
+        Some(Finding::new(
+            Technology::DnsServer,
+            None,
+            &evidence,
+            &evidence_text,
+            None,
+        ))
+    }
+
+    /// This checker supports the DNS servers
+    fn get_technology(&self) -> Technology {
+        Technology::DnsServer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    /// Builds a response to the query, with the given response code and
+    /// TXT strings. No answer is given if there's no string.
+    fn get_response(response_code: u8, strings: &[&str]) -> Vec<u8> {
+        let checker = DnsChecker::new();
+        let mut data = checker.probe_payload();
+        // QR and AA set
+        data[2] = 0x84;
+        data[3] = response_code;
+        if strings.is_empty() {
+            return data;
+        }
+        data[7] = 1;
+        let mut record_data = Vec::new();
+        for string in strings {
+            record_data.push(string.len() as u8);
+            record_data.extend_from_slice(string.as_bytes());
+        }
+        // The name is a pointer to the question
+        data.extend_from_slice(&[0xC0, 0x0C, 0x00, 0x10, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00]);
+        data.extend_from_slice(&(record_data.len() as u16).to_be_bytes());
+        data.extend_from_slice(&record_data);
+        data
+    }
+
+    #[test]
+    fn bind_matches() {
+        let checker = DnsChecker::new();
+        let finding = checker
+            .check_udp(&get_response(0, &["9.18.24-1ubuntu1.4-Ubuntu"]))
+            .unwrap();
+        check_finding_fields(&finding, "9.18.24", Technology::Bind, Some("9.18.24"), None);
+
+        let finding = checker
+            .check_udp(&get_response(
+                0,
+                &["9.11.4-P2-RedHat-9.11.4-26.P2.el7_9.15"],
+            ))
+            .unwrap();
+        check_finding_fields(
+            &finding,
+            "9.11.4-P2",
+            Technology::Bind,
+            Some("9.11.4"),
+            None,
+        );
+    }
+
+    #[test]
+    fn powerdns_and_dnsmasq_match() {
+        let checker = DnsChecker::new();
+        let finding = checker
+            .check_udp(&get_response(
+                0,
+                &["PowerDNS Authoritative Server 4.8.3 (built Nov 21 2023 11:19:17 by root@localhost)"],
+            ))
+            .unwrap();
+        check_finding_fields(
+            &finding,
+            "PowerDNS Authoritative Server 4.8.3",
+            Technology::PowerDNS,
+            Some("4.8.3"),
+            None,
+        );
+
+        let finding = checker
+            .check_udp(&get_response(0, &["PowerDNS Recursor ", "4.9.2"]))
+            .unwrap();
+        check_finding_fields(
+            &finding,
+            "PowerDNS Recursor 4.9.2",
+            Technology::PowerDNSRecursor,
+            Some("4.9.2"),
+            None,
+        );
+
+        let finding = checker
+            .check_udp(&get_response(0, &["dnsmasq-2.80"]))
+            .unwrap();
+        check_finding_fields(
+            &finding,
+            "dnsmasq-2.80",
+            Technology::Dnsmasq,
+            Some("2.80"),
+            None,
+        );
+    }
+
+    #[test]
+    fn hidden_versions_match() {
+        let checker = DnsChecker::new();
+        let finding = checker
+            .check_udp(&get_response(0, &["none of your business"]))
+            .unwrap();
+        check_finding_fields(
+            &finding,
+            "none of your business",
+            Technology::DnsServer,
+            None,
+            None,
+        );
+
+        let finding = checker.check_udp(&get_response(5, &[])).unwrap();
+        check_finding_fields(&finding, "REFUSED", Technology::DnsServer, None, None);
+    }
+
+    #[test]
+    fn response_doesnt_match() {
+        let checker = DnsChecker::new();
+        // The query itself is not a response
+        assert!(checker.check_udp(&checker.probe_payload()).is_none());
+        let data = get_response(0, &["9.18.24"]);
+        assert!(checker.check_udp(&data[..data.len() - 4]).is_none());
+        assert!(checker.check_udp(b"SSH-2.0-OpenSSH_8.9p1").is_none());
+    }
+}
@@ -10,6 +10,13 @@ use crate::models::{technology::Technology, Finding};
 use log::{info, trace};
 use regex::Regex;
 
+/// The probe listing the capabilities, for IMAP (CAPABILITY) and POP3
+/// (CAPA). Each protocol rejects the commands of the other one.
+const CAPABILITY_PROBE: &[u8] = b"a1 CAPABILITY\r\nCAPA\r\na2 LOGOUT\r\nQUIT\r\n";
+
+/// The SASL mechanisms sending the password in cleartext
+const CLEARTEXT_MECHANISMS: [&str; 2] = ["PLAIN", "LOGIN"];
+
 /// The Dovecot checker
 pub struct DovecotChecker<'a> {
     /// The regexes and their parameters used to recognize the technology
@@ -27,10 +34,93 @@ impl<'a> DovecotChecker<'a> {
         let mut regexes = HashMap::new();
         // Example: * OK [CAPABILITY IMAP4rev1 SASL-IR LOGIN-REFERRALS ID ENABLE IDLE LITERAL+ STARTTLS AUTH=PLAIN AUTH=LOGIN] Dovecot (Ubuntu) ready.
         // Example: +OK Dovecot (Ubuntu) ready.
-        let regex = Regex::new(r"(?P<wholematch>OK \[.+\] Dovecot .* ready\.)").unwrap();
+        let regex = Regex::new(r"(?P<wholematch>OK (\[.+\] )?Dovecot( .*)? ready\.)").unwrap();
         regexes.insert("dovecot-banner", (regex, 20, 20));
         Self { regexes: regexes }
     }
+
+    /// Returns the capabilities advertised in the response to the probe.
+    /// For IMAP they are in the CAPABILITY response (or in the greeting),
+    /// for POP3 they are the lines of the CAPA response.
+    fn get_capabilities(response: &str) -> Vec<String> {
+        // IMAP, the untagged response is more complete than the greeting
+        let imap_regex =
+            Regex::new(r"(?m)^\* (OK \[)?CAPABILITY (?P<capabilities>[^\]\r\n]+)").unwrap();
+        if let Some(caps) = imap_regex.captures_iter(response).last() {
+            return caps["capabilities"]
+                .split_whitespace()
+                .map(|capability| capability.to_string())
+                .collect();
+        }
+        // POP3, the list ends with a single dot
+        let mut lines = response.lines().map(|line| line.trim_end());
+        if lines.by_ref().any(|line| line.starts_with("+OK")) {
+            let mut capabilities = Vec::new();
+            let mut in_list = false;
+            for line in lines {
+                if line.starts_with("+OK") {
+                    in_list = true;
+                } else if line == "." {
+                    break;
+                } else if line.starts_with("-ERR") {
+                    in_list = false;
+                } else if in_list && !line.is_empty() {
+                    capabilities.push(line.to_string());
+                }
+            }
+            return capabilities;
+        }
+        Vec::new()
+    }
+
+    /// Describes the capabilities: the SASL mechanisms, STARTTLS, and the
+    /// cleartext mechanisms offered without TLS.
+    fn describe_capabilities(capabilities: &[String]) -> String {
+        let mut mechanisms: Vec<String> = Vec::new();
+        for capability in capabilities {
+            if let Some(mechanism) = capability.strip_prefix("AUTH=") {
+                // IMAP
+                mechanisms.push(mechanism.to_uppercase());
+            } else if let Some(sasl) = capability.strip_prefix("SASL ") {
+                // POP3
+                mechanisms.extend(sasl.split_whitespace().map(|m| m.to_uppercase()));
+            }
+        }
+        let starttls = capabilities
+            .iter()
+            .any(|capability| capability == "STARTTLS" || capability == "STLS");
+        // The cleartext logins are refused until STARTTLS
+        let login_disabled = capabilities
+            .iter()
+            .any(|capability| capability == "LOGINDISABLED");
+
+        let mut description = format!(", the capabilities are \"{}\"", capabilities.join(" "));
+        if !mechanisms.is_empty() {
+            description.push_str(&format!(
+                ", the SASL mechanisms are {}",
+                mechanisms.join(", ")
+            ));
+        }
+        description.push_str(if starttls {
+            ", STARTTLS is offered"
+        } else {
+            ", STARTTLS is not offered"
+        });
+        let cleartext_mechanisms: Vec<&str> = CLEARTEXT_MECHANISMS
+            .iter()
+            .filter(|mechanism| mechanisms.iter().any(|m| m == *mechanism))
+            .copied()
+            .collect();
+        if login_disabled {
+            description.push_str(", the logins require TLS (LOGINDISABLED)");
+        } else if !cleartext_mechanisms.is_empty() {
+            description.push_str(&format!(
+                ", the cleartext mechanisms {} are offered without TLS",
+                cleartext_mechanisms.join(", ")
+            ));
+        }
+        description
+    }
 }
 
 impl<'a> Checker for DovecotChecker<'a> {}
@@ -67,8 +157,102 @@ impl<'a> TcpChecker for DovecotChecker<'a> {
         return None;
     }
 
+    /// Sends CAPABILITY (IMAP) and CAPA (POP3) after the greeting, to
+    /// list the authentication mechanisms offered.
+    fn get_tcp_probe(&self) -> Option<Vec<u8>> {
+        Some(CAPABILITY_PROBE.to_vec())
+    }
+
+    /// Checks the greeting in the response, then adds the capabilities
+    /// to the evidence.
+    fn check_tcp_probe_response(&self, data: &[u8]) -> Option<Finding> {
+        trace!("Running DovecotChecker::check_tcp_probe_response()");
+        let response = String::from_utf8_lossy(data).to_string();
+        let mut finding = self.check_tcp(std::slice::from_ref(&response))?;
+        let capabilities = Self::get_capabilities(&response);
+        if !capabilities.is_empty() {
+            info!("Dovecot capabilities: {:?}", capabilities);
+            finding
+                .evidence_text
+                .push_str(&Self::describe_capabilities(&capabilities));
+        }
+        Some(finding)
+    }
+
     /// This checker supports Dovecot
     fn get_technology(&self) -> Technology {
         Technology::Dovecot
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn imap_capabilities_are_reported() {
+        let checker = DovecotChecker::new();
+        let response = "* OK [CAPABILITY IMAP4rev1 SASL-IR LOGIN-REFERRALS ID ENABLE IDLE LITERAL+ STARTTLS AUTH=PLAIN AUTH=LOGIN] Dovecot (Ubuntu) ready.\r\n\
+            * CAPABILITY IMAP4rev1 SASL-IR LOGIN-REFERRALS ID ENABLE IDLE LITERAL+ STARTTLS AUTH=PLAIN AUTH=LOGIN\r\n\
+            a1 OK Pre-login capabilities listed, post-login capabilities have more.\r\n\
+            CAPA BAD Error in IMAP command received by server.\r\n\
+            * BYE Logging out\r\n\
+            a2 OK Logout completed (0.001 + 0.000 secs).\r\n";
+        let finding = checker
+            .check_tcp_probe_response(response.as_bytes())
+            .unwrap();
+        check_finding_fields(&finding, "(Ubuntu) ready.", Technology::Dovecot, None, None);
+        assert!(finding
+            .evidence_text
+            .contains("the SASL mechanisms are PLAIN, LOGIN, STARTTLS is offered"));
+        assert!(finding
+            .evidence_text
+            .contains("the cleartext mechanisms PLAIN, LOGIN are offered without TLS"));
+    }
+
+    #[test]
+    fn login_disabled_is_reported() {
+        let checker = DovecotChecker::new();
+        let response = "* OK [CAPABILITY IMAP4rev1 STARTTLS LOGINDISABLED] Dovecot ready.\r\n\
+            * CAPABILITY IMAP4rev1 SASL-IR ID ENABLE IDLE LITERAL+ STARTTLS LOGINDISABLED\r\n\
+            a1 OK Pre-login capabilities listed.\r\n";
+        let finding = checker
+            .check_tcp_probe_response(response.as_bytes())
+            .unwrap();
+        assert!(finding
+            .evidence_text
+            .contains("the logins require TLS (LOGINDISABLED)"));
+        assert!(!finding.evidence_text.contains("SASL mechanisms"));
+    }
+
+    #[test]
+    fn pop3_capabilities_are_reported() {
+        let checker = DovecotChecker::new();
+        let response = "+OK Dovecot ready.\r\n\
+            -ERR Unknown command: A1\r\n\
+            +OK\r\nCAPA\r\nTOP\r\nUIDL\r\nRESP-CODES\r\nPIPELINING\r\nAUTH-RESP-CODE\r\nUSER\r\nSASL PLAIN LOGIN\r\n.\r\n\
+            -ERR Unknown command: A2\r\n\
+            +OK Logging out.\r\n";
+        let finding = checker
+            .check_tcp_probe_response(response.as_bytes())
+            .unwrap();
+        check_finding_fields(&finding, "Dovecot ready.", Technology::Dovecot, None, None);
+        assert!(finding.evidence_text.contains(
+            "the capabilities are \"CAPA TOP UIDL RESP-CODES PIPELINING AUTH-RESP-CODE USER SASL PLAIN LOGIN\""
+        ));
+        assert!(finding.evidence_text.contains("STARTTLS is not offered"));
+        assert!(finding
+            .evidence_text
+            .contains("the cleartext mechanisms PLAIN, LOGIN are offered without TLS"));
+    }
+
+    #[test]
+    fn other_servers_dont_match() {
+        let checker = DovecotChecker::new();
+        let response = "* OK [CAPABILITY IMAP4rev1 STARTTLS AUTH=PLAIN] Courier-IMAP ready.\r\n";
+        assert!(checker
+            .check_tcp_probe_response(response.as_bytes())
+            .is_none());
+    }
+}
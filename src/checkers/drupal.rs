@@ -7,7 +7,7 @@ use std::collections::HashMap;
 
 use super::{Checker, HttpChecker};
 use crate::models::reqres::{UrlRequestType, UrlResponse};
-use crate::models::{technology::Technology, Finding};
+use crate::models::{technology::Technology, Confidence, Finding};
 use log::{info, trace};
 use regex::Regex;
 
@@ -28,7 +28,14 @@ impl<'a> DrupalChecker<'a> {
         let mut regexes = HashMap::new();
         // Example: <meta name="Generator" content="Drupal 9 (https://www.drupal.org)" />
         let body_meta_regex = Regex::new(r#"(?P<wholematch><meta\s+name\s*=\s*['"][Gg]enerator['"]\s+content\s*=\s*['"]Drupal\s+(?P<version1>\d+(\.\d+)?)( \(https://www.drupal.org\))?['"]\s*\/>)"#).unwrap();
+        // Example: Drupal 7.98, 2023-06-07
+        // The first version encountered in the CHANGELOG.txt is the latest
+        let changelog_regex = Regex::new(
+            r"(?P<wholematch>Drupal (?P<version1>\d+\.\d+(\.\d+)?), \d\d\d\d-\d\d-\d\d)",
+        )
+        .unwrap();
         regexes.insert("http-body-meta", (body_meta_regex, 50, 50));
+        regexes.insert("http-body-changelog", (changelog_regex, 30, 30));
         Self { regexes: regexes }
     }
 
@@ -56,7 +63,34 @@ impl<'a> DrupalChecker<'a> {
 		keep_right_meta.to_owned(),
 		Technology::Drupal,
 		"$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$"
-	    ));
+	    ).with_confidence(Confidence::Certain));
+        }
+
+        // Drupal 7 has its CHANGELOG.txt at the root, Drupal 8+ has it
+        // in core/ (removed in Drupal 8.8). Restrict the regex to these
+        // files to avoid false positive results.
+        if url_response.url.ends_with("/CHANGELOG.txt") {
+            let body_changelog_regex_params = self
+                .regexes
+                .get("http-body-changelog")
+                .expect("Regex Drupal/http-body-changelog not found");
+            let (regex_changelog, keep_left_changelog, keep_right_changelog) =
+                body_changelog_regex_params;
+            let caps_result = regex_changelog.captures(&url_response.body);
+
+            // The regex matches
+            if caps_result.is_some() {
+                info!("Regex Drupal/http-body-changelog matches");
+                let caps = caps_result.unwrap();
+                return Some(self.extract_finding_from_captures(
+		    caps,
+		    Some(url_response),
+		    keep_left_changelog.to_owned(),
+		    keep_right_changelog.to_owned(),
+		    Technology::Drupal,
+		    "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$"
+		));
+            }
         }
         None
     }
@@ -66,10 +100,18 @@ impl<'a> Checker for DrupalChecker<'a> {}
 
 impl<'a> HttpChecker for DrupalChecker<'a> {
     /// Check for a HTTP scan.
+    /// The CHANGELOG.txt gives the full version, while the meta tag gives
+    /// only the major one, so the CHANGELOG.txt are checked first.
     fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
         trace!("Running DrupalChecker::check_http()");
 
-        for url_response in data {
+        let is_changelog =
+            |url_response: &&UrlResponse| url_response.url.ends_with("/CHANGELOG.txt");
+        let changelogs = data.iter().filter(is_changelog);
+        let others = data
+            .iter()
+            .filter(|url_response| !is_changelog(url_response));
+        for url_response in changelogs.chain(others) {
             // JavaScript files could be hosted on a different server
             // Don't check the JavaScript files to avoid false positive,
             // Check only the "main" requests.
@@ -133,6 +175,65 @@ mod tests {
         assert!(finding.is_none());
     }
 
+    #[test]
+    fn changelog_matches() {
+        let checker = DrupalChecker::new();
+        // Drupal 7 layout
+        let body1 = "\nDrupal 7.98, 2023-06-07\n-----------------------\n- Fixed security issues\n\nDrupal 7.97, 2023-04-21\n";
+        let url1 = "https://www.example.com/CHANGELOG.txt";
+        let url_response1 =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+        let finding1 = checker.check_http_body(&url_response1);
+        assert!(finding1.is_some());
+        check_finding_fields(
+            &finding1.unwrap(),
+            "Drupal 7.98, 2023-06-07",
+            Technology::Drupal,
+            Some("7.98"),
+            Some(url1),
+        );
+
+        // Drupal 8+ layout
+        let body2 =
+            "Drupal 8.7.14, 2020-05-20\n-------------------------\n- Fixed security issues\n";
+        let url2 = "https://www.example.com/drupal/core/CHANGELOG.txt";
+        let url_response2 =
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 200);
+        let finding2 = checker.check_http_body(&url_response2);
+        assert!(finding2.is_some());
+        check_finding_fields(
+            &finding2.unwrap(),
+            "Drupal 8.7.14, 2020-05-20",
+            Technology::Drupal,
+            Some("8.7.14"),
+            Some(url2),
+        );
+    }
+
+    #[test]
+    fn changelog_doesnt_match() {
+        let checker = DrupalChecker::new();
+        // Not a CHANGELOG.txt
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/news.html",
+            HashMap::new(),
+            "Drupal 7.98, 2023-06-07",
+            UrlRequestType::Default,
+            200,
+        );
+        assert!(checker.check_http_body(&url_response_invalid).is_none());
+
+        // An error page quoting the version is ignored
+        let url_response_error = UrlResponse::new(
+            "https://www.example.com/core/CHANGELOG.txt",
+            HashMap::new(),
+            "Drupal 7.98, 2023-06-07",
+            UrlRequestType::Default,
+            404,
+        );
+        assert!(checker.check_http(&[url_response_error]).is_empty());
+    }
+
     #[test]
     fn finds_match_in_url_responses() {
         let checker = DrupalChecker::new();
@@ -156,6 +257,25 @@ mod tests {
             Some("9"),
             Some(url1),
         );
+
+        // The CHANGELOG.txt is more precise than the meta tag
+        let body2 = r#"<meta name="Generator" content="Drupal 8 (https://www.drupal.org)" />"#;
+        let url2 = "https://www.example.com/";
+        let body3 =
+            "Drupal 8.7.14, 2020-05-20\n-------------------------\n- Fixed security issues\n";
+        let url3 = "https://www.example.com/core/CHANGELOG.txt";
+        let findings = checker.check_http(&[
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 200),
+            UrlResponse::new(url3, HashMap::new(), body3, UrlRequestType::Default, 200),
+        ]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Drupal 8.7.14, 2020-05-20",
+            Technology::Drupal,
+            Some("8.7.14"),
+            Some(url3),
+        );
     }
 
     #[test]
@@ -0,0 +1,318 @@
+//! The exposure checker.
+//! This module contains the checker of the aggressive mode, reporting the
+//! backup files and the administration panels reachable on the asset. The
+//! directories probed are checked by the DirectoryListingChecker.
+//!
+//! The probes are intrusive: they request files that shouldn't exist and
+//! the administration panels, that are often monitored. They are only
+//! sent with --aggressive, for the detected technologies.
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequest, UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, truncate_evidence, Finding};
+use log::{info, trace};
+
+/// The suffixes of the backup files left by the editors and the admins
+const BACKUP_SUFFIXES: [&str; 4] = [".bak", "~", ".old", ".orig"];
+
+/// The files whose backups are guessed, by technology. The files of the
+/// Misconfiguration technology are guessed for all the assets.
+const BACKUP_FILES: &[(Technology, &str)] = &[
+    (Technology::Misconfiguration, "index.php"),
+    (Technology::PHP, "config.php"),
+    (Technology::WordPress, "wp-config.php"),
+    (Technology::Drupal, "sites/default/settings.php"),
+    (Technology::Typo3, "typo3conf/LocalConfiguration.php"),
+    (Technology::PhpMyAdmin, "config.inc.php"),
+    (Technology::Moodle, "config.php"),
+    (Technology::Matomo, "config/config.ini.php"),
+    (Technology::Prestashop, "config/settings.inc.php"),
+];
+
+/// The administration panels, by technology
+const ADMIN_PANELS: &[(Technology, &str)] = &[
+    (Technology::Misconfiguration, "admin/"),
+    (Technology::Misconfiguration, "administrator/"),
+    (Technology::WordPress, "wp-admin/"),
+    (Technology::Drupal, "user/login"),
+    (Technology::Typo3, "typo3/"),
+    (Technology::PhpMyAdmin, "phpmyadmin/"),
+    (Technology::Tomcat, "manager/html"),
+    (Technology::Tomcat, "host-manager/html"),
+    (Technology::Moodle, "admin/"),
+    (
+        Technology::Jira,
+        "secure/admin/ViewApplicationProperties.jspa",
+    ),
+];
+
+/// The directories that may be listed, by technology
+const DIRECTORIES: &[(Technology, &str)] = &[
+    (Technology::Misconfiguration, "backup/"),
+    (Technology::Misconfiguration, "uploads/"),
+    (Technology::WordPress, "wp-content/uploads/"),
+    (Technology::WordPress, "wp-content/plugins/"),
+    (Technology::Drupal, "sites/default/files/"),
+    (Technology::Typo3, "fileadmin/"),
+];
+
+/// The maximum number of chars of the body kept in the evidence
+const MAX_EVIDENCE_LENGTH: usize = 60;
+
+/// The checker
+pub struct ExposureChecker {}
+
+impl Checker for ExposureChecker {}
+
+impl ExposureChecker {
+    /// Creates a new ExposureChecker.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Returns the aggressive probes of the detected technologies: the
+    /// backups of their configuration files, their administration panels
+    /// and their directories. The generic probes are always returned.
+    pub fn get_url_requests(main_url: &str, technologies: &[Technology]) -> Vec<UrlRequest> {
+        let is_probed = |technology: &Technology| {
+            *technology == Technology::Misconfiguration || technologies.contains(technology)
+        };
+        let mut paths: Vec<String> = Vec::new();
+        for (_, file) in BACKUP_FILES.iter().filter(|(t, _)| is_probed(t)) {
+            for suffix in BACKUP_SUFFIXES {
+                paths.push(format!("{}{}", file, suffix));
+            }
+            // The swap file of vim: dir/.file.swp
+            let swap_file = match file.rsplit_once('/') {
+                Some((directory, name)) => format!("{}/.{}.swp", directory, name),
+                None => format!(".{}.swp", file),
+            };
+            paths.push(swap_file);
+        }
+        for (_, path) in ADMIN_PANELS
+            .iter()
+            .chain(DIRECTORIES.iter())
+            .filter(|(t, _)| is_probed(t))
+        {
+            paths.push(path.to_string());
+        }
+
+        let mut url_requests: Vec<UrlRequest> = Vec::new();
+        for path in paths {
+            let url_request = UrlRequest::from_path(main_url, &format!("/{}", path), false);
+            if !url_requests
+                .iter()
+                .any(|existing| existing.url == url_request.url)
+            {
+                url_requests.push(url_request);
+            }
+        }
+        url_requests
+    }
+
+    /// Returns the path of the URL, without the query string.
+    fn get_path(url: &str) -> &str {
+        let path = url
+            .split_once("://")
+            .and_then(|(_, rest)| rest.find('/').map(|position| &rest[position..]))
+            .unwrap_or("/");
+        path.split(['?', '#']).next().unwrap_or(path)
+    }
+
+    /// Checks whether the response is an HTML page.
+    fn is_html(url_response: &UrlResponse) -> bool {
+        let body = url_response.body.trim_start().to_lowercase();
+        url_response
+            .headers
+            .get("Content-type")
+            .is_some_and(|content_type| content_type.contains("html"))
+            || body.starts_with("<!doctype html")
+            || body.starts_with("<html")
+    }
+
+    /// Checks whether the response is a backup file: a file which isn't
+    /// an HTML page at a backup path.
+    fn check_backup(&self, url_response: &UrlResponse) -> Option<Finding> {
+        let path = Self::get_path(&url_response.url);
+        let is_backup_path = BACKUP_SUFFIXES
+            .iter()
+            .chain([".swp"].iter())
+            .any(|suffix| path.ends_with(suffix));
+        if !is_backup_path
+            || !url_response.has_status(&[200])
+            || url_response.body.trim().is_empty()
+            || Self::is_html(url_response)
+        {
+            return None;
+        }
+        info!("Backup file found at {}", url_response.url);
+        let evidence = truncate_evidence(url_response.body.trim(), MAX_EVIDENCE_LENGTH, 0);
+        let evidence_text = format!(
+            "{}: a backup file is exposed, it may reveal the source code or the credentials. We found \"{}\" at this url: {}",
+            Technology::Misconfiguration,
+            evidence,
+            url_response.url
+        );
+        Some(Finding::new(
+            Technology::Misconfiguration,
+            None,
+            &evidence,
+            &evidence_text,
+            Some(&url_response.url),
+        ))
+    }
+
+    /// Checks whether the response is a reachable administration panel,
+    /// even if it's protected by an authentication.
+    fn check_admin_panel(&self, url_response: &UrlResponse) -> Option<Finding> {
+        let path = Self::get_path(&url_response.url);
+        let is_admin_path = ADMIN_PANELS
+            .iter()
+            .any(|(_, admin_path)| path.ends_with(&format!("/{}", admin_path)));
+        if !is_admin_path || !url_response.has_status(&[200, 401]) {
+            return None;
+        }
+        info!("Administration panel found at {}", url_response.url);
+        let evidence = format!("HTTP {}", url_response.status_code);
+        let evidence_text = format!(
+            "{}: an administration panel is reachable, it should be restricted to the admins. We found \"{}\" at this url: {}",
+            Technology::Misconfiguration,
+            evidence,
+            url_response.url
+        );
+        Some(Finding::new(
+            Technology::Misconfiguration,
+            None,
+            &evidence,
+            &evidence_text,
+            Some(&url_response.url),
+        ))
+    }
+}
+
+impl HttpChecker for ExposureChecker {
+    /// Check the responses to the aggressive probes.
+    /// A response gives at most one finding.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running ExposureChecker::check_http()");
+        data.iter()
+            .filter(|url_response| url_response.request_type == UrlRequestType::Default)
+            .filter_map(|url_response| {
+                self.check_backup(url_response)
+                    .or_else(|| self.check_admin_panel(url_response))
+            })
+            .collect()
+    }
+
+    /// The findings are misconfigurations
+    fn get_technology(&self) -> Technology {
+        Technology::Misconfiguration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+    use std::collections::HashMap;
+
+    #[test]
+    fn get_url_requests_works() {
+        let main_url = "https://www.example.com/blog/";
+        let urls: Vec<String> = ExposureChecker::get_url_requests(main_url, &[])
+            .into_iter()
+            .map(|url_request| url_request.url)
+            .collect();
+        assert!(urls.contains(&"https://www.example.com/index.php.bak".to_string()));
+        assert!(urls.contains(&"https://www.example.com/.index.php.swp".to_string()));
+        assert!(urls.contains(&"https://www.example.com/admin/".to_string()));
+        assert!(!urls.iter().any(|url| url.contains("wp-config")));
+
+        let urls: Vec<String> =
+            ExposureChecker::get_url_requests(main_url, &[Technology::WordPress])
+                .into_iter()
+                .map(|url_request| url_request.url)
+                .collect();
+        assert!(urls.contains(&"https://www.example.com/wp-config.php~".to_string()));
+        assert!(urls.contains(&"https://www.example.com/wp-admin/".to_string()));
+        assert!(urls.contains(&"https://www.example.com/wp-content/uploads/".to_string()));
+    }
+
+    #[test]
+    fn exposures_match() {
+        let checker = ExposureChecker::new();
+        let url1 = "https://www.example.com/wp-config.php.bak";
+        let url_response1 = UrlResponse::new(
+            url1,
+            HashMap::new(),
+            "<?php\ndefine( 'DB_NAME', 'wordpress' );\ndefine( 'DB_USER', 'wp' );",
+            UrlRequestType::Default,
+            200,
+        );
+        let url2 = "https://www.example.com/manager/html";
+        let url_response2 =
+            UrlResponse::new(url2, HashMap::new(), "", UrlRequestType::Default, 401);
+
+        let findings = checker.check_http(&[url_response1, url_response2]);
+        assert_eq!(2, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "<?php\ndefine( 'DB_NAME', 'wordpress' );",
+            Technology::Misconfiguration,
+            None,
+            Some(url1),
+        );
+        assert!(findings[0]
+            .evidence_text
+            .contains("a backup file is exposed"));
+        check_finding_fields(
+            &findings[1],
+            "HTTP 401",
+            Technology::Misconfiguration,
+            None,
+            Some(url2),
+        );
+        assert!(findings[1]
+            .evidence_text
+            .contains("an administration panel"));
+    }
+
+    #[test]
+    fn exposures_dont_match() {
+        let checker = ExposureChecker::new();
+        let mut headers = HashMap::new();
+        headers.insert("Content-type".to_string(), "text/html".to_string());
+        let url_responses = vec![
+            // A custom page answered for the backup file
+            UrlResponse::new(
+                "https://www.example.com/index.php~",
+                headers,
+                "<p>Not found</p>",
+                UrlRequestType::Default,
+                200,
+            ),
+            UrlResponse::new(
+                "https://www.example.com/wp-config.php.old",
+                HashMap::new(),
+                "Not found",
+                UrlRequestType::Default,
+                404,
+            ),
+            UrlResponse::new(
+                "https://www.example.com/admin/",
+                HashMap::new(),
+                "Forbidden",
+                UrlRequestType::Default,
+                403,
+            ),
+            UrlResponse::new(
+                "https://www.example.com/about/",
+                HashMap::new(),
+                "<h2>Index of our products</h2>",
+                UrlRequestType::Default,
+                200,
+            ),
+        ];
+        assert!(checker.check_http(&url_responses).is_empty());
+    }
+}
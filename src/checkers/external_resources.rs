@@ -0,0 +1,299 @@
+//! The external resources checker.
+//! This module contains the checker used to list the third-party origins
+//! of the scripts and the stylesheets of the pages, and to report the
+//! mixed content.
+//!
+//! These findings are informational, they give the supply chain of the
+//! pages: each third-party origin can change the scripts it serves. The
+//! scripts and the stylesheets loaded over HTTP by an HTTPS page (mixed
+//! content) can be tampered with, they're a misconfiguration.
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use crate::readers::http::HttpReader;
+use log::{info, trace};
+
+/// An origin of the resources of the pages
+struct ExternalOrigin {
+    /// The origin (e.g. https://cdn.jsdelivr.net)
+    origin: String,
+    /// The number of scripts loaded from it
+    scripts: usize,
+    /// The number of stylesheets loaded from it
+    stylesheets: usize,
+    /// The URL of the first page loading it
+    page_url: String,
+}
+
+/// The external resources checker
+pub struct ExternalResourcesChecker<'a> {
+    /// The reader extracting the URLs of the scripts and the stylesheets
+    http_reader: HttpReader<'a>,
+}
+
+impl Checker for ExternalResourcesChecker<'_> {}
+
+impl ExternalResourcesChecker<'_> {
+    /// Creates a new ExternalResourcesChecker.
+    pub fn new() -> Self {
+        Self {
+            http_reader: HttpReader::new(),
+        }
+    }
+
+    /// Returns the scheme and the host (with the port, if any) of the URL.
+    fn get_scheme_host(url: &str) -> Option<(String, String)> {
+        let (scheme, rest) = url.split_once("://")?;
+        let host = rest.split(['/', '?', '#']).next()?;
+        if host.is_empty() {
+            return None;
+        }
+        Some((scheme.to_lowercase(), host.to_lowercase()))
+    }
+
+    /// Returns the finding of the mixed content of the page, if any.
+    fn check_mixed_content(
+        &self,
+        url_response: &UrlResponse,
+        http_urls: &[String],
+    ) -> Option<Finding> {
+        let first_url = http_urls.first()?;
+        info!(
+            "{} resources loaded over HTTP by {}",
+            http_urls.len(),
+            url_response.url
+        );
+        let evidence_text = format!(
+            "{}: mixed content, {} script(s) or stylesheet(s) are loaded over HTTP by the HTTPS page (e.g. \"{}\") at this url: {}",
+            Technology::Misconfiguration,
+            http_urls.len(),
+            first_url,
+            url_response.url
+        );
+        Some(Finding::new(
+            Technology::Misconfiguration,
+            None,
+            first_url,
+            &evidence_text,
+            Some(&url_response.url),
+        ))
+    }
+
+    /// Returns the finding of a third-party origin.
+    fn get_origin_finding(&self, external_origin: &ExternalOrigin) -> Finding {
+        let resources = match (external_origin.scripts, external_origin.stylesheets) {
+            (scripts, 0) => format!("{} script(s)", scripts),
+            (0, stylesheets) => format!("{} stylesheet(s)", stylesheets),
+            (scripts, stylesheets) => {
+                format!("{} script(s) and {} stylesheet(s)", scripts, stylesheets)
+            }
+        };
+        let evidence_text = format!(
+            "{}: {} loaded from the third-party origin \"{}\" at this url: {}",
+            Technology::Infrastructure,
+            resources,
+            external_origin.origin,
+            external_origin.page_url
+        );
+        Finding::new(
+            Technology::Infrastructure,
+            None,
+            &external_origin.origin,
+            &evidence_text,
+            Some(&external_origin.page_url),
+        )
+    }
+}
+
+impl HttpChecker for ExternalResourcesChecker<'_> {
+    /// Check the origins of the scripts and the stylesheets of the pages.
+    ///
+    /// Returns one finding by third-party origin, deduplicated across the
+    /// pages, and one finding by HTTPS page having mixed content.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running ExternalResourcesChecker::check_http()");
+        let mut findings = Vec::new();
+        let mut external_origins: Vec<ExternalOrigin> = Vec::new();
+        // Only the pages are checked, not their scripts or stylesheets
+        for url_response in data
+            .iter()
+            .filter(|url_response| url_response.request_type == UrlRequestType::Default)
+        {
+            let (page_scheme, page_host) = match Self::get_scheme_host(&url_response.url) {
+                Some(scheme_host) => scheme_host,
+                None => continue,
+            };
+            let url = &url_response.url;
+            let body = &url_response.body;
+            let scripts = self.http_reader.extract_urls(url, body, None, "scripts");
+            let stylesheets = self
+                .http_reader
+                .extract_urls(url, body, Some(".css"), "css");
+            let resources = scripts
+                .iter()
+                .map(|url_request| (&url_request.url, true))
+                .chain(
+                    stylesheets
+                        .iter()
+                        .map(|url_request| (&url_request.url, false)),
+                );
+
+            let mut http_urls: Vec<String> = Vec::new();
+            for (resource_url, is_script) in resources {
+                let (scheme, host) = match Self::get_scheme_host(resource_url) {
+                    Some(scheme_host) => scheme_host,
+                    None => continue,
+                };
+                if scheme == "http" && !http_urls.contains(resource_url) {
+                    http_urls.push(resource_url.to_string());
+                }
+                if host == page_host {
+                    continue;
+                }
+                let origin = format!("{}://{}", scheme, host);
+                let index = match external_origins.iter().position(|o| o.origin == origin) {
+                    Some(index) => index,
+                    None => {
+                        external_origins.push(ExternalOrigin {
+                            origin,
+                            scripts: 0,
+                            stylesheets: 0,
+                            page_url: url.to_string(),
+                        });
+                        external_origins.len() - 1
+                    }
+                };
+                if is_script {
+                    external_origins[index].scripts += 1;
+                } else {
+                    external_origins[index].stylesheets += 1;
+                }
+            }
+            if page_scheme == "https" {
+                findings.extend(self.check_mixed_content(url_response, &http_urls));
+            }
+        }
+        findings.extend(
+            external_origins
+                .iter()
+                .map(|external_origin| self.get_origin_finding(external_origin)),
+        );
+        findings
+    }
+
+    /// The external resources are part of the infrastructure
+    fn get_technology(&self) -> Technology {
+        Technology::Infrastructure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+    use std::collections::HashMap;
+
+    #[test]
+    fn external_origins_match() {
+        let checker = ExternalResourcesChecker::new();
+        let url1 = "https://www.example.com/";
+        let body1 = r#"<link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/bootstrap@5.3.3/dist/css/bootstrap.min.css">
+        <link rel="stylesheet" href="/css/main.css">
+        <script src="https://cdn.jsdelivr.net/npm/bootstrap@5.3.3/dist/js/bootstrap.bundle.min.js"></script>
+        <script src="//www.googletagmanager.com/gtag/js?id=G-XXXX"></script>
+        <script src="/js/app.js"></script>"#;
+        let url2 = "https://www.example.com/contact";
+        let body2 = r#"<script src="https://www.googletagmanager.com/gtag/js?id=G-XXXX"></script>"#;
+        let url_responses = vec![
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200),
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 200),
+        ];
+
+        let findings = checker.check_http(&url_responses);
+        assert_eq!(2, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "https://cdn.jsdelivr.net",
+            Technology::Infrastructure,
+            None,
+            Some(url1),
+        );
+        assert!(findings[0]
+            .evidence_text
+            .starts_with("Infrastructure: 1 script(s) and 1 stylesheet(s) loaded from"));
+        check_finding_fields(
+            &findings[1],
+            "https://www.googletagmanager.com",
+            Technology::Infrastructure,
+            None,
+            Some(url1),
+        );
+        assert!(findings[1]
+            .evidence_text
+            .starts_with("Infrastructure: 2 script(s) loaded from"));
+    }
+
+    #[test]
+    fn mixed_content_matches() {
+        let checker = ExternalResourcesChecker::new();
+        let url = "https://www.example.com/";
+        let body = r#"<script src="http://www.example.com/js/app.js"></script>
+        <link rel="stylesheet" href="http://fonts.example.net/font.css">"#;
+        let url_response =
+            UrlResponse::new(url, HashMap::new(), body, UrlRequestType::Default, 200);
+
+        let findings = checker.check_http(&[url_response]);
+        assert_eq!(2, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "http://www.example.com/js/app.js",
+            Technology::Misconfiguration,
+            None,
+            Some(url),
+        );
+        assert!(findings[0]
+            .evidence_text
+            .contains("mixed content, 2 script(s)"));
+        check_finding_fields(
+            &findings[1],
+            "http://fonts.example.net",
+            Technology::Infrastructure,
+            None,
+            Some(url),
+        );
+    }
+
+    #[test]
+    fn local_resources_dont_match() {
+        let checker = ExternalResourcesChecker::new();
+        let body = r#"<script src="/js/app.js"></script><script src="http://cdn.example.net/a.js"></script>"#;
+        // The scripts aren't pages
+        let url_responses = vec![
+            UrlResponse::new(
+                "https://www.example.com/",
+                HashMap::new(),
+                r#"<script src="/js/app.js"></script>"#,
+                UrlRequestType::Default,
+                200,
+            ),
+            UrlResponse::new(
+                "https://www.example.com/js/app.js",
+                HashMap::new(),
+                body,
+                UrlRequestType::JavaScript,
+                200,
+            ),
+        ];
+        assert!(checker.check_http(&url_responses).is_empty());
+        // The HTTP pages have no mixed content
+        let url_response = UrlResponse::new(
+            "http://www.example.com/",
+            HashMap::new(),
+            r#"<script src="http://www.example.com/js/app.js"></script>"#,
+            UrlRequestType::Default,
+            200,
+        );
+        assert!(checker.check_http(&[url_response]).is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+//! The GraphQL checker.
+//! This module contains the checker used to determine if a GraphQL
+//! endpoint is exposed by the asset, and if its introspection is enabled.
+//! https://graphql.org
+//!
+//! An enabled introspection leaks the whole schema, including the
+//! queries and mutations not used by the frontend. It should be disabled
+//! in production.
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The checker
+pub struct GraphQLChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> GraphQLChecker<'a> {
+    /// Creates the checker.
+    /// By doing so, the regex will is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: {"data":{"__schema":{"queryType":{"name":"Query"}}}}
+        let introspection_regex = Regex::new(
+            r#"(?P<wholematch>"__schema"\s*:\s*\{\s*"queryType"\s*:\s*\{\s*"name"\s*:\s*"[A-Za-z_]+"\s*\})"#,
+        )
+        .unwrap();
+        // Example: {"errors":[{"message":"Must provide query string."}]}
+        let error_regex = Regex::new(
+            r#"(?P<wholematch>"errors"\s*:\s*\[\s*\{\s*"message"\s*:\s*"[^"]*(query|Query|QUERY)[^"]*")"#,
+        )
+        .unwrap();
+        regexes.insert("http-body-introspection", (introspection_regex, 60, 60));
+        regexes.insert("http-body-error", (error_regex, 60, 60));
+        Self { regexes }
+    }
+
+    /// Checks in HTTP response body.
+    /// The introspection regex is only checked against the introspection
+    /// query, the error regex against the GraphQL endpoints.
+    fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running GraphQLChecker::check_http_body() on {}",
+            url_response.url
+        );
+
+        if !url_response.url.contains("/graphql") {
+            return None;
+        }
+        let (regex_name, evidence_text) = if url_response.url.contains("__schema") {
+            (
+                "http-body-introspection",
+                "$techno_name$$techno_version$ has been identified with its introspection enabled, which leaks the schema, because we found \"$evidence$\" at this url: $url_of_finding$",
+            )
+        } else {
+            (
+                "http-body-error",
+                "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$",
+            )
+        };
+
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get(regex_name)
+            .unwrap_or_else(|| panic!("Regex GraphQL/{} not found", regex_name));
+        // The regex matches
+        if let Some(caps) = regex.captures(&url_response.body) {
+            info!("Regex GraphQL/{} matches", regex_name);
+            return Some(self.extract_finding_from_captures(
+                caps,
+                Some(url_response),
+                keep_left.to_owned(),
+                keep_right.to_owned(),
+                Technology::GraphQL,
+                evidence_text,
+            ));
+        }
+        None
+    }
+}
+
+impl<'a> Checker for GraphQLChecker<'a> {}
+
+impl<'a> HttpChecker for GraphQLChecker<'a> {
+    /// Check for a HTTP scan.
+    ///
+    /// Returns only one finding, otherwise findings would be duplicated each
+    /// time it's found.
+    /// A finding showing an enabled introspection is always preferred
+    /// over the one only showing the endpoint.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running GraphQLChecker::check_http()");
+
+        let mut endpoint_finding = None;
+        for url_response in data {
+            // The GraphQL errors are usually sent with a 400 status code,
+            // so all the status codes are checked.
+            if url_response.request_type != UrlRequestType::Default {
+                continue;
+            }
+
+            if let Some(finding) = self.check_http_body(url_response) {
+                if finding.evidence.contains("__schema") {
+                    return vec![finding];
+                }
+                if endpoint_finding.is_none() {
+                    endpoint_finding = Some(finding);
+                }
+            }
+        }
+
+        if let Some(finding) = endpoint_finding {
+            return vec![finding];
+        }
+        Vec::new()
+    }
+
+    /// The technology supported by the checker
+    fn get_technology(&self) -> Technology {
+        Technology::GraphQL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn source_code_matches() {
+        let checker = GraphQLChecker::new();
+        let url_response_valid = UrlResponse::new(
+            "https://www.example.com/graphql?query=%7B__schema%7BqueryType%7Bname%7D%7D%7D",
+            HashMap::new(),
+            r#"{"data":{"__schema":{"queryType":{"name":"Query"}}}}"#,
+            UrlRequestType::Default,
+            200,
+        );
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        let finding = finding.unwrap();
+        check_finding_fields(
+            &finding,
+            r#""__schema":{"queryType":{"name":"Query"}"#,
+            Technology::GraphQL,
+            None,
+            Some("https://www.example.com/graphql?query=%7B__schema%7BqueryType%7Bname%7D%7D%7D"),
+        );
+        assert!(finding.evidence_text.contains("introspection enabled"));
+
+        let url_response_valid = UrlResponse::new(
+            "https://www.example.com/api/graphql",
+            HashMap::new(),
+            r#"{"errors":[{"message":"Must provide query string."}]}"#,
+            UrlRequestType::Default,
+            400,
+        );
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        let finding = finding.unwrap();
+        check_finding_fields(
+            &finding,
+            r#""errors":[{"message":"Must provide query string.""#,
+            Technology::GraphQL,
+            None,
+            Some("https://www.example.com/api/graphql"),
+        );
+        assert!(!finding.evidence_text.contains("introspection"));
+    }
+
+    #[test]
+    fn source_code_doesnt_match() {
+        let checker = GraphQLChecker::new();
+        // Introspection disabled
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/graphql?query=%7B__schema%7BqueryType%7Bname%7D%7D%7D",
+            HashMap::new(),
+            r#"{"errors":[{"message":"GraphQL introspection is not allowed"}]}"#,
+            UrlRequestType::Default,
+            400,
+        );
+        assert!(checker.check_http_body(&url_response_invalid).is_none());
+
+        // Not a GraphQL endpoint
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/api/users",
+            HashMap::new(),
+            r#"{"errors":[{"message":"Invalid query parameter"}]}"#,
+            UrlRequestType::Default,
+            400,
+        );
+        assert!(checker.check_http_body(&url_response_invalid).is_none());
+
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/graphql",
+            HashMap::new(),
+            "<html><body>Not Found</body></html>",
+            UrlRequestType::Default,
+            404,
+        );
+        assert!(checker.check_http_body(&url_response_invalid).is_none());
+    }
+
+    #[test]
+    fn introspection_is_preferred() {
+        let checker = GraphQLChecker::new();
+        let url_responses = vec![
+            UrlResponse::new(
+                "https://www.example.com/graphql",
+                HashMap::new(),
+                r#"{"errors":[{"message":"Query not found"}]}"#,
+                UrlRequestType::Default,
+                400,
+            ),
+            UrlResponse::new(
+                "https://www.example.com/graphql?query=%7B__schema%7BqueryType%7Bname%7D%7D%7D",
+                HashMap::new(),
+                r#"{"data":{"__schema":{"queryType":{"name":"RootQuery"}}}}"#,
+                UrlRequestType::Default,
+                200,
+            ),
+        ];
+        let findings = checker.check_http(&url_responses);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            r#""__schema":{"queryType":{"name":"RootQuery"}"#,
+            Technology::GraphQL,
+            None,
+            Some("https://www.example.com/graphql?query=%7B__schema%7BqueryType%7Bname%7D%7D%7D"),
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! The HAProxy checker.
+//! This module contains the checker used to determine if HAProxy is
+//! used in front of the asset.
+//! https://www.haproxy.org
+//!
+//! HAProxy is a load balancer, the headers like Server then belong to the
+//! backends rather than to the asset seen from the outside.
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The HAProxy checker
+pub struct HAProxyChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> HAProxyChecker<'a> {
+    /// Creates a new HAProxyChecker.
+    /// By doing so, the regex will is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // The error pages generated by HAProxy itself
+        // Example: <html><body><h1>503 Service Unavailable</h1>
+        // No server is available to handle this request.
+        // </body></html>
+        let body_regex = Regex::new(r"^<html><body>(?P<wholematch><h1>\d{3} [^<]+</h1>\s*(No server is available to handle this request\.|Request forbidden by administrative rules\.|The server didn't respond in time\.|The server returned an invalid or incomplete response\.|Your browser sent an invalid request\.|Your browser sent a request that this server could not understand\.))\s*</body></html>\s*$").unwrap();
+
+        regexes.insert("http-body-error-page", (body_regex, 40, 40));
+        Self { regexes }
+    }
+
+    /// Check for the technology in HTTP headers.
+    /// The X-Haproxy-* headers are added by some configurations.
+    fn check_http_headers(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running HAProxyChecker::check_http_headers() on {}",
+            url_response.url
+        );
+        // Sort the headers to get the same finding on each run
+        let mut header_names: Vec<&String> = url_response
+            .headers
+            .keys()
+            .filter(|name| name.to_lowercase().starts_with("x-haproxy-"))
+            .collect();
+        header_names.sort();
+        let header_name = header_names.first()?;
+        info!("HAProxy header {} found", header_name);
+
+        let evidence = format!("{}: {}", header_name, url_response.headers[*header_name]);
+        let evidence_text = format!(
+            "{} has been identified using the HTTP header \"{}\" returned at the following URL: {}",
+            Technology::HAProxy,
+            evidence,
+            url_response.url
+        );
+        Some(Finding::new(
+            Technology::HAProxy,
+            None,
+            &evidence,
+            &evidence_text,
+            Some(&url_response.url),
+        ))
+    }
+
+    /// Check for the technology in the body.
+    /// The error pages of HAProxy are sent without the Server header, one
+    /// would come from a backend.
+    fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running HAProxyChecker::check_http_body() on {}",
+            url_response.url
+        );
+        if url_response.headers.contains_key("Server") {
+            return None;
+        }
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get("http-body-error-page")
+            .expect("Regex HAProxy/http-body-error-page not found");
+
+        // The regex matches
+        if let Some(caps) = regex.captures(&url_response.body) {
+            info!("Regex HAProxy/http-body-error-page matches");
+            return Some(self.extract_finding_from_captures(
+                caps,
+                Some(url_response),
+                keep_left.to_owned(),
+                keep_right.to_owned(),
+                Technology::HAProxy,
+                "$techno_name$$techno_version$ has been identified because its error page \"$evidence$\" has been returned without a Server header at this page: $url_of_finding$",
+            ));
+        }
+        None
+    }
+}
+
+impl<'a> Checker for HAProxyChecker<'a> {}
+
+impl<'a> HttpChecker for HAProxyChecker<'a> {
+    /// Check if the asset is behind HAProxy.
+    /// It looks in the X-Haproxy-* HTTP headers and in the error pages
+    /// generated by HAProxy.
+    ///
+    /// Returns only one finding, otherwise findings would be duplicated each
+    /// time it's found.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running HAProxyChecker::check_http()");
+
+        for url_response in data {
+            // JavaScript files could be hosted on a different server
+            // Don't check the JavaScript files to avoid false positive,
+            // Check only the "main" requests.
+            if url_response.request_type != UrlRequestType::Default {
+                continue;
+            }
+
+            // Check in HTTP headers first
+            if let Some(finding) = self.check_http_headers(url_response) {
+                return vec![finding];
+            }
+            // Check in response body then
+            if let Some(finding) = self.check_http_body(url_response) {
+                return vec![finding];
+            }
+        }
+        Vec::new()
+    }
+
+    /// This checker supports HAProxy
+    fn get_technology(&self) -> Technology {
+        Technology::HAProxy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn header_matches() {
+        let checker = HAProxyChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("Server".to_string(), "Apache".to_string());
+        headers1.insert("X-haproxy-server-state".to_string(), "UP 2/3".to_string());
+        let url1 = "https://www.example.com/";
+        let url_response_valid =
+            UrlResponse::new(url1, headers1, "the body", UrlRequestType::Default, 200);
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "X-haproxy-server-state: UP 2/3",
+            Technology::HAProxy,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn header_doesnt_match() {
+        let checker = HAProxyChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("Server".to_string(), "nginx".to_string());
+        headers1.insert("X-proxy".to_string(), "haproxy".to_string());
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/",
+            headers1,
+            "the body",
+            UrlRequestType::Default,
+            200,
+        );
+        let finding = checker.check_http_headers(&url_response_invalid);
+        assert!(finding.is_none());
+    }
+
+    #[test]
+    fn source_code_matches() {
+        let checker = HAProxyChecker::new();
+        let body1 = "<html><body><h1>503 Service Unavailable</h1>\nNo server is available to handle this request.\n</body></html>\n";
+        let url1 = "https://www.example.com/";
+        let mut url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 503);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "<h1>503 Service Unavailable</h1>",
+            Technology::HAProxy,
+            None,
+            Some(url1),
+        );
+
+        let body2 = "<html><body><h1>403 Forbidden</h1>\nRequest forbidden by administrative rules.\n</body></html>\n";
+        url_response_valid.body = body2.to_string();
+        url_response_valid.status_code = 403;
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "<h1>403 Forbidden</h1>",
+            Technology::HAProxy,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn source_code_doesnt_match() {
+        let checker = HAProxyChecker::new();
+        let body = "<html><body><h1>503 Service Unavailable</h1>\nNo server is available to handle this request.\n</body></html>\n";
+        // A backend sending the same page gives its Server header
+        let mut headers1 = HashMap::new();
+        headers1.insert("Server".to_string(), "Apache".to_string());
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/",
+            headers1,
+            body,
+            UrlRequestType::Default,
+            503,
+        );
+        let finding = checker.check_http_body(&url_response_invalid);
+        assert!(finding.is_none());
+
+        let body = "<html><body><h1>503 Service Unavailable</h1>\nWe are under maintenance.\n</body></html>\n";
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/",
+            HashMap::new(),
+            body,
+            UrlRequestType::Default,
+            503,
+        );
+        let finding = checker.check_http_body(&url_response_invalid);
+        assert!(finding.is_none());
+    }
+
+    #[test]
+    fn doesnt_find_match_in_url_responses() {
+        let checker = HAProxyChecker::new();
+        let body = "<html><body><h1>503 Service Unavailable</h1>\nNo server is available to handle this request.\n</body></html>\n";
+        let url_response_invalid = UrlResponse::new(
+            "https://cdn.example.com/app.js",
+            HashMap::new(),
+            body,
+            UrlRequestType::JavaScript,
+            503,
+        );
+        let findings = checker.check_http(&[url_response_invalid]);
+        assert!(
+            findings.is_empty(),
+            "HAProxy must not be detected against JavaScript URLs to avoid false positive"
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! The hint checker.
+//! This module contains the checker used to extract generic hints about
+//! the technologies used by the asset: the page title, the generator and
+//! the "powered by" mentions.
+//!
+//! These findings have a low confidence, they are only given when no
+//! other checker found anything (see `--hints`). They help to understand
+//! an unrecognized stack, and to decide which checker to write next.
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Confidence, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The maximum number of chars of a hint kept in the evidence
+const MAX_HINT_LENGTH: usize = 100;
+
+/// The hint checker
+pub struct HintChecker<'a> {
+    /// The regexes used to extract the hints, the hint is in the
+    /// "hint" group
+    regexes: HashMap<&'a str, Regex>,
+}
+
+impl<'a> HintChecker<'a> {
+    /// Creates a new HintChecker.
+    /// By doing so, the regex will is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: <title>Router Login</title>
+        let title_regex = Regex::new(r"(?is)<title[^>]*>(?P<hint>[^<]+)</title>").unwrap();
+        // Example: <meta name="generator" content="Hugo 0.121.1">
+        let generator_regex = Regex::new(
+            r#"(?i)<meta\s+(name=['"]generator['"]\s+content=['"](?P<hint>[^'"]+)['"]|content=['"](?P<hint2>[^'"]+)['"]\s+name=['"]generator['"])"#,
+        )
+        .unwrap();
+        // Example: Powered by <a href="https://example.org">Example CMS</a>
+        let powered_by_regex = Regex::new(
+            r"(?i)powered\s+by\s*:?\s*(<a\s[^>]*>\s*)?(?P<hint>[a-z0-9][a-z0-9 ._/-]{1,40}[a-z0-9])",
+        )
+        .unwrap();
+        regexes.insert("title", title_regex);
+        regexes.insert("generator", generator_regex);
+        regexes.insert("powered-by", powered_by_regex);
+        Self { regexes }
+    }
+
+    /// Extracts the hint of the given kind from the body.
+    fn extract_hint(&self, kind: &str, body: &str) -> Option<String> {
+        let regex = self
+            .regexes
+            .get(kind)
+            .unwrap_or_else(|| panic!("Regex Hint/{} not found", kind));
+        let caps = regex.captures(body)?;
+        let hint = caps.name("hint").or_else(|| caps.name("hint2"))?;
+        // Keep the hint on one line
+        let hint: String = hint
+            .as_str()
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .chars()
+            .take(MAX_HINT_LENGTH)
+            .collect();
+        if hint.is_empty() {
+            return None;
+        }
+        Some(hint)
+    }
+
+    /// Checks for the hint of the given kind in the response.
+    fn check_hint(&self, url_response: &UrlResponse, kind: &str) -> Option<Finding> {
+        trace!(
+            "Running HintChecker::check_hint() on {} for {}",
+            url_response.url,
+            kind
+        );
+        let (hint, source) = match kind {
+            // The X-Powered-By header is an obvious "powered by" mention
+            "powered-by" => match url_response.headers.get("X-powered-by") {
+                Some(header) => (
+                    header.chars().take(MAX_HINT_LENGTH).collect(),
+                    "the X-Powered-By header",
+                ),
+                None => (
+                    self.extract_hint(kind, &url_response.body)?,
+                    "a \"powered by\" mention",
+                ),
+            },
+            "generator" => (
+                self.extract_hint(kind, &url_response.body)?,
+                "the generator",
+            ),
+            _ => (
+                self.extract_hint(kind, &url_response.body)?,
+                "the page title",
+            ),
+        };
+        info!("Hint {} found: {}", kind, hint);
+
+        let evidence_text = format!(
+            "{}: {} is \"{}\" at this url: {}",
+            Technology::Hint,
+            source,
+            hint,
+            url_response.url
+        );
+        Some(
+            Finding::new(
+                Technology::Hint,
+                None,
+                &hint,
+                &evidence_text,
+                Some(&url_response.url),
+            )
+            .with_confidence(Confidence::Low),
+        )
+    }
+}
+
+impl<'a> Checker for HintChecker<'a> {}
+
+impl<'a> HttpChecker for HintChecker<'a> {
+    /// Check for the hints.
+    ///
+    /// Returns one finding by kind of hint, the first response having
+    /// the hint is used.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running HintChecker::check_http()");
+
+        let mut findings = Vec::new();
+        for kind in ["title", "generator", "powered-by"] {
+            // JavaScript files could be hosted on a different server
+            // Check only the "main" requests.
+            let finding = data
+                .iter()
+                .filter(|url_response| url_response.request_type == UrlRequestType::Default)
+                .find_map(|url_response| self.check_hint(url_response, kind));
+            if let Some(finding) = finding {
+                findings.push(finding);
+            }
+        }
+        findings
+    }
+
+    /// This checker supports the hints
+    fn get_technology(&self) -> Technology {
+        Technology::Hint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn hints_match() {
+        let checker = HintChecker::new();
+        let url1 = "https://www.example.com/";
+        let body1 = r#"<html><head><title>
+            Acme   Router Login
+        </title><meta content="Hugo 0.121.1" name="generator"></head>
+        <body><footer>Powered by <a href="https://example.org">Example CMS</a></footer></body></html>"#;
+        let url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+
+        let findings = checker.check_http(&[url_response_valid]);
+        assert_eq!(3, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Acme Router Login",
+            Technology::Hint,
+            None,
+            Some(url1),
+        );
+        check_finding_fields(
+            &findings[1],
+            "Hugo 0.121.1",
+            Technology::Hint,
+            None,
+            Some(url1),
+        );
+        check_finding_fields(
+            &findings[2],
+            "Example CMS",
+            Technology::Hint,
+            None,
+            Some(url1),
+        );
+        assert!(findings[2].evidence_text.contains("\"powered by\" mention"));
+        assert!(findings
+            .iter()
+            .all(|finding| finding.confidence == Confidence::Low));
+    }
+
+    #[test]
+    fn header_is_preferred() {
+        let checker = HintChecker::new();
+        let url1 = "https://www.example.com/";
+        let mut headers = HashMap::new();
+        headers.insert("X-powered-by".to_string(), "Express".to_string());
+        let url_response_valid = UrlResponse::new(
+            url1,
+            headers,
+            "<p>Powered by Example CMS</p>",
+            UrlRequestType::Default,
+            200,
+        );
+        let finding = checker.check_hint(&url_response_valid, "powered-by");
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "Express",
+            Technology::Hint,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn hints_dont_match() {
+        let checker = HintChecker::new();
+        let url_responses = vec![
+            UrlResponse::new(
+                "https://www.example.com/",
+                HashMap::new(),
+                "<html><title> </title><body>Nothing to see</body></html>",
+                UrlRequestType::Default,
+                200,
+            ),
+            UrlResponse::new(
+                "https://cdn.example.net/app.js",
+                HashMap::new(),
+                "/* <title>Not a page</title> */",
+                UrlRequestType::JavaScript,
+                200,
+            ),
+        ];
+        assert!(checker.check_http(&url_responses).is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+//! The HTTP/2 checker.
+//! This module contains the checker used to report the support of HTTP/2
+//! by the asset, from the responses of the HTTP/2 probe (see `--http2`).
+//!
+//! Over HTTPS, the protocol is negotiated with ALPN: the server selects h2
+//! if it supports HTTP/2, http/1.1 otherwise. Over HTTP, the probe is sent
+//! in HTTP/2 directly (h2c), it only gets a response if h2c is supported.
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::UrlResponse;
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+
+/// The HTTP/2 checker
+pub struct Http2Checker {}
+
+impl Http2Checker {
+    /// Creates a new Http2Checker.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Checks the HTTP version of the response to the probe.
+    fn check_http_version(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!("Running Http2Checker::check_http_version()");
+        let http_version = url_response.http_version.as_ref()?;
+        let https = url_response.url.to_lowercase().starts_with("https://");
+        let description = match (http_version.as_str(), https) {
+            ("HTTP/2.0", true) => "HTTP/2 is supported, h2 has been selected with ALPN",
+            ("HTTP/2.0", false) => "HTTP/2 over cleartext (h2c) is supported",
+            (_, true) => "HTTP/2 is not supported, http/1.1 has been selected with ALPN",
+            // Without ALPN, the probe only gets a response in HTTP/2
+            (_, false) => return None,
+        };
+        info!("HTTP/2 probe on {}: {}", url_response.url, http_version);
+
+        let evidence_text = format!(
+            "{}: {} (response in {}) at this url: {}",
+            Technology::Infrastructure,
+            description,
+            http_version,
+            url_response.url
+        );
+        Some(Finding::new(
+            Technology::Infrastructure,
+            None,
+            http_version,
+            &evidence_text,
+            Some(&url_response.url),
+        ))
+    }
+}
+
+impl Checker for Http2Checker {}
+
+impl HttpChecker for Http2Checker {
+    /// Check for the support of HTTP/2.
+    /// Only the responses of the HTTP/2 probe have to be given, the other
+    /// requests are sent in HTTP/1.1.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running Http2Checker::check_http()");
+        data.iter()
+            .filter_map(|url_response| self.check_http_version(url_response))
+            .collect()
+    }
+
+    /// The support of HTTP/2 is part of the infrastructure
+    fn get_technology(&self) -> Technology {
+        Technology::Infrastructure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+    use crate::models::reqres::UrlRequestType;
+    use std::collections::HashMap;
+
+    #[test]
+    fn http2_is_reported() {
+        let checker = Http2Checker::new();
+        let url1 = "https://www.example.com/";
+        let url_response_h2 =
+            UrlResponse::new(url1, HashMap::new(), "", UrlRequestType::Default, 200)
+                .with_http_version("HTTP/2.0");
+        let url2 = "http://www.example.com/";
+        let url_response_h2c =
+            UrlResponse::new(url2, HashMap::new(), "", UrlRequestType::Default, 200)
+                .with_http_version("HTTP/2.0");
+
+        let findings = checker.check_http(&[url_response_h2, url_response_h2c]);
+        assert_eq!(2, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "HTTP/2.0",
+            Technology::Infrastructure,
+            None,
+            Some(url1),
+        );
+        assert!(findings[0].evidence_text.contains("h2 has been selected"));
+        check_finding_fields(
+            &findings[1],
+            "HTTP/2.0",
+            Technology::Infrastructure,
+            None,
+            Some(url2),
+        );
+        assert!(findings[1].evidence_text.contains("(h2c) is supported"));
+    }
+
+    #[test]
+    fn http1_is_reported_with_alpn() {
+        let checker = Http2Checker::new();
+        let url1 = "https://www.example.com/";
+        let url_response_http1 =
+            UrlResponse::new(url1, HashMap::new(), "", UrlRequestType::Default, 200)
+                .with_http_version("HTTP/1.1");
+        let url_response_unknown =
+            UrlResponse::new(url1, HashMap::new(), "", UrlRequestType::Default, 200);
+
+        let findings = checker.check_http(&[url_response_http1, url_response_unknown]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "HTTP/1.1",
+            Technology::Infrastructure,
+            None,
+            Some(url1),
+        );
+        assert!(findings[0].evidence_text.contains("not supported"));
+    }
+}
@@ -0,0 +1,185 @@
+//! The HTTP methods checker.
+//! This module contains the checker used to report the HTTP methods
+//! allowed by the asset, from the response to the OPTIONS probe (see
+//! `--http-methods`).
+//!
+//! The methods are listed in the Allow header, or in the Public header for
+//! IIS. TRACE allows the cross-site tracing, PUT and DELETE may allow to
+//! change the files of the server.
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::UrlResponse;
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+
+/// The headers listing the allowed methods
+const METHODS_HEADERS: [&str; 2] = ["Allow", "Public"];
+/// The methods that shouldn't be allowed on a production server
+const DANGEROUS_METHODS: [&str; 5] = ["TRACE", "TRACK", "PUT", "DELETE", "CONNECT"];
+
+/// The HTTP methods checker
+pub struct HttpMethodsChecker {}
+
+impl HttpMethodsChecker {
+    /// Creates a new HttpMethodsChecker.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Checks the methods listed in the response to the OPTIONS probe.
+    /// The allowed methods are informational, the dangerous ones are a
+    /// misconfiguration.
+    fn check_allowed_methods(&self, url_response: &UrlResponse) -> Vec<Finding> {
+        trace!("Running HttpMethodsChecker::check_allowed_methods()");
+        let (header_name, header_value) = match METHODS_HEADERS.iter().find_map(|header_name| {
+            url_response
+                .headers
+                .get(*header_name)
+                .map(|header_value| (header_name, header_value))
+        }) {
+            Some(header) => header,
+            None => return Vec::new(),
+        };
+        let methods: Vec<String> = header_value
+            .split(',')
+            .map(|method| method.trim().to_uppercase())
+            .filter(|method| !method.is_empty())
+            .collect();
+        if methods.is_empty() {
+            return Vec::new();
+        }
+        info!(
+            "HTTP methods allowed on {}: {:?}",
+            url_response.url, methods
+        );
+
+        let evidence = format!("{}: {}", header_name, header_value);
+        let evidence_text = format!(
+            "{}: the HTTP methods {} are allowed, we found \"{}\" in the response to OPTIONS at this url: {}",
+            Technology::Infrastructure,
+            methods.join(", "),
+            evidence,
+            url_response.url
+        );
+        let mut findings = vec![Finding::new(
+            Technology::Infrastructure,
+            None,
+            &evidence,
+            &evidence_text,
+            Some(&url_response.url),
+        )];
+
+        let dangerous: Vec<&str> = DANGEROUS_METHODS
+            .into_iter()
+            .filter(|method| methods.iter().any(|allowed| allowed == method))
+            .collect();
+        if !dangerous.is_empty() {
+            info!("Dangerous HTTP methods allowed: {:?}", dangerous);
+            let evidence_text = format!(
+                "{}: the dangerous HTTP methods {} are allowed, they should be disabled. We found \"{}\" in the response to OPTIONS at this url: {}",
+                Technology::Misconfiguration,
+                dangerous.join(", "),
+                evidence,
+                url_response.url
+            );
+            findings.push(Finding::new(
+                Technology::Misconfiguration,
+                None,
+                &evidence,
+                &evidence_text,
+                Some(&url_response.url),
+            ));
+        }
+        findings
+    }
+}
+
+impl Checker for HttpMethodsChecker {}
+
+impl HttpChecker for HttpMethodsChecker {
+    /// Check the HTTP methods allowed.
+    /// Only the responses of the OPTIONS probe have to be given, a GET
+    /// response can list the methods of another resource.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running HttpMethodsChecker::check_http()");
+        data.iter()
+            .flat_map(|url_response| self.check_allowed_methods(url_response))
+            .collect()
+    }
+
+    /// The allowed methods are part of the infrastructure
+    fn get_technology(&self) -> Technology {
+        Technology::Infrastructure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+    use crate::models::reqres::UrlRequestType;
+    use std::collections::HashMap;
+
+    #[test]
+    fn allowed_methods_match() {
+        let checker = HttpMethodsChecker::new();
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Allow".to_string(),
+            "GET,HEAD,POST,OPTIONS,TRACE".to_string(),
+        );
+        let url = "https://www.example.com/";
+        let url_response = UrlResponse::new(url, headers, "", UrlRequestType::Default, 200);
+        let findings = checker.check_http(&[url_response]);
+        assert_eq!(2, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Allow: GET,HEAD,POST,OPTIONS,TRACE",
+            Technology::Infrastructure,
+            None,
+            Some(url),
+        );
+        assert!(findings[0]
+            .evidence_text
+            .contains("GET, HEAD, POST, OPTIONS, TRACE are allowed"));
+        check_finding_fields(
+            &findings[1],
+            "Allow: GET,HEAD,POST,OPTIONS,TRACE",
+            Technology::Misconfiguration,
+            None,
+            Some(url),
+        );
+        assert!(findings[1]
+            .evidence_text
+            .contains("the dangerous HTTP methods TRACE are allowed"));
+
+        // IIS lists them in the Public header
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Public".to_string(),
+            "OPTIONS, TRACE, GET, HEAD, POST, PUT, DELETE".to_string(),
+        );
+        let url_response = UrlResponse::new(url, headers, "", UrlRequestType::Default, 200);
+        let findings = checker.check_http(&[url_response]);
+        assert_eq!(2, findings.len());
+        assert!(findings[1]
+            .evidence_text
+            .contains("the dangerous HTTP methods TRACE, PUT, DELETE are allowed"));
+    }
+
+    #[test]
+    fn allowed_methods_dont_match() {
+        let checker = HttpMethodsChecker::new();
+        let mut headers = HashMap::new();
+        headers.insert("Allow".to_string(), "GET, HEAD, OPTIONS".to_string());
+        let url = "https://www.example.com/";
+        let url_response = UrlResponse::new(url, headers, "", UrlRequestType::Default, 200);
+        let findings = checker.check_http(&[url_response]);
+        // Only informational
+        assert_eq!(1, findings.len());
+        assert_eq!(Technology::Infrastructure, findings[0].technology);
+
+        let url_response = UrlResponse::new(url, HashMap::new(), "", UrlRequestType::Default, 405);
+        assert!(checker.check_http(&[url_response]).is_empty());
+    }
+}
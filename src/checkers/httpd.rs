@@ -124,7 +124,10 @@ impl<'a> HttpChecker for ApacheHttpdChecker<'a> {
             // JavaScript files could be hosted on a different server
             // Don't check the JavaScript files to avoid false positive,
             // Check only the "main" requests.
-            if url_response.request_type != UrlRequestType::Default {
+            // A redirection to another host leads to another server.
+            if url_response.request_type != UrlRequestType::Default
+                || url_response.is_redirected_to_another_host()
+            {
                 continue;
             }
 
@@ -0,0 +1,308 @@
+//! The IIS checker.
+//! This module contains the checker used to determine if Microsoft IIS is
+//! used by the asset.
+//! https://www.iis.net
+//!
+//! IIS gives its version in the Server header, unless it has been removed
+//! by the administrators. The detailed error pages give the version too.
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The IIS checker
+pub struct IISChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> IISChecker<'a> {
+    /// Creates a new IISChecker.
+    /// By doing so, the regex will is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: Microsoft-IIS/10.0
+        // The version is missing when the header has been customized.
+        let header_regex =
+            Regex::new(r"^(?P<wholematch>Microsoft-IIS(\/(?P<version1>\d+\.\d+))?)\b").unwrap();
+        // The detailed error pages, displayed to the local requests or
+        // when the custom errors are disabled
+        // Example: <title>IIS 10.0 Detailed Error - 404.0 - Not Found</title>
+        let body_error_page_regex =
+            Regex::new(r"<title>(?P<wholematch>IIS (?P<version1>\d+\.\d+) Detailed Error)")
+                .unwrap();
+        // The default page of a fresh installation
+        // Example: <title>IIS Windows Server</title>
+        let body_default_page_regex =
+            Regex::new(r"<title>(?P<wholematch>IIS Windows( Server)?)</title>").unwrap();
+
+        regexes.insert("http-header", (header_regex, 30, 30));
+        regexes.insert("http-body-error-page", (body_error_page_regex, 30, 30));
+        regexes.insert("http-body-default-page", (body_default_page_regex, 30, 30));
+        Self { regexes }
+    }
+
+    /// Check for the technology in HTTP headers.
+    fn check_http_headers(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running IISChecker::check_http_headers() on {}",
+            url_response.url
+        );
+        // Check the HTTP headers of each UrlResponse
+        let headers_to_check = url_response.get_headers(&["Server".to_string()]);
+        let (regex_header, keep_left_header, keep_right_header) = self
+            .regexes
+            .get("http-header")
+            .expect("Regex IIS/http-header not found");
+
+        // Check in the headers to check present in this UrlResponse
+        for (header_name, header_value) in headers_to_check {
+            trace!("Checking header: {} / {}", header_name, header_value);
+            // The regex matches
+            if let Some(caps) = regex_header.captures(&header_value) {
+                info!("Regex IIS/http-header matches");
+                return Some(self.extract_finding_from_captures(
+                    caps,
+                    Some(url_response),
+                    keep_left_header.to_owned(),
+                    keep_right_header.to_owned(),
+                    Technology::IIS,
+                    &format!("$techno_name$$techno_version$ has been identified using the HTTP header \"{}: $evidence$\" returned at the following URL: $url_of_finding$", header_name),
+                ));
+            }
+        }
+        None
+    }
+
+    /// Check for the technology in the body.
+    fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running IISChecker::check_http_body() on {}",
+            url_response.url
+        );
+        let regexes = [
+            (
+                "http-body-error-page",
+                "$techno_name$$techno_version$ has been identified by looking at its error page \"$evidence$\" at this page: $url_of_finding$",
+            ),
+            (
+                "http-body-default-page",
+                "$techno_name$$techno_version$ has been identified because its default page \"$evidence$\" has been returned at this page: $url_of_finding$",
+            ),
+        ];
+
+        for (regex_name, template) in regexes {
+            let (regex, keep_left, keep_right) = self
+                .regexes
+                .get(regex_name)
+                .unwrap_or_else(|| panic!("Regex IIS/{} not found", regex_name));
+            // The regex matches
+            if let Some(caps) = regex.captures(&url_response.body) {
+                info!("Regex IIS/{} matches", regex_name);
+                return Some(self.extract_finding_from_captures(
+                    caps,
+                    Some(url_response),
+                    keep_left.to_owned(),
+                    keep_right.to_owned(),
+                    Technology::IIS,
+                    template,
+                ));
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Checker for IISChecker<'a> {}
+
+impl<'a> HttpChecker for IISChecker<'a> {
+    /// Check if the asset is running IIS.
+    /// It looks in the Server HTTP header, and in the error and default
+    /// pages.
+    ///
+    /// Returns only one finding, otherwise findings would be duplicated each
+    /// time it's found. A finding with a version is preferred.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running IISChecker::check_http()");
+        let mut versionless_finding = None;
+
+        for url_response in data {
+            // JavaScript files could be hosted on a different server
+            // Don't check the JavaScript files to avoid false positive,
+            // Check only the "main" requests.
+            // A redirection to another host leads to another server.
+            if url_response.request_type != UrlRequestType::Default
+                || url_response.is_redirected_to_another_host()
+            {
+                continue;
+            }
+
+            // Check in HTTP headers first, then in response body
+            for finding in [
+                self.check_http_headers(url_response),
+                self.check_http_body(url_response),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                if finding.version.is_some() {
+                    return vec![finding];
+                }
+                versionless_finding.get_or_insert(finding);
+            }
+        }
+        versionless_finding.into_iter().collect()
+    }
+
+    /// This checker supports IIS
+    fn get_technology(&self) -> Technology {
+        Technology::IIS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn header_matches() {
+        let checker = IISChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("Accept".to_string(), "text/html".to_string());
+        headers1.insert("Server".to_string(), "Microsoft-IIS/10.0".to_string());
+        let url1 = "https://www.example.com/";
+        let mut url_response_valid =
+            UrlResponse::new(url1, headers1, "the body", UrlRequestType::Default, 200);
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "Microsoft-IIS/10.0",
+            Technology::IIS,
+            Some("10.0"),
+            Some(url1),
+        );
+
+        let mut headers2 = HashMap::new();
+        headers2.insert("Server".to_string(), "Microsoft-IIS".to_string());
+        url_response_valid.headers = headers2;
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        let finding = finding.unwrap();
+        check_finding_fields(&finding, "Microsoft-IIS", Technology::IIS, None, Some(url1));
+        assert!(finding.version.is_none());
+    }
+
+    #[test]
+    fn header_doesnt_match() {
+        let checker = IISChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("Server".to_string(), "Microsoft-HTTPAPI/2.0".to_string());
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/",
+            headers1,
+            "the body",
+            UrlRequestType::Default,
+            200,
+        );
+        let finding = checker.check_http_headers(&url_response_invalid);
+        assert!(finding.is_none());
+    }
+
+    #[test]
+    fn source_code_matches() {
+        let checker = IISChecker::new();
+        let body1 = r#"<head><title>IIS 8.5 Detailed Error - 404.0 - Not Found</title>"#;
+        let url1 = "https://www.example.com/pageNotFoundNotFound";
+        let mut url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 404);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "IIS 8.5 Detailed Error",
+            Technology::IIS,
+            Some("8.5"),
+            Some(url1),
+        );
+
+        let body2 = r#"<head><title>IIS Windows Server</title></head>"#;
+        url_response_valid.body = body2.to_string();
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "IIS Windows Server",
+            Technology::IIS,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn version_is_preferred_over_hidden_version() {
+        let checker = IISChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("Server".to_string(), "Microsoft-IIS".to_string());
+        let url_response1 = UrlResponse::new(
+            "https://www.example.com/",
+            headers1,
+            "the body",
+            UrlRequestType::Default,
+            200,
+        );
+        let body2 = r#"<title>IIS 10.0 Detailed Error - 404.0 - Not Found</title>"#;
+        let url2 = "https://www.example.com/pageNotFoundNotFound";
+        let url_response2 =
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 404);
+        let findings = checker.check_http(&[url_response1.clone(), url_response2]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "IIS 10.0 Detailed Error",
+            Technology::IIS,
+            Some("10.0"),
+            Some(url2),
+        );
+
+        let findings = checker.check_http(std::slice::from_ref(&url_response1));
+        assert_eq!(1, findings.len());
+        assert!(findings[0].version.is_none());
+    }
+
+    #[test]
+    fn doesnt_find_match_in_url_responses() {
+        let checker = IISChecker::new();
+        let body1 = r#"How to configure IIS on Windows Server"#;
+        let url_response_invalid1 = UrlResponse::new(
+            "https://www.example.com/blog",
+            HashMap::new(),
+            body1,
+            UrlRequestType::Default,
+            200,
+        );
+        let mut headers1 = HashMap::new();
+        headers1.insert("Server".to_string(), "Microsoft-IIS/10.0".to_string());
+        let url_response_invalid2 = UrlResponse::new(
+            "https://cdn.example.com/app.js",
+            headers1,
+            "the body",
+            UrlRequestType::JavaScript,
+            200,
+        );
+        let findings = checker.check_http(&[url_response_invalid1, url_response_invalid2]);
+        assert!(
+            findings.is_empty(),
+            "IIS must not be detected against JavaScript URLs to avoid false positive"
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! The infrastructure checker.
+//! This module contains the checker used to report the caches and CDNs
+//! through which the asset is delivered.
+//!
+//! These findings are informational, they help to understand the
+//! delivery path: a scan may hit a cache or a CDN edge instead of the
+//! origin server.
+//!
+//! Cloudflare, Akamai and Sucuri are reported by the WAF/CDN checker.
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+
+/// The headers revealing the infrastructure, with what they reveal
+const INFRASTRUCTURE_HEADERS: &[(&str, &str)] = &[
+    ("X-amz-cf-id", "the Amazon CloudFront CDN"),
+    ("X-amz-cf-pop", "the Amazon CloudFront edge"),
+    ("X-served-by", "the Fastly or Varnish cache"),
+    ("X-cache", "a caching proxy"),
+    ("Server-timing", "the server timing metrics"),
+];
+/// The maximum number of chars of the header value kept in the evidence
+const MAX_VALUE_LENGTH: usize = 100;
+
+/// The infrastructure checker
+pub struct InfrastructureChecker {}
+
+impl Checker for InfrastructureChecker {}
+
+impl InfrastructureChecker {
+    /// Creates a new InfrastructureChecker.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Check for the given header in the response.
+    fn check_http_header(
+        &self,
+        url_response: &UrlResponse,
+        header_name: &str,
+        description: &str,
+    ) -> Option<Finding> {
+        trace!(
+            "Running InfrastructureChecker::check_http_header() on {}",
+            url_response.url
+        );
+        let headers = url_response.get_headers(&[header_name.to_string()]);
+        let header_value = headers.get(header_name)?;
+        info!("Header {} found", header_name);
+
+        let value: String = header_value.chars().take(MAX_VALUE_LENGTH).collect();
+        let evidence = format!("{}: {}", header_name, value);
+        let evidence_text = format!(
+            "{} has been identified, the asset is delivered through {}, using the HTTP header \"{}\" returned at the following URL: {}",
+            Technology::Infrastructure,
+            description,
+            evidence,
+            url_response.url
+        );
+        Some(Finding::new(
+            Technology::Infrastructure,
+            None,
+            &evidence,
+            &evidence_text,
+            Some(&url_response.url),
+        ))
+    }
+}
+
+impl HttpChecker for InfrastructureChecker {
+    /// Check for the headers revealing the infrastructure.
+    ///
+    /// Returns one finding by header, the first response having the
+    /// header is used.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running InfrastructureChecker::check_http()");
+
+        let mut findings = Vec::new();
+        for (header_name, description) in INFRASTRUCTURE_HEADERS {
+            // JavaScript files could be hosted on a different server
+            // Check only the "main" requests.
+            let finding = data
+                .iter()
+                .filter(|url_response| url_response.request_type == UrlRequestType::Default)
+                .find_map(|url_response| {
+                    self.check_http_header(url_response, header_name, description)
+                });
+            if let Some(finding) = finding {
+                findings.push(finding);
+            }
+        }
+        findings
+    }
+
+    /// This checker supports the infrastructure
+    fn get_technology(&self) -> Technology {
+        Technology::Infrastructure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+    use std::collections::HashMap;
+
+    #[test]
+    fn headers_match() {
+        let checker = InfrastructureChecker::new();
+        let url1 = "https://www.example.com/";
+        let mut headers1 = HashMap::new();
+        headers1.insert("X-amz-cf-id".to_string(), "4d2d8a3f".to_string());
+        headers1.insert("Server".to_string(), "CloudFront".to_string());
+        let url2 = "https://www.example.com/pageNotFoundNotFound";
+        let mut headers2 = HashMap::new();
+        headers2.insert("X-amz-cf-id".to_string(), "9e1f0b2c".to_string());
+        headers2.insert(
+            "Server-timing".to_string(),
+            "cfL4;desc=\"?rtt=12\"".to_string(),
+        );
+        let url_responses = vec![
+            UrlResponse::new(url1, headers1, "", UrlRequestType::Default, 200),
+            UrlResponse::new(url2, headers2, "", UrlRequestType::Default, 404),
+        ];
+
+        let findings = checker.check_http(&url_responses);
+        assert_eq!(2, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "X-amz-cf-id: 4d2d8a3f",
+            Technology::Infrastructure,
+            None,
+            Some(url1),
+        );
+        assert!(findings[0]
+            .evidence_text
+            .contains("the Amazon CloudFront CDN"));
+        check_finding_fields(
+            &findings[1],
+            "Server-timing: cfL4;desc=\"?rtt=12\"",
+            Technology::Infrastructure,
+            None,
+            Some(url2),
+        );
+    }
+
+    #[test]
+    fn headers_dont_match() {
+        let checker = InfrastructureChecker::new();
+        let mut headers = HashMap::new();
+        headers.insert("Server".to_string(), "cloudflare".to_string());
+        headers.insert("Cf-ray".to_string(), "8a1b2c3d4e5f6a7b-CDG".to_string());
+        let mut js_headers = HashMap::new();
+        js_headers.insert("X-cache".to_string(), "Hit from cloudfront".to_string());
+        let url_responses = vec![
+            UrlResponse::new(
+                "https://www.example.com/",
+                headers,
+                "",
+                UrlRequestType::Default,
+                200,
+            ),
+            UrlResponse::new(
+                "https://cdn.example.net/app.js",
+                js_headers,
+                "",
+                UrlRequestType::JavaScript,
+                200,
+            ),
+        ];
+        assert!(checker.check_http(&url_responses).is_empty());
+    }
+}
@@ -6,7 +6,7 @@
 use std::collections::HashMap;
 
 use super::{Checker, HttpChecker};
-use crate::models::{reqres::UrlResponse, technology::Technology, Finding};
+use crate::models::{reqres::UrlResponse, technology::Technology, Confidence, Finding};
 use log::{info, trace};
 use regex::Regex;
 
@@ -66,6 +66,12 @@ impl<'a> JQueryChecker<'a> {
             if caps_result.is_some() {
                 info!("Regex JQuery/{} matches", regex_name);
                 let caps = caps_result.unwrap();
+                // The minified sources are recognized by a pattern, the
+                // license comment gives the name
+                let confidence = match *regex_name {
+                    "http-body-comment" => Confidence::Certain,
+                    _ => Confidence::Medium,
+                };
                 return Some(self.extract_finding_from_captures(
 		    caps,
 		    Some(url_response),
@@ -73,7 +79,7 @@ impl<'a> JQueryChecker<'a> {
 		    keep_right.to_owned(),
 		    Technology::JQuery,
 		    "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$"
-		));
+		).with_confidence(confidence));
             }
         }
         None
@@ -185,6 +191,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn confidence_depends_on_the_regex() {
+        let checker = JQueryChecker::new();
+        let url = "https://www.example.com/js/jquery.min.js";
+        let url_response = UrlResponse::new(
+            url,
+            HashMap::new(),
+            "/*! jQuery v3.7.0 | (c) OpenJS Foundation */",
+            UrlRequestType::JavaScript,
+            200,
+        );
+        let finding = checker.check_http_body(&url_response).unwrap();
+        assert_eq!(Confidence::Certain, finding.confidence);
+
+        let url_response = UrlResponse::new(
+            url,
+            HashMap::new(),
+            r#"w.prototype={jquery:"3.3.1",constructor:w,length:0"#,
+            UrlRequestType::JavaScript,
+            200,
+        );
+        let finding = checker.check_http_body(&url_response).unwrap();
+        assert_eq!(Confidence::Medium, finding.confidence);
+    }
+
     #[test]
     fn comment_doesnt_match() {
         let checker = JQueryChecker::new();
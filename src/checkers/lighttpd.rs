@@ -0,0 +1,313 @@
+//! The Lighttpd checker.
+//! This module contains the checker used to determine if Lighttpd is
+//! used by the asset.
+//! https://www.lighttpd.net
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The Lighttpd checker
+pub struct LighttpdChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> LighttpdChecker<'a> {
+    /// Creates a new LighttpdChecker.
+    /// By doing so, the regex will is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: lighttpd/1.4.59
+        let header_regex =
+            Regex::new(r"^(?P<wholematch>lighttpd(\/(?P<version1>\d+(\.\d+(\.\d+)?)?))?)").unwrap();
+        // The directory listings have the signature in their footer
+        // Example: <div class="foot">lighttpd/1.4.59</div>
+        let body_signature_regex = Regex::new(
+            r#"<div class="foot">(?P<wholematch>lighttpd(\/(?P<version1>\d+\.\d+\.\d+))?)</div>"#,
+        )
+        .unwrap();
+        // The default error pages don't give the name, but their
+        // XHTML skeleton is specific to Lighttpd
+        // Example: <?xml version="1.0" encoding="iso-8859-1"?>
+        // <!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Transitional//EN"
+        //          "http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd">
+        // <html xmlns="http://www.w3.org/1999/xhtml" xml:lang="en" lang="en">
+        //  <head>
+        //   <title>404 Not Found</title>
+        //  </head>
+        //  <body>
+        //   <h1>404 Not Found</h1>
+        //  </body>
+        // </html>
+        let body_error_page_regex = Regex::new(r#"^<\?xml version="1\.0" encoding="iso-8859-1"\?>\s*<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1\.0 Transitional//EN"\s*"http://www\.w3\.org/TR/xhtml1/DTD/xhtml1-transitional\.dtd">\s*<html xmlns="http://www\.w3\.org/1999/xhtml" xml:lang="en" lang="en">\s*<head>\s*<title>(?P<title>\d{3} [^<]+)</title>\s*</head>\s*<body>\s*(?P<wholematch><h1>\d{3} [^<]+</h1>)\s*</body>\s*</html>\s*$"#).unwrap();
+
+        regexes.insert("http-header", (header_regex, 45, 45));
+        regexes.insert("http-body-signature", (body_signature_regex, 10, 15));
+        regexes.insert("http-body-error-page", (body_error_page_regex, 30, 30));
+        Self { regexes }
+    }
+
+    /// Check for the technology in HTTP headers.
+    fn check_http_headers(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running LighttpdChecker::check_http_headers() on {}",
+            url_response.url
+        );
+        // Check the HTTP headers of each UrlResponse
+        let headers_to_check =
+            url_response.get_headers(&["Server".to_string(), "X-powered-by".to_string()]);
+        let (regex_header, keep_left_header, keep_right_header) = self
+            .regexes
+            .get("http-header")
+            .expect("Regex Lighttpd/http-header not found");
+
+        // Check in the headers to check present in this UrlResponse
+        for (header_name, header_value) in headers_to_check {
+            trace!("Checking header: {} / {}", header_name, header_value);
+            // The regex matches
+            if let Some(caps) = regex_header.captures(&header_value) {
+                info!("Regex Lighttpd/http-header matches");
+                return Some(self.extract_finding_from_captures(
+                    caps,
+                    Some(url_response),
+                    keep_left_header.to_owned(),
+                    keep_right_header.to_owned(),
+                    Technology::Lighttpd,
+                    &format!("$techno_name$$techno_version$ has been identified using the HTTP header \"{}: $evidence$\" returned at the following URL: $url_of_finding$", header_name),
+                ));
+            }
+        }
+        None
+    }
+
+    /// Check for the technology in the body.
+    fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running LighttpdChecker::check_http_body() on {}",
+            url_response.url
+        );
+        // The regexes are sorted by relevance, the version is preferred
+        let regexes = [
+            (
+                "http-body-signature",
+                "$techno_name$$techno_version$ has been identified by looking at its signature \"$evidence$\" at this page: $url_of_finding$",
+            ),
+            (
+                "http-body-error-page",
+                "$techno_name$$techno_version$ has been identified because its default error page \"$evidence$\" has been returned at this page: $url_of_finding$",
+            ),
+        ];
+
+        for (regex_name, template) in regexes {
+            let (regex, keep_left, keep_right) = self
+                .regexes
+                .get(regex_name)
+                .unwrap_or_else(|| panic!("Regex Lighttpd/{} not found", regex_name));
+            // The regex matches
+            if let Some(caps) = regex.captures(&url_response.body) {
+                info!("Regex Lighttpd/{} matches", regex_name);
+                return Some(self.extract_finding_from_captures(
+                    caps,
+                    Some(url_response),
+                    keep_left.to_owned(),
+                    keep_right.to_owned(),
+                    Technology::Lighttpd,
+                    template,
+                ));
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Checker for LighttpdChecker<'a> {}
+
+impl<'a> HttpChecker for LighttpdChecker<'a> {
+    /// Check if the asset is running Lighttpd.
+    /// It looks in the following HTTP headers:
+    /// - Server
+    /// - X-Powered-By
+    ///
+    /// and in the directory listings and default error pages.
+    ///
+    /// Returns only one finding, otherwise findings would be duplicated each
+    /// time it's found.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running LighttpdChecker::check_http()");
+
+        for url_response in data {
+            // JavaScript files could be hosted on a different server
+            // Don't check the JavaScript files to avoid false positive,
+            // Check only the "main" requests.
+            if url_response.request_type != UrlRequestType::Default {
+                continue;
+            }
+
+            // Check in HTTP headers first
+            if let Some(finding) = self.check_http_headers(url_response) {
+                return vec![finding];
+            }
+            // Check in response body then
+            if let Some(finding) = self.check_http_body(url_response) {
+                return vec![finding];
+            }
+        }
+        Vec::new()
+    }
+
+    /// This checker supports Lighttpd
+    fn get_technology(&self) -> Technology {
+        Technology::Lighttpd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn header_matches() {
+        let checker = LighttpdChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("Accept".to_string(), "text/html".to_string());
+        headers1.insert("Server".to_string(), "lighttpd/1.4.59".to_string());
+        let url1 = "https://www.example.com/that.php?abc=def";
+        let mut url_response_valid =
+            UrlResponse::new(url1, headers1, "the body", UrlRequestType::Default, 200);
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "lighttpd/1.4.59",
+            Technology::Lighttpd,
+            Some("1.4.59"),
+            Some(url1),
+        );
+
+        let mut headers2 = HashMap::new();
+        headers2.insert("Server".to_string(), "lighttpd".to_string());
+        url_response_valid.headers = headers2;
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "lighttpd",
+            Technology::Lighttpd,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn header_doesnt_match() {
+        let checker = LighttpdChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("Server".to_string(), "nginx/1.22.2".to_string());
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/that.php?abc=def",
+            headers1,
+            "the body",
+            UrlRequestType::Default,
+            200,
+        );
+        let finding = checker.check_http_headers(&url_response_invalid);
+        assert!(finding.is_none());
+    }
+
+    #[test]
+    fn source_code_matches() {
+        let checker = LighttpdChecker::new();
+        let body1 = r#"</table></div><div class="foot">lighttpd/1.4.64</div></body></html>"#;
+        let url1 = "https://www.example.com/files/";
+        let mut url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "lighttpd/1.4.64",
+            Technology::Lighttpd,
+            Some("1.4.64"),
+            Some(url1),
+        );
+
+        let body2 = r#"<?xml version="1.0" encoding="iso-8859-1"?>
+<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Transitional//EN"
+         "http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd">
+<html xmlns="http://www.w3.org/1999/xhtml" xml:lang="en" lang="en">
+ <head>
+  <title>404 Not Found</title>
+ </head>
+ <body>
+  <h1>404 Not Found</h1>
+ </body>
+</html>
+"#;
+        url_response_valid.body = body2.to_string();
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "<h1>404 Not Found</h1>",
+            Technology::Lighttpd,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn source_code_doesnt_match() {
+        let checker = LighttpdChecker::new();
+        // A custom page using the same skeleton but with more content
+        let body = r#"<?xml version="1.0" encoding="iso-8859-1"?>
+<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Transitional//EN"
+         "http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd">
+<html xmlns="http://www.w3.org/1999/xhtml" xml:lang="en" lang="en">
+ <head>
+  <title>404 Not Found</title>
+ </head>
+ <body>
+  <h1>404 Not Found</h1>
+  <p>Go back to the <a href="/">home page</a>.</p>
+ </body>
+</html>"#;
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/pageNotFoundNotFound",
+            HashMap::new(),
+            body,
+            UrlRequestType::Default,
+            404,
+        );
+        let finding = checker.check_http_body(&url_response_invalid);
+        assert!(finding.is_none());
+    }
+
+    #[test]
+    fn doesnt_find_match_in_url_responses() {
+        let checker = LighttpdChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("Server".to_string(), "lighttpd/1.4.59".to_string());
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/abc-1/de-f1.js",
+            headers1,
+            "the body",
+            UrlRequestType::JavaScript,
+            200,
+        );
+        let findings = checker.check_http(&[url_response_invalid]);
+        assert!(
+            findings.is_empty(),
+            "Lighttpd must not be detected against JavaScript URLs to avoid false positive"
+        );
+    }
+}
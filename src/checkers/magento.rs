@@ -0,0 +1,387 @@
+//! The Magento checker.
+//! This module contains the checker used to determine if Magento (or
+//! Adobe Commerce) is used by the asset.
+//! https://business.adobe.com/products/magento/magento-commerce.html
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The headers sent by Magento, mainly by its full page cache
+const MAGENTO_HEADERS: [&str; 3] = [
+    "X-magento-cache-debug",
+    "X-magento-cache-control",
+    "X-magento-tags",
+];
+
+/// The checker
+pub struct MagentoChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> MagentoChecker<'a> {
+    /// Creates the checker.
+    /// By doing so, the regex will is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: Magento/2.4 (Community)
+        let version_regex = Regex::new(
+            r"^\s*(?P<wholematch>Magento/(?P<version1>\d+\.\d+(\.\d+)?) \((?P<edition>[A-Za-z ]+)\))",
+        )
+        .unwrap();
+        // Example: <script src="https://www.example.com/static/version1712345678/frontend/Magento/luma/en_US/requirejs/require.js"></script>
+        // The number after "version" is the deployment timestamp, not the
+        // Magento version.
+        let static_regex = Regex::new(
+            r"(?P<wholematch>/static/(version\d+/)?frontend/[A-Za-z0-9_]+/[A-Za-z0-9_]+/[a-z]{2}_[A-Z]{2}/)",
+        )
+        .unwrap();
+        // Example: X-Magento-Vary=9a1d2b3c4e5f; path=/
+        let cookie_regex =
+            Regex::new(r"(?P<wholematch>(X-Magento-Vary|mage-cache-sessid)=)").unwrap();
+        regexes.insert("http-body-version", (version_regex, 40, 10));
+        regexes.insert("http-body-static", (static_regex, 60, 30));
+        regexes.insert("http-header-cookie", (cookie_regex, 30, 30));
+        Self { regexes }
+    }
+
+    /// Checks in HTTP response body.
+    /// The /magento_version endpoint gives the release line and the
+    /// edition, the static assets only confirm Magento is there.
+    fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running MagentoChecker::check_http_body() on {}",
+            url_response.url
+        );
+
+        // Don't look for the version on an error page quoting the URL
+        if url_response.url.ends_with("/magento_version") {
+            if !url_response.is_success() {
+                return None;
+            }
+            let (regex, keep_left, keep_right) = self
+                .regexes
+                .get("http-body-version")
+                .expect("Regex Magento/http-body-version not found");
+            if let Some(caps) = regex.captures(&url_response.body) {
+                info!("Regex Magento/http-body-version matches");
+                let edition = caps["edition"].to_string();
+                return Some(self.extract_finding_from_captures(
+		    caps,
+		    Some(url_response),
+		    keep_left.to_owned(),
+		    keep_right.to_owned(),
+		    Technology::Magento,
+		    &format!("$techno_name$$techno_version$ ({} edition) has been identified because we found \"$evidence$\" at this url: $url_of_finding$", edition)
+		));
+            }
+            return None;
+        }
+
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get("http-body-static")
+            .expect("Regex Magento/http-body-static not found");
+        if let Some(caps) = regex.captures(&url_response.body) {
+            info!("Regex Magento/http-body-static matches");
+            return Some(self.extract_finding_from_captures(
+		caps,
+		Some(url_response),
+		keep_left.to_owned(),
+		keep_right.to_owned(),
+		Technology::Magento,
+		"$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$"));
+        }
+        None
+    }
+
+    /// Checks in HTTP headers.
+    /// The headers and the cookies don't give the version, they are only
+    /// used to confirm Magento is there.
+    fn check_http_headers(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running MagentoChecker::check_http_headers() on {}",
+            url_response.url
+        );
+        for header_name in MAGENTO_HEADERS {
+            if let Some(header_value) = url_response.headers.get(header_name) {
+                info!("Header Magento/{} found", header_name);
+                let evidence = format!("{}: {}", header_name, header_value);
+                let evidence_text = format!(
+                    "{} has been identified using the HTTP header \"{}\" returned at the following URL: {}",
+                    Technology::Magento,
+                    evidence,
+                    url_response.url
+                );
+                return Some(Finding::new(
+                    Technology::Magento,
+                    None,
+                    &evidence,
+                    &evidence_text,
+                    Some(&url_response.url),
+                ));
+            }
+        }
+
+        let headers_to_check = url_response.get_headers(&["Set-cookie".to_string()]);
+        let (regex_header, keep_left_header, keep_right_header) = self
+            .regexes
+            .get("http-header-cookie")
+            .expect("Regex Magento/http-header-cookie not found");
+
+        for (header_name, header_value) in headers_to_check {
+            trace!("Checking header: {} / {}", header_name, header_value);
+            // The regex matches
+            if let Some(caps) = regex_header.captures(&header_value) {
+                info!("Regex Magento/http-header-cookie matches");
+                return Some(self.extract_finding_from_captures(
+		    caps,
+		    Some(url_response),
+		    keep_left_header.to_owned(),
+		    keep_right_header.to_owned(),
+		    Technology::Magento,
+		    &format!("$techno_name$$techno_version$ has been identified using the HTTP header \"{}: $evidence$\" returned at the following URL: $url_of_finding$", header_name)
+		));
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Checker for MagentoChecker<'a> {}
+
+impl<'a> HttpChecker for MagentoChecker<'a> {
+    /// Check for a HTTP scan.
+    ///
+    /// Returns only one finding, otherwise findings would be duplicated each
+    /// time it's found.
+    /// A finding with a version is always preferred over the versionless
+    /// findings from the static assets, the headers or the cookies.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running MagentoChecker::check_http()");
+
+        let mut versionless_finding = None;
+        for url_response in data {
+            // JavaScript files could be hosted on a different server
+            // Don't check the JavaScript files to avoid false positive,
+            // Check only the "main" requests.
+            if url_response.request_type != UrlRequestType::Default {
+                continue;
+            }
+
+            if let Some(finding) = self.check_http_body(url_response) {
+                if finding.version.is_some() {
+                    return vec![finding];
+                }
+                if versionless_finding.is_none() {
+                    versionless_finding = Some(finding);
+                }
+            }
+
+            if versionless_finding.is_none() {
+                versionless_finding = self.check_http_headers(url_response);
+            }
+        }
+
+        if let Some(finding) = versionless_finding {
+            return vec![finding];
+        }
+        Vec::new()
+    }
+
+    /// The technology supported by the checker
+    fn get_technology(&self) -> Technology {
+        Technology::Magento
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn magento_version_matches() {
+        let checker = MagentoChecker::new();
+        let body1 = "Magento/2.4 (Community)";
+        let url1 = "https://www.example.com/magento_version";
+        let url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        let finding = finding.unwrap();
+        check_finding_fields(
+            &finding,
+            "Magento/2.4 (Community)",
+            Technology::Magento,
+            Some("2.4"),
+            Some(url1),
+        );
+        assert!(finding.evidence_text.contains("Community edition"));
+
+        let body2 = "Magento/2.3 (Enterprise)";
+        let url2 = "https://www.example.com/shop/magento_version";
+        let url_response_valid2 =
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response_valid2).unwrap();
+        assert_eq!(Some("2.3".to_string()), finding.version);
+        assert!(finding.evidence_text.contains("Enterprise edition"));
+    }
+
+    #[test]
+    fn magento_version_doesnt_match() {
+        let checker = MagentoChecker::new();
+        // An error page quoting the URL
+        let url_response_error = UrlResponse::new(
+            "https://www.example.com/magento_version",
+            HashMap::new(),
+            "Magento/2.4 (Community)",
+            UrlRequestType::Default,
+            404,
+        );
+        assert!(checker.check_http_body(&url_response_error).is_none());
+
+        // The version is only read from /magento_version
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/blog/upgrade-to-magento-2",
+            HashMap::new(),
+            "Magento/2.4 (Community)",
+            UrlRequestType::Default,
+            200,
+        );
+        assert!(checker.check_http_body(&url_response_invalid).is_none());
+    }
+
+    #[test]
+    fn static_path_matches() {
+        let checker = MagentoChecker::new();
+        let body1 = r#"<script type="text/javascript" src="https://www.example.com/static/version1712345678/frontend/Magento/luma/en_US/requirejs/require.js"></script>"#;
+        let url1 = "https://www.example.com/";
+        let url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "/static/version1712345678/frontend/Magento/luma/en_US/",
+            Technology::Magento,
+            None,
+            Some(url1),
+        );
+
+        let body2 = r#"<link rel="stylesheet" href="/static/frontend/Vendor/theme/fr_FR/css/styles-m.css" />"#;
+        let url_response_valid2 =
+            UrlResponse::new(url1, HashMap::new(), body2, UrlRequestType::Default, 200);
+        assert!(checker.check_http_body(&url_response_valid2).is_some());
+
+        let body3 = r#"<link rel="stylesheet" href="/static/css/main.css" />"#;
+        let url_response_invalid =
+            UrlResponse::new(url1, HashMap::new(), body3, UrlRequestType::Default, 200);
+        assert!(checker.check_http_body(&url_response_invalid).is_none());
+    }
+
+    #[test]
+    fn headers_match() {
+        let checker = MagentoChecker::new();
+        let url1 = "https://www.example.com/";
+        let mut headers1 = HashMap::new();
+        headers1.insert("X-magento-cache-debug".to_string(), "HIT".to_string());
+        let url_response_valid = UrlResponse::new(url1, headers1, "", UrlRequestType::Default, 200);
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "X-magento-cache-debug: HIT",
+            Technology::Magento,
+            None,
+            Some(url1),
+        );
+
+        let mut headers2 = HashMap::new();
+        headers2.insert(
+            "Set-cookie".to_string(),
+            "X-Magento-Vary=9a1d2b3c4e5f; path=/; HttpOnly".to_string(),
+        );
+        let url_response_valid2 =
+            UrlResponse::new(url1, headers2, "", UrlRequestType::Default, 200);
+        let finding = checker.check_http_headers(&url_response_valid2);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "X-Magento-Vary=",
+            Technology::Magento,
+            None,
+            Some(url1),
+        );
+
+        let mut headers3 = HashMap::new();
+        headers3.insert(
+            "Set-cookie".to_string(),
+            "PHPSESSID=abcdef; path=/".to_string(),
+        );
+        let url_response_invalid =
+            UrlResponse::new(url1, headers3, "", UrlRequestType::Default, 200);
+        assert!(checker.check_http_headers(&url_response_invalid).is_none());
+    }
+
+    #[test]
+    fn finds_match_in_url_responses() {
+        let checker = MagentoChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("X-magento-tags".to_string(), "store,cms_b".to_string());
+        let url_response_versionless = UrlResponse::new(
+            "https://www.example.com/",
+            headers1,
+            "",
+            UrlRequestType::Default,
+            200,
+        );
+        let url2 = "https://www.example.com/magento_version";
+        let url_response_version = UrlResponse::new(
+            url2,
+            HashMap::new(),
+            "Magento/2.4 (Community)",
+            UrlRequestType::Default,
+            200,
+        );
+        let findings = checker.check_http(&[url_response_versionless, url_response_version]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Magento/2.4 (Community)",
+            Technology::Magento,
+            Some("2.4"),
+            Some(url2),
+        );
+    }
+
+    #[test]
+    fn doesnt_find_match_in_url_responses() {
+        let checker = MagentoChecker::new();
+        let url_response_invalid1 = UrlResponse::new(
+            "https://www.example.com/",
+            HashMap::new(),
+            "<h1>Welcome to our shop</h1>",
+            UrlRequestType::Default,
+            200,
+        );
+        let url_response_invalid2 = UrlResponse::new(
+            "https://www.example.com/magento_version",
+            HashMap::new(),
+            "<html><body>Not found</body></html>",
+            UrlRequestType::Default,
+            404,
+        );
+        let findings = checker.check_http(&[url_response_invalid1, url_response_invalid2]);
+        assert!(findings.is_empty());
+    }
+}
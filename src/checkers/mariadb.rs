@@ -5,7 +5,7 @@
 
 use std::collections::HashMap;
 
-use super::{Checker, TcpChecker};
+use super::{Checker, MySQLHandshake, TcpChecker};
 use crate::models::{technology::Technology, Finding};
 use log::{info, trace};
 use regex::Regex;
@@ -27,52 +27,57 @@ impl<'a> MariaDBChecker<'a> {
     /// reused.
     pub fn new() -> Self {
         let mut regexes = HashMap::new();
-        // Example: q
-        // 5.5.5-10.10.2-MariaDB-1:10.10.2+maria~ubu1804CZA"$-TB,R-=xdmx1+%s:bamysql_native_password
+        // The regex is applied on the server version read in the
+        // handshake, not on the whole banner which is binary.
+        // Example: 5.5.5-10.10.2-MariaDB-1:10.10.2+maria~ubu1804
         //
-        // (?s) means that . also matches a newline.
+        // OR, since MariaDB 11 which dropped the 5.5.5- prefix
+        //
+        // 11.4.2-MariaDB-ubu2404
         let regex =
-            Regex::new(r"(?s)(?P<wholematch>\d+\.\d+\.\d+\-(?P<version1>\d+\.\d+\.\d+)-MariaDB.+)")
+            Regex::new(r"^(?P<wholematch>(\d+\.\d+\.\d+-)?(?P<version1>\d+\.\d+\.\d+)-MariaDB.*)$")
                 .unwrap();
-        // MariaDB banner contains characters which are replaced by the
-        // replacement character (U+FFFD). Splitting an evidence on this
-        // character makes the program panic because it's a multi-bytes
-        // character. Instead, take the whole banner.
-        regexes.insert("mariadb-banner", (regex, 100, 100));
-        Self { regexes: regexes }
+        regexes.insert("mariadb-server-version", (regex, 50, 50));
+        Self { regexes }
     }
 }
 
 impl<'a> TcpChecker for MariaDBChecker<'a> {
     /// Check if the asset is running MariaDB.
-    /// It looks for the MariaDB banner.
+    /// It looks for the MariaDB handshake in the banner.
     fn check_tcp(&self, data: &[String]) -> Option<Finding> {
         trace!("Running MariaDBChecker::check_tcp()");
-        // For each item, check if it's an MariaDB banner
-        for item in data {
-            trace!("Checking item: {}", item);
-            let banner_regex_params = self
-                .regexes
-                .get("mariadb-banner")
-                .expect("Regex MariaDB/mariadb-banner not found");
-            let (regex, keep_left, keep_right) = banner_regex_params;
-            let caps_result = regex.captures(&item);
+        data.iter()
+            .find_map(|item| self.check_tcp_banner(item.as_bytes()))
+    }
+
+    /// Parses the handshake sent by the server to read its version.
+    fn check_tcp_banner(&self, data: &[u8]) -> Option<Finding> {
+        trace!("Running MariaDBChecker::check_tcp_banner()");
+        let handshake = MySQLHandshake::parse(data)?;
+        trace!("Handshake: {:?}", handshake);
 
-            // The regex matches
-            if caps_result.is_some() {
-                info!("Regex MariaDB/mariadb-banner matches");
-                let caps = caps_result.unwrap();
-                return Some(self.extract_finding_from_captures(
-		    caps,
-		    None,
-		    keep_left.to_owned(),
-		    keep_right.to_owned(),
-		    Technology::MariaDB,
-		    "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" in its banner",
-		));
-            }
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get("mariadb-server-version")
+            .expect("Regex MariaDB/mariadb-server-version not found");
+        let caps = regex.captures(&handshake.server_version)?;
+        info!("Regex MariaDB/mariadb-server-version matches");
+        let mut finding = self.extract_finding_from_captures(
+            caps,
+            None,
+            keep_left.to_owned(),
+            keep_right.to_owned(),
+            Technology::MariaDB,
+            "$techno_name$$techno_version$ has been identified because its handshake gives the server version \"$evidence$\"",
+        );
+        if let Some(auth_plugin_name) = &handshake.auth_plugin_name {
+            finding.evidence_text.push_str(&format!(
+                ", the authentication plugin is \"{}\"",
+                auth_plugin_name
+            ));
         }
-        return None;
+        Some(finding)
     }
 
     /// This checker supports MariaDB
@@ -80,3 +85,47 @@ impl<'a> TcpChecker for MariaDBChecker<'a> {
         Technology::MariaDB
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn handshake_matches() {
+        let checker = MariaDBChecker::new();
+        let banner = b"c\x00\x00\x00\x0a5.5.5-10.3.17-MariaDB-0+deb10u1\x00\x0b\x00\x00\x0042g0YPc#\x00\xfe\xf7\x08\x02\x00\xbf\x81\x15\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00#%3mMz=aPLlZ\x00mysql_native_password\x00";
+        let finding = checker.check_tcp_banner(banner).unwrap();
+        check_finding_fields(
+            &finding,
+            "5.5.5-10.3.17-MariaDB-0+deb10u1",
+            Technology::MariaDB,
+            Some("10.3.17"),
+            None,
+        );
+        assert!(finding
+            .evidence_text
+            .contains("the authentication plugin is \"mysql_native_password\""));
+
+        // MariaDB 11 doesn't prefix its version with 5.5.5-
+        let banner = b"Y\x00\x00\x00\x0a11.4.2-MariaDB-ubu2404\x00\x0b\x00\x00\x00\xa1\xb2c3d4e5\x00\xfe\xf7-\x02\x00\xff\x81\x15\x00\x00\x00\x00\x00\x1d\x00\x00\x00f6g7h8i9j0k1\x00mysql_native_password\x00";
+        let finding = checker.check_tcp_banner(banner).unwrap();
+        check_finding_fields(
+            &finding,
+            "11.4.2-MariaDB-ubu2404",
+            Technology::MariaDB,
+            Some("11.4.2"),
+            None,
+        );
+    }
+
+    #[test]
+    fn handshake_doesnt_match() {
+        let checker = MariaDBChecker::new();
+        let banner = b"[\x00\x00\x00\x0a8.0.36-0ubuntu0.20.04.1\x00\x0b\x00\x00\x00$en#@\xabbL\x00\xff\xff\xff\x02\x00\xff\xdf\x15\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00<%m6k/D\x15\xc1\x8e\x09r\x00caching_sha2_password\x00";
+        assert!(checker.check_tcp_banner(banner).is_none());
+        // An error packet, the host isn't allowed to connect
+        let banner = b"E\x00\x00\x00\xffj\x04Host '10.0.0.1' is not allowed to connect to this MariaDB server";
+        assert!(checker.check_tcp_banner(banner).is_none());
+    }
+}
@@ -0,0 +1,258 @@
+//! The Matomo checker.
+//! This module contains the checker used to determine if Matomo (formerly
+//! Piwik) is used by the asset.
+//! https://matomo.org
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The checker
+pub struct MatomoChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> MatomoChecker<'a> {
+    /// Creates the checker.
+    /// By doing so, the regex will is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: {"value":"4.15.1"}
+        let api_regex =
+            Regex::new(r#"^\s*(?P<wholematch>\{\s*"value"\s*:\s*"(?P<version1>\d+\.\d+\.\d+)(-[a-z0-9]+)?"\s*\})\s*$"#)
+                .unwrap();
+        // Example: ## Matomo 4.15.1
+        let changelog_regex =
+            Regex::new(r"(?m)^(?P<wholematch>##\s+Matomo\s+(?P<version1>\d+\.\d+\.\d+))\s*$")
+                .unwrap();
+        // Example: * Matomo - free/libre analytics platform
+        let tracker_regex =
+            Regex::new(r"(?P<wholematch>(Matomo|Piwik) - (free/libre|Web) analytics platform)")
+                .unwrap();
+        regexes.insert("http-body-api", (api_regex, 30, 30));
+        regexes.insert("http-body-changelog", (changelog_regex, 30, 30));
+        regexes.insert("http-body-tracker", (tracker_regex, 50, 50));
+        Self { regexes }
+    }
+
+    /// Checks in HTTP response body.
+    /// Each regex is only checked against the URL it's made for, the API
+    /// response and the changelog are too generic otherwise.
+    fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running MatomoChecker::check_http_body() on {}",
+            url_response.url
+        );
+
+        let regex_name = if url_response.url.contains("API.getMatomoVersion") {
+            "http-body-api"
+        } else if url_response.url.ends_with("/CHANGELOG.md") {
+            "http-body-changelog"
+        } else if url_response.url.ends_with("/matomo.js")
+            || url_response.url.ends_with("/piwik.js")
+        {
+            "http-body-tracker"
+        } else {
+            return None;
+        };
+
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get(regex_name)
+            .unwrap_or_else(|| panic!("Regex Matomo/{} not found", regex_name));
+        // The regex matches
+        if let Some(caps) = regex.captures(&url_response.body) {
+            info!("Regex Matomo/{} matches", regex_name);
+            return Some(self.extract_finding_from_captures(
+		caps,
+		Some(url_response),
+		keep_left.to_owned(),
+		keep_right.to_owned(),
+		Technology::Matomo,
+		"$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$"));
+        }
+        None
+    }
+}
+
+impl<'a> Checker for MatomoChecker<'a> {}
+
+impl<'a> HttpChecker for MatomoChecker<'a> {
+    /// Check for a HTTP scan.
+    ///
+    /// Returns only one finding, otherwise findings would be duplicated each
+    /// time it's found.
+    /// A finding with a version (from the API or the changelog) is always
+    /// preferred over the versionless finding from the JavaScript tracker.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running MatomoChecker::check_http()");
+
+        let mut versionless_finding = None;
+        for url_response in data {
+            // JavaScript files could be hosted on a different server
+            // Don't check the JavaScript files to avoid false positive,
+            // Check only the "main" requests.
+            if url_response.request_type != UrlRequestType::Default
+                || url_response.status_code != 200
+            {
+                continue;
+            }
+
+            if let Some(finding) = self.check_http_body(url_response) {
+                if finding.version.is_some() {
+                    return vec![finding];
+                }
+                if versionless_finding.is_none() {
+                    versionless_finding = Some(finding);
+                }
+            }
+        }
+
+        if let Some(finding) = versionless_finding {
+            return vec![finding];
+        }
+        Vec::new()
+    }
+
+    /// The technology supported by the checker
+    fn get_technology(&self) -> Technology {
+        Technology::Matomo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn source_code_matches() {
+        let checker = MatomoChecker::new();
+        let url1 =
+            "https://www.example.com/index.php?module=API&method=API.getMatomoVersion&format=json";
+        let mut url_response_valid = UrlResponse::new(
+            url1,
+            HashMap::new(),
+            r#"{"value":"4.15.1"}"#,
+            UrlRequestType::Default,
+            200,
+        );
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "{\"value\":\"4.15.1\"}",
+            Technology::Matomo,
+            Some("4.15.1"),
+            Some(url1),
+        );
+
+        let url2 = "https://www.example.com/CHANGELOG.md";
+        url_response_valid.url = url2.to_string();
+        url_response_valid.body = "# Matomo Platform Changelog\n\nThis is the Developer Changelog for Matomo platform developers.\n\n## Matomo 5.0.0\n\n### Breaking Changes\n\n## Matomo 4.16.0\n".to_string();
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "## Matomo 5.0.0",
+            Technology::Matomo,
+            Some("5.0.0"),
+            Some(url2),
+        );
+
+        let url3 = "https://www.example.com/piwik.js";
+        url_response_valid.url = url3.to_string();
+        url_response_valid.body = "/*!!\n * Matomo - free/libre analytics platform\n *\n * JavaScript tracking client\n */".to_string();
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "Matomo - free/libre analytics platform",
+            Technology::Matomo,
+            None,
+            Some(url3),
+        );
+    }
+
+    #[test]
+    fn source_code_doesnt_match() {
+        let checker = MatomoChecker::new();
+        // The API response is only trusted on the API URL
+        let mut url_response_invalid = UrlResponse::new(
+            "https://www.example.com/api/version",
+            HashMap::new(),
+            r#"{"value":"4.15.1"}"#,
+            UrlRequestType::Default,
+            200,
+        );
+        let finding = checker.check_http_body(&url_response_invalid);
+        assert!(finding.is_none());
+
+        url_response_invalid.url = "https://www.example.com/CHANGELOG.md".to_string();
+        url_response_invalid.body = "## Version 1.2.3\n\nMigrated from Matomo 4.15.1".to_string();
+        let finding = checker.check_http_body(&url_response_invalid);
+        assert!(finding.is_none());
+    }
+
+    #[test]
+    fn finds_match_in_url_responses() {
+        let checker = MatomoChecker::new();
+        let url_response_tracker = UrlResponse::new(
+            "https://www.example.com/matomo.js",
+            HashMap::new(),
+            "/*! Matomo - free/libre analytics platform */",
+            UrlRequestType::Default,
+            200,
+        );
+        let url1 =
+            "https://www.example.com/index.php?module=API&method=API.getMatomoVersion&format=json";
+        let url_response_api = UrlResponse::new(
+            url1,
+            HashMap::new(),
+            r#"{"value":"4.15.1"}"#,
+            UrlRequestType::Default,
+            200,
+        );
+
+        // The version from the API is preferred over the tracker
+        let findings = checker.check_http(&[url_response_tracker, url_response_api]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "{\"value\":\"4.15.1\"}",
+            Technology::Matomo,
+            Some("4.15.1"),
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn doesnt_find_match_in_url_responses() {
+        let checker = MatomoChecker::new();
+        let url_response_invalid1 = UrlResponse::new(
+            "https://cdn.example.com/matomo.js",
+            HashMap::new(),
+            "/*! Matomo - free/libre analytics platform */",
+            UrlRequestType::JavaScript,
+            200,
+        );
+        let url_response_invalid2 = UrlResponse::new(
+            "https://www.example.com/CHANGELOG.md",
+            HashMap::new(),
+            "## Matomo 4.15.1",
+            UrlRequestType::Default,
+            404,
+        );
+        let findings = checker.check_http(&[url_response_invalid1, url_response_invalid2]);
+        assert!(findings.is_empty());
+    }
+}
@@ -15,26 +15,46 @@
 
 pub mod angular;
 pub mod angularjs;
+pub mod aspnet;
 pub mod bootstrap;
+pub mod caddy;
 pub mod ckeditor;
+pub mod confluence;
+pub mod dirlisting;
+pub mod dns;
 pub mod dovecot;
 pub mod drupal;
 pub mod exim;
+pub mod exposure;
+pub mod external_resources;
+pub mod graphql;
 pub mod gsap;
 pub mod handlebars;
+pub mod haproxy;
 pub mod highcharts;
+pub mod hint;
 pub mod horde;
+pub mod http2;
+pub mod http_methods;
 pub mod httpd;
+pub mod iis;
+pub mod infrastructure;
 pub mod jira;
 pub mod jquery;
 pub mod jquerymobile;
 pub mod jqueryui;
 pub mod knockout;
+pub mod lighttpd;
 pub mod lodash;
+pub mod magento;
 pub mod mariadb;
+pub mod matomo;
 pub mod melis;
+pub mod moodle;
+pub mod mssql;
 pub mod mysql;
 pub mod nginx;
+pub mod ntp;
 pub mod openssh;
 pub mod openssl;
 pub mod os;
@@ -46,34 +66,171 @@ pub mod prestashop;
 pub mod proftpd;
 pub mod pureftpd;
 pub mod reactjs;
+pub mod session_cookie;
+pub mod snmp;
 pub mod squirrel_mail;
 pub mod symfony;
+pub mod telnet;
 pub mod tinymce;
+pub mod tls;
 pub mod tomcat;
 pub mod twisted;
 pub mod twistedweb;
 pub mod typo3;
+pub mod versionfile;
+pub mod waf;
 pub mod wordpress;
+pub mod wordpress_api;
 pub mod wp_plugins;
 pub mod wp_themes;
 
-use crate::models::{reqres::UrlResponse, technology::Technology, Finding};
+use crate::models::{reqres::UrlResponse, technology::Technology, truncate_evidence, Finding};
 use log::trace;
-use regex::Captures;
+use regex::{Captures, Regex};
+
+/// The probe sent to the FTP servers. The greeting is followed by the
+/// replies to these commands, which can reveal what the greeting hides.
+/// QUIT closes the connection, so the reader doesn't wait for a timeout.
+pub const FTP_PROBE: &[u8] = b"SYST\r\nFEAT\r\nSTAT\r\nQUIT\r\n";
+
+/// Returns the system type given by an FTP server in its reply to SYST.
+/// Example: 215 UNIX Type: L8
+pub fn get_ftp_system(response: &str) -> Option<String> {
+    let regex = Regex::new(r"(?m)^215 (?P<system>[^\r\n]+)").unwrap();
+    regex
+        .captures(response)
+        .map(|caps| caps["system"].trim().to_string())
+}
+
+/// The initial handshake packet sent by the MySQL and MariaDB servers
+/// (protocol version 10).
+/// https://dev.mysql.com/doc/dev/mysql-server/latest/page_protocol_connection_phase_packets_protocol_handshake_v10.html
+#[derive(Debug, PartialEq)]
+pub struct MySQLHandshake {
+    /// The version of the server.
+    /// Example: 5.7.34-log, 5.5.5-10.3.17-MariaDB-0+deb10u1
+    pub server_version: String,
+    /// The capability flags of the server
+    pub capabilities: u32,
+    /// The default authentication plugin, if given.
+    /// Example: caching_sha2_password
+    pub auth_plugin_name: Option<String>,
+}
+
+impl MySQLHandshake {
+    /// The capability flag set when the authentication plugin is given
+    const CLIENT_PLUGIN_AUTH: u32 = 0x0008_0000;
+
+    /// Parses the handshake packet, reading the fields at their offsets
+    /// instead of searching them in the banner.
+    /// Returns None if the data is not a handshake packet.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        // The packet header: the payload length (3 bytes) and the
+        // sequence id, always 0 for the handshake
+        let header = data.get(0..4)?;
+        let payload_length =
+            header[0] as usize | (header[1] as usize) << 8 | (header[2] as usize) << 16;
+        if header[3] != 0 {
+            return None;
+        }
+        let payload = &data[4..data.len().min(4 + payload_length)];
+        // The protocol version
+        if *payload.first()? != 10 {
+            return None;
+        }
+
+        let read_string = |position: usize| -> Option<(String, usize)> {
+            let bytes = payload.get(position..)?;
+            let length = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+            let text = std::str::from_utf8(&bytes[..length]).ok()?;
+            Some((text.to_string(), position + length + 1))
+        };
+        let read_u16 = |position: usize| -> Option<u32> {
+            payload
+                .get(position..position + 2)
+                .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as u32)
+        };
+
+        let (server_version, mut position) = read_string(1)?;
+        if !server_version.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        // The thread id (4 bytes), the beginning of the authentication
+        // data (8 bytes) and a filler
+        position += 13;
+        let mut capabilities = read_u16(position).unwrap_or(0);
+        // The character set (1 byte) and the status flags (2 bytes)
+        position += 5;
+        capabilities |= read_u16(position).unwrap_or(0) << 16;
+        position += 2;
+        let auth_data_length = payload.get(position).copied().unwrap_or(0) as usize;
+        // The reserved bytes, then the rest of the authentication data
+        position += 11 + (auth_data_length.saturating_sub(8)).max(13);
+        let auth_plugin_name = if capabilities & Self::CLIENT_PLUGIN_AUTH != 0 {
+            read_string(position)
+                .map(|(name, _)| name)
+                .filter(|name| !name.is_empty())
+        } else {
+            None
+        };
+
+        Some(Self {
+            server_version,
+            capabilities,
+            auth_plugin_name,
+        })
+    }
+}
 
 /// A common interface between all TCP checkers
-pub trait TcpChecker: Checker {
+pub trait TcpChecker: Checker + Sync {
     /// Checks data to determine if a given technology matches.
     /// data will usually contain only one string (the banner), but
     /// some technologies could provide more information.
     fn check_tcp(&self, data: &[String]) -> Option<Finding>;
 
+    /// Returns the probe to send to the service before reading its
+    /// response, for services that don't send a banner by themselves.
+    /// By default, no probe is sent and the banner is given to check_tcp().
+    fn get_tcp_probe(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Checks the raw banner sent by the service.
+    /// By default, the banner is converted to a string and checked by
+    /// check_tcp(). Checkers expecting a binary banner should override it.
+    fn check_tcp_banner(&self, data: &[u8]) -> Option<Finding> {
+        self.check_tcp(&[String::from_utf8_lossy(data).to_string()])
+    }
+
+    /// Checks the raw response received after sending the probe.
+    /// By default, the response is converted to a string and checked by
+    /// check_tcp(). Checkers expecting a binary response should override it.
+    fn check_tcp_probe_response(&self, data: &[u8]) -> Option<Finding> {
+        self.check_tcp(&[String::from_utf8_lossy(data).to_string()])
+    }
+
+    /// Get the technology supported by the checker.
+    fn get_technology(&self) -> Technology;
+}
+
+/// A common interface between all UDP checkers
+pub trait UdpChecker: Checker + Sync {
+    /// Returns the probe to send to the service.
+    /// UDP services don't send anything by themselves, so each checker
+    /// defines the probe its service will answer to.
+    fn probe_payload(&self) -> Vec<u8>;
+
+    /// Checks the response to the probe to determine if a given
+    /// technology matches.
+    fn check_udp(&self, data: &[u8]) -> Option<Finding>;
+
     /// Get the technology supported by the checker.
     fn get_technology(&self) -> Technology;
 }
 
 /// A common interface between all HTTP checkers
-pub trait HttpChecker: Checker {
+pub trait HttpChecker: Checker + Sync {
     /// Checks data to determine if a given technology matches.
     /// data will contain information about HTTP request & response.
     fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding>;
@@ -95,6 +252,8 @@ pub trait Checker {
     /// If the evidence (wholematch) is longer than evidence_first_chars + evidence_last_chars,
     /// it will be cut in the middle. So, only the given number of chars will
     /// remains at the beginning, and the other given number for the end.
+    /// The whole evidence is kept, --evidence-length and --full-evidence
+    /// override the truncation afterwards.
     fn extract_finding_from_captures(
         &self,
         captures: Captures,
@@ -105,16 +264,10 @@ pub trait Checker {
         evidence_text_templace: &str,
     ) -> Finding {
         trace!("Running HttpChecker::extract_finding_from_captures()");
-        let mut evidence = captures["wholematch"].to_string();
-        trace!("Evidence: {}", evidence);
-        let evidence_length = evidence.len();
-        if evidence_length > evidence_first_chars + evidence_last_chars {
-            trace!("Evidence is too long, truncate it");
-            let evidencep1 = evidence[0..evidence_first_chars].to_string();
-            let evidencep2 = evidence[evidence_length - evidence_last_chars..].to_string();
-            evidence = format!("{}[...]{}", evidencep1, evidencep2);
-            trace!("New evidence: {}", evidence);
-        }
+        let full_evidence = captures["wholematch"].to_string();
+        trace!("Evidence: {}", full_evidence);
+        let evidence = truncate_evidence(&full_evidence, evidence_first_chars, evidence_last_chars);
+        trace!("Truncated evidence: {}", evidence);
 
         let mut version = None;
         let mut version_text = String::new();
@@ -157,13 +310,16 @@ pub trait Checker {
 
         trace!("Evidence text: {}", evidence_text);
 
-        return Finding::new(
+        let mut finding = Finding::new(
             technology,
             version.as_deref(),
             &evidence,
             &evidence_text,
             url_option,
         );
+        // Keep the whole evidence in case the truncation is overridden
+        finding.full_evidence = full_evidence;
+        finding
     }
 }
 
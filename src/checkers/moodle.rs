@@ -0,0 +1,290 @@
+//! The Moodle checker.
+//! This module contains the checker used to determine if Moodle is
+//! used by the asset.
+//! https://moodle.org
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The checker
+pub struct MoodleChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> MoodleChecker<'a> {
+    /// Creates the checker.
+    /// By doing so, the regex will is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: This files describes API changes in core libraries and APIs,
+        // information provided here is intended especially for developers.
+        //
+        // === 4.1 ===
+        let upgrade_regex = Regex::new(r"(?s)(?P<wholematch>This files? describes API changes.*?===\s*(?P<version1>\d+\.\d+(\.\d+)?)\s*===)").unwrap();
+        // Example: <meta name="keywords" content="moodle, Log in to the site" />
+        let body_regex = Regex::new(
+            r#"(?P<wholematch><meta\s+name\s*=\s*['"]keywords['"]\s+content\s*=\s*['"]moodle,)"#,
+        )
+        .unwrap();
+        // Example: MoodleSession=8f3k2l1p0o9i8u7y6t5r4e3w2q; path=/
+        let header_regex = Regex::new(r"(?P<wholematch>MoodleSession[a-zA-Z0-9_]*=)").unwrap();
+        regexes.insert("http-body-upgrade", (upgrade_regex, 40, 20));
+        regexes.insert("http-body", (body_regex, 50, 50));
+        regexes.insert("http-header", (header_regex, 30, 30));
+        Self { regexes }
+    }
+
+    /// Checks in HTTP response body.
+    /// The /lib/upgrade.txt file gives the release line, the login page
+    /// markup only confirms Moodle is there.
+    fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running MoodleChecker::check_http_body() on {}",
+            url_response.url
+        );
+
+        let regex_name = if url_response.url.ends_with("/lib/upgrade.txt") {
+            "http-body-upgrade"
+        } else {
+            "http-body"
+        };
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get(regex_name)
+            .unwrap_or_else(|| panic!("Regex Moodle/{} not found", regex_name));
+        // The regex matches
+        if let Some(caps) = regex.captures(&url_response.body) {
+            info!("Regex Moodle/{} matches", regex_name);
+            return Some(self.extract_finding_from_captures(
+		caps,
+		Some(url_response),
+		keep_left.to_owned(),
+		keep_right.to_owned(),
+		Technology::Moodle,
+		"$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$"));
+        }
+        None
+    }
+
+    /// Checks in HTTP headers.
+    /// The MoodleSession cookie doesn't give the version, it's only used
+    /// to confirm Moodle is there.
+    fn check_http_headers(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running MoodleChecker::check_http_headers() on {}",
+            url_response.url
+        );
+        let headers_to_check = url_response.get_headers(&["Set-cookie".to_string()]);
+        let (regex_header, keep_left_header, keep_right_header) = self
+            .regexes
+            .get("http-header")
+            .expect("Regex Moodle/http-header not found");
+
+        for (header_name, header_value) in headers_to_check {
+            trace!("Checking header: {} / {}", header_name, header_value);
+            // The regex matches
+            if let Some(caps) = regex_header.captures(&header_value) {
+                info!("Regex Moodle/http-header matches");
+                return Some(self.extract_finding_from_captures(
+		    caps,
+		    Some(url_response),
+		    keep_left_header.to_owned(),
+		    keep_right_header.to_owned(),
+		    Technology::Moodle,
+		    &format!("$techno_name$$techno_version$ has been identified using the HTTP header \"{}: $evidence$\" returned at the following URL: $url_of_finding$", header_name)
+		));
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Checker for MoodleChecker<'a> {}
+
+impl<'a> HttpChecker for MoodleChecker<'a> {
+    /// Check for a HTTP scan.
+    ///
+    /// Returns only one finding, otherwise findings would be duplicated each
+    /// time it's found.
+    /// A finding with a version is always preferred over the versionless
+    /// findings from the login page or the session cookie.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running MoodleChecker::check_http()");
+
+        let mut versionless_finding = None;
+        for url_response in data {
+            // JavaScript files could be hosted on a different server
+            // Don't check the JavaScript files to avoid false positive,
+            // Check only the "main" requests.
+            if url_response.request_type != UrlRequestType::Default {
+                continue;
+            }
+
+            if let Some(finding) = self.check_http_body(url_response) {
+                if finding.version.is_some() {
+                    return vec![finding];
+                }
+                if versionless_finding.is_none() {
+                    versionless_finding = Some(finding);
+                }
+            }
+
+            if versionless_finding.is_none() {
+                versionless_finding = self.check_http_headers(url_response);
+            }
+        }
+
+        if let Some(finding) = versionless_finding {
+            return vec![finding];
+        }
+        Vec::new()
+    }
+
+    /// The technology supported by the checker
+    fn get_technology(&self) -> Technology {
+        Technology::Moodle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn source_code_matches() {
+        let checker = MoodleChecker::new();
+        let body1 = "This files describes API changes in core libraries and APIs,\ninformation provided here is intended especially for developers.\n\n=== 4.1 ===\n\n* The following functions have been finally deprecated\n\n=== 4.0 ===\n";
+        let url1 = "https://www.example.com/lib/upgrade.txt";
+        let url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "=== 4.1 ===",
+            Technology::Moodle,
+            Some("4.1"),
+            Some(url1),
+        );
+
+        let body2 = r#"<meta name="keywords" content="moodle, Log in to the site" />"#;
+        let url2 = "https://www.example.com/login/index.php";
+        let url_response_valid =
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "content=\"moodle,",
+            Technology::Moodle,
+            None,
+            Some(url2),
+        );
+    }
+
+    #[test]
+    fn source_code_doesnt_match() {
+        let checker = MoodleChecker::new();
+        // The upgrade notes are only trusted on /lib/upgrade.txt
+        let body = "This files describes API changes in core libraries and APIs\n=== 4.1 ===";
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/docs/upgrade.txt",
+            HashMap::new(),
+            body,
+            UrlRequestType::Default,
+            200,
+        );
+        let finding = checker.check_http_body(&url_response_invalid);
+        assert!(finding.is_none());
+    }
+
+    #[test]
+    fn header_matches() {
+        let checker = MoodleChecker::new();
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Set-cookie".to_string(),
+            "MoodleSession=8f3k2l1p0o9i8u7y6t5r4e3w2q; path=/".to_string(),
+        );
+        let url1 = "https://www.example.com/";
+        let url_response_valid =
+            UrlResponse::new(url1, headers, "the body", UrlRequestType::Default, 200);
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "MoodleSession=",
+            Technology::Moodle,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn finds_match_in_url_responses() {
+        let checker = MoodleChecker::new();
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Set-cookie".to_string(),
+            "MoodleSession=8f3k2l1p0o9i8u7y6t5r4e3w2q; path=/".to_string(),
+        );
+        let url_response_header = UrlResponse::new(
+            "https://www.example.com/",
+            headers,
+            "nothing to find in body",
+            UrlRequestType::Default,
+            200,
+        );
+        let url1 = "https://www.example.com/lib/upgrade.txt";
+        let url_response_valid = UrlResponse::new(
+            url1,
+            HashMap::new(),
+            "This file describes API changes in /lib/*\n\n=== 3.11.4 ===\n",
+            UrlRequestType::Default,
+            200,
+        );
+
+        // The version from upgrade.txt is preferred over the cookie
+        let findings = checker.check_http(&[url_response_header, url_response_valid]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "=== 3.11.4 ===",
+            Technology::Moodle,
+            Some("3.11.4"),
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn doesnt_find_match_in_url_responses() {
+        let checker = MoodleChecker::new();
+        let url_response_invalid1 = UrlResponse::new(
+            "https://cdn.example.com/moodle.js",
+            HashMap::new(),
+            r#"<meta name="keywords" content="moodle, Log in to the site" />"#,
+            UrlRequestType::JavaScript,
+            200,
+        );
+        let url_response_invalid2 = UrlResponse::new(
+            "https://www.example.com/login/index.php",
+            HashMap::new(),
+            "Moodle 4.1 login",
+            UrlRequestType::Default,
+            200,
+        );
+        let findings = checker.check_http(&[url_response_invalid1, url_response_invalid2]);
+        assert!(findings.is_empty());
+    }
+}
@@ -0,0 +1,245 @@
+//! The MSSQL checker.
+//! This module contains the checker used to determine if Microsoft SQL
+//! Server is used by the asset.
+//! https://www.microsoft.com/sql-server
+//!
+//! SQL Server doesn't send a banner, but it answers to a TDS pre-login
+//! packet with its version before any authentication.
+
+use super::{Checker, TcpChecker};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+
+/// The TDS packet type of a pre-login request
+const TDS_PRELOGIN: u8 = 0x12;
+/// The TDS packet type of a response
+const TDS_RESPONSE: u8 = 0x04;
+/// The pre-login option token giving the version
+const PRELOGIN_VERSION: u8 = 0x00;
+/// The pre-login option token ending the options list
+const PRELOGIN_TERMINATOR: u8 = 0xFF;
+
+/// The MSSQL checker
+pub struct MSSQLChecker {}
+
+impl Checker for MSSQLChecker {}
+
+impl MSSQLChecker {
+    /// Creates a new MSSQLChecker.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Parses the version of a pre-login response.
+    /// Returns the major, minor, build and sub-build numbers.
+    fn parse_prelogin_response(data: &[u8]) -> Option<(u8, u8, u16, u16)> {
+        // The 8-byte TDS header is followed by the options list
+        if data.len() < 8 || data[0] != TDS_RESPONSE {
+            return None;
+        }
+        let payload = &data[8..];
+
+        // Each option is a token, followed by its offset and its length
+        // on 2 bytes (big endian)
+        let mut i = 0;
+        while i < payload.len() && payload[i] != PRELOGIN_TERMINATOR {
+            if i + 5 > payload.len() {
+                return None;
+            }
+            let offset = u16::from_be_bytes([payload[i + 1], payload[i + 2]]) as usize;
+            let length = u16::from_be_bytes([payload[i + 3], payload[i + 4]]) as usize;
+            if payload[i] == PRELOGIN_VERSION {
+                if length < 6 || offset + 6 > payload.len() {
+                    return None;
+                }
+                let version = &payload[offset..offset + 6];
+                return Some((
+                    version[0],
+                    version[1],
+                    u16::from_be_bytes([version[2], version[3]]),
+                    u16::from_le_bytes([version[4], version[5]]),
+                ));
+            }
+            i += 5;
+        }
+        None
+    }
+
+    /// Builds a pre-login packet.
+    /// The options are given as (token, length) and their data are
+    /// concatenated, in the same order, in data.
+    fn build_prelogin_packet(packet_type: u8, options: &[(u8, u16)], data: &[u8]) -> Vec<u8> {
+        // Each option takes 5 bytes, plus 1 for the terminator
+        let mut offset = (options.len() * 5 + 1) as u16;
+        let mut payload = Vec::new();
+        for (token, length) in options {
+            payload.push(*token);
+            payload.extend_from_slice(&offset.to_be_bytes());
+            payload.extend_from_slice(&length.to_be_bytes());
+            offset += length;
+        }
+        payload.push(PRELOGIN_TERMINATOR);
+        payload.extend_from_slice(data);
+
+        // The 8-byte TDS header: type, status (end of message), length,
+        // SPID, packet ID and window
+        let length = (payload.len() + 8) as u16;
+        let mut packet = vec![packet_type, 0x01];
+        packet.extend_from_slice(&length.to_be_bytes());
+        packet.extend_from_slice(&[0x00, 0x00, 0x01, 0x00]);
+        packet.append(&mut payload);
+        packet
+    }
+
+    /// Returns the SQL Server release matching the major and minor
+    /// versions, as used in the CPE and as displayed.
+    fn get_release(major: u8, minor: u8) -> Option<(&'static str, &'static str)> {
+        match (major, minor) {
+            (8, _) => Some(("2000", "SQL Server 2000")),
+            (9, _) => Some(("2005", "SQL Server 2005")),
+            (10, 50) => Some(("2008", "SQL Server 2008 R2")),
+            (10, _) => Some(("2008", "SQL Server 2008")),
+            (11, _) => Some(("2012", "SQL Server 2012")),
+            (12, _) => Some(("2014", "SQL Server 2014")),
+            (13, _) => Some(("2016", "SQL Server 2016")),
+            (14, _) => Some(("2017", "SQL Server 2017")),
+            (15, _) => Some(("2019", "SQL Server 2019")),
+            (16, _) => Some(("2022", "SQL Server 2022")),
+            _ => None,
+        }
+    }
+}
+
+impl TcpChecker for MSSQLChecker {
+    /// SQL Server doesn't send a banner, so there is nothing to check.
+    /// The version is found in the response to the pre-login probe.
+    fn check_tcp(&self, _data: &[String]) -> Option<Finding> {
+        trace!("Running MSSQLChecker::check_tcp()");
+        None
+    }
+
+    /// Returns a TDS pre-login packet.
+    fn get_tcp_probe(&self) -> Option<Vec<u8>> {
+        // VERSION, ENCRYPTION, INSTOPT, THREADID and MARS options
+        let options = [(0x00, 6), (0x01, 1), (0x02, 1), (0x03, 4), (0x04, 1)];
+        // Client version 0, encryption not supported, default instance,
+        // thread ID 0 and MARS disabled
+        let data = [0, 0, 0, 0, 0, 0, 0x02, 0x00, 0, 0, 0, 0, 0x00];
+        Some(Self::build_prelogin_packet(TDS_PRELOGIN, &options, &data))
+    }
+
+    /// Check if the asset is running SQL Server.
+    /// It parses the version given in the pre-login response.
+    fn check_tcp_probe_response(&self, data: &[u8]) -> Option<Finding> {
+        trace!("Running MSSQLChecker::check_tcp_probe_response()");
+        let (major, minor, build, _subbuild) = Self::parse_prelogin_response(data)?;
+        info!("The pre-login response of MSSQL has been parsed");
+
+        let evidence = format!("{}.{}.{}", major, minor, build);
+        let (version, release_name) = match Self::get_release(major, minor) {
+            Some((version, release_name)) => (version.to_string(), release_name.to_string()),
+            None => (evidence.clone(), "an unknown release".to_string()),
+        };
+        let evidence_text = format!(
+            "{} {} has been identified because the TDS pre-login response announced the version \"{}\", which matches {}",
+            Technology::MSSQL,
+            version,
+            evidence,
+            release_name
+        );
+
+        Some(Finding::new(
+            Technology::MSSQL,
+            Some(&version),
+            &evidence,
+            &evidence_text,
+            None,
+        ))
+    }
+
+    /// This checker supports MSSQL
+    fn get_technology(&self) -> Technology {
+        Technology::MSSQL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    /// Builds a pre-login response announcing the given version
+    fn prelogin_response(version: [u8; 6]) -> Vec<u8> {
+        let mut data = version.to_vec();
+        // Encryption not supported & default instance
+        data.extend_from_slice(&[0x02, 0x00]);
+        MSSQLChecker::build_prelogin_packet(TDS_RESPONSE, &[(0x00, 6), (0x01, 1), (0x02, 1)], &data)
+    }
+
+    #[test]
+    fn prelogin_response_matches() {
+        let checker = MSSQLChecker::new();
+        // 15.0.2000.5
+        let data = prelogin_response([0x0F, 0x00, 0x07, 0xD0, 0x05, 0x00]);
+        let finding = checker.check_tcp_probe_response(&data);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "15.0.2000",
+            Technology::MSSQL,
+            Some("2019"),
+            None,
+        );
+
+        // 10.50.6000.34
+        let data = prelogin_response([0x0A, 0x32, 0x17, 0x70, 0x22, 0x00]);
+        let finding = checker.check_tcp_probe_response(&data).unwrap();
+        check_finding_fields(
+            &finding,
+            "10.50.6000",
+            Technology::MSSQL,
+            Some("2008"),
+            None,
+        );
+        assert!(finding.evidence_text.contains("SQL Server 2008 R2"));
+
+        // Unknown release
+        let data = prelogin_response([0x11, 0x00, 0x00, 0x64, 0x00, 0x00]);
+        let finding = checker.check_tcp_probe_response(&data);
+        check_finding_fields(
+            &finding.unwrap(),
+            "17.0.100",
+            Technology::MSSQL,
+            Some("17.0.100"),
+            None,
+        );
+    }
+
+    #[test]
+    fn prelogin_response_doesnt_match() {
+        let checker = MSSQLChecker::new();
+        let finding = checker.check_tcp_probe_response(b"SSH-2.0-OpenSSH_8.9p1 Ubuntu-3ubuntu0.6");
+        assert!(finding.is_none());
+
+        // Truncated response
+        let data = prelogin_response([0x0F, 0x00, 0x07, 0xD0, 0x05, 0x00]);
+        let finding = checker.check_tcp_probe_response(&data[..20]);
+        assert!(finding.is_none());
+
+        let finding =
+            checker.check_tcp(&["5.7.37-nmm1-logm{pX^4gw9JD]Sg4mysql_native_password".to_string()]);
+        assert!(finding.is_none());
+    }
+
+    #[test]
+    fn probe_is_valid() {
+        let checker = MSSQLChecker::new();
+        let probe = checker.get_tcp_probe().unwrap();
+        assert_eq!(TDS_PRELOGIN, probe[0]);
+        assert_eq!(
+            probe.len(),
+            u16::from_be_bytes([probe[2], probe[3]]) as usize
+        );
+        assert_eq!(PRELOGIN_TERMINATOR, probe[8 + 25]);
+    }
+}
@@ -5,7 +5,7 @@
 
 use std::collections::HashMap;
 
-use super::{Checker, TcpChecker};
+use super::{Checker, MySQLHandshake, TcpChecker};
 use crate::models::{technology::Technology, Finding};
 use log::{info, trace};
 use regex::Regex;
@@ -27,61 +27,60 @@ impl<'a> MySQLChecker<'a> {
     /// reused.
     pub fn new() -> Self {
         let mut regexes = HashMap::new();
-        // Example: S
-        // 5.7.37-nmm1-logm{pX^4gw9JD]Sg4mysql_native_password
+        // The regex is applied on the server version read in the
+        // handshake, not on the whole banner which is binary.
+        // Example: 5.7.37-nmm1-log
         //
         // OR
         //
-        // 8.0.36-0ubuntu0.20.04.1$en#@�bXL<%m6k/Dcaching_sha2_password
-        //
-        // (?s) means that . also matches a newline.
-        let regex =
-            Regex::new(r"(?s)(?P<wholematch>(?P<version1>\d+\.\d+(\.\d+)?).+_password)").unwrap();
-        // MySQL banner contains characters which are replaced by the
-        // replacement character (U+FFFD). Splitting an evidence on this
-        // character makes the program panic because it's a multi-bytes
-        // character. Instead, take the whole banner.
-        regexes.insert("mysql-banner", (regex, 100, 100));
-        Self { regexes: regexes }
+        // 8.0.36-0ubuntu0.20.04.1
+        let regex = Regex::new(r"^(?P<wholematch>(?P<version1>\d+\.\d+(\.\d+)?).*)$").unwrap();
+        regexes.insert("mysql-server-version", (regex, 50, 50));
+        Self { regexes }
     }
 }
 
 impl<'a> TcpChecker for MySQLChecker<'a> {
     /// Check if the asset is running MySQL.
-    /// It looks for the MySQL banner.
+    /// It looks for the MySQL handshake in the banner.
     fn check_tcp(&self, data: &[String]) -> Option<Finding> {
         trace!("Running MySQLChecker::check_tcp()");
-        // For each item, check if it's an MySQL banner
-        for item in data {
-            trace!("Checking item: {}", item);
-            // Avoid false positive when MariaDB is present in the banner
-            if item.clone().to_lowercase().contains("mariadb") {
-                trace!("Item contains \"mariadb\", ignored");
-                continue;
-            }
+        data.iter()
+            .find_map(|item| self.check_tcp_banner(item.as_bytes()))
+    }
 
-            let banner_regex_params = self
-                .regexes
-                .get("mysql-banner")
-                .expect("Regex MySQL/mysql-banner not found");
-            let (regex, keep_left, keep_right) = banner_regex_params;
-            let caps_result = regex.captures(&item);
+    /// Parses the handshake sent by the server to read its version.
+    fn check_tcp_banner(&self, data: &[u8]) -> Option<Finding> {
+        trace!("Running MySQLChecker::check_tcp_banner()");
+        let handshake = MySQLHandshake::parse(data)?;
+        trace!("Handshake: {:?}", handshake);
+        // Avoid false positive, MariaDB uses the same handshake
+        if handshake.server_version.to_lowercase().contains("mariadb") {
+            trace!("Server version contains \"mariadb\", ignored");
+            return None;
+        }
 
-            // The regex matches
-            if caps_result.is_some() {
-                info!("Regex MySQL/mysql-banner matches");
-                let caps = caps_result.unwrap();
-                return Some(self.extract_finding_from_captures(
-		    caps,
-		    None,
-		    keep_left.to_owned(),
-		    keep_right.to_owned(),
-		    Technology::MySQL,
-		    "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" in its banner",
-		));
-            }
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get("mysql-server-version")
+            .expect("Regex MySQL/mysql-server-version not found");
+        let caps = regex.captures(&handshake.server_version)?;
+        info!("Regex MySQL/mysql-server-version matches");
+        let mut finding = self.extract_finding_from_captures(
+            caps,
+            None,
+            keep_left.to_owned(),
+            keep_right.to_owned(),
+            Technology::MySQL,
+            "$techno_name$$techno_version$ has been identified because its handshake gives the server version \"$evidence$\"",
+        );
+        if let Some(auth_plugin_name) = &handshake.auth_plugin_name {
+            finding.evidence_text.push_str(&format!(
+                ", the authentication plugin is \"{}\"",
+                auth_plugin_name
+            ));
         }
-        return None;
+        Some(finding)
     }
 
     /// This checker supports MySQL
@@ -89,3 +88,48 @@ impl<'a> TcpChecker for MySQLChecker<'a> {
         Technology::MySQL
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn handshake_matches() {
+        let checker = MySQLChecker::new();
+        let banner = b"[\x00\x00\x00\x0a8.0.36-0ubuntu0.20.04.1\x00\x0b\x00\x00\x00$en#@\xabbL\x00\xff\xff\xff\x02\x00\xff\xdf\x15\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00<%m6k/D\x15\xc1\x8e\x09r\x00caching_sha2_password\x00";
+        let finding = checker.check_tcp_banner(banner).unwrap();
+        check_finding_fields(
+            &finding,
+            "8.0.36-0ubuntu0.20.04.1",
+            Technology::MySQL,
+            Some("8.0.36"),
+            None,
+        );
+        assert!(finding
+            .evidence_text
+            .contains("the authentication plugin is \"caching_sha2_password\""));
+
+        // Without the authentication plugin
+        let banner = b"8\x00\x00\x00\x0a5.7.34-log\x00\x0b\x00\x00\x00abcdefgh\x00\xff\xf7\x08\x02\x00\x00\x00\x15\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00ijklmnopqrst\x00";
+        let finding = checker.check_tcp_banner(banner).unwrap();
+        check_finding_fields(
+            &finding,
+            "5.7.34-log",
+            Technology::MySQL,
+            Some("5.7.34"),
+            None,
+        );
+        assert!(!finding.evidence_text.contains("authentication plugin"));
+    }
+
+    #[test]
+    fn handshake_doesnt_match() {
+        let checker = MySQLChecker::new();
+        let banner = b"c\x00\x00\x00\x0a5.5.5-10.3.17-MariaDB-0+deb10u1\x00\x0b\x00\x00\x0042g0YPc#\x00\xfe\xf7\x08\x02\x00\xbf\x81\x15\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00#%3mMz=aPLlZ\x00mysql_native_password\x00";
+        assert!(checker.check_tcp_banner(banner).is_none());
+        // The version in a text banner isn't a handshake
+        let banner = "220 5.7.34 mysql_native_password";
+        assert!(checker.check_tcp(&[banner.to_string()]).is_none());
+    }
+}
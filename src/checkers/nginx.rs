@@ -7,10 +7,21 @@ use std::collections::HashMap;
 
 use super::{Checker, HttpChecker};
 use crate::models::reqres::{UrlRequestType, UrlResponse};
-use crate::models::{technology::Technology, Finding};
+use crate::models::{technology::Technology, Confidence, Finding};
 use log::{info, trace};
 use regex::Regex;
 
+/// The X-Accel-* headers, used by the applications to control Nginx.
+/// Nginx doesn't forward them by default, but seeing them means the
+/// application is made to run behind Nginx.
+const ACCEL_HEADERS: [&str; 5] = [
+    "X-accel-buffering",
+    "X-accel-redirect",
+    "X-accel-expires",
+    "X-accel-limit-rate",
+    "X-accel-charset",
+];
+
 /// The Nginx checker
 pub struct NginxChecker<'a> {
     /// The regexes and their parameters used to recognize the technology
@@ -32,13 +43,24 @@ impl<'a> NginxChecker<'a> {
                 .unwrap();
         // Example: <hr><center>nginx/1.22.3</center>
         let body_regex = Regex::new(r"<hr><center>(?P<wholematch>nginx(\/(?P<version1>\d+\.\d+\.\d+)( \([^\)]+\)))?)</center>").unwrap();
+        // The default error page, when the signature is replaced or removed
+        // Example: <body>
+        // <center><h1>404 Not Found</h1></center>
+        // <hr><center>webserver</center>
+        let body_error_page_regex = Regex::new(
+            r#"<body>\r?\n(?P<wholematch><center><h1>\d{3} [^<]+</h1></center>)\r?\n(<hr><center>[^<]*</center>\r?\n)?</body>"#,
+        )
+        .unwrap();
 
         regexes.insert("http-header", (header_regex, 45, 45));
         regexes.insert("http-body", (body_regex, 10, 15));
+        regexes.insert("http-body-error-page", (body_error_page_regex, 50, 50));
         Self { regexes: regexes }
     }
 
     /// Check for the technology in HTTP headers.
+    /// When the version is hidden (server_tokens off), the X-Accel-* headers
+    /// give a versionless finding.
     fn check_http_headers(&self, url_response: &UrlResponse) -> Option<Finding> {
         trace!(
             "Running NginxChecker::check_http_headers() on {}",
@@ -71,6 +93,29 @@ impl<'a> NginxChecker<'a> {
 		));
             }
         }
+
+        for header_name in ACCEL_HEADERS {
+            if let Some(header_value) = url_response.headers.get(header_name) {
+                info!("Header Nginx/{} found", header_name);
+                let evidence = format!("{}: {}", header_name, header_value);
+                let evidence_text = format!(
+                    "{} has been identified using the HTTP header \"{}\" returned at the following URL: {}",
+                    Technology::Nginx,
+                    evidence,
+                    url_response.url
+                );
+                return Some(
+                    Finding::new(
+                        Technology::Nginx,
+                        None,
+                        &evidence,
+                        &evidence_text,
+                        Some(&url_response.url),
+                    )
+                    .with_confidence(Confidence::Low),
+                );
+            }
+        }
         None
     }
 
@@ -97,6 +142,22 @@ impl<'a> NginxChecker<'a> {
 		"$techno_name$$techno_version$ has been identified by looking at its signature \"$evidence$\" at this page: $url_of_finding$"
 	    ));
         }
+
+        let (regex_error_page, keep_left_error_page, keep_right_error_page) = self
+            .regexes
+            .get("http-body-error-page")
+            .expect("Regex Nginx/http-body-error-page not found");
+        if let Some(caps) = regex_error_page.captures(&url_response.body) {
+            info!("Regex Nginx/http-body-error-page matches");
+            return Some(self.extract_finding_from_captures(
+		caps,
+		Some(url_response),
+		keep_left_error_page.to_owned(),
+		keep_right_error_page.to_owned(),
+		Technology::Nginx,
+		"$techno_name$$techno_version$ has been identified by looking at its default error page \"$evidence$\" at this page: $url_of_finding$"
+	    ).with_confidence(Confidence::Medium));
+        }
         None
     }
 }
@@ -108,32 +169,46 @@ impl<'a> HttpChecker for NginxChecker<'a> {
     /// It looks in the following HTTP headers:
     /// - Server
     /// - X-Powered-By
+    /// - X-Accel-*
     /// and in the "not found" page content
     ///
     /// Returns only one finding, otherwise findings would be duplicated each
     /// time it's found.
+    /// A finding with a version is always preferred over the versionless
+    /// ones, given when the version is hidden.
     fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
         trace!("Running NginxChecker::check_http()");
 
+        let mut versionless_finding = None;
         for url_response in data {
             // JavaScript files could be hosted on a different server
             // Don't check the JavaScript files to avoid false positive,
             // Check only the "main" requests.
-            if url_response.request_type != UrlRequestType::Default {
+            // A redirection to another host leads to another server.
+            if url_response.request_type != UrlRequestType::Default
+                || url_response.is_redirected_to_another_host()
+            {
                 continue;
             }
 
-            // Check in HTTP headers first
-            let header_finding = self.check_http_headers(url_response);
-            if header_finding.is_some() {
-                return vec![header_finding.unwrap()];
-            }
-            // Check in response body then
-            let body_finding = self.check_http_body(url_response);
-            if body_finding.is_some() {
-                return vec![body_finding.unwrap()];
+            // Check in HTTP headers first, in response body then
+            let findings = [
+                self.check_http_headers(url_response),
+                self.check_http_body(url_response),
+            ];
+            for finding in findings.into_iter().flatten() {
+                if finding.version.is_some() {
+                    return vec![finding];
+                }
+                if versionless_finding.is_none() {
+                    versionless_finding = Some(finding);
+                }
             }
         }
+
+        if let Some(finding) = versionless_finding {
+            return vec![finding];
+        }
         Vec::new()
     }
 
@@ -265,6 +340,96 @@ mod tests {
         assert!(finding.is_none());
     }
 
+    #[test]
+    fn hidden_version_gives_versionless_finding() {
+        let checker = NginxChecker::new();
+        let url1 = "https://www.example.com/download.php";
+        // server_tokens off; with the Server header removed
+        let mut headers1 = HashMap::new();
+        headers1.insert("X-accel-buffering".to_string(), "no".to_string());
+        let url_response_valid =
+            UrlResponse::new(url1, headers1, "the body", UrlRequestType::Default, 200);
+        let finding = checker.check_http_headers(&url_response_valid);
+        assert!(finding.is_some());
+        let finding = finding.unwrap();
+        check_finding_fields(
+            &finding,
+            "X-accel-buffering: no",
+            Technology::Nginx,
+            None,
+            Some(url1),
+        );
+        assert_eq!(Confidence::Low, finding.confidence);
+
+        // The signature of the error page is replaced
+        let body2 = "<html>\r\n<head><title>404 Not Found</title></head>\r\n<body>\r\n<center><h1>404 Not Found</h1></center>\r\n<hr><center>webserver</center>\r\n</body>\r\n</html>";
+        let url2 = "https://www.example.com/pageNotFoundNotFound";
+        let url_response_valid2 =
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 404);
+        let finding = checker.check_http_body(&url_response_valid2);
+        assert!(finding.is_some());
+        let finding = finding.unwrap();
+        check_finding_fields(
+            &finding,
+            "<center><h1>404 Not Found</h1></center>",
+            Technology::Nginx,
+            None,
+            Some(url2),
+        );
+        assert_eq!(Confidence::Medium, finding.confidence);
+
+        // The signature of the error page is removed
+        let body3 = "<html>\n<head><title>400 Bad Request</title></head>\n<body>\n<center><h1>400 Bad Request</h1></center>\n</body>\n</html>";
+        let url_response_valid3 =
+            UrlResponse::new(url2, HashMap::new(), body3, UrlRequestType::Default, 400);
+        assert!(checker.check_http_body(&url_response_valid3).is_some());
+
+        // Another server's error page
+        let body4 =
+            "<html><body><h1>404 Not Found</h1><p>The page was not found.</p></body></html>";
+        let url_response_invalid =
+            UrlResponse::new(url2, HashMap::new(), body4, UrlRequestType::Default, 404);
+        assert!(checker.check_http_body(&url_response_invalid).is_none());
+    }
+
+    #[test]
+    fn version_is_preferred_over_hidden_version() {
+        let checker = NginxChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("Server".to_string(), "nginx".to_string());
+        let url_response_versionless = UrlResponse::new(
+            "https://www.example.com/",
+            headers1,
+            "the body",
+            UrlRequestType::Default,
+            200,
+        );
+        let body2 = "<hr><center>nginx/1.24.0 (Ubuntu)</center>";
+        let url2 = "https://www.example.com/pageNotFoundNotFound";
+        let url_response_version =
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 404);
+        let findings = checker.check_http(&[url_response_versionless, url_response_version]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "nginx/1.24.0",
+            Technology::Nginx,
+            Some("1.24.0"),
+            Some(url2),
+        );
+
+        // Only versionless findings
+        let mut headers3 = HashMap::new();
+        headers3.insert("Server".to_string(), "nginx".to_string());
+        headers3.insert("X-accel-buffering".to_string(), "no".to_string());
+        let url3 = "https://www.example.com/";
+        let url_response_versionless =
+            UrlResponse::new(url3, headers3, "the body", UrlRequestType::Default, 200);
+        let findings = checker.check_http(&[url_response_versionless]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(&findings[0], "nginx", Technology::Nginx, None, Some(url3));
+    }
+
     #[test]
     fn finds_match_in_url_responses() {
         let checker = NginxChecker::new();
@@ -313,6 +478,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn redirects_to_another_host_are_ignored() {
+        use crate::models::reqres::Redirect;
+
+        let checker = NginxChecker::new();
+        let mut headers1 = HashMap::new();
+        headers1.insert("Server".to_string(), "nginx/1.24.0".to_string());
+        let url_response_redirected = UrlResponse::new(
+            "https://login.example.net/",
+            headers1,
+            "the body",
+            UrlRequestType::Default,
+            200,
+        )
+        .with_redirects(vec![Redirect {
+            url: "https://www.example.com/admin/".to_string(),
+            status_code: 302,
+            location: "https://login.example.net/".to_string(),
+        }]);
+        assert!(checker
+            .check_http(std::slice::from_ref(&url_response_redirected))
+            .is_empty());
+
+        // The redirections on the same host are followed
+        let mut url_response_same_host = url_response_redirected;
+        url_response_same_host.redirects[0].url = "https://login.example.net".to_string();
+        assert_eq!(1, checker.check_http(&[url_response_same_host]).len());
+    }
+
     #[test]
     fn doesnt_find_match_in_url_responses() {
         let checker = NginxChecker::new();
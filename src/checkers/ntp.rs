@@ -0,0 +1,257 @@
+//! The NTP checker.
+//! This module contains the checker used to determine if NTP is exposed
+//! by the asset and which daemon answers.
+//!
+//! It sends an NTP control (mode 6) READVAR request, the system variables
+//! in the response usually give the version of ntpd and the host OS.
+//! Answering control queries can be abused for amplification, so it's
+//! flagged in the evidence.
+//! https://www.ntp.org
+
+use super::{Checker, UdpChecker};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The length of the header of a control message
+const CONTROL_HEADER_LENGTH: usize = 12;
+/// The mode of the control messages
+const MODE_CONTROL: u8 = 6;
+/// The opcode of the READVAR request
+const OPCODE_READVAR: u8 = 2;
+/// The response bit, set in the second byte of the responses
+const RESPONSE_BIT: u8 = 0x80;
+/// The maximum number of chars of a variable kept in the evidence
+const MAX_VARIABLE_LENGTH: usize = 100;
+
+/// The NTP checker
+pub struct NtpChecker {
+    /// The regex used to extract the version of ntpd
+    version_regex: Regex,
+}
+
+impl Checker for NtpChecker {}
+
+impl NtpChecker {
+    /// Creates a new NtpChecker.
+    /// By doing so, the regex is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        // Example: ntpd 4.2.8p15@1.3728-o Wed Sep 23 11:46:38 UTC 2020 (1)
+        let version_regex =
+            Regex::new(r"^(?P<wholematch>ntpd (?P<version1>\d+\.\d+\.\d+(p\d+)?))").unwrap();
+        Self { version_regex }
+    }
+
+    /// Parses a READVAR response and returns its variables, in their
+    /// order. The quotes around the values are removed.
+    /// Only the first fragment is read, the version comes first anyway.
+    fn parse_response(data: &[u8]) -> Option<Vec<(String, String)>> {
+        let header = data.get(..CONTROL_HEADER_LENGTH)?;
+        if header[0] & 0x07 != MODE_CONTROL
+            || header[1] & RESPONSE_BIT == 0
+            || header[1] & 0x1F != OPCODE_READVAR
+        {
+            return None;
+        }
+        let count = u16::from_be_bytes([header[10], header[11]]) as usize;
+        // A truncated datagram keeps what it has
+        let end = (CONTROL_HEADER_LENGTH + count).min(data.len());
+        let content = String::from_utf8_lossy(&data[CONTROL_HEADER_LENGTH..end]);
+
+        // Example: version="ntpd 4.2.8p15", processor="x86_64", leap=0
+        let mut variables = Vec::new();
+        let mut rest = content.trim_start();
+        while !rest.is_empty() {
+            let (name, after_name) = match rest.split_once('=') {
+                Some(split) => split,
+                None => break,
+            };
+            let (value, after_value) = match after_name.strip_prefix('"') {
+                Some(quoted) => match quoted.split_once('"') {
+                    Some((value, after)) => (value, after),
+                    None => (quoted, ""),
+                },
+                None => after_name.split_once(',').unwrap_or((after_name, "")),
+            };
+            variables.push((name.trim().to_string(), value.trim().to_string()));
+            rest = after_value.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        }
+        Some(variables)
+    }
+}
+
+impl UdpChecker for NtpChecker {
+    /// Returns a control READVAR request for the system variables
+    fn probe_payload(&self) -> Vec<u8> {
+        // LI 0, version 2, mode 6, then the opcode, the sequence number,
+        // and the status, the association ID, the offset and the count
+        // set to 0
+        vec![
+            (2 << 3) | MODE_CONTROL,
+            OPCODE_READVAR,
+            0x00,
+            0x01,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+        ]
+    }
+
+    /// Check if the asset exposes NTP.
+    /// Any valid response means the control queries are answered. The
+    /// version of ntpd, the system and the processor are taken from the
+    /// variables when available.
+    fn check_udp(&self, data: &[u8]) -> Option<Finding> {
+        trace!("Running NtpChecker::check_udp()");
+        let variables = Self::parse_response(data)?;
+        info!(
+            "Got an NTP control response with {} variables",
+            variables.len()
+        );
+        let get_variable = |name: &str| -> Option<String> {
+            variables
+                .iter()
+                .find(|(variable_name, _)| variable_name == name)
+                .map(|(_, value)| value.chars().take(MAX_VARIABLE_LENGTH).collect())
+        };
+
+        let version_variable = get_variable("version");
+        let caps = version_variable
+            .as_deref()
+            .and_then(|version| self.version_regex.captures(version));
+        let mut finding = match caps {
+            Some(caps) => {
+                info!("Regex NTP/ntpd-version matches");
+                self.extract_finding_from_captures(
+                    caps,
+                    None,
+                    30,
+                    30,
+                    Technology::Ntp,
+                    "$techno_name$$techno_version$ has been identified because the response to a control query contains \"$evidence$\"",
+                )
+            }
+            None => {
+                // Another daemon, or the version is hidden
+                let evidence = match &version_variable {
+                    Some(version) => version.clone(),
+                    None => "mode 6 response".to_string(),
+                };
+                let evidence_text = format!(
+                    "{} has been identified because the control queries are answered (\"{}\")",
+                    Technology::Ntp,
+                    evidence
+                );
+                Finding::new(Technology::Ntp, None, &evidence, &evidence_text, None)
+            }
+        };
+
+        if let Some(system) = get_variable("system") {
+            finding
+                .evidence_text
+                .push_str(&format!(", the system is \"{}\"", system));
+        }
+        if let Some(processor) = get_variable("processor") {
+            finding
+                .evidence_text
+                .push_str(&format!(", the processor is \"{}\"", processor));
+        }
+        finding
+            .evidence_text
+            .push_str(", answering the control queries (mode 6) can be abused for amplification");
+        Some(finding)
+    }
+
+    /// This checker supports NTP
+    fn get_technology(&self) -> Technology {
+        Technology::Ntp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    /// Builds a READVAR response with the given variables
+    fn get_response(variables: &str) -> Vec<u8> {
+        let mut data = vec![0x16, 0x82, 0x00, 0x01, 0x06, 0x18, 0x00, 0x00, 0x00, 0x00];
+        data.extend_from_slice(&(variables.len() as u16).to_be_bytes());
+        data.extend_from_slice(variables.as_bytes());
+        // Padding to a multiple of 4 bytes
+        data.extend(vec![0; (4 - data.len() % 4) % 4]);
+        data
+    }
+
+    #[test]
+    fn response_matches() {
+        let checker = NtpChecker::new();
+        let data = get_response(
+            "version=\"ntpd 4.2.8p15@1.3728-o Wed Sep 23 11:46:38 UTC 2020 (1)\",\r\n\
+            processor=\"x86_64\", system=\"Linux/5.4.0-169-generic\", leap=0,\r\n\
+            stratum=2, precision=-24, rootdelay=1.234",
+        );
+        let finding = checker.check_udp(&data).unwrap();
+        check_finding_fields(
+            &finding,
+            "ntpd 4.2.8p15",
+            Technology::Ntp,
+            Some("4.2.8p15"),
+            None,
+        );
+        assert!(finding
+            .evidence_text
+            .contains("the system is \"Linux/5.4.0-169-generic\", the processor is \"x86_64\""));
+        assert!(finding.evidence_text.contains("amplification"));
+    }
+
+    #[test]
+    fn response_without_version_matches() {
+        let checker = NtpChecker::new();
+        let data = get_response("leap=0, stratum=3, precision=-20");
+        let finding = checker.check_udp(&data).unwrap();
+        check_finding_fields(&finding, "mode 6 response", Technology::Ntp, None, None);
+        assert!(!finding.evidence_text.contains("the system is"));
+
+        let data = get_response("version=\"chronyd\", system=\"UNIX\"");
+        let finding = checker.check_udp(&data).unwrap();
+        check_finding_fields(&finding, "chronyd", Technology::Ntp, None, None);
+        assert!(finding.evidence_text.contains("the system is \"UNIX\""));
+    }
+
+    #[test]
+    fn response_doesnt_match() {
+        let checker = NtpChecker::new();
+        // The request itself is not a response
+        assert!(checker.check_udp(&checker.probe_payload()).is_none());
+        // A mode 4 (server) response to a time request
+        let mut data = vec![0x24, 0x02, 0x06, 0xE8];
+        data.extend(vec![0; 44]);
+        assert!(checker.check_udp(&data).is_none());
+        assert!(checker.check_udp(&[0x16, 0x82]).is_none());
+    }
+
+    #[test]
+    fn parse_response_works() {
+        let data = get_response("version=\"ntpd 4.2.6p5\", leap=00, clock=\"e9a1, 2024\"");
+        assert_eq!(
+            Some(vec![
+                ("version".to_string(), "ntpd 4.2.6p5".to_string()),
+                ("leap".to_string(), "00".to_string()),
+                ("clock".to_string(), "e9a1, 2024".to_string()),
+            ]),
+            NtpChecker::parse_response(&data)
+        );
+        // Truncated
+        assert_eq!(
+            Some(vec![("version".to_string(), "ntpd 4.2".to_string())]),
+            NtpChecker::parse_response(&data[..29])
+        );
+    }
+}
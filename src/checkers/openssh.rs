@@ -10,6 +10,52 @@ use crate::models::{technology::Technology, Finding};
 use log::{info, trace};
 use regex::Regex;
 
+/// The identification string sent to the server, so it sends its
+/// KEXINIT packet
+const SSH_CLIENT_IDENTIFICATION: &[u8] = b"SSH-2.0-Sanca\r\n";
+
+/// The weak or deprecated algorithms, flagged when the server offers them
+const WEAK_ALGORITHMS: [&str; 24] = [
+    // Key exchange
+    "diffie-hellman-group1-sha1",
+    "diffie-hellman-group14-sha1",
+    "diffie-hellman-group-exchange-sha1",
+    "gss-group1-sha1-toWM5Slw5Ew8Mqkay+al2g==",
+    // Host keys
+    "ssh-rsa",
+    "ssh-dss",
+    "ssh-rsa-cert-v01@openssh.com",
+    "ssh-dss-cert-v01@openssh.com",
+    // Ciphers
+    "3des-cbc",
+    "aes128-cbc",
+    "aes192-cbc",
+    "aes256-cbc",
+    "blowfish-cbc",
+    "cast128-cbc",
+    "arcfour",
+    "arcfour128",
+    "arcfour256",
+    "rijndael-cbc@lysator.liu.se",
+    // MACs
+    "hmac-md5",
+    "hmac-md5-96",
+    "hmac-sha1-96",
+    "hmac-md5-etm@openssh.com",
+    "hmac-md5-96-etm@openssh.com",
+    "hmac-sha1-96-etm@openssh.com",
+];
+
+/// The name-lists of the KEXINIT packet, in their order
+const KEXINIT_NAME_LISTS: [&str; 6] = [
+    "kex",
+    "host key",
+    "cipher client to server",
+    "cipher server to client",
+    "mac client to server",
+    "mac server to client",
+];
+
 /// The OpenSSH checker
 pub struct OpenSSHChecker<'a> {
     /// The regexes and their parameters used to recognize the technology
@@ -35,6 +81,56 @@ impl<'a> OpenSSHChecker<'a> {
         regexes.insert("openssh-banner", (regex, 20, 20));
         OpenSSHChecker { regexes: regexes }
     }
+
+    /// Returns the weak algorithms offered by the server in its KEXINIT
+    /// packet, with the name-list they are in.
+    /// The packet follows the identification line, it can be truncated,
+    /// only the name-lists read completely are checked.
+    fn get_weak_algorithms(&self, data: &[u8]) -> Vec<(String, &'static str)> {
+        let mut weak_algorithms = Vec::new();
+        // The binary packet starts after the identification line
+        let identification_start = match data.windows(4).position(|w| w == b"SSH-") {
+            Some(position) => position,
+            None => return weak_algorithms,
+        };
+        let packet_start = match data[identification_start..]
+            .iter()
+            .position(|b| *b == b'\n')
+        {
+            Some(position) => identification_start + position + 1,
+            None => return weak_algorithms,
+        };
+        // The packet length (4 bytes) and the padding length (1 byte),
+        // then the payload: SSH_MSG_KEXINIT (20) and the cookie (16 bytes)
+        let payload = &data[packet_start..];
+        if payload.get(5) != Some(&20) {
+            trace!("No KEXINIT packet after the identification");
+            return weak_algorithms;
+        }
+
+        let mut position = 5 + 1 + 16;
+        for name_list in KEXINIT_NAME_LISTS {
+            let length = match payload.get(position..position + 4) {
+                Some(bytes) => {
+                    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize
+                }
+                None => break,
+            };
+            position += 4;
+            let names = match payload.get(position..position + length) {
+                Some(names) => String::from_utf8_lossy(names).to_string(),
+                None => break,
+            };
+            position += length;
+            trace!("KEXINIT {}: {}", name_list, names);
+            for name in names.split(',') {
+                if WEAK_ALGORITHMS.contains(&name) {
+                    weak_algorithms.push((name.to_string(), name_list));
+                }
+            }
+        }
+        weak_algorithms
+    }
 }
 
 impl<'a> TcpChecker for OpenSSHChecker<'a> {
@@ -70,8 +166,146 @@ impl<'a> TcpChecker for OpenSSHChecker<'a> {
         return None;
     }
 
+    /// Sends the identification of the client, the server answers with its
+    /// KEXINIT packet listing the algorithms it supports.
+    fn get_tcp_probe(&self) -> Option<Vec<u8>> {
+        Some(SSH_CLIENT_IDENTIFICATION.to_vec())
+    }
+
+    /// Checks the banner in the response, then audits the algorithms of
+    /// the KEXINIT packet. The weak algorithms are added to the evidence.
+    fn check_tcp_probe_response(&self, data: &[u8]) -> Option<Finding> {
+        trace!("Running OpenSSHChecker::check_tcp_probe_response()");
+        // The identification line is the banner
+        let response = String::from_utf8_lossy(data);
+        let banner = response
+            .lines()
+            .find(|line| line.starts_with("SSH-"))?
+            .trim_end();
+        let mut finding = self.check_tcp(&[banner.to_string()])?;
+
+        let weak_algorithms = self.get_weak_algorithms(data);
+        if !weak_algorithms.is_empty() {
+            info!("OpenSSH offers weak algorithms: {:?}", weak_algorithms);
+            let weak_algorithms_text: Vec<String> = weak_algorithms
+                .iter()
+                .map(|(name, name_list)| format!("{} ({})", name, name_list))
+                .collect();
+            finding.evidence_text.push_str(&format!(
+                ", the server offers weak algorithms: {}",
+                weak_algorithms_text.join(", ")
+            ));
+        }
+        Some(finding)
+    }
+
     /// This checker supports OpenSSH
     fn get_technology(&self) -> Technology {
         Technology::OpenSSH
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    /// Builds the response of a server: its identification line and its
+    /// KEXINIT packet with the given name-lists.
+    fn get_response(identification: &str, name_lists: &[&str]) -> Vec<u8> {
+        let mut payload = vec![20];
+        payload.extend_from_slice(&[0x5a; 16]);
+        for name_list in name_lists {
+            payload.extend_from_slice(&(name_list.len() as u32).to_be_bytes());
+            payload.extend_from_slice(name_list.as_bytes());
+        }
+        let padding_length = 8 - (payload.len() + 5) % 8;
+        let mut response = format!("{}\r\n", identification).into_bytes();
+        response.extend_from_slice(&((payload.len() + padding_length + 1) as u32).to_be_bytes());
+        response.push(padding_length as u8);
+        response.extend_from_slice(&payload);
+        response.extend(vec![0; padding_length]);
+        response
+    }
+
+    #[test]
+    fn banner_matches() {
+        let checker = OpenSSHChecker::new();
+        let banner = "SSH-2.0-OpenSSH_9.2p1 Debian-2+deb12u2";
+        let finding = checker.check_tcp(&[banner.to_string()]).unwrap();
+        check_finding_fields(
+            &finding,
+            "SSH-2.0-OpenSSH_9.2p1",
+            Technology::OpenSSH,
+            Some("9.2p1"),
+            None,
+        );
+    }
+
+    #[test]
+    fn weak_algorithms_are_reported() {
+        let checker = OpenSSHChecker::new();
+        let response = get_response(
+            "SSH-2.0-OpenSSH_7.4",
+            &[
+                "curve25519-sha256,diffie-hellman-group14-sha1,diffie-hellman-group1-sha1",
+                "ssh-rsa,rsa-sha2-512,ssh-ed25519",
+                "aes128-ctr,aes128-cbc",
+                "aes128-ctr,aes128-cbc",
+                "hmac-sha2-256,hmac-md5",
+                "hmac-sha2-256,hmac-md5",
+                "none,zlib@openssh.com",
+                "none,zlib@openssh.com",
+            ],
+        );
+        let finding = checker.check_tcp_probe_response(&response).unwrap();
+        check_finding_fields(
+            &finding,
+            "SSH-2.0-OpenSSH_7.4",
+            Technology::OpenSSH,
+            Some("7.4"),
+            None,
+        );
+        assert!(finding.evidence_text.contains(
+            "the server offers weak algorithms: diffie-hellman-group14-sha1 (kex), \
+            diffie-hellman-group1-sha1 (kex), ssh-rsa (host key), \
+            aes128-cbc (cipher client to server), aes128-cbc (cipher server to client), \
+            hmac-md5 (mac client to server), hmac-md5 (mac server to client)"
+        ));
+    }
+
+    #[test]
+    fn strong_algorithms_are_not_reported() {
+        let checker = OpenSSHChecker::new();
+        let response = get_response(
+            "SSH-2.0-OpenSSH_9.6",
+            &[
+                "sntrup761x25519-sha512@openssh.com,curve25519-sha256",
+                "rsa-sha2-512,rsa-sha2-256,ssh-ed25519",
+                "chacha20-poly1305@openssh.com,aes256-gcm@openssh.com",
+                "chacha20-poly1305@openssh.com,aes256-gcm@openssh.com",
+                "hmac-sha2-256-etm@openssh.com",
+                "hmac-sha2-256-etm@openssh.com",
+            ],
+        );
+        let finding = checker.check_tcp_probe_response(&response).unwrap();
+        assert!(!finding.evidence_text.contains("weak algorithms"));
+
+        // A truncated packet only gives the name-lists read completely
+        let response = get_response("SSH-2.0-OpenSSH_7.4", &["diffie-hellman-group1-sha1"]);
+        let finding = checker
+            .check_tcp_probe_response(&response[..response.len() - 10])
+            .unwrap();
+        assert!(!finding.evidence_text.contains("weak algorithms"));
+    }
+
+    #[test]
+    fn other_servers_dont_match() {
+        let checker = OpenSSHChecker::new();
+        let response = get_response("SSH-2.0-dropbear_2022.83", &["diffie-hellman-group1-sha1"]);
+        assert!(checker.check_tcp_probe_response(&response).is_none());
+        assert!(checker
+            .check_tcp_probe_response(b"220 FTP Server ready.\r\n")
+            .is_none());
+    }
+}
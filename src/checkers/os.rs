@@ -4,9 +4,9 @@
 
 use std::collections::HashMap;
 
-use super::{Checker, HttpChecker, TcpChecker};
+use super::{Checker, HttpChecker, MySQLHandshake, TcpChecker};
 use crate::models::reqres::{UrlRequestType, UrlResponse};
-use crate::models::{technology::Technology, Finding};
+use crate::models::{target::Target, technology::Technology, version::Version, Finding};
 use log::{debug, info, trace};
 use regex::Regex;
 
@@ -42,7 +42,7 @@ impl<'a> OSChecker<'a> {
         //
         // Actually, detect only Debian with deb|bpo
         let mariadb_regex = Regex::new(
-            r"\d+\.\d+\.\d+\-(?P<version1>\d+\.\d+\.\d+)-MariaDB(.+(deb|bpo)(?P<osversion>\d+))",
+            r"(\d+\.\d+\.\d+\-)?(?P<version1>\d+\.\d+\.\d+)-MariaDB(.+(deb|bpo)(?P<osversion>\d+))",
         )
         .unwrap();
 
@@ -252,65 +252,73 @@ impl<'a> OSChecker<'a> {
         trace!("Running OSChecker::get_os_version");
         let os = os_name.to_lowercase();
         let software = software_name.to_lowercase();
-        let version = software_version.to_lowercase();
+        let version = Version::parse(software_version);
 
         debug!("Trying to guess OS version with the following values: OS name = {}, Software = {}, Software version = {}", os, software, version);
 
         // List the known versions of software
-        let mut versions: HashMap<(&str, &str, &str), &str> = HashMap::new();
+        let mut versions: Vec<((&str, &str, &str), &str)> = Vec::new();
         // Ubuntu / Apache httpd
-        versions.insert(("ubuntu", "apache", "2.4.29"), "18.04");
-        versions.insert(("ubuntu", "apache", "2.4.41"), "20.04");
-        versions.insert(("ubuntu", "apache", "2.4.52"), "22.04");
-        versions.insert(("ubuntu", "apache", "2.4.54"), "22.10");
-        versions.insert(("ubuntu", "apache", "2.4.55"), "23.04");
-        versions.insert(("ubuntu", "apache", "2.4.57"), "23.10");
-        versions.insert(("ubuntu", "apache", "2.4.58"), "24.04");
-        versions.insert(("ubuntu", "apache", "2.4.59"), "24.10");
+        versions.push((("ubuntu", "apache", "2.4.29"), "18.04"));
+        versions.push((("ubuntu", "apache", "2.4.41"), "20.04"));
+        versions.push((("ubuntu", "apache", "2.4.52"), "22.04"));
+        versions.push((("ubuntu", "apache", "2.4.54"), "22.10"));
+        versions.push((("ubuntu", "apache", "2.4.55"), "23.04"));
+        versions.push((("ubuntu", "apache", "2.4.57"), "23.10"));
+        versions.push((("ubuntu", "apache", "2.4.58"), "24.04"));
+        versions.push((("ubuntu", "apache", "2.4.59"), "24.10"));
 
         // Ubuntu / Nginx
-        versions.insert(("ubuntu", "nginx", "1.14.0"), "18.04");
-        versions.insert(("ubuntu", "nginx", "1.18.0"), "20.04|22.04");
-        versions.insert(("ubuntu", "nginx", "1.22.0"), "22.10|23.04");
-        versions.insert(("ubuntu", "nginx", "1.24.0"), "24.04");
+        versions.push((("ubuntu", "nginx", "1.14.0"), "18.04"));
+        versions.push((("ubuntu", "nginx", "1.18.0"), "20.04|22.04"));
+        versions.push((("ubuntu", "nginx", "1.22.0"), "22.10|23.04"));
+        versions.push((("ubuntu", "nginx", "1.24.0"), "24.04"));
 
         // Ubuntu / OpenSSH
-        versions.insert(("ubuntu", "openssh", "7.6"), "18.04");
-        versions.insert(("ubuntu", "openssh", "8.2"), "20.04");
-        versions.insert(("ubuntu", "openssh", "8.9"), "22.04");
-        versions.insert(("ubuntu", "openssh", "9.0"), "22.10|23.04");
-        versions.insert(("ubuntu", "openssh", "9.4"), "24.04");
+        versions.push((("ubuntu", "openssh", "7.6"), "18.04"));
+        versions.push((("ubuntu", "openssh", "8.2"), "20.04"));
+        versions.push((("ubuntu", "openssh", "8.9"), "22.04"));
+        versions.push((("ubuntu", "openssh", "9.0"), "22.10|23.04"));
+        versions.push((("ubuntu", "openssh", "9.4"), "24.04"));
 
         // Debian / Apache httpd
-        versions.insert(("debian", "apache", "2.2.22"), "7");
-        versions.insert(("debian", "apache", "2.4.25"), "9");
-        versions.insert(("debian", "apache", "2.4.38"), "10");
-        versions.insert(("debian", "apache", "2.4.54"), "11");
-        versions.insert(("debian", "apache", "2.4.57"), "12");
-        versions.insert(("debian", "apache", "2.4.58"), "13");
+        versions.push((("debian", "apache", "2.2.22"), "7"));
+        versions.push((("debian", "apache", "2.4.25"), "9"));
+        versions.push((("debian", "apache", "2.4.38"), "10"));
+        versions.push((("debian", "apache", "2.4.54"), "11"));
+        versions.push((("debian", "apache", "2.4.57"), "12"));
+        versions.push((("debian", "apache", "2.4.58"), "13"));
 
         // Debian / Nginx
-        versions.insert(("debian", "nginx", "1.14.2"), "10");
-        versions.insert(("debian", "nginx", "1.18.0"), "11");
-        versions.insert(("debian", "nginx", "1.22.1"), "12");
-        versions.insert(("debian", "nginx", "1.24.0"), "13");
+        versions.push((("debian", "nginx", "1.14.2"), "10"));
+        versions.push((("debian", "nginx", "1.18.0"), "11"));
+        versions.push((("debian", "nginx", "1.22.1"), "12"));
+        versions.push((("debian", "nginx", "1.24.0"), "13"));
 
         // Debian / OpenSSH
-        versions.insert(("debian", "openssh", "6.7"), "8");
-        versions.insert(("debian", "openssh", "7.9"), "10");
-        versions.insert(("debian", "openssh", "8.4"), "11");
-        versions.insert(("debian", "openssh", "9.2"), "12");
-        versions.insert(("debian", "openssh", "9.5"), "13");
+        versions.push((("debian", "openssh", "6.7"), "8"));
+        versions.push((("debian", "openssh", "7.9"), "10"));
+        versions.push((("debian", "openssh", "8.4"), "11"));
+        versions.push((("debian", "openssh", "9.2"), "12"));
+        versions.push((("debian", "openssh", "9.5"), "13"));
 
         // Oracle / OpenSSL
-        versions.insert(("oracle", "openssl", "3.0.1"), "9.1");
+        versions.push((("oracle", "openssl", "3.0.1"), "9.1"));
 
         // CentOS / Apache httpd
-        versions.insert(("centos", "apache", "2.4.6"), "7");
-        versions.insert(("centos", "apache", "2.4.37"), "8");
-        versions.insert(("centos", "apache", "2.4.57"), "9");
-
-        versions.get(&(&os, &software, &version)).copied()
+        versions.push((("centos", "apache", "2.4.6"), "7"));
+        versions.push((("centos", "apache", "2.4.37"), "8"));
+        versions.push((("centos", "apache", "2.4.57"), "9"));
+
+        // The versions are compared once parsed, so that 7.6 matches 7.6.0
+        versions
+            .into_iter()
+            .find(|((known_os, known_software, known_version), _)| {
+                *known_os == os
+                    && *known_software == software
+                    && Version::parse(known_version) == version
+            })
+            .map(|(_, os_version)| os_version)
     }
 }
 
@@ -402,7 +410,12 @@ impl<'a> TcpChecker for OSChecker<'a> {
             let (mariadb_regex, _keep_left_mariadb, _keep_right_mariadb) =
                 body_mariadb_regex_params;
 
-            let caps_result = mariadb_regex.captures(item_clean);
+            // Use the server version of the handshake when the banner
+            // can be parsed, the whole banner otherwise
+            let server_version =
+                MySQLHandshake::parse(item.as_bytes()).map(|handshake| handshake.server_version);
+            let mariadb_banner = server_version.as_deref().unwrap_or(item_clean);
+            let caps_result = mariadb_regex.captures(mariadb_banner);
             // The regex matches
             if caps_result.is_some() {
                 info!("Regex OS/mariadb-banner matches");
@@ -429,7 +442,7 @@ impl<'a> TcpChecker for OSChecker<'a> {
                         "The operating system {}{} has been identified using the banner presented by MariaDB: {}",
                         os_name,
                         version_text,
-                        item_clean
+                        mariadb_banner
                 );
 
                 let os_technology = if let Some(t) = self.get_technology_os(&os_name) {
@@ -442,7 +455,7 @@ impl<'a> TcpChecker for OSChecker<'a> {
                 return Some(Finding::new(
                     os_technology,
                     os_version,
-                    item_clean,
+                    mariadb_banner,
                     &os_evidence_text,
                     None,
                 ));
@@ -467,7 +480,10 @@ impl<'a> HttpChecker for OSChecker<'a> {
             // JavaScript files could be hosted on a different server
             // Don't check the JavaScript files to avoid false positive,
             // Check only the "main" requests.
-            if url_response.request_type != UrlRequestType::Default {
+            // A redirection to another host leads to another server.
+            if url_response.request_type != UrlRequestType::Default
+                || url_response.is_redirected_to_another_host()
+            {
                 continue;
             }
             // Check in HTTP headers first
@@ -490,10 +506,156 @@ impl<'a> HttpChecker for OSChecker<'a> {
     }
 }
 
+/// Merges the OS findings of each host into a single best guess.
+///
+/// Several services of a same host (e.g. OpenSSH, MariaDB, Apache httpd)
+/// can each give a hint about the OS. Instead of several partial findings,
+/// each OS finding is a vote for an OS, findings with a version weighing
+/// twice as much. The OS with the highest weight wins, and its version is
+/// the one most sources agree on. The confidence is the share of the
+/// weight agreeing with the result, and every source is recorded in the
+/// evidence text.
+///
+/// The hosts are told apart by the target of the findings, without its
+/// port. The other findings are returned unchanged, the merged OS finding
+/// of a host takes the place of its first OS finding.
+pub fn os_consensus(findings: Vec<Finding>) -> Vec<Finding> {
+    trace!("Running os_consensus()");
+    // The indexes of the OS findings of each host
+    let mut hosts: Vec<(Option<String>, Vec<usize>)> = Vec::new();
+    for (index, finding) in findings.iter().enumerate() {
+        if !finding.technology.is_os() {
+            continue;
+        }
+        let host = finding
+            .target
+            .as_deref()
+            .map(|target| Target::new(target, None).get_host());
+        match hosts.iter_mut().find(|(h, _)| *h == host) {
+            Some((_, indexes)) => indexes.push(index),
+            None => hosts.push((host, vec![index])),
+        }
+    }
+
+    // The merged finding replaces the first OS finding of the host, the
+    // other ones are dropped
+    let mut replacements: HashMap<usize, Option<Finding>> = HashMap::new();
+    for (_, indexes) in hosts.iter().filter(|(_, indexes)| indexes.len() > 1) {
+        let os_findings: Vec<&Finding> = indexes.iter().map(|index| &findings[*index]).collect();
+        replacements.insert(indexes[0], Some(merge_os_findings(&os_findings)));
+        for index in &indexes[1..] {
+            replacements.insert(*index, None);
+        }
+    }
+    findings
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, finding)| replacements.remove(&index).unwrap_or(Some(finding)))
+        .collect()
+}
+
+/// Merges the OS findings of a host, see [`os_consensus`].
+fn merge_os_findings(os_findings: &[&Finding]) -> Finding {
+    let weight = |finding: &Finding| if finding.version.is_some() { 2 } else { 1 };
+    let total_weight: usize = os_findings.iter().map(|f| weight(f)).sum();
+
+    // Elect the OS, the first one wins in case of a tie
+    let mut os_weights: Vec<(Technology, usize)> = Vec::new();
+    for finding in os_findings {
+        match os_weights
+            .iter_mut()
+            .find(|(t, _)| *t == finding.technology)
+        {
+            Some((_, w)) => *w += weight(finding),
+            None => os_weights.push((finding.technology.clone(), weight(finding))),
+        }
+    }
+    let (os, os_weight) = os_weights
+        .iter()
+        .fold(&os_weights[0], |best, current| {
+            if current.1 > best.1 {
+                current
+            } else {
+                best
+            }
+        })
+        .clone();
+    let (agreeing, disagreeing): (Vec<&Finding>, Vec<&Finding>) =
+        os_findings.iter().partition(|f| f.technology == os);
+    // Elect the version among the sources agreeing on the OS.
+    // Some versions are ambiguous (e.g. "20.04|22.04"), each candidate
+    // gets a vote.
+    let mut version_votes: Vec<(&str, usize)> = Vec::new();
+    for finding in &agreeing {
+        if let Some(version) = &finding.version {
+            for candidate in version.split('|') {
+                match version_votes.iter_mut().find(|(v, _)| *v == candidate) {
+                    Some((_, votes)) => *votes += 1,
+                    None => version_votes.push((candidate, 1)),
+                }
+            }
+        }
+    }
+    let max_votes = version_votes.iter().map(|(_, v)| *v).max().unwrap_or(0);
+    let version = if max_votes > 0 {
+        let best: Vec<&str> = version_votes
+            .iter()
+            .filter(|(_, v)| *v == max_votes)
+            .map(|(c, _)| *c)
+            .collect();
+        Some(best.join("|"))
+    } else {
+        None
+    };
+
+    let version_text = version
+        .as_ref()
+        .map(|v| format!(" {}", v))
+        .unwrap_or_default();
+    let mut evidence_text = format!(
+        "The operating system {}{} has been identified by {} of {} sources (confidence: {}%):",
+        os,
+        version_text,
+        agreeing.len(),
+        os_findings.len(),
+        os_weight * 100 / total_weight
+    );
+    for finding in &agreeing {
+        evidence_text.push_str(&format!(" [{}]", finding.evidence_text));
+    }
+    if !disagreeing.is_empty() {
+        evidence_text.push_str(" Other sources disagree:");
+        for finding in &disagreeing {
+            evidence_text.push_str(&format!(" [{}]", finding.evidence_text));
+        }
+    }
+    let evidence: Vec<&str> = agreeing.iter().map(|f| f.evidence.as_str()).collect();
+    let mut merged = Finding::new(
+        os,
+        version.as_deref(),
+        &evidence.join(" | "),
+        &evidence_text,
+        agreeing.iter().find_map(|f| f.url_of_finding.as_deref()),
+    );
+    // The sources agreeing come from the same host, possibly on
+    // different ports
+    merged.target = agreeing[0].target.clone();
+    merged.checker_technology = agreeing[0].checker_technology.clone();
+    merged.confidence = agreeing
+        .iter()
+        .map(|f| f.confidence)
+        .max()
+        .unwrap_or_default();
+    merged.eol = agreeing.iter().find_map(|f| f.eol);
+
+    merged
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::checkers::check_finding_fields;
+    use crate::models::Confidence;
 
     #[test]
     fn source_code_matches() {
@@ -657,6 +819,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn same_os_in_several_responses_gives_one_finding() {
+        let checker = OSChecker::new();
+        let mut url_responses = Vec::new();
+        for path in ["/", "/pageNotFoundNotFound", "/phpmyadmin/"] {
+            let mut headers = HashMap::new();
+            headers.insert("Server".to_string(), "Apache/2.4.52 (Ubuntu)".to_string());
+            url_responses.push(UrlResponse::new(
+                &format!("https://www.example.com{}", path),
+                headers,
+                "<address>Apache/2.4.52 (Ubuntu) Server at www.example.com Port 443</address>",
+                UrlRequestType::Default,
+                404,
+            ));
+        }
+        let findings = checker.check_http(&url_responses);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Apache/2.4.52 (Ubuntu)",
+            Technology::Ubuntu,
+            Some("22.04"),
+            Some("https://www.example.com/"),
+        );
+    }
+
     #[test]
     fn doesnt_find_match_in_url_responses() {
         let checker = OSChecker::new();
@@ -731,6 +919,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn mariadb_handshake_matches() {
+        let checker = OSChecker::new();
+        let banner = b"c\x00\x00\x00\x0a5.5.5-10.3.17-MariaDB-0+deb10u1\x00\x0b\x00\x00\x0042g0YPc#\x00\xfe\xf7\x08\x02\x00\xbf\x81\x15\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00#%3mMz=aPLlZ\x00mysql_native_password\x00";
+        let finding = checker.check_tcp_banner(banner).unwrap();
+        // Only the server version is kept, without the binary noise
+        assert_eq!("5.5.5-10.3.17-MariaDB-0+deb10u1", finding.evidence);
+        check_finding_fields(
+            &finding,
+            "5.5.5-10.3.17-MariaDB-0+deb10u1",
+            Technology::Debian,
+            Some("10"),
+            None,
+        );
+    }
+
     #[test]
     fn tcp_banner_doesnt_match() {
         let checker = OSChecker::new();
@@ -738,4 +942,131 @@ mod tests {
         let finding = checker.check_tcp(&[banner.to_string()]);
         assert!(finding.is_none());
     }
+
+    #[test]
+    fn os_consensus_merges_findings() {
+        let findings = vec![
+            Finding::new(
+                Technology::Debian,
+                Some("12"),
+                "SSH-2.0-OpenSSH_9.2p1 Debian-2+deb12u2",
+                "The operating system Debian 12 has been identified using the banner presented by OpenSSH: SSH-2.0-OpenSSH_9.2p1 Debian-2+deb12u2",
+                None,
+            ),
+            Finding::new(
+                Technology::OpenSSH,
+                Some("9.2"),
+                "SSH-2.0-OpenSSH_9.2p1",
+                "OpenSSH 9.2 has been identified",
+                None,
+            ),
+            Finding::new(
+                Technology::Ubuntu,
+                None,
+                "Apache/2.4.99 (Ubuntu)",
+                "The operating system Ubuntu has been identified using the HTTP header",
+                None,
+            ),
+            Finding::new(
+                Technology::Debian,
+                Some("11|12"),
+                "10.11.6-MariaDB-0+deb12u1",
+                "The operating system Debian 11|12 has been identified using the banner presented by MariaDB",
+                None,
+            ),
+        ];
+        let merged = os_consensus(findings);
+        assert_eq!(2, merged.len());
+        check_finding_fields(
+            &merged[0],
+            "Debian-2+deb12u2",
+            Technology::Debian,
+            Some("12"),
+            None,
+        );
+        assert!(merged[0]
+            .evidence_text
+            .contains("2 of 3 sources (confidence: 80%)"));
+        assert!(merged[0]
+            .evidence_text
+            .contains("banner presented by MariaDB"));
+        assert!(merged[0]
+            .evidence_text
+            .contains("Other sources disagree: [The operating system Ubuntu"));
+        assert_eq!(Technology::OpenSSH, merged[1].technology);
+    }
+
+    #[test]
+    fn os_consensus_merges_findings_by_host() {
+        let os_finding = |technology, version, target: &str| {
+            let mut finding = Finding::new(
+                technology,
+                version,
+                "evidence",
+                &format!("The operating system has been identified on {}", target),
+                None,
+            )
+            .with_confidence(Confidence::Medium);
+            finding.target = Some(target.to_string());
+            finding
+        };
+        let mut findings = vec![
+            os_finding(Technology::Debian, Some("12"), "203.0.113.10:22"),
+            os_finding(Technology::Ubuntu, Some("22.04"), "203.0.113.11:22"),
+            os_finding(Technology::Debian, None, "203.0.113.10:3306"),
+        ];
+        findings[2].eol = Some(true);
+        let merged = os_consensus(findings);
+        assert_eq!(2, merged.len());
+        assert_eq!(Technology::Debian, merged[0].technology);
+        assert_eq!(Some("12".to_string()), merged[0].version);
+        assert_eq!(Some("203.0.113.10:22".to_string()), merged[0].target);
+        assert_eq!(Confidence::Medium, merged[0].confidence);
+        assert_eq!(Some(true), merged[0].eol);
+        assert!(merged[0].evidence_text.contains("2 of 2 sources"));
+        assert!(!merged[0].evidence_text.contains("disagree"));
+        // The other host is left alone
+        assert_eq!(Technology::Ubuntu, merged[1].technology);
+        assert_eq!(Some("203.0.113.11:22".to_string()), merged[1].target);
+        assert_eq!(
+            "The operating system has been identified on 203.0.113.11:22",
+            merged[1].evidence_text
+        );
+    }
+
+    #[test]
+    fn os_consensus_keeps_single_finding() {
+        let findings = vec![Finding::new(
+            Technology::Ubuntu,
+            Some("22.04"),
+            "SSH-2.0-OpenSSH_8.9p1 Ubuntu-3ubuntu0.6",
+            "The operating system Ubuntu 22.04 has been identified",
+            None,
+        )];
+        let merged = os_consensus(findings);
+        assert_eq!(1, merged.len());
+        assert_eq!(
+            "The operating system Ubuntu 22.04 has been identified",
+            merged[0].evidence_text
+        );
+    }
+
+    #[test]
+    fn os_version_is_found_with_equivalent_versions() {
+        let checker = OSChecker::new();
+        assert_eq!(
+            Some("18.04"),
+            checker.get_os_version("Ubuntu", "OpenSSH", "7.6")
+        );
+        assert_eq!(
+            Some("18.04"),
+            checker.get_os_version("Ubuntu", "OpenSSH", "7.6.0")
+        );
+        assert_eq!(
+            Some("7"),
+            checker.get_os_version("CentOS", "Apache", "2.4.6")
+        );
+        assert_eq!(None, checker.get_os_version("Ubuntu", "OpenSSH", "7.6.1"));
+        assert_eq!(None, checker.get_os_version("Fedora", "Apache", "2.4.6"));
+    }
 }
@@ -11,6 +11,15 @@ use crate::models::{technology::Technology, Finding};
 use log::{info, trace};
 use regex::Regex;
 
+/// The sections of phpinfo() leaking sensitive data, with their marker in
+/// the page
+const SENSITIVE_SECTIONS: [(&str, &str); 4] = [
+    ("$_SERVER", "$_SERVER["),
+    ("$_ENV", "$_ENV["),
+    ("Environment", "<h2>Environment</h2>"),
+    ("HTTP headers", "<h2>HTTP Headers Information</h2>"),
+];
+
 /// The PHP checker
 pub struct PHPChecker<'a> {
     /// The regexes and their parameters used to recognize the technology
@@ -32,8 +41,15 @@ impl<'a> PHPChecker<'a> {
         // Example: <h1 class="p">PHP Version 8.2.2</h1>
         let body_regex = Regex::new(r#"(?P<wholematch><h1 class="p">PHP Version (?P<version1>\d+\.\d+\.\d+(-[a-z0-9._-]+)?)</h1>)"#).unwrap();
 
+        // Example: <td class="e">mysqli.default_pw</td><td class="v">s3cr3t </td>
+        let credentials_regex = Regex::new(
+            r#"<td class="e">\s*(?P<wholematch>mysqli?\.default_(pw|password|user))\s*</td><td class="v">\s*(?P<value>[^<]*?)\s*</td>"#,
+        )
+        .unwrap();
+
         regexes.insert("http-header", (header_regex, 45, 45));
         regexes.insert("http-body", (body_regex, 30, 30));
+        regexes.insert("mysql-credentials", (credentials_regex, 30, 0));
         Self { regexes: regexes }
     }
 
@@ -104,6 +120,63 @@ impl<'a> PHPChecker<'a> {
         }
         None
     }
+
+    /// Check whether the page is an exposed phpinfo() output. It leaks the
+    /// paths, the extensions and the environment, whatever the version.
+    /// The sensitive sections found in the page are the evidence.
+    fn check_phpinfo_exposure(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running PHPChecker::check_phpinfo_exposure() on {}",
+            url_response.url
+        );
+        let (regex_body, _, _) = self
+            .regexes
+            .get("http-body")
+            .expect("Regex PHP/http-body not found");
+        if url_response.status_code != 200
+            || !(regex_body.is_match(&url_response.body)
+                || url_response.body.contains("<title>phpinfo()</title>"))
+        {
+            return None;
+        }
+        info!("phpinfo() page found at {}", url_response.url);
+
+        let mut sections: Vec<&str> = SENSITIVE_SECTIONS
+            .iter()
+            .filter(|(_, marker)| url_response.body.contains(marker))
+            .map(|(name, _)| *name)
+            .collect();
+        // The MySQL credentials are only sensitive if they are set
+        let (credentials_regex, _, _) = self
+            .regexes
+            .get("mysql-credentials")
+            .expect("Regex PHP/mysql-credentials not found");
+        if credentials_regex
+            .captures_iter(&url_response.body)
+            .any(|caps| caps["value"] != *"no value" && !caps["value"].is_empty())
+        {
+            sections.push("mysql credentials");
+        }
+
+        let evidence = if sections.is_empty() {
+            "phpinfo()".to_string()
+        } else {
+            format!("phpinfo() with {}", sections.join(", "))
+        };
+        let evidence_text = format!(
+            "{}: a phpinfo() page is exposed, it leaks the paths, the extensions and the configuration of the server. We found \"{}\" at this url: {}",
+            Technology::Misconfiguration,
+            evidence,
+            url_response.url
+        );
+        Some(Finding::new(
+            Technology::Misconfiguration,
+            None,
+            &evidence,
+            &evidence_text,
+            Some(&url_response.url),
+        ))
+    }
 }
 
 impl<'a> Checker for PHPChecker<'a> {}
@@ -115,30 +188,28 @@ impl<'a> HttpChecker for PHPChecker<'a> {
     /// - X-Powered-By
     /// and in the "not found" page content
     ///
-    /// Returns only one finding, otherwise findings would be duplicated each
-    /// time it's found.
+    /// Returns only one version finding, otherwise findings would be
+    /// duplicated each time it's found. An exposed phpinfo() page gives a
+    /// separate misconfiguration finding.
     fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
         trace!("Running PHPChecker::check_http()");
 
-        for url_response in data {
-            // JavaScript files could be hosted on a different server
-            // Don't check the JavaScript files to avoid false positive,
-            // Check only the "main" requests.
-            if url_response.request_type != UrlRequestType::Default {
-                continue;
-            }
-
-            let header_finding = self.check_http_headers(url_response);
-            if header_finding.is_some() {
-                return vec![header_finding.unwrap()];
-            }
+        // JavaScript files could be hosted on a different server
+        // Don't check the JavaScript files to avoid false positive,
+        // Check only the "main" requests.
+        let mut url_responses = data
+            .iter()
+            .filter(|url_response| url_response.request_type == UrlRequestType::Default);
 
-            let body_finding = self.check_http_body(url_response);
-            if body_finding.is_some() {
-                return vec![body_finding.unwrap()];
-            }
-        }
-        return Vec::new();
+        let mut findings = Vec::new();
+        findings.extend(url_responses.clone().find_map(|url_response| {
+            self.check_http_headers(url_response)
+                .or_else(|| self.check_http_body(url_response))
+        }));
+        findings.extend(
+            url_responses.find_map(|url_response| self.check_phpinfo_exposure(url_response)),
+        );
+        findings
     }
 
     /// The technology supported by the checker.
@@ -197,6 +268,54 @@ mod tests {
         assert!(finding.is_none());
     }
 
+    #[test]
+    fn phpinfo_exposure_matches() {
+        let checker = PHPChecker::new();
+        let body = r#"<title>phpinfo()</title></head><body><div class="center">
+<h1 class="p">PHP Version 8.1.2-1ubuntu2.14</h1>
+<h2><a name="module_mysqli">mysqli</a></h2>
+<tr><td class="e">mysqli.default_pw</td><td class="v">s3cr3t</td><td class="v">no value</td></tr>
+<tr><td class="e">mysqli.default_user</td><td class="v">no value</td><td class="v">no value</td></tr>
+<h2>Environment</h2>
+<tr><td class="e">APP_SECRET </td><td class="v">abcdef </td></tr>
+<h2>PHP Variables</h2>
+<tr><td class="e">$_SERVER['DOCUMENT_ROOT']</td><td class="v">/var/www/html</td></tr>"#;
+        let url = "https://www.example.com/phpinfo.php";
+        let url_response =
+            UrlResponse::new(url, HashMap::new(), body, UrlRequestType::Default, 200);
+        let finding = checker.check_phpinfo_exposure(&url_response);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "phpinfo() with $_SERVER, Environment, mysql credentials",
+            Technology::Misconfiguration,
+            None,
+            Some(url),
+        );
+    }
+
+    #[test]
+    fn phpinfo_exposure_doesnt_match() {
+        let checker = PHPChecker::new();
+        let body = r#"<h1 class="p">PHP Version 8.2.0</h1>"#;
+        let url_response = UrlResponse::new(
+            "https://www.example.com/phpinfo.php",
+            HashMap::new(),
+            body,
+            UrlRequestType::Default,
+            404,
+        );
+        assert!(checker.check_phpinfo_exposure(&url_response).is_none());
+        let url_response = UrlResponse::new(
+            "https://www.example.com/about.php",
+            HashMap::new(),
+            "<h1>About phpinfo</h1>",
+            UrlRequestType::Default,
+            200,
+        );
+        assert!(checker.check_phpinfo_exposure(&url_response).is_none());
+    }
+
     #[test]
     fn header_matches() {
         let checker = PHPChecker::new();
@@ -271,7 +390,7 @@ mod tests {
             200,
         );
         let findings = checker.check_http(&[url_response_invalid, url_response_valid]);
-        assert_eq!(1, findings.len());
+        assert_eq!(2, findings.len());
         check_finding_fields(
             &findings[0],
             "PHP Version 5.6.40",
@@ -279,6 +398,13 @@ mod tests {
             Some("5.6.40"),
             Some(url1),
         );
+        check_finding_fields(
+            &findings[1],
+            "phpinfo()",
+            Technology::Misconfiguration,
+            None,
+            Some(url1),
+        );
 
         let mut headers1 = HashMap::new();
         headers1.insert("Accept".to_string(), "text/html".to_string());
@@ -68,8 +68,9 @@ impl<'a> PhpMyAdminChecker<'a> {
         }
 
         // This regex is not restrictive and could generate false positive
-        // results, so restrict its usage on URLs containing /ChangeLog
-        if url_response.url.contains("/ChangeLog") {
+        // results, so restrict its usage on URLs containing /ChangeLog,
+        // and not on an error page (e.g. a 404 quoting the URL)
+        if url_response.url.contains("/ChangeLog") && url_response.is_success() {
             let body_changelog_regex_params = self
                 .regexes
                 .get("http-body-changelog")
@@ -93,6 +94,39 @@ impl<'a> PhpMyAdminChecker<'a> {
         }
         None
     }
+
+    /// Checks for the HTTP authentication of phpMyAdmin (auth_type http).
+    /// The 401 doesn't give the version, it's used only if the body
+    /// doesn't give it.
+    /// Example: WWW-Authenticate: Basic realm="phpMyAdmin localhost"
+    fn check_http_authentication(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running phpMyAdminChecker::check_http_authentication() on {}",
+            url_response.url
+        );
+        if !url_response.has_status(&[401]) {
+            return None;
+        }
+        let www_authenticate = url_response.headers.get("Www-authenticate")?;
+        if !www_authenticate.contains("realm=\"phpMyAdmin") {
+            return None;
+        }
+        info!("phpMyAdmin asks for an HTTP authentication");
+        let evidence = format!("HTTP 401, WWW-Authenticate: {}", www_authenticate);
+        let evidence_text = format!(
+            "{} has been identified because it asks for an HTTP authentication \"{}\" at this url: {}",
+            Technology::PhpMyAdmin,
+            evidence,
+            url_response.url
+        );
+        Some(Finding::new(
+            Technology::PhpMyAdmin,
+            None,
+            &evidence,
+            &evidence_text,
+            Some(&url_response.url),
+        ))
+    }
 }
 
 impl<'a> Checker for PhpMyAdminChecker<'a> {}
@@ -101,10 +135,12 @@ impl<'a> HttpChecker for PhpMyAdminChecker<'a> {
     /// Check for a HTTP scan.
     ///
     /// Returns only one finding, otherwise findings would be duplicated each
-    /// time it's found.
+    /// time it's found. The HTTP authentication is a fallback, without
+    /// version.
     fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
         trace!("Running PhpMyAdminChecker::check_http()");
 
+        let mut authentication_finding = None;
         for url_response in data {
             // JavaScript files could be hosted on a different server
             // Don't check the JavaScript files to avoid false positive,
@@ -117,8 +153,11 @@ impl<'a> HttpChecker for PhpMyAdminChecker<'a> {
             if response.is_some() {
                 return vec![response.unwrap()];
             }
+            if authentication_finding.is_none() {
+                authentication_finding = self.check_http_authentication(url_response);
+            }
         }
-        return Vec::new();
+        authentication_finding.into_iter().collect()
     }
 
     /// The technology supported by the checker
@@ -248,4 +287,62 @@ mod tests {
         let findings = checker.check_http(&[url_response_invalid1, url_response_invalid2]);
         assert!(findings.is_empty());
     }
+
+    #[test]
+    fn http_authentication_matches() {
+        let checker = PhpMyAdminChecker::new();
+        let url = "https://www.example.com/phpmyadmin/doc/html/index.html";
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Www-authenticate".to_string(),
+            r#"Basic realm="phpMyAdmin localhost""#.to_string(),
+        );
+        let url_response = UrlResponse::new(url, headers.clone(), "", UrlRequestType::Default, 401);
+        let findings = checker.check_http(std::slice::from_ref(&url_response));
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            r#"HTTP 401, WWW-Authenticate: Basic realm="phpMyAdmin localhost""#,
+            Technology::PhpMyAdmin,
+            None,
+            Some(url),
+        );
+
+        // The version of the documentation is preferred
+        let body = r#"<title>Welcome to phpMyAdmin's documentation! phpMyAdmin 5.2.1 documentation</title>"#;
+        let url_response_doc = UrlResponse::new(
+            "https://www.example.com/doc/html/index.html",
+            HashMap::new(),
+            body,
+            UrlRequestType::Default,
+            200,
+        );
+        let findings = checker.check_http(&[url_response, url_response_doc]);
+        assert_eq!(1, findings.len());
+        assert_eq!(Some("5.2.1".to_string()), findings[0].version);
+
+        // The realm must be the one of phpMyAdmin, with a 401
+        let url_response = UrlResponse::new(url, headers, "", UrlRequestType::Default, 200);
+        assert!(checker.check_http_authentication(&url_response).is_none());
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Www-authenticate".to_string(),
+            r#"Basic realm="Restricted""#.to_string(),
+        );
+        let url_response = UrlResponse::new(url, headers, "", UrlRequestType::Default, 401);
+        assert!(checker.check_http_authentication(&url_response).is_none());
+    }
+
+    #[test]
+    fn changelog_error_page_doesnt_match() {
+        let checker = PhpMyAdminChecker::new();
+        let url_response = UrlResponse::new(
+            "https://www.example.com/ChangeLog",
+            HashMap::new(),
+            "<p>Not found. Build 2.4.1 (2023-01-01)</p>",
+            UrlRequestType::Default,
+            404,
+        );
+        assert!(checker.check_http_body(&url_response).is_none());
+    }
 }
@@ -5,7 +5,7 @@
 
 use std::collections::HashMap;
 
-use super::{Checker, TcpChecker};
+use super::{get_ftp_system, Checker, TcpChecker, FTP_PROBE};
 use crate::models::{technology::Technology, Finding};
 use log::{info, trace};
 use regex::Regex;
@@ -31,46 +31,169 @@ impl<'a> ProFTPDChecker<'a> {
         // The IP address is ignored by the regex
         let regex = Regex::new(r"^(?P<wholematch>\d\d\d ProFTPD (?P<version1>\d+\.\d+\.\d+[a-z]?) Server \((?P<proftpdname>.+)\))").unwrap();
         regexes.insert("proftpd-banner", (regex, 30, 30));
+        // The version can be in the other replies, even if the greeting
+        // hides it.
+        // Example: 211-ProFTPD 1.3.5e Server (Hardened FTP)
+        let regex = Regex::new(
+            r"(?m)^(?P<wholematch>\d\d\d[ -]\s*ProFTPD (?P<version1>\d+\.\d+\.\d+[a-z]?)\b.*?)\r?$",
+        )
+        .unwrap();
+        regexes.insert("proftpd-reply-version", (regex, 30, 30));
+        // Example: 220 ProFTPD Server (ProFTPD Default Installation) [11.22.33.44]
+        let regex =
+            Regex::new(r"^(?P<wholematch>\d\d\d ProFTPD Server \((?P<proftpdname>[^)]+)\))")
+                .unwrap();
+        regexes.insert("proftpd-banner-without-version", (regex, 30, 30));
+        // mod_copy adds the SITE CPFR and SITE CPTO commands, which are
+        // specific to ProFTPD
+        // Example: 211-Features:\r\n SITE COPY\r\n
+        let regex = Regex::new(r"(?m)^\s+(?P<wholematch>SITE (COPY|CPFR|CPTO))\r?$").unwrap();
+        regexes.insert("proftpd-feat-mod-copy", (regex, 30, 30));
         Self { regexes: regexes }
     }
 }
 
 impl<'a> TcpChecker for ProFTPDChecker<'a> {
     /// Check if the asset is running ProFTPD.
-    /// It looks for the ProFTPD banner.
+    /// It looks for the ProFTPD banner, then for the version or the
+    /// specific features in the replies to the probe.
     fn check_tcp(&self, data: &[String]) -> Option<Finding> {
         trace!("Running ProFTPDChecker::check_tcp()");
+        // The regexes are sorted by relevance, the version is preferred
+        let regexes = [
+            (
+                "proftpd-banner",
+                "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" in its banner",
+            ),
+            (
+                "proftpd-reply-version",
+                "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" in its replies to SYST, FEAT and STAT",
+            ),
+            (
+                "proftpd-banner-without-version",
+                "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" in its banner",
+            ),
+            (
+                "proftpd-feat-mod-copy",
+                "$techno_name$$techno_version$ has been identified because it supports \"$evidence$\" (mod_copy) in its reply to FEAT",
+            ),
+        ];
         // For each item, check if it's an ProFTPD banner
         for item in data {
             trace!("Checking item: {}", item);
 
-            let banner_regex_params = self
-                .regexes
-                .get("proftpd-banner")
-                .expect("Regex ProFTPD/proftpd-banner not found");
-            let (regex, keep_left, keep_right) = banner_regex_params;
-            let caps_result = regex.captures(&item);
-
-            // The regex matches
-            if caps_result.is_some() {
-                info!("Regex ProFTPD/proftpd-banner matches");
-                let caps = caps_result.unwrap();
-                let _proftpd_name = caps.name("proftpdname");
-                return Some(self.extract_finding_from_captures(
-		    caps,
-		    None,
-		    keep_left.to_owned(),
-		    keep_right.to_owned(),
-		    Technology::ProFTPD,
-		    "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" in its banner",
-		));
+            for (regex_name, template) in regexes {
+                let (regex, keep_left, keep_right) = self
+                    .regexes
+                    .get(regex_name)
+                    .unwrap_or_else(|| panic!("Regex ProFTPD/{} not found", regex_name));
+                let caps_result = regex.captures(item);
+
+                // The regex matches
+                if let Some(caps) = caps_result {
+                    info!("Regex ProFTPD/{} matches", regex_name);
+                    let mut finding = self.extract_finding_from_captures(
+                        caps,
+                        None,
+                        keep_left.to_owned(),
+                        keep_right.to_owned(),
+                        Technology::ProFTPD,
+                        template,
+                    );
+                    if let Some(system) = get_ftp_system(item) {
+                        finding
+                            .evidence_text
+                            .push_str(&format!(", the system is \"{}\"", system));
+                    }
+                    return Some(finding);
+                }
             }
         }
         return None;
     }
 
+    /// Sends SYST, FEAT and STAT after the greeting, their replies can
+    /// reveal what the greeting hides.
+    fn get_tcp_probe(&self) -> Option<Vec<u8>> {
+        Some(FTP_PROBE.to_vec())
+    }
+
     /// This checker supports ProFTPD
     fn get_technology(&self) -> Technology {
         Technology::ProFTPD
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn banner_matches() {
+        let checker = ProFTPDChecker::new();
+        let response = "220 ProFTPD 1.3.5b Server (ProFTPD) [11.22.33.44]\r\n215 UNIX Type: L8\r\n";
+        let finding = checker.check_tcp(&[response.to_string()]).unwrap();
+        check_finding_fields(
+            &finding,
+            "220 ProFTPD 1.3.5b Server (ProFTPD)",
+            Technology::ProFTPD,
+            Some("1.3.5b"),
+            None,
+        );
+        assert!(finding
+            .evidence_text
+            .contains("the system is \"UNIX Type: L8\""));
+    }
+
+    #[test]
+    fn version_is_found_in_replies() {
+        let checker = ProFTPDChecker::new();
+        let response = "220 FTP Server ready.\r\n\
+            215 UNIX Type: L8\r\n\
+            211-Features:\r\n MDTM\r\n SIZE\r\n SITE COPY\r\n211 End\r\n\
+            211-ProFTPD 1.3.6c Server (Hardened FTP)\r\n Connected from 10.0.0.1\r\n211 End of status\r\n\
+            221 Goodbye.\r\n";
+        let finding = checker.check_tcp(&[response.to_string()]).unwrap();
+        check_finding_fields(
+            &finding,
+            "211-ProFTPD 1.3.6c Server",
+            Technology::ProFTPD,
+            Some("1.3.6c"),
+            None,
+        );
+        assert!(finding
+            .evidence_text
+            .contains("replies to SYST, FEAT and STAT"));
+    }
+
+    #[test]
+    fn features_match_without_version() {
+        let checker = ProFTPDChecker::new();
+        let response = "220 FTP Server ready.\r\n\
+            500 SYST not understood\r\n\
+            211-Features:\r\n MDTM\r\n SITE CPFR\r\n SITE CPTO\r\n211 End\r\n";
+        let finding = checker.check_tcp(&[response.to_string()]).unwrap();
+        check_finding_fields(&finding, "SITE CPFR", Technology::ProFTPD, None, None);
+        assert!(!finding.evidence_text.contains("the system is"));
+
+        let response = "220 ProFTPD Server (ProFTPD Default Installation) [10.0.0.1]\r\n";
+        let finding = checker.check_tcp(&[response.to_string()]).unwrap();
+        check_finding_fields(
+            &finding,
+            "220 ProFTPD Server (ProFTPD Default Installation)",
+            Technology::ProFTPD,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn other_servers_dont_match() {
+        let checker = ProFTPDChecker::new();
+        let response = "220---------- Welcome to Pure-FTPd [privsep] [TLS] ----------\r\n\
+            215 UNIX Type: L8\r\n\
+            211-Extensions supported:\r\n EPRT\r\n ESTA\r\n ESTP\r\n211 End.\r\n";
+        assert!(checker.check_tcp(&[response.to_string()]).is_none());
+    }
+}
@@ -5,7 +5,7 @@
 
 use std::collections::HashMap;
 
-use super::{Checker, TcpChecker};
+use super::{get_ftp_system, Checker, TcpChecker, FTP_PROBE};
 use crate::models::{technology::Technology, Finding};
 use log::{info, trace};
 use regex::Regex;
@@ -32,45 +32,112 @@ impl<'a> PureFTPdChecker<'a> {
             Regex::new(r"(?P<wholematch>Welcome to Pure-FTPd \[(?P<srvname>[a-zA-z0-9-_.]+)\])")
                 .unwrap();
         regexes.insert("pureftpd-banner", (regex, 30, 30));
+        // The ESTA command (establish active) is specific to Pure-FTPd,
+        // it's advertised even when the greeting is customized
+        // Example: 211-Extensions supported:\r\n ESTP\r\n PASV\r\n ESTA\r\n
+        let regex = Regex::new(r"(?m)^\s+(?P<wholematch>ESTA)\r?$").unwrap();
+        regexes.insert("pureftpd-feat-esta", (regex, 30, 30));
         Self { regexes: regexes }
     }
 }
 
 impl<'a> TcpChecker for PureFTPdChecker<'a> {
     /// Check if the asset is running Pure-FTPd.
-    /// It looks for the Pure-FTPd banner.
+    /// It looks for the Pure-FTPd banner, then for the specific features
+    /// in the reply to FEAT.
     fn check_tcp(&self, data: &[String]) -> Option<Finding> {
         trace!("Running PureFTPdChecker::check_tcp()");
+        let regexes = [
+            (
+                "pureftpd-banner",
+                "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" in its banner",
+            ),
+            (
+                "pureftpd-feat-esta",
+                "$techno_name$$techno_version$ has been identified because it supports \"$evidence$\" in its reply to FEAT",
+            ),
+        ];
         // For each item, check if it's an Pure-FTPd banner
         for item in data {
             trace!("Checker item: {}", item);
-            let banner_regex_params = self
-                .regexes
-                .get("pureftpd-banner")
-                .expect("Regex PureFTPd/pureftpd-banner not found");
-            let (regex, keep_left, keep_right) = banner_regex_params;
-            let caps_result = regex.captures(&item);
+            for (regex_name, template) in regexes {
+                let (regex, keep_left, keep_right) = self
+                    .regexes
+                    .get(regex_name)
+                    .unwrap_or_else(|| panic!("Regex PureFTPd/{} not found", regex_name));
+                let caps_result = regex.captures(item);
 
-            // The regex matches
-            if caps_result.is_some() {
-                info!("Regex PureFTPd/pureftpd-banner matches");
-                let caps = caps_result.unwrap();
-                let _srvname = caps.name("srvname");
-                return Some(self.extract_finding_from_captures(
-		    caps,
-		    None,
-		    keep_left.to_owned(),
-		    keep_right.to_owned(),
-		    Technology::PureFTPd,
-		    "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" in its banner",
-		));
+                // The regex matches
+                if let Some(caps) = caps_result {
+                    info!("Regex PureFTPd/{} matches", regex_name);
+                    let mut finding = self.extract_finding_from_captures(
+                        caps,
+                        None,
+                        keep_left.to_owned(),
+                        keep_right.to_owned(),
+                        Technology::PureFTPd,
+                        template,
+                    );
+                    if let Some(system) = get_ftp_system(item) {
+                        finding
+                            .evidence_text
+                            .push_str(&format!(", the system is \"{}\"", system));
+                    }
+                    return Some(finding);
+                }
             }
         }
         return None;
     }
 
+    /// Sends SYST, FEAT and STAT after the greeting, their replies can
+    /// reveal what the greeting hides.
+    fn get_tcp_probe(&self) -> Option<Vec<u8>> {
+        Some(FTP_PROBE.to_vec())
+    }
+
     /// This checker supports PureFTPd
     fn get_technology(&self) -> Technology {
         Technology::PureFTPd
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn banner_matches() {
+        let checker = PureFTPdChecker::new();
+        let response = "220---------- Welcome to Pure-FTPd [privsep] [TLS] ----------\r\n\
+            215 UNIX Type: L8\r\n";
+        let finding = checker.check_tcp(&[response.to_string()]).unwrap();
+        check_finding_fields(
+            &finding,
+            "Welcome to Pure-FTPd [privsep]",
+            Technology::PureFTPd,
+            None,
+            None,
+        );
+        assert!(finding
+            .evidence_text
+            .contains("the system is \"UNIX Type: L8\""));
+    }
+
+    #[test]
+    fn features_match_with_custom_banner() {
+        let checker = PureFTPdChecker::new();
+        let response = "220 Welcome to the FTP server\r\n\
+            215 UNIX Type: L8\r\n\
+            211-Extensions supported:\r\n EPRT\r\n IDLE\r\n ESTP\r\n PASV\r\n SPSV\r\n ESTA\r\n AUTH TLS\r\n211 End.\r\n\
+            221-Goodbye.\r\n";
+        let finding = checker.check_tcp(&[response.to_string()]).unwrap();
+        check_finding_fields(&finding, "ESTA", Technology::PureFTPd, None, None);
+        assert!(finding.evidence_text.contains("reply to FEAT"));
+
+        let response = "220 ProFTPD 1.3.5b Server (ProFTPD) [11.22.33.44]\r\n\
+            211-Features:\r\n MDTM\r\n SIZE\r\n211 End\r\n";
+        assert!(checker.check_tcp(&[response.to_string()]).is_none());
+    }
+}
@@ -0,0 +1,225 @@
+//! The session cookie checker.
+//! This module contains the checker used to determine the framework of
+//! the asset from the name of its session cookie (e.g. laravel_session).
+//!
+//! The cookies don't give any version, so the findings are versionless.
+//! A checker is created for each technology, so they can be selected
+//! separately.
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+
+/// The default names of the session cookies of each technology
+const SESSION_COOKIES: &[(Technology, &[&str])] = &[
+    (Technology::Laravel, &["laravel_session"]),
+    (Technology::CodeIgniter, &["ci_session"]),
+    (Technology::Express, &["connect.sid"]),
+    (Technology::JavaServlet, &["JSESSIONID"]),
+];
+
+/// The session cookie checker
+pub struct SessionCookieChecker {
+    /// The technology to find
+    technology: Technology,
+    /// The names of its session cookies
+    cookie_names: &'static [&'static str],
+}
+
+impl Checker for SessionCookieChecker {}
+
+impl SessionCookieChecker {
+    /// Creates a new SessionCookieChecker for the technology.
+    /// A technology without known session cookie is never found.
+    pub fn new(technology: Technology) -> Self {
+        let cookie_names = SESSION_COOKIES
+            .iter()
+            .find(|(session_technology, _)| *session_technology == technology)
+            .map(|(_, cookie_names)| *cookie_names)
+            .unwrap_or_default();
+        Self {
+            technology,
+            cookie_names,
+        }
+    }
+
+    /// Check for the session cookies in the Set-Cookie headers.
+    fn check_set_cookies(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running SessionCookieChecker::check_set_cookies() on {}",
+            url_response.url
+        );
+        let cookie = url_response
+            .get_set_cookies()
+            .into_iter()
+            .find(|cookie| self.cookie_names.contains(&cookie.name.as_str()))?;
+        info!(
+            "Session cookie {} of {} found",
+            cookie.name, self.technology
+        );
+
+        // The value is a session ID, don't keep it
+        let evidence = format!("Set-cookie: {}=", cookie.name);
+        let evidence_text = format!(
+            "{} has been identified using its session cookie in the HTTP header \"{}\" returned at the following URL: {}",
+            self.technology,
+            evidence,
+            url_response.url
+        );
+        Some(Finding::new(
+            self.technology.clone(),
+            None,
+            &evidence,
+            &evidence_text,
+            Some(&url_response.url),
+        ))
+    }
+}
+
+impl HttpChecker for SessionCookieChecker {
+    /// Check for the session cookies of the technology.
+    ///
+    /// Returns only one finding, otherwise findings would be duplicated each
+    /// time it's found.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running SessionCookieChecker::check_http()");
+
+        // JavaScript files could be hosted on a different server
+        // Check only the "main" requests.
+        // A redirection to another host leads to another server.
+        data.iter()
+            .filter(|url_response| {
+                url_response.request_type == UrlRequestType::Default
+                    && !url_response.is_redirected_to_another_host()
+            })
+            .find_map(|url_response| self.check_set_cookies(url_response))
+            .into_iter()
+            .collect()
+    }
+
+    /// This checker supports the technology it has been created for
+    fn get_technology(&self) -> Technology {
+        self.technology.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+    use std::collections::HashMap;
+
+    fn get_url_response(url: &str, set_cookies: &[&str]) -> UrlResponse {
+        let mut headers = HashMap::new();
+        headers.insert("Set-cookie".to_string(), set_cookies.join("\n"));
+        UrlResponse::new(url, headers, "the body", UrlRequestType::Default, 200)
+    }
+
+    #[test]
+    fn session_cookies_match() {
+        let url1 = "https://www.example.com/";
+        let url_response = get_url_response(
+            url1,
+            &[
+                "XSRF-TOKEN=eyJpdiI6; expires=Wed, 21 Oct 2026 07:28:00 GMT; Max-Age=7200; path=/; samesite=lax",
+                "laravel_session=eyJpdiI7; expires=Wed, 21 Oct 2026 07:28:00 GMT; path=/; httponly",
+            ],
+        );
+        let findings = SessionCookieChecker::new(Technology::Laravel)
+            .check_http(std::slice::from_ref(&url_response));
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Set-cookie: laravel_session=",
+            Technology::Laravel,
+            None,
+            Some(url1),
+        );
+        assert!(!findings[0].evidence_text.contains("eyJpdiI7"));
+        assert!(SessionCookieChecker::new(Technology::Express)
+            .check_http(&[url_response])
+            .is_empty());
+
+        let url_response = get_url_response(
+            url1,
+            &[
+                "lang=en; Path=/",
+                "JSESSIONID=5A1B2C3D4E5F; Path=/app; Secure; HttpOnly",
+                "connect.sid=s%3Aabc.def; Path=/; HttpOnly",
+            ],
+        );
+        let findings = SessionCookieChecker::new(Technology::JavaServlet)
+            .check_http(std::slice::from_ref(&url_response));
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Set-cookie: JSESSIONID=",
+            Technology::JavaServlet,
+            None,
+            Some(url1),
+        );
+        let findings = SessionCookieChecker::new(Technology::Express).check_http(&[url_response]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Set-cookie: connect.sid=",
+            Technology::Express,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn one_finding_in_several_responses() {
+        let checker = SessionCookieChecker::new(Technology::CodeIgniter);
+        let url1 = "https://www.example.com/";
+        let url2 = "https://www.example.com/pageNotFoundNotFound";
+        let findings = checker.check_http(&[
+            get_url_response(url1, &["ci_session=a1b2c3; path=/; HttpOnly"]),
+            get_url_response(url2, &["ci_session=d4e5f6; path=/; HttpOnly"]),
+        ]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Set-cookie: ci_session=",
+            Technology::CodeIgniter,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn session_cookies_dont_match() {
+        let checker = SessionCookieChecker::new(Technology::Laravel);
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Set-cookie".to_string(),
+            "laravel_session=eyJpdiI7; path=/".to_string(),
+        );
+        let url_responses = vec![
+            // The name appears only in the value or in an attribute
+            get_url_response(
+                "https://www.example.com/",
+                &["session=laravel_session", "id=1; Path=/laravel_session"],
+            ),
+            UrlResponse::new(
+                "https://cdn.example.net/app.js",
+                headers,
+                "",
+                UrlRequestType::JavaScript,
+                200,
+            ),
+        ];
+        assert!(checker.check_http(&url_responses).is_empty());
+
+        // Without known session cookie
+        let checker = SessionCookieChecker::new(Technology::Nginx);
+        assert!(checker
+            .check_http(&[get_url_response(
+                "https://www.example.com/",
+                &["laravel_session=eyJpdiI7; path=/"],
+            )])
+            .is_empty());
+    }
+}
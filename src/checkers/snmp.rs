@@ -0,0 +1,243 @@
+//! The SNMP checker.
+//! This module contains the checker used to determine if SNMP is
+//! exposed by the asset with the default "public" community.
+//!
+//! It sends an SNMPv2c GET request for sysDescr.0, which usually
+//! describes the device and its operating system.
+
+use super::{Checker, UdpChecker};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+
+/// The community used in the GET request
+const COMMUNITY: &[u8] = b"public";
+/// The OID of sysDescr.0 (1.3.6.1.2.1.1.1.0), BER-encoded
+const SYS_DESCR_OID: &[u8] = &[0x2B, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00];
+/// The maximum number of chars of the description kept in the evidence
+const MAX_DESCRIPTION_LENGTH: usize = 200;
+
+/// The SNMP checker
+pub struct SnmpChecker {}
+
+impl Checker for SnmpChecker {}
+
+impl SnmpChecker {
+    /// Creates a new SnmpChecker.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Encodes a BER TLV. Only short-form lengths are supported, which is
+    /// enough for the request.
+    fn encode_tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut tlv = vec![tag, content.len() as u8];
+        tlv.extend_from_slice(content);
+        tlv
+    }
+
+    /// Reads the BER TLV starting at position pos.
+    /// Returns the tag, and the start and the end of the content.
+    fn read_tlv(data: &[u8], pos: usize) -> Option<(u8, usize, usize)> {
+        let tag = *data.get(pos)?;
+        let first_length_byte = *data.get(pos + 1)? as usize;
+        let (length, content_start) = if first_length_byte < 0x80 {
+            (first_length_byte, pos + 2)
+        } else {
+            // Long form: the low bits give the number of length bytes
+            let length_bytes = first_length_byte & 0x7F;
+            if length_bytes == 0 || length_bytes > 4 {
+                return None;
+            }
+            let mut length = 0;
+            for i in 0..length_bytes {
+                length = (length << 8) | *data.get(pos + 2 + i)? as usize;
+            }
+            (length, pos + 2 + length_bytes)
+        };
+        let content_end = content_start.checked_add(length)?;
+        if content_end > data.len() {
+            return None;
+        }
+        Some((tag, content_start, content_end))
+    }
+
+    /// Parses a GetResponse and returns the community and the value of
+    /// the first variable if it's an OCTET STRING.
+    fn parse_response(data: &[u8]) -> Option<(String, Option<String>)> {
+        // Message: SEQUENCE { version, community, GetResponse-PDU }
+        let (tag, start, _end) = Self::read_tlv(data, 0)?;
+        if tag != 0x30 {
+            return None;
+        }
+        let (tag, _start, version_end) = Self::read_tlv(data, start)?;
+        if tag != 0x02 {
+            return None;
+        }
+        let (tag, community_start, community_end) = Self::read_tlv(data, version_end)?;
+        if tag != 0x04 {
+            return None;
+        }
+        let community = String::from_utf8_lossy(&data[community_start..community_end]).to_string();
+        let (tag, pdu_start, _end) = Self::read_tlv(data, community_end)?;
+        if tag != 0xA2 {
+            return None;
+        }
+
+        // PDU: request-id, error-status, error-index, variable bindings
+        let mut pos = pdu_start;
+        for _ in 0..3 {
+            let (tag, _start, end) = Self::read_tlv(data, pos)?;
+            if tag != 0x02 {
+                return None;
+            }
+            pos = end;
+        }
+        let value = Self::read_tlv(data, pos)
+            .filter(|(tag, _, _)| *tag == 0x30)
+            .and_then(|(_, bindings_start, _)| Self::read_tlv(data, bindings_start))
+            .filter(|(tag, _, _)| *tag == 0x30)
+            .and_then(|(_, binding_start, _)| Self::read_tlv(data, binding_start))
+            .filter(|(tag, _, _)| *tag == 0x06)
+            .and_then(|(_, _, oid_end)| Self::read_tlv(data, oid_end))
+            .filter(|(tag, _, _)| *tag == 0x04)
+            .map(|(_, value_start, value_end)| {
+                String::from_utf8_lossy(&data[value_start..value_end])
+                    .trim()
+                    .to_string()
+            });
+        Some((community, value))
+    }
+}
+
+impl UdpChecker for SnmpChecker {
+    /// Returns an SNMPv2c GET request for sysDescr.0
+    fn probe_payload(&self) -> Vec<u8> {
+        let mut varbind = Self::encode_tlv(0x06, SYS_DESCR_OID);
+        // NULL value
+        varbind.extend_from_slice(&[0x05, 0x00]);
+        let varbind_list = Self::encode_tlv(0x30, &Self::encode_tlv(0x30, &varbind));
+
+        // request-id, error-status and error-index
+        let mut pdu = Self::encode_tlv(0x02, &[0x53, 0x41, 0x4E, 0x43]);
+        pdu.extend_from_slice(&[0x02, 0x01, 0x00, 0x02, 0x01, 0x00]);
+        pdu.extend_from_slice(&varbind_list);
+
+        // Version 2c is encoded as 1
+        let mut message = vec![0x02, 0x01, 0x01];
+        message.extend_from_slice(&Self::encode_tlv(0x04, COMMUNITY));
+        message.extend_from_slice(&Self::encode_tlv(0xA0, &pdu));
+        Self::encode_tlv(0x30, &message)
+    }
+
+    /// Check if the asset exposes SNMP with the "public" community.
+    /// Any valid response proves the community is readable, and the
+    /// description is added to the evidence when available.
+    fn check_udp(&self, data: &[u8]) -> Option<Finding> {
+        trace!("Running SnmpChecker::check_udp()");
+        let (community, description) = Self::parse_response(data)?;
+        info!("Got an SNMP response with community {}", community);
+
+        let (evidence, evidence_text) = match description {
+            Some(description) => {
+                let evidence: String = description.chars().take(MAX_DESCRIPTION_LENGTH).collect();
+                let evidence_text = format!(
+                    "{} has been identified because the community \"{}\" is readable, sysDescr.0 is \"{}\"",
+                    Technology::Snmp,
+                    community,
+                    evidence
+                );
+                (evidence, evidence_text)
+            }
+            None => (
+                format!("community \"{}\"", community),
+                format!(
+                    "{} has been identified because the community \"{}\" is readable",
+                    Technology::Snmp,
+                    community
+                ),
+            ),
+        };
+
+        Some(Finding::new(
+            Technology::Snmp,
+            None,
+            &evidence,
+            &evidence_text,
+            None,
+        ))
+    }
+
+    /// This checker supports SNMP
+    fn get_technology(&self) -> Technology {
+        Technology::Snmp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    /// Builds a GetResponse with the given value for sysDescr.0
+    fn get_response(value: &[u8]) -> Vec<u8> {
+        let mut varbind = SnmpChecker::encode_tlv(0x06, SYS_DESCR_OID);
+        varbind.extend_from_slice(value);
+        let varbind_list = SnmpChecker::encode_tlv(0x30, &SnmpChecker::encode_tlv(0x30, &varbind));
+        let mut pdu = SnmpChecker::encode_tlv(0x02, &[0x53, 0x41, 0x4E, 0x43]);
+        pdu.extend_from_slice(&[0x02, 0x01, 0x00, 0x02, 0x01, 0x00]);
+        pdu.extend_from_slice(&varbind_list);
+        let mut message = vec![0x02, 0x01, 0x01];
+        message.extend_from_slice(&SnmpChecker::encode_tlv(0x04, COMMUNITY));
+        message.extend_from_slice(&SnmpChecker::encode_tlv(0xA2, &pdu));
+        SnmpChecker::encode_tlv(0x30, &message)
+    }
+
+    #[test]
+    fn response_matches() {
+        let checker = SnmpChecker::new();
+        let description = b"Linux router 4.14.180 #1 SMP Tue Jun 2 10:47:15 UTC 2020 armv7l";
+        let data = get_response(&SnmpChecker::encode_tlv(0x04, description));
+        let finding = checker.check_udp(&data);
+        assert!(finding.is_some());
+        let finding = finding.unwrap();
+        check_finding_fields(
+            &finding,
+            "Linux router 4.14.180",
+            Technology::Snmp,
+            None,
+            None,
+        );
+        assert!(finding.evidence_text.contains("\"public\" is readable"));
+
+        // noSuchObject, the community is readable anyway
+        let data = get_response(&[0x80, 0x00]);
+        let finding = checker.check_udp(&data);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "community \"public\"",
+            Technology::Snmp,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn response_doesnt_match() {
+        let checker = SnmpChecker::new();
+        // The request itself is not a response
+        assert!(checker.check_udp(&checker.probe_payload()).is_none());
+
+        let data = get_response(&SnmpChecker::encode_tlv(0x04, b"Cisco IOS Software"));
+        assert!(checker.check_udp(&data[..data.len() - 10]).is_none());
+        assert!(checker.check_udp(b"SSH-2.0-OpenSSH_8.9p1").is_none());
+    }
+
+    #[test]
+    fn reads_long_form_lengths() {
+        let mut data = vec![0x04, 0x81, 0x80];
+        data.extend_from_slice(&[0x41; 0x80]);
+        assert_eq!(Some((0x04, 3, 0x83)), SnmpChecker::read_tlv(&data, 0));
+        assert_eq!(None, SnmpChecker::read_tlv(&data[..0x50], 0));
+    }
+}
@@ -36,7 +36,22 @@ impl<'a> SymfonyChecker<'a> {
         // (?s) means the . character matches also newlines
         let source_code_regex =
             Regex::new(r#"(?s).*<h2>Symfony Configuration</h2>.+(?P<wholematch><span\s+class\s*=\s*['"]value['"]\s*>(?P<version1>\d+\.\d+\.\d+)</span>).+<span class=['"]label['"]>Symfony version</span>"#).unwrap();
+        // Example:
+        // <h2>PHP Configuration</h2>
+        //    <div class="metrics">
+        //      <div class="metric">
+        //       <span class="value">8.1.2 <span class="unit">-1ubuntu2.14</span></span>
+        //       <span class="label">PHP version</span>
+        let php_version_regex =
+            Regex::new(r#"(?s)<h2>PHP Configuration</h2>.+?(?P<wholematch><span\s+class\s*=\s*['"]value['"]\s*>(?P<version1>\d+\.\d+\.\d+)).+?<span class=['"]label['"]>PHP version</span>"#).unwrap();
+        // Example: <title>Symfony Profiler</title>
+        let profiler_regex = Regex::new(
+            r#"(?P<wholematch><title>Symfony Profiler</title>|<h2>Symfony Configuration</h2>)"#,
+        )
+        .unwrap();
         regexes.insert("http-body-source", (source_code_regex, 50, 50));
+        regexes.insert("http-body-php", (php_version_regex, 50, 50));
+        regexes.insert("http-body-profiler", (profiler_regex, 50, 0));
         Self { regexes: regexes }
     }
 
@@ -71,6 +86,59 @@ impl<'a> SymfonyChecker<'a> {
         }
         None
     }
+
+    /// Checks the PHP version given by the configuration panel of the
+    /// profiler.
+    fn check_php_version(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running SymfonyChecker::check_php_version() on {}",
+            url_response.url
+        );
+        if !url_response.url.contains("/_profiler/") {
+            return None;
+        }
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get("http-body-php")
+            .expect("Regex Symfony/http-body-php not found");
+        let caps = regex.captures(&url_response.body)?;
+        info!("Regex Symfony/http-body-php matches");
+        Some(self.extract_finding_from_captures(
+            caps,
+            Some(url_response),
+            keep_left.to_owned(),
+            keep_right.to_owned(),
+            Technology::PHP,
+            "$techno_name$$techno_version$ has been identified because the Symfony profiler was reachable and we found \"$evidence$\" at this url: $url_of_finding$",
+        ))
+    }
+
+    /// Checks whether the profiler is reachable. It must never be enabled
+    /// in production, it gives the configuration, the requests and the
+    /// environment variables to anyone.
+    fn check_profiler_exposure(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running SymfonyChecker::check_profiler_exposure() on {}",
+            url_response.url
+        );
+        if !url_response.url.contains("/_profiler/") || url_response.status_code != 200 {
+            return None;
+        }
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get("http-body-profiler")
+            .expect("Regex Symfony/http-body-profiler not found");
+        let caps = regex.captures(&url_response.body)?;
+        info!("Regex Symfony/http-body-profiler matches");
+        Some(self.extract_finding_from_captures(
+            caps,
+            Some(url_response),
+            keep_left.to_owned(),
+            keep_right.to_owned(),
+            Technology::Misconfiguration,
+            "$techno_name$: the Symfony profiler is reachable, a high-severity issue in production since it leaks the configuration, the requests and the environment variables. We found \"$evidence$\" at this url: $url_of_finding$",
+        ))
+    }
 }
 
 impl<'a> Checker for SymfonyChecker<'a> {}
@@ -78,8 +146,9 @@ impl<'a> Checker for SymfonyChecker<'a> {}
 impl<'a> HttpChecker for SymfonyChecker<'a> {
     /// Check for a HTTP scan.
     ///
-    /// Returns only one finding, otherwise findings would be duplicated each
-    /// time it's found.
+    /// Returns the findings of the first profiler page only, otherwise
+    /// findings would be duplicated each time it's found: the Symfony
+    /// version, the PHP version and the exposure of the profiler.
     fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
         trace!("Running SymfonyChecker::check_http()");
 
@@ -91,12 +160,19 @@ impl<'a> HttpChecker for SymfonyChecker<'a> {
                 continue;
             }
 
-            let response = self.check_http_body(&url_response);
-            if response.is_some() {
-                return vec![response.unwrap()];
+            let findings: Vec<Finding> = [
+                self.check_http_body(url_response),
+                self.check_php_version(url_response),
+                self.check_profiler_exposure(url_response),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            if !findings.is_empty() {
+                return findings;
             }
         }
-        return Vec::new();
+        Vec::new()
     }
 
     /// The technology supported by the checker
@@ -133,6 +209,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn profiler_config_panel_matches() {
+        let checker = SymfonyChecker::new();
+        let body = r#"<!DOCTYPE html>
+<html><head><title>Symfony Profiler</title></head><body>
+    <h2>Symfony Configuration</h2>
+    <div class="metrics">
+        <div class="metric">
+            <span class="value">5.4.21</span>
+            <span class="label">Symfony version</span>
+        </div>
+        <div class="metric">
+            <span class="value">dev</span>
+            <span class="label">Environment</span>
+        </div>
+    </div>
+    <h2>PHP Configuration</h2>
+    <div class="metrics">
+        <div class="metric">
+            <span class="value">8.1.2 <span class="unit">-1ubuntu2.14</span></span>
+            <span class="label">PHP version</span>
+        </div>
+        <div class="metric">
+            <span class="value">8.1.2</span>
+            <span class="label">PHP Architecture</span>
+        </div>
+    </div>"#;
+        let url = "https://www.example.com/_profiler/1f3a8c?panel=config";
+        let url_response =
+            UrlResponse::new(url, HashMap::new(), body, UrlRequestType::Default, 200);
+        let findings = checker.check_http(&[url_response]);
+        assert_eq!(3, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "<span class=\"value\">5.4.21</span>",
+            Technology::Symfony,
+            Some("5.4.21"),
+            Some(url),
+        );
+        check_finding_fields(
+            &findings[1],
+            "<span class=\"value\">8.1.2",
+            Technology::PHP,
+            Some("8.1.2"),
+            Some(url),
+        );
+        check_finding_fields(
+            &findings[2],
+            "<title>Symfony Profiler</title>",
+            Technology::Misconfiguration,
+            None,
+            Some(url),
+        );
+        assert!(findings[2].evidence_text.contains("high-severity"));
+    }
+
     #[test]
     fn source_code_doesnt_match() {
         let checker = SymfonyChecker::new();
@@ -167,7 +299,7 @@ mod tests {
             200,
         );
         let findings = checker.check_http(&[url_response_invalid, url_response_valid]);
-        assert_eq!(1, findings.len());
+        assert_eq!(2, findings.len());
         check_finding_fields(
             &findings[0],
             "<span class='value'>4.1.2</span>",
@@ -175,6 +307,13 @@ mod tests {
             Some("4.1.2"),
             Some(url1),
         );
+        check_finding_fields(
+            &findings[1],
+            "<h2>Symfony Configuration</h2>",
+            Technology::Misconfiguration,
+            None,
+            Some(url1),
+        );
     }
 
     #[test]
@@ -0,0 +1,204 @@
+//! The Telnet checker.
+//! This module contains the checker used to determine if Telnet is
+//! exposed by the asset.
+//!
+//! Telnet is inherently insecure since everything, including the
+//! credentials, is sent in clear text. Its login banner frequently
+//! reveals the device or the OS.
+
+use super::{Checker, TcpChecker};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+
+/// Interpret As Command, starts each Telnet command
+const IAC: u8 = 0xFF;
+/// The sender won't use the option
+const WONT: u8 = 0xFC;
+/// Subnegotiation begin
+const SB: u8 = 0xFA;
+/// Subnegotiation end
+const SE: u8 = 0xF0;
+/// The options commonly asked by the servers before sending the login
+/// banner: ECHO, SUPPRESS-GO-AHEAD, TERMINAL-TYPE, NAWS, TERMINAL-SPEED,
+/// LINEMODE, X-DISPLAY-LOCATION, ENVIRON and NEW-ENVIRON
+const COMMON_OPTIONS: &[u8] = &[0x01, 0x03, 0x18, 0x1F, 0x20, 0x22, 0x23, 0x24, 0x27];
+/// The maximum number of chars of the banner kept in the evidence
+const MAX_BANNER_LENGTH: usize = 100;
+
+/// The Telnet checker
+pub struct TelnetChecker {}
+
+impl Checker for TelnetChecker {}
+
+impl TelnetChecker {
+    /// Creates a new TelnetChecker.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Splits the data in the Telnet commands and the text.
+    /// Returns whether an option negotiation has been found, and the text
+    /// without the commands.
+    fn strip_commands(data: &[u8]) -> (bool, String) {
+        let mut negotiation_found = false;
+        let mut text = Vec::new();
+        let mut i = 0;
+        while i < data.len() {
+            if data[i] != IAC {
+                text.push(data[i]);
+                i += 1;
+                continue;
+            }
+            match data.get(i + 1) {
+                // An escaped 0xFF byte
+                Some(&IAC) => {
+                    text.push(IAC);
+                    i += 2;
+                }
+                // Skip the subnegotiation until IAC SE
+                Some(&SB) => {
+                    negotiation_found = true;
+                    i += 2;
+                    while i < data.len() && !(data[i] == IAC && data.get(i + 1) == Some(&SE)) {
+                        i += 1;
+                    }
+                    i += 2;
+                }
+                // WILL, WONT, DO, DONT followed by the option
+                Some(command) if (0xFB..=0xFE).contains(command) => {
+                    negotiation_found = true;
+                    i += 3;
+                }
+                // Other commands don't have any argument
+                _ => i += 2,
+            }
+        }
+        (
+            negotiation_found,
+            String::from_utf8_lossy(&text).to_string(),
+        )
+    }
+}
+
+impl TcpChecker for TelnetChecker {
+    /// The Telnet negotiation is binary, so it can't be detected in the
+    /// banner read as text. It's detected in the response to the probe.
+    fn check_tcp(&self, _data: &[String]) -> Option<Finding> {
+        trace!("Running TelnetChecker::check_tcp()");
+        None
+    }
+
+    /// Returns the refusal of the options commonly asked by the servers,
+    /// so that they send the login banner without waiting for the
+    /// negotiation.
+    fn get_tcp_probe(&self) -> Option<Vec<u8>> {
+        Some(
+            COMMON_OPTIONS
+                .iter()
+                .flat_map(|option| [IAC, WONT, *option])
+                .collect(),
+        )
+    }
+
+    /// Check if the asset is running Telnet.
+    /// The service must negotiate Telnet options, the banner is then
+    /// added to the evidence.
+    fn check_tcp_probe_response(&self, data: &[u8]) -> Option<Finding> {
+        trace!("Running TelnetChecker::check_tcp_probe_response()");
+        let (negotiation_found, text) = Self::strip_commands(data);
+        if !negotiation_found {
+            return None;
+        }
+        info!("A Telnet negotiation has been found");
+
+        // Keep the banner on one line
+        let banner: String = text
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .chars()
+            .take(MAX_BANNER_LENGTH)
+            .collect();
+        let (evidence, evidence_text) = if banner.is_empty() {
+            (
+                "Telnet option negotiation".to_string(),
+                format!(
+                    "{} has been identified because the service started a Telnet option negotiation",
+                    Technology::Telnet
+                ),
+            )
+        } else {
+            let evidence_text = format!(
+                "{} has been identified because the service negotiated Telnet options, its banner is \"{}\"",
+                Technology::Telnet,
+                banner
+            );
+            (banner, evidence_text)
+        };
+
+        Some(Finding::new(
+            Technology::Telnet,
+            None,
+            &evidence,
+            &evidence_text,
+            None,
+        ))
+    }
+
+    /// This checker supports Telnet
+    fn get_technology(&self) -> Technology {
+        Technology::Telnet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn negotiation_matches() {
+        let checker = TelnetChecker::new();
+        let mut data = vec![IAC, 0xFD, 0x18, IAC, 0xFD, 0x20, IAC, 0xFB, 0x01];
+        data.extend_from_slice(&[IAC, SB, 0x18, 0x01, IAC, SE]);
+        data.extend_from_slice(b"\r\nUbuntu 22.04.3 LTS\r\nrouter login: ");
+        let finding = checker.check_tcp_probe_response(&data);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "Ubuntu 22.04.3 LTS router login:",
+            Technology::Telnet,
+            None,
+            None,
+        );
+
+        // Only the negotiation is received
+        let data = vec![IAC, 0xFD, 0x18, IAC, 0xFD, 0x20];
+        let finding = checker.check_tcp_probe_response(&data);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "Telnet option negotiation",
+            Technology::Telnet,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn negotiation_doesnt_match() {
+        let checker = TelnetChecker::new();
+        let finding = checker.check_tcp_probe_response(b"220 (vsFTPd 3.0.3)\r\n");
+        assert!(finding.is_none());
+        let finding = checker.check_tcp_probe_response(b"router login: ");
+        assert!(finding.is_none());
+    }
+
+    #[test]
+    fn probe_refuses_options() {
+        let checker = TelnetChecker::new();
+        let probe = checker.get_tcp_probe().unwrap();
+        assert_eq!(COMMON_OPTIONS.len() * 3, probe.len());
+        assert_eq!(&[IAC, WONT, 0x18], &probe[6..9]);
+    }
+}
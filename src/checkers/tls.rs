@@ -0,0 +1,164 @@
+//! The TLS certificate checker.
+//! This module contains the checker reporting the certificate of the
+//! HTTPS assets, read by the [`TlsReader`](crate::readers::tls::TlsReader).
+//!
+//! The certificate gives the hostnames of the asset (common name and
+//! Subject Alternative Name), its issuer and the end of its validity.
+//! An expired certificate is reported as a misconfiguration too.
+
+use super::Checker;
+use crate::models::{technology::Technology, Confidence, Finding};
+use crate::readers::tls::TlsCertificate;
+use log::{info, trace};
+
+/// The TLS certificate checker
+pub struct TlsCertificateChecker {}
+
+impl TlsCertificateChecker {
+    /// Creates a new TlsCertificateChecker.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Checks the certificate read on the URL.
+    /// Returns the finding of the certificate, and the one of the
+    /// misconfiguration if it expired.
+    pub fn check_certificate(&self, certificate: &TlsCertificate, url: &str) -> Vec<Finding> {
+        trace!(
+            "Running TlsCertificateChecker::check_certificate() on {}",
+            url
+        );
+        let mut findings = Vec::new();
+
+        let mut evidence = format!("Subject: {}", certificate.subject);
+        if !certificate.subject_alt_names.is_empty() {
+            evidence.push_str(&format!(
+                "; SAN: {}",
+                certificate.subject_alt_names.join(", ")
+            ));
+        }
+        evidence.push_str(&format!(
+            "; Issuer: {}; Not after: {}",
+            certificate.issuer, certificate.not_after
+        ));
+        let mut notes = Vec::new();
+        if certificate.expired {
+            notes.push("expired");
+        }
+        if certificate.self_signed {
+            notes.push("self-signed");
+        }
+        let notes = if notes.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", notes.join(", "))
+        };
+        info!("TLS certificate of {}: {}{}", url, evidence, notes);
+        let evidence_text = format!(
+            "{}{} has been read during the TLS handshake, \"{}\" at this url: {}",
+            Technology::TlsCertificate,
+            notes,
+            evidence,
+            url
+        );
+        findings.push(
+            Finding::new(
+                Technology::TlsCertificate,
+                None,
+                &evidence,
+                &evidence_text,
+                Some(url),
+            )
+            .with_confidence(Confidence::Certain),
+        );
+
+        if certificate.expired {
+            let evidence = format!("Not after: {}", certificate.not_after);
+            let evidence_text = format!(
+                "{}: the TLS certificate expired, \"{}\" at this url: {}",
+                Technology::Misconfiguration,
+                evidence,
+                url
+            );
+            let mut finding = Finding::new(
+                Technology::Misconfiguration,
+                None,
+                &evidence,
+                &evidence_text,
+                Some(url),
+            )
+            .with_confidence(Confidence::Certain);
+            finding.checker_technology = Some(Technology::TlsCertificate);
+            findings.push(finding);
+        }
+        findings
+    }
+}
+
+impl Checker for TlsCertificateChecker {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    fn get_certificate() -> TlsCertificate {
+        TlsCertificate {
+            subject: "CN=www.example.com".to_string(),
+            common_name: Some("www.example.com".to_string()),
+            subject_alt_names: vec!["www.example.com".to_string(), "example.com".to_string()],
+            issuer: "C=US, O=Let's Encrypt, CN=R3".to_string(),
+            not_after: "Jan  1 00:00:00 2030 +00:00".to_string(),
+            expired: false,
+            self_signed: false,
+        }
+    }
+
+    #[test]
+    fn certificate_is_reported() {
+        let checker = TlsCertificateChecker::new();
+        let url = "https://www.example.com/";
+        let findings = checker.check_certificate(&get_certificate(), url);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Subject: CN=www.example.com; SAN: www.example.com, example.com; Issuer: C=US, O=Let's Encrypt, CN=R3; Not after: Jan  1 00:00:00 2030 +00:00",
+            Technology::TlsCertificate,
+            None,
+            Some(url),
+        );
+        assert_eq!(Confidence::Certain, findings[0].confidence);
+    }
+
+    #[test]
+    fn expired_certificate_is_a_misconfiguration() {
+        let checker = TlsCertificateChecker::new();
+        let url = "https://www.example.com/";
+        let mut certificate = get_certificate();
+        certificate.subject_alt_names = Vec::new();
+        certificate.issuer = certificate.subject.clone();
+        certificate.not_after = "Jan  1 00:00:00 2021 +00:00".to_string();
+        certificate.expired = true;
+        certificate.self_signed = true;
+        let findings = checker.check_certificate(&certificate, url);
+        assert_eq!(2, findings.len());
+        assert_eq!(
+            "Subject: CN=www.example.com; Issuer: CN=www.example.com; Not after: Jan  1 00:00:00 2021 +00:00",
+            findings[0].evidence
+        );
+        assert!(findings[0]
+            .evidence_text
+            .starts_with("TLS certificate (expired, self-signed)"));
+        check_finding_fields(
+            &findings[1],
+            "Not after: Jan  1 00:00:00 2021 +00:00",
+            Technology::Misconfiguration,
+            None,
+            Some(url),
+        );
+        assert_eq!(
+            Some(Technology::TlsCertificate),
+            findings[1].checker_technology
+        );
+    }
+}
@@ -7,10 +7,47 @@ use std::collections::HashMap;
 
 use super::{Checker, HttpChecker};
 use crate::models::reqres::{UrlRequestType, UrlResponse};
-use crate::models::{technology::Technology, Finding};
+use crate::models::{technology::Technology, Confidence, Finding};
 use log::{info, trace};
 use regex::Regex;
 
+/// The management surfaces which should not be exposed.
+/// Each one is given with the end of its URL, its name, and the
+/// signatures found in its body or in its WWW-Authenticate header.
+const EXPOSED_SURFACES: &[(&str, &str, &[&str])] = &[
+    (
+        "/manager/html",
+        "the Manager application",
+        &[
+            "Tomcat Manager Application",
+            "Tomcat Web Application Manager",
+            "manager-gui",
+        ],
+    ),
+    (
+        "/host-manager/html",
+        "the Host Manager application",
+        &[
+            "Tomcat Host Manager Application",
+            "Tomcat Virtual Host Manager",
+            "admin-gui",
+        ],
+    ),
+    (
+        "/examples/",
+        "the examples",
+        &[
+            "Apache Tomcat Examples",
+            "Servlets Examples",
+            "JSP Examples",
+        ],
+    ),
+];
+
+/// The status codes of the default error pages of Tomcat, which show its
+/// version in their footer.
+const ERROR_STATUS_CODES: &[u16] = &[400, 404, 500];
+
 /// The Tomcat checker
 pub struct TomcatChecker<'a> {
     /// The regexes and their parameters used to recognize the technology
@@ -26,12 +63,20 @@ impl<'a> TomcatChecker<'a> {
     /// reused.
     pub fn new() -> Self {
         let mut regexes = HashMap::new();
+        // The footer of the default error pages
         // Example: <h3>Apache Tomcat/9.1.17</h3>
-        let body_regex =
-            Regex::new(r"<h3>(?P<wholematch>Apache Tomcat\/(?P<version1>\d+\.\d+\.\d+))<\/h3>")
-                .unwrap();
+        // Example: <h3>Apache Tomcat/10.1.0-M17</h3>
+        // Example: <h3>Apache Tomcat/8.0.32 (Ubuntu)</h3>
+        let body_regex = Regex::new(
+            r"<h3>(?P<wholematch>Apache Tomcat\/(?P<version1>\d+\.\d+\.\d+)(-M\d+)?)( \([^)<]*\))?<\/h3>",
+        )
+        .unwrap();
+        // The Coyote connector of Tomcat, without the version of Tomcat
+        // Example: Apache-Coyote/1.1
+        let header_regex = Regex::new(r"^(?P<wholematch>Apache-Coyote\/\d+\.\d+)").unwrap();
 
         regexes.insert("http-body", (body_regex, 45, 45));
+        regexes.insert("http-header", (header_regex, 30, 30));
         Self { regexes: regexes }
     }
 
@@ -42,9 +87,13 @@ impl<'a> TomcatChecker<'a> {
             url_response.url
         );
 
-        // Checks only on the not found page to avoid false positive
-        // and on a page that should trigger a bug.
-        if url_response.url.contains("/pageNotFoundNotFound") || url_response.url.contains("..;") {
+        // Checks only on the not found page to avoid false positive,
+        // on a page that should trigger a bug, and on the error pages
+        // of any other URL.
+        if url_response.url.contains("/pageNotFoundNotFound")
+            || url_response.url.contains("..;")
+            || url_response.has_status(ERROR_STATUS_CODES)
+        {
             let body_regex_params = self
                 .regexes
                 .get("http-body")
@@ -68,22 +117,93 @@ impl<'a> TomcatChecker<'a> {
         }
         None
     }
+
+    /// Check for the Coyote connector in the Server header.
+    /// It doesn't give the version, it's used only if the error pages
+    /// don't give it.
+    fn check_http_headers(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running TomcatChecker::check_http_headers() on {}",
+            url_response.url
+        );
+        let headers = url_response.get_headers(&["Server".to_string()]);
+        let header_value = headers.get("Server")?;
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get("http-header")
+            .expect("Regex Tomcat/http-header not found");
+        let caps = regex.captures(header_value)?;
+        info!("Regex Tomcat/http-header matches");
+        Some(self.extract_finding_from_captures(
+            caps,
+            Some(url_response),
+            keep_left.to_owned(),
+            keep_right.to_owned(),
+            Technology::Tomcat,
+            "$techno_name$$techno_version$ has been identified using the HTTP header \"Server: $evidence$\" returned at the following URL: $url_of_finding$",
+        ).with_confidence(Confidence::Medium))
+    }
+
+    /// Check if a management surface is exposed.
+    /// A 401 or a 403 still shows that the surface is reachable, the
+    /// status code is given as evidence.
+    fn check_exposed_surface(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running TomcatChecker::check_exposed_surface() on {}",
+            url_response.url
+        );
+
+        if !url_response.has_status(&[200, 401, 403]) {
+            return None;
+        }
+        let (_path, name, signatures) = EXPOSED_SURFACES
+            .iter()
+            .find(|(path, _, _)| url_response.url.ends_with(path))?;
+        let www_authenticate = url_response
+            .headers
+            .get("Www-authenticate")
+            .map(|header| header.as_str())
+            .unwrap_or("");
+        let signature = signatures.iter().find(|signature| {
+            url_response.body.contains(*signature) || www_authenticate.contains(*signature)
+        })?;
+
+        info!("Tomcat exposes {}", name);
+        let evidence = format!("HTTP {}", url_response.status_code);
+        let evidence_text = format!(
+            "{} exposes {}, the page answered \"{}\" with the signature \"{}\" at this url: {}",
+            Technology::Tomcat,
+            name,
+            evidence,
+            signature,
+            url_response.url
+        );
+        Some(Finding::new(
+            Technology::Tomcat,
+            None,
+            &evidence,
+            &evidence_text,
+            Some(&url_response.url),
+        ))
+    }
 }
 
 impl<'a> Checker for TomcatChecker<'a> {}
 
 impl<'a> HttpChecker for TomcatChecker<'a> {
     /// Perform a HTTP scan.
-    /// It looks in the following HTTP headers:
-    /// - Server
-    /// - X-Powered-By
-    /// and in the "not found" page content
+    /// It looks in the "not found" page content, and falls back on the
+    /// Server header (Apache-Coyote) without version.
     ///
-    /// Returns only one finding, otherwise findings would be duplicated each
-    /// time it's found.
+    /// Returns only one finding for the version, otherwise findings would
+    /// be duplicated each time it's found. A finding is added for each
+    /// exposed management surface.
     fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
         trace!("Running TomcatChecker::check_http()");
 
+        let mut findings = Vec::new();
+        let mut version_found = false;
+        let mut header_finding = None;
         for url_response in data {
             // JavaScript files could be hosted on a different server
             // Don't check the JavaScript files to avoid false positive,
@@ -94,12 +214,24 @@ impl<'a> HttpChecker for TomcatChecker<'a> {
 
             trace!("Checking {}", url_response.url);
             // Check in response body
-            let body_finding = self.check_http_body(url_response);
-            if body_finding.is_some() {
-                return vec![body_finding.unwrap()];
+            if !version_found {
+                if let Some(body_finding) = self.check_http_body(url_response) {
+                    version_found = true;
+                    findings.insert(0, body_finding);
+                    continue;
+                }
+            }
+            if header_finding.is_none() {
+                header_finding = self.check_http_headers(url_response);
             }
+            if let Some(surface_finding) = self.check_exposed_surface(url_response) {
+                findings.push(surface_finding);
+            }
+        }
+        if let (false, Some(header_finding)) = (version_found, header_finding) {
+            findings.insert(0, header_finding);
         }
-        Vec::new()
+        findings
     }
 
     /// Get the technology supported by the checker
@@ -131,6 +263,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn default_error_page_matches() {
+        let checker = TomcatChecker::new();
+        let body = r#"<!doctype html><html lang="en"><head><title>HTTP Status 404 – Not Found</title><style type="text/css">body {font-family:Tahoma,Arial,sans-serif;} h1, h2, h3, b {color:white;background-color:#525D76;} h1 {font-size:22px;} h2 {font-size:16px;} h3 {font-size:14px;} p {font-size:12px;} a {color:black;} .line {height:1px;background-color:#525D76;border:none;}</style></head><body><h1>HTTP Status 404 – Not Found</h1><hr class="line" /><p><b>Type</b> Status Report</p><p><b>Message</b> The requested resource [&#47;pageNotFoundNotFound] is not available</p><p><b>Description</b> The origin server did not find a current representation for the target resource or is not willing to disclose that one exists.</p><hr class="line" /><h3>Apache Tomcat/9.0.71</h3></body></html>"#;
+        let url = "https://www.example.com/pageNotFoundNotFound";
+        let url_response =
+            UrlResponse::new(url, HashMap::new(), body, UrlRequestType::Default, 404);
+        let findings = checker.check_http(&[url_response]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Apache Tomcat/9.0.71",
+            Technology::Tomcat,
+            Some("9.0.71"),
+            Some(url),
+        );
+
+        let body = "<hr class=\"line\" /><h3>Apache Tomcat/8.0.32 (Ubuntu)</h3></body></html>";
+        let url_response =
+            UrlResponse::new(url, HashMap::new(), body, UrlRequestType::Default, 404);
+        let finding = checker.check_http_body(&url_response).unwrap();
+        assert_eq!(Some("8.0.32".to_string()), finding.version);
+    }
+
+    #[test]
+    fn error_pages_match() {
+        let checker = TomcatChecker::new();
+        let body = "<hr class=\"line\" /><h3>Apache Tomcat/9.0.71</h3></body></html>";
+        let url = "https://www.example.com/api/missing";
+        for status_code in [400, 404, 500] {
+            let url_response = UrlResponse::new(
+                url,
+                HashMap::new(),
+                body,
+                UrlRequestType::Default,
+                status_code,
+            );
+            let finding = checker.check_http_body(&url_response);
+            assert!(finding.is_some());
+            check_finding_fields(
+                &finding.unwrap(),
+                "Apache Tomcat/9.0.71",
+                Technology::Tomcat,
+                Some("9.0.71"),
+                Some(url),
+            );
+        }
+
+        // A page of the application could quote the footer
+        let url_response =
+            UrlResponse::new(url, HashMap::new(), body, UrlRequestType::Default, 200);
+        assert!(checker.check_http_body(&url_response).is_none());
+    }
+
+    #[test]
+    fn coyote_header_is_a_fallback() {
+        let checker = TomcatChecker::new();
+        let url1 = "https://www.example.com/";
+        let mut headers = HashMap::new();
+        headers.insert("Server".to_string(), "Apache-Coyote/1.1".to_string());
+        let url_response1 = UrlResponse::new(url1, headers, "", UrlRequestType::Default, 200);
+        let findings = checker.check_http(std::slice::from_ref(&url_response1));
+        assert_eq!(1, findings.len());
+        assert_eq!(Confidence::Medium, findings[0].confidence);
+        check_finding_fields(
+            &findings[0],
+            "Apache-Coyote/1.1",
+            Technology::Tomcat,
+            None,
+            Some(url1),
+        );
+
+        // The version of the error page is preferred
+        let url2 = "https://www.example.com/pageNotFoundNotFound";
+        let body2 = "<h3>Apache Tomcat/7.0.109</h3>";
+        let url_response2 =
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 404);
+        let findings = checker.check_http(&[url_response1, url_response2]);
+        assert_eq!(1, findings.len());
+        assert_eq!(Some("7.0.109".to_string()), findings[0].version);
+    }
+
     #[test]
     fn source_code_doesnt_match() {
         let checker = TomcatChecker::new();
@@ -194,4 +408,97 @@ mod tests {
         let findings = checker.check_http(&[url_response_invalid1, url_response_invalid2]);
         assert!(findings.is_empty());
     }
+
+    #[test]
+    fn exposed_surfaces_match() {
+        let checker = TomcatChecker::new();
+        let url1 = "https://www.example.com/manager/html";
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Www-authenticate".to_string(),
+            r#"Basic realm="Tomcat Manager Application""#.to_string(),
+        );
+        let url_response_valid = UrlResponse::new(url1, headers, "", UrlRequestType::Default, 401);
+        let finding = checker.check_exposed_surface(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "HTTP 401",
+            Technology::Tomcat,
+            None,
+            Some(url1),
+        );
+
+        let url2 = "https://www.example.com/examples/";
+        let body2 = "<title>Apache Tomcat Examples</title>";
+        let url_response_valid =
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 200);
+        let finding = checker.check_exposed_surface(&url_response_valid);
+        assert!(finding.is_some());
+        let finding = finding.unwrap();
+        check_finding_fields(&finding, "HTTP 200", Technology::Tomcat, None, Some(url2));
+        assert!(finding.evidence_text.contains("the examples"));
+    }
+
+    #[test]
+    fn exposed_surfaces_dont_match() {
+        let checker = TomcatChecker::new();
+        // Not found
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/manager/html",
+            HashMap::new(),
+            "<h1>HTTP Status 404 - Not Found</h1>",
+            UrlRequestType::Default,
+            404,
+        );
+        assert!(checker
+            .check_exposed_surface(&url_response_invalid)
+            .is_none());
+
+        // A 401 which is not sent by Tomcat
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Www-authenticate".to_string(),
+            r#"Basic realm="Restricted""#.to_string(),
+        );
+        let url_response_invalid = UrlResponse::new(
+            "https://www.example.com/host-manager/html",
+            headers,
+            "",
+            UrlRequestType::Default,
+            401,
+        );
+        assert!(checker
+            .check_exposed_surface(&url_response_invalid)
+            .is_none());
+    }
+
+    #[test]
+    fn finds_version_and_exposed_surfaces() {
+        let checker = TomcatChecker::new();
+        let url1 = "https://www.example.com/manager/html";
+        let body1 = "<h1>403 Access Denied</h1><p>role=\"manager-gui\"</p>";
+        let url2 = "https://www.example.com/pageNotFoundNotFound";
+        let body2 = "<h3>Apache Tomcat/9.0.83</h3>";
+        let url_responses = vec![
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 403),
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 404),
+        ];
+        let findings = checker.check_http(&url_responses);
+        assert_eq!(2, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Apache Tomcat/9.0.83",
+            Technology::Tomcat,
+            Some("9.0.83"),
+            Some(url2),
+        );
+        check_finding_fields(
+            &findings[1],
+            "HTTP 403",
+            Technology::Tomcat,
+            None,
+            Some(url1),
+        );
+    }
 }
@@ -1,7 +1,10 @@
 //! The TYPO3 checker.
 //! This module contains the checker used to determine if TYPO3 is
-//! used by the asset.
+//! used by the asset, and its third-party extensions.
 //! https://typo3.org
+//!
+//! The extensions have no common CPE, their findings are only given
+//! for information.
 
 use std::collections::HashMap;
 
@@ -18,6 +21,8 @@ pub struct Typo3Checker<'a> {
     /// evidence, from the left, if the regex matches. The right-side is
     /// similar but it's about the number of chars to keep from the right.
     regexes: HashMap<&'a str, (Regex, usize, usize)>,
+    /// The regex to get the key of an extension from its composer.json URL
+    extension_url_regex: Regex,
 }
 
 impl<'a> Typo3Checker<'a> {
@@ -32,15 +37,50 @@ impl<'a> Typo3Checker<'a> {
         )
         .unwrap();
 
+        // The development versions only give the release line
+        // Example: "typo3/cms-core": "12.4.*@dev"
+        // Example: "typo3/cms-core": "^11.5"
+        let composer_release_regex = Regex::new(
+            r#"(?P<wholematch>"typo3\/cms-core" *: *"[\^~]?(?P<version1>\d+\.\d+)(\.\d+|\.\*)?(@dev)?")"#,
+        )
+        .unwrap();
+
         // Example: <meta name="generator" content="TYPO3 4.7 CMS">
         let source_code_regex = Regex::new(
             r#"(?P<wholematch><meta\s+name\s*=\s*['"][Gg]enerator['"]\s+content\s*=\s*['"]TYPO3 (?P<version1>\d+\.\d+(\.\d+)?)\s+CMS['"]\s*/?>)"#,
         )
         .unwrap();
 
+        // Example: /typo3conf/ext/news/Resources/Public/Css/news-basic.css
+        let extension_regex =
+            Regex::new(r#"(?P<wholematch>typo3conf/ext/(?P<extension>[A-Za-z0-9_]+)/)"#).unwrap();
+
+        // Example: "version": "11.4.1"
+        let extension_version_regex =
+            Regex::new(r#"(?P<wholematch>"version" *: *"v?(?P<version1>\d+\.\d+\.\d+)")"#).unwrap();
+
+        // Example: "name": "georgringer/news"
+        let extension_name_regex = Regex::new(r#"(?P<wholematch>"name" *: *"[^"]+")"#).unwrap();
+
+        let extension_url_regex =
+            Regex::new(r#"/typo3conf/ext/(?P<extension>[A-Za-z0-9_]+)/composer\.json"#).unwrap();
+
         regexes.insert("http-body-composer", (composer_regex, 30, 30));
+        regexes.insert(
+            "http-body-composer-release",
+            (composer_release_regex, 30, 30),
+        );
         regexes.insert("http-body-source", (source_code_regex, 30, 30));
-        Self { regexes: regexes }
+        regexes.insert("http-body-extension", (extension_regex, 30, 30));
+        regexes.insert(
+            "http-body-extension-version",
+            (extension_version_regex, 30, 30),
+        );
+        regexes.insert("http-body-extension-name", (extension_name_regex, 30, 30));
+        Self {
+            regexes: regexes,
+            extension_url_regex,
+        }
     }
 
     /// Checks in HTTP response body.
@@ -72,8 +112,12 @@ impl<'a> Typo3Checker<'a> {
         }
 
         // This regex is not restrictive and could generate false positive
-        // results, so restrict its usage on URLs containing composer.json
-        if url_response.url.contains("/composer.json") {
+        // results, so restrict its usage on URLs containing composer.json.
+        // The extensions also require typo3/cms-core, but only give the
+        // TYPO3 versions they support.
+        if url_response.url.contains("/composer.json")
+            && !url_response.url.contains("/typo3conf/ext/")
+        {
             let body_composer_regex_params = self
                 .regexes
                 .get("http-body-composer")
@@ -95,10 +139,107 @@ impl<'a> Typo3Checker<'a> {
 		    "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$"
 		));
             }
+
+            // The release line is enough to look for the CVEs
+            let (regex_release, keep_left_release, keep_right_release) = self
+                .regexes
+                .get("http-body-composer-release")
+                .expect("Regex TYPO3/http-body-composer-release not found");
+            if let Some(caps) = regex_release.captures(&url_response.body) {
+                info!("Regex TYPO3/http-body-composer-release matches");
+                return Some(self.extract_finding_from_captures(
+                    caps,
+                    Some(url_response),
+                    keep_left_release.to_owned(),
+                    keep_right_release.to_owned(),
+                    Technology::Typo3,
+                    "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$",
+                ));
+            }
+        }
+
+        None
+    }
+
+    /// Checks the composer.json of an extension, in typo3conf/ext.
+    /// Returns the key of the extension and its finding, with the
+    /// version if the composer.json gives it.
+    fn check_extension_composer(&self, url_response: &UrlResponse) -> Option<(String, Finding)> {
+        trace!(
+            "Running Typo3Checker::check_extension_composer() on {}",
+            url_response.url
+        );
+        let extension = self
+            .extension_url_regex
+            .captures(&url_response.url)?
+            .name("extension")?
+            .as_str()
+            .to_string();
+        // A missing extension can be answered with an HTML page
+        if url_response.status_code != 200 || !url_response.body.trim_start().starts_with('{') {
+            return None;
         }
 
+        for regex_name in ["http-body-extension-version", "http-body-extension-name"] {
+            let (regex, keep_left, keep_right) = self
+                .regexes
+                .get(regex_name)
+                .expect("Regex TYPO3/http-body-extension-* not found");
+            if let Some(caps) = regex.captures(&url_response.body) {
+                info!("Regex TYPO3/{} matches", regex_name);
+                let template = format!(
+                    "$techno_name$ {}$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$",
+                    extension
+                );
+                let finding = self.extract_finding_from_captures(
+                    caps,
+                    Some(url_response),
+                    keep_left.to_owned(),
+                    keep_right.to_owned(),
+                    Technology::Typo3Extension,
+                    &template,
+                );
+                return Some((extension, finding));
+            }
+        }
         None
     }
+
+    /// Checks the references to the extensions (typo3conf/ext/) in the
+    /// HTTP response body. Returns the keys of the extensions and their
+    /// findings, without version.
+    fn check_extension_references(&self, url_response: &UrlResponse) -> Vec<(String, Finding)> {
+        trace!(
+            "Running Typo3Checker::check_extension_references() on {}",
+            url_response.url
+        );
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get("http-body-extension")
+            .expect("Regex TYPO3/http-body-extension not found");
+        let mut extensions: Vec<(String, Finding)> = Vec::new();
+        for caps in regex.captures_iter(&url_response.body) {
+            let extension = caps["extension"].to_string();
+            if extensions.iter().any(|(key, _)| *key == extension) {
+                continue;
+            }
+            info!("Regex TYPO3/http-body-extension matches for {}", extension);
+            let template = format!(
+                "$techno_name$ {}$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$",
+                extension
+            );
+            let finding = self.extract_finding_from_captures(
+                caps,
+                Some(url_response),
+                keep_left.to_owned(),
+                keep_right.to_owned(),
+                Technology::Typo3Extension,
+                &template,
+            );
+            extensions.push((extension, finding));
+        }
+        extensions
+    }
 }
 
 impl<'a> Checker for Typo3Checker<'a> {}
@@ -106,25 +247,43 @@ impl<'a> Checker for Typo3Checker<'a> {}
 impl<'a> HttpChecker for Typo3Checker<'a> {
     /// Check for a HTTP scan.
     ///
-    /// Returns only one finding, otherwise findings would be duplicated each
-    /// time it's found.
+    /// Returns only one finding for TYPO3, otherwise findings would be
+    /// duplicated each time it's found, and one finding per extension.
     fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
         trace!("Running Typo3Checker::check_http()");
 
-        for url_response in data {
-            // JavaScript files could be hosted on a different server
-            // Don't check the JavaScript files to avoid false positive,
-            // Check only the "main" requests.
-            if url_response.request_type != UrlRequestType::Default {
-                continue;
-            }
+        // JavaScript files could be hosted on a different server
+        // Don't check the JavaScript files to avoid false positive,
+        // Check only the "main" requests.
+        let url_responses: Vec<&UrlResponse> = data
+            .iter()
+            .filter(|url_response| url_response.request_type == UrlRequestType::Default)
+            .collect();
 
+        let mut findings = Vec::new();
+        for url_response in &url_responses {
             let response = self.check_http_body(&url_response);
             if response.is_some() {
-                return vec![response.unwrap()];
+                findings.push(response.unwrap());
+                break;
             }
         }
-        return Vec::new();
+
+        // The composer.json files give the versions of the extensions, the
+        // references in the pages only their keys
+        let mut extensions: Vec<(String, Finding)> = url_responses
+            .iter()
+            .filter_map(|url_response| self.check_extension_composer(url_response))
+            .collect();
+        for url_response in &url_responses {
+            for (extension, finding) in self.check_extension_references(url_response) {
+                if !extensions.iter().any(|(key, _)| *key == extension) {
+                    extensions.push((extension, finding));
+                }
+            }
+        }
+        findings.extend(extensions.into_iter().map(|(_, finding)| finding));
+        return findings;
     }
 
     /// The technology supported by the checker
@@ -262,4 +421,101 @@ mod tests {
         let finding = checker.check_http(&[url_response_valid3]);
         assert!(finding.is_empty());
     }
+
+    #[test]
+    fn release_line_matches() {
+        let checker = Typo3Checker::new();
+        let url = "https://www.example.com/typo3/sysext/install/composer.json";
+        let body = r#"{"name": "typo3/cms-install", "require": {"typo3/cms-core": "12.4.*@dev"}}"#;
+        let url_response =
+            UrlResponse::new(url, HashMap::new(), body, UrlRequestType::Default, 200);
+        let findings = checker.check_http(&[url_response]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "12.4.*@dev",
+            Technology::Typo3,
+            Some("12.4"),
+            Some(url),
+        );
+
+        let body = r#"{"require": {"php": "^7.4", "typo3/cms-core": "^11.5"}}"#;
+        let url_response =
+            UrlResponse::new(url, HashMap::new(), body, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response);
+        assert_eq!(Some("11.5".to_string()), finding.unwrap().version);
+    }
+
+    #[test]
+    fn extensions_match() {
+        let checker = Typo3Checker::new();
+        let url1 = "https://www.example.com/";
+        let body1 = r#"<meta name="generator" content="TYPO3 CMS">
+            <link rel="stylesheet" href="/typo3conf/ext/news/Resources/Public/Css/news-basic.css">
+            <script src="/typo3conf/ext/powermail/Resources/Public/JavaScript/Powermail/Form.min.js"></script>
+            <img src="/typo3conf/ext/news/Resources/Public/Images/dummy.png">"#;
+        let url_response1 =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+        let url2 = "https://www.example.com/typo3conf/ext/news/composer.json";
+        let body2 = r#"{
+            "name": "georgringer/news",
+            "version": "11.4.1",
+            "require": {"typo3/cms-core": "^11.5 || ^12.4"}
+        }"#;
+        let url_response2 =
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 200);
+
+        let findings = checker.check_http(&[url_response1, url_response2]);
+        assert_eq!(2, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "\"version\": \"11.4.1\"",
+            Technology::Typo3Extension,
+            Some("11.4.1"),
+            Some(url2),
+        );
+        assert!(findings[0]
+            .evidence_text
+            .starts_with("TYPO3 extension news 11.4.1 has been"));
+        check_finding_fields(
+            &findings[1],
+            "typo3conf/ext/powermail/",
+            Technology::Typo3Extension,
+            None,
+            Some(url1),
+        );
+        assert!(findings[1]
+            .evidence_text
+            .starts_with("TYPO3 extension powermail has been"));
+    }
+
+    #[test]
+    fn extensions_dont_match() {
+        let checker = Typo3Checker::new();
+        // A missing extension answered with a page
+        let url_response1 = UrlResponse::new(
+            "https://www.example.com/typo3conf/ext/mask/composer.json",
+            HashMap::new(),
+            "<html><body>Page not found</body></html>",
+            UrlRequestType::Default,
+            200,
+        );
+        // The supported TYPO3 versions aren't the installed one
+        let url_response2 = UrlResponse::new(
+            "https://www.example.com/typo3conf/ext/container/composer.json",
+            HashMap::new(),
+            r#"{"require": {"typo3/cms-core": "^11.5"}}"#,
+            UrlRequestType::Default,
+            404,
+        );
+        let url_response3 = UrlResponse::new(
+            "https://www.example.com/assets/app.js",
+            HashMap::new(),
+            "load('/typo3conf/ext/news/Resources/Public/JavaScript/news.js')",
+            UrlRequestType::JavaScript,
+            200,
+        );
+        let findings = checker.check_http(&[url_response1, url_response2, url_response3]);
+        assert!(findings.is_empty());
+    }
 }
@@ -0,0 +1,338 @@
+//! The generic version file checker.
+//! This module contains the checker used to read the version of any
+//! software exposing a plain version file (e.g. /VERSION, /api/version,
+//! /package.json), without a bespoke checker.
+//!
+//! The version files are given in a TOML file with `--version-files`. Each
+//! of them lists the paths to request and the regex extracting the version,
+//! which must contain the group `version1`. The technology is optional,
+//! it's needed to look for the CVEs. Without it the finding is a hint.
+//!
+//! Example of version files:
+//!
+//! ```toml
+//! [[version_files]]
+//! name = "Grafana"
+//! paths = ["/api/health"]
+//! regex = '"version":\s*"(?P<version1>\d+\.\d+\.\d+)"'
+//!
+//! [[version_files]]
+//! name = "phpMyAdmin"
+//! technology = "phpmyadmin"
+//! paths = ["/phpmyadmin/ChangeLog", "/phpmyadmin/README"]
+//! regex = '(?m)^Version (?P<version1>\d+\.\d+\.\d+)'
+//! ```
+
+use std::fs;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequest, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use clap::ValueEnum;
+use log::{debug, info, trace};
+use regex::Regex;
+use serde::Deserialize;
+
+/// The maximum size of a version file, the bigger responses are pages
+const MAX_VERSION_FILE_LENGTH: usize = 65536;
+
+/// A version file, as written in the TOML file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VersionFileEntry {
+    /// The name of the software
+    name: String,
+    /// The technology, as given with -t
+    technology: Option<String>,
+    /// The paths of the version file
+    paths: Vec<String>,
+    /// The regex extracting the version
+    regex: String,
+}
+
+/// The content of the TOML file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VersionFileEntries {
+    /// The version files
+    version_files: Vec<VersionFileEntry>,
+}
+
+/// A version file to check
+struct VersionFile {
+    /// The name of the software
+    name: String,
+    /// The technology of the findings
+    technology: Technology,
+    /// The paths of the version file
+    paths: Vec<String>,
+    /// The regex extracting the version
+    regex: Regex,
+}
+
+/// The generic version file checker
+pub struct GenericVersionFileChecker {
+    /// The version files to check
+    version_files: Vec<VersionFile>,
+}
+
+impl Checker for GenericVersionFileChecker {}
+
+impl GenericVersionFileChecker {
+    /// Reads the version files from the TOML file at the given path.
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        trace!("Running GenericVersionFileChecker::from_file() on {}", path);
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read the version files {}: {}", path, e))?;
+        Self::parse(&content).map_err(|e| format!("Invalid version files {}: {}", path, e))
+    }
+
+    /// Parses the content of a version files TOML file.
+    /// The regexes are compiled once, an invalid one is an error.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let entries: VersionFileEntries = toml::from_str(content).map_err(|e| e.to_string())?;
+        let mut version_files = Vec::new();
+        for entry in entries.version_files {
+            if !entry.regex.contains("(?P<version1>") {
+                return Err(format!("The regex of {} has no version1 group", entry.name));
+            }
+            // The whole match is the evidence
+            let regex = if entry.regex.contains("(?P<wholematch>") {
+                Regex::new(&entry.regex)
+            } else {
+                Regex::new(&format!("(?P<wholematch>{})", entry.regex))
+            }
+            .map_err(|e| format!("Invalid regex for {}: {}", entry.name, e))?;
+            let technology = match &entry.technology {
+                Some(technology) => Technology::from_str(technology, true)?,
+                None => Technology::Hint,
+            };
+            debug!("Version file for {}: {:?}", entry.name, entry.paths);
+            version_files.push(VersionFile {
+                name: entry.name,
+                technology,
+                paths: entry.paths,
+                regex,
+            });
+        }
+        Ok(Self { version_files })
+    }
+
+    /// Returns the requests of the version files for the main URL.
+    pub fn get_url_requests(&self, main_url: &str) -> Vec<UrlRequest> {
+        self.version_files
+            .iter()
+            .flat_map(|version_file| version_file.paths.iter())
+            .map(|path| UrlRequest::from_path(main_url, path, false))
+            .collect()
+    }
+
+    /// Checks whether the response looks like a version file: a small
+    /// text which is not an HTML page (e.g. a custom 404 page).
+    fn is_version_file(url_response: &UrlResponse) -> bool {
+        let is_html = url_response
+            .headers
+            .get("Content-type")
+            .is_some_and(|content_type| content_type.contains("html"))
+            || url_response.body.trim_start().starts_with('<');
+        url_response.status_code == 200
+            && !is_html
+            && !url_response.body.trim().is_empty()
+            && url_response.body.len() <= MAX_VERSION_FILE_LENGTH
+    }
+}
+
+impl HttpChecker for GenericVersionFileChecker {
+    /// Check the version files of the responses.
+    /// Each version file gives at most one finding.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running GenericVersionFileChecker::check_http()");
+        let mut findings = Vec::new();
+        for version_file in &self.version_files {
+            let url_responses = data.iter().filter(|url_response| {
+                // The path of the URL, without the query string
+                let path = url_response
+                    .url
+                    .split_once("://")
+                    .and_then(|(_, rest)| rest.find('/').map(|position| &rest[position..]))
+                    .unwrap_or("/");
+                let path = path.split(['?', '#']).next().unwrap_or(path);
+                version_file
+                    .paths
+                    .iter()
+                    .any(|version_file_path| path.ends_with(version_file_path.as_str()))
+            });
+            for url_response in url_responses {
+                if !Self::is_version_file(url_response) {
+                    debug!("{} is not a version file", url_response.url);
+                    continue;
+                }
+                if let Some(caps) = version_file.regex.captures(&url_response.body) {
+                    info!("Version file of {} found", version_file.name);
+                    // Without technology, the name is given by the hint
+                    let name = match version_file.technology {
+                        Technology::Hint => format!("$techno_name$: {}", version_file.name),
+                        _ => "$techno_name$".to_string(),
+                    };
+                    let template = format!(
+                        "{}$techno_version$ has been identified because we found \"$evidence$\" in the version file at this url: $url_of_finding$",
+                        name
+                    );
+                    findings.push(self.extract_finding_from_captures(
+                        caps,
+                        Some(url_response),
+                        30,
+                        30,
+                        version_file.technology.clone(),
+                        &template,
+                    ));
+                    break;
+                }
+            }
+        }
+        findings
+    }
+
+    /// The technologies are given by the version files, a hint by default
+    fn get_technology(&self) -> Technology {
+        Technology::Hint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+    use crate::models::reqres::UrlRequestType;
+    use std::collections::HashMap;
+
+    const VERSION_FILES: &str = r#"
+        [[version_files]]
+        name = "Grafana"
+        paths = ["/api/health"]
+        regex = '"version":\s*"(?P<version1>\d+\.\d+\.\d+)"'
+
+        [[version_files]]
+        name = "phpMyAdmin"
+        technology = "phpmyadmin"
+        paths = ["/phpmyadmin/ChangeLog", "/phpmyadmin/README"]
+        regex = '(?m)^Version (?P<version1>\d+\.\d+\.\d+)'
+    "#;
+
+    #[test]
+    fn version_files_match() {
+        let checker = GenericVersionFileChecker::parse(VERSION_FILES).unwrap();
+        let url1 = "https://www.example.com/api/health";
+        let mut headers = HashMap::new();
+        headers.insert("Content-type".to_string(), "application/json".to_string());
+        let url_response1 = UrlResponse::new(
+            url1,
+            headers,
+            r#"{"commit": "03f502a94d", "database": "ok", "version": "10.4.2"}"#,
+            UrlRequestType::Default,
+            200,
+        );
+        let url2 = "https://www.example.com/phpmyadmin/README?v=1";
+        let url_response2 = UrlResponse::new(
+            url2,
+            HashMap::new(),
+            "phpMyAdmin - Readme\n===================\n\nVersion 4.9.7\n",
+            UrlRequestType::Default,
+            200,
+        );
+
+        let findings = checker.check_http(&[url_response1, url_response2]);
+        assert_eq!(2, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "\"version\": \"10.4.2\"",
+            Technology::Hint,
+            Some("10.4.2"),
+            Some(url1),
+        );
+        assert!(findings[0]
+            .evidence_text
+            .starts_with("Technology hint: Grafana 10.4.2 has been"));
+        check_finding_fields(
+            &findings[1],
+            "Version 4.9.7",
+            Technology::PhpMyAdmin,
+            Some("4.9.7"),
+            Some(url2),
+        );
+    }
+
+    #[test]
+    fn pages_dont_match() {
+        let checker = GenericVersionFileChecker::parse(VERSION_FILES).unwrap();
+        let url = "https://www.example.com/phpmyadmin/ChangeLog";
+        let body = "Version 4.9.7\n";
+        // A custom 404 page
+        let url_response1 = UrlResponse::new(
+            url,
+            HashMap::new(),
+            &format!("<html><body>Not found: {}</body></html>", body),
+            UrlRequestType::Default,
+            200,
+        );
+        let url_response2 =
+            UrlResponse::new(url, HashMap::new(), body, UrlRequestType::Default, 404);
+        // Another path
+        let url_response3 = UrlResponse::new(
+            "https://www.example.com/ChangeLog.txt",
+            HashMap::new(),
+            body,
+            UrlRequestType::Default,
+            200,
+        );
+        let findings = checker.check_http(&[url_response1, url_response2, url_response3]);
+        assert!(findings.is_empty());
+    }
+
+    #[test]
+    fn parse_reports_errors() {
+        let invalid_regex = r#"
+            [[version_files]]
+            name = "App"
+            paths = ["/version"]
+            regex = '(?P<version1>\d+'
+        "#;
+        assert!(matches!(
+            GenericVersionFileChecker::parse(invalid_regex),
+            Err(e) if e.starts_with("Invalid regex for App")
+        ));
+        let no_version = r#"
+            [[version_files]]
+            name = "App"
+            paths = ["/version"]
+            regex = '\d+'
+        "#;
+        assert!(GenericVersionFileChecker::parse(no_version).is_err());
+        let invalid_technology = r#"
+            [[version_files]]
+            name = "App"
+            technology = "notatechnology"
+            paths = ["/version"]
+            regex = '(?P<version1>\d+)'
+        "#;
+        assert!(GenericVersionFileChecker::parse(invalid_technology).is_err());
+    }
+
+    #[test]
+    fn get_url_requests_works() {
+        let checker = GenericVersionFileChecker::parse(VERSION_FILES).unwrap();
+        let urls: Vec<String> = checker
+            .get_url_requests("https://www.example.com/blog/")
+            .into_iter()
+            .map(|url_request| url_request.url)
+            .collect();
+        assert_eq!(
+            vec![
+                "https://www.example.com/api/health",
+                "https://www.example.com/phpmyadmin/ChangeLog",
+                "https://www.example.com/phpmyadmin/README",
+            ],
+            urls
+        );
+    }
+}
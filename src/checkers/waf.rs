@@ -0,0 +1,284 @@
+//! The WAF/CDN checker.
+//! This module contains the checker used to determine if the asset is
+//! protected by a WAF or delivered by a CDN: Cloudflare, Akamai or Sucuri.
+//!
+//! The other findings may then come from this layer instead of the real
+//! stack, and the requests may be filtered. These services have no
+//! version, so the findings are versionless.
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+
+/// The signatures of each provider: the headers revealing it (a trailing
+/// "*" matches any suffix), and the values of the Server header.
+const WAF_SIGNATURES: &[(Technology, &[&str], &[&str])] = &[
+    (
+        Technology::Cloudflare,
+        &["Cf-ray", "Cf-cache-status"],
+        &["cloudflare"],
+    ),
+    (
+        Technology::Akamai,
+        &["X-akamai-*", "Akamai-grn"],
+        &["AkamaiGHost", "AkamaiNetStorage"],
+    ),
+    (
+        Technology::Sucuri,
+        &["X-sucuri-id", "X-sucuri-cache"],
+        &["Sucuri/Cloudproxy"],
+    ),
+];
+/// The maximum number of chars of the header value kept in the evidence
+const MAX_VALUE_LENGTH: usize = 100;
+
+/// The WAF/CDN checker
+pub struct WafChecker {}
+
+impl Checker for WafChecker {}
+
+impl WafChecker {
+    /// Creates a new WafChecker.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Check for the signatures of the provider in the HTTP headers.
+    fn check_http_headers(
+        &self,
+        url_response: &UrlResponse,
+        technology: &Technology,
+        header_names: &[&str],
+        server_values: &[&str],
+    ) -> Option<Finding> {
+        trace!(
+            "Running WafChecker::check_http_headers() on {}",
+            url_response.url
+        );
+        let mut found_header = None;
+        if let Some(server) = url_response.headers.get("Server") {
+            let server_lowercase = server.to_lowercase();
+            if server_values
+                .iter()
+                .any(|value| server_lowercase.starts_with(&value.to_lowercase()))
+            {
+                found_header = Some(("Server", server));
+            }
+        }
+        for header_name in header_names {
+            if found_header.is_some() {
+                break;
+            }
+            found_header = match header_name.strip_suffix('*') {
+                // The first one in alphabetical order, to be reproducible
+                Some(prefix) => url_response
+                    .headers
+                    .iter()
+                    .filter(|(name, _)| name.starts_with(prefix))
+                    .min_by_key(|(name, _)| name.as_str())
+                    .map(|(name, value)| (name.as_str(), value)),
+                None => url_response
+                    .headers
+                    .get(*header_name)
+                    .map(|value| (*header_name, value)),
+            };
+        }
+        let (header_name, header_value) = found_header?;
+        info!("Header {} of {} found", header_name, technology);
+
+        let value: String = header_value.chars().take(MAX_VALUE_LENGTH).collect();
+        let evidence = format!("{}: {}", header_name, value);
+        let evidence_text = format!(
+            "{} has been identified, the asset is behind this WAF/CDN, using the HTTP header \"{}\" returned at the following URL: {}",
+            technology,
+            evidence,
+            url_response.url
+        );
+        Some(Finding::new(
+            technology.clone(),
+            None,
+            &evidence,
+            &evidence_text,
+            Some(&url_response.url),
+        ))
+    }
+}
+
+impl HttpChecker for WafChecker {
+    /// Check for the headers revealing a WAF or a CDN.
+    ///
+    /// Returns one finding by provider, the first response having one of
+    /// its signatures is used.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running WafChecker::check_http()");
+
+        let mut findings = Vec::new();
+        for (technology, header_names, server_values) in WAF_SIGNATURES {
+            // JavaScript files could be hosted on a different server
+            // Check only the "main" requests.
+            // A redirection to another host leads to another server.
+            let finding = data
+                .iter()
+                .filter(|url_response| {
+                    url_response.request_type == UrlRequestType::Default
+                        && !url_response.is_redirected_to_another_host()
+                })
+                .find_map(|url_response| {
+                    self.check_http_headers(url_response, technology, header_names, server_values)
+                });
+            if let Some(finding) = finding {
+                findings.push(finding);
+            }
+        }
+        findings
+    }
+
+    /// This checker supports the WAF and CDN
+    fn get_technology(&self) -> Technology {
+        Technology::Waf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+    use std::collections::HashMap;
+
+    fn get_url_response(url: &str, headers: &[(&str, &str)]) -> UrlResponse {
+        let headers: HashMap<String, String> = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        UrlResponse::new(url, headers, "the body", UrlRequestType::Default, 200)
+    }
+
+    #[test]
+    fn cloudflare_matches() {
+        let checker = WafChecker::new();
+        let url1 = "https://www.example.com/";
+        let findings = checker.check_http(&[get_url_response(
+            url1,
+            &[("Server", "cloudflare"), ("Cf-ray", "8a1b2c3d4e5f6a7b-CDG")],
+        )]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Server: cloudflare",
+            Technology::Cloudflare,
+            None,
+            Some(url1),
+        );
+
+        let findings = checker.check_http(&[get_url_response(
+            url1,
+            &[("Cf-ray", "8a1b2c3d4e5f6a7b-CDG")],
+        )]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Cf-ray: 8a1b2c3d4e5f6a7b-CDG",
+            Technology::Cloudflare,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn akamai_matches() {
+        let checker = WafChecker::new();
+        let url1 = "https://www.example.com/";
+        let findings = checker.check_http(&[get_url_response(
+            url1,
+            &[
+                ("X-akamai-transformed", "9 - 0 pmb=mRUM,1"),
+                ("X-akamai-request-id", "1a2b3c"),
+            ],
+        )]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "X-akamai-request-id: 1a2b3c",
+            Technology::Akamai,
+            None,
+            Some(url1),
+        );
+
+        let findings = checker.check_http(&[get_url_response(url1, &[("Server", "AkamaiGHost")])]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Server: AkamaiGHost",
+            Technology::Akamai,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn sucuri_matches() {
+        let checker = WafChecker::new();
+        let url1 = "https://www.example.com/";
+        let findings = checker.check_http(&[get_url_response(
+            url1,
+            &[("Server", "nginx"), ("X-sucuri-id", "18015")],
+        )]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "X-sucuri-id: 18015",
+            Technology::Sucuri,
+            None,
+            Some(url1),
+        );
+    }
+
+    #[test]
+    fn one_finding_by_provider() {
+        let checker = WafChecker::new();
+        let url1 = "https://www.example.com/";
+        let url2 = "https://www.example.com/pageNotFoundNotFound";
+        let findings = checker.check_http(&[
+            get_url_response(url1, &[("Cf-cache-status", "HIT")]),
+            get_url_response(url2, &[("Cf-ray", "8a1b2c3d4e5f6a7b-CDG")]),
+            get_url_response(url2, &[("X-sucuri-cache", "MISS")]),
+        ]);
+        assert_eq!(2, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Cf-cache-status: HIT",
+            Technology::Cloudflare,
+            None,
+            Some(url1),
+        );
+        check_finding_fields(
+            &findings[1],
+            "X-sucuri-cache: MISS",
+            Technology::Sucuri,
+            None,
+            Some(url2),
+        );
+    }
+
+    #[test]
+    fn headers_dont_match() {
+        let checker = WafChecker::new();
+        let mut js_headers = HashMap::new();
+        js_headers.insert("Server".to_string(), "cloudflare".to_string());
+        let url_responses = vec![
+            get_url_response(
+                "https://www.example.com/",
+                &[("Server", "nginx"), ("X-cache", "HIT")],
+            ),
+            UrlResponse::new(
+                "https://cdn.example.net/app.js",
+                js_headers,
+                "",
+                UrlRequestType::JavaScript,
+                200,
+            ),
+        ];
+        assert!(checker.check_http(&url_responses).is_empty());
+    }
+}
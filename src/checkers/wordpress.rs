@@ -7,8 +7,8 @@ use std::collections::HashMap;
 
 use super::{Checker, HttpChecker};
 use crate::models::reqres::{UrlRequestType, UrlResponse};
-use crate::models::{technology::Technology, Finding};
-use log::{info, trace};
+use crate::models::{technology::Technology, Confidence, Finding};
+use log::{debug, info, trace};
 use regex::Regex;
 
 /// The checker
@@ -31,12 +31,92 @@ impl<'a> WordPressChecker<'a> {
         // Example: [...]/style.min.css?ver=6.2.2'
         let body_login_regex =
             Regex::new(r#"(?P<wholematch>\?ver=(?P<version1>\d+\.\d+(\.\d+)?))"#).unwrap();
+        // Example: <generator>https://wordpress.org/?v=6.4.2</generator>
+        let body_feed_regex = Regex::new(r#"(?P<wholematch><generator>https?://wordpress\.org/\?v=(?P<version1>\d+\.\d+(\.\d+)?)</generator>)"#).unwrap();
+        // Example: <br /> Version 4.9.8
+        let body_readme_regex =
+            Regex::new(r#"(?P<wholematch><br\s*/?>\s*[Vv]ersion (?P<version1>\d+\.\d+(\.\d+)?))"#)
+                .unwrap();
         regexes.insert("http-body-meta", (body_meta_regex, 30, 30));
         regexes.insert("http-body-login", (body_login_regex, 30, 30));
+        regexes.insert("http-body-feed", (body_feed_regex, 30, 30));
+        regexes.insert("http-body-readme", (body_readme_regex, 30, 30));
         Self { regexes: regexes }
     }
 
+    /// Checks whether two versions are consistent, meaning one is the
+    /// same as the other, or more specific.
+    /// Example: 6.4 and 6.4.2 are consistent, 6.4.1 and 6.4.2 are not.
+    fn versions_agree(version_a: &str, version_b: &str) -> bool {
+        version_a == version_b
+            || version_a.starts_with(&format!("{}.", version_b))
+            || version_b.starts_with(&format!("{}.", version_a))
+    }
+
+    /// Merges the findings coming from several sources into one.
+    ///
+    /// The findings must be sorted from the most reliable source to the
+    /// least reliable one. The most reliable finding is kept, unless another
+    /// source gives a more specific version consistent with it. All the
+    /// other sources are listed in the evidence text, and the disagreeing
+    /// ones are noted.
+    fn merge_findings(&self, findings: Vec<Finding>) -> Option<Finding> {
+        trace!("Running WordPressChecker::merge_findings()");
+        if findings.is_empty() {
+            return None;
+        }
+
+        let mut chosen_index = 0;
+        for (index, finding) in findings.iter().enumerate().skip(1) {
+            let chosen_version = findings[chosen_index]
+                .version
+                .as_deref()
+                .unwrap_or_default();
+            let version = finding.version.as_deref().unwrap_or_default();
+            if Self::versions_agree(chosen_version, version) && version.len() > chosen_version.len()
+            {
+                chosen_index = index;
+            }
+        }
+
+        let mut findings = findings;
+        let mut chosen = findings.remove(chosen_index);
+        let chosen_version = chosen.version.clone().unwrap_or_default();
+        let mut agreeing_sources = Vec::new();
+        let mut disagreeing_sources = Vec::new();
+        for finding in findings {
+            let version = finding.version.unwrap_or_default();
+            let url = finding.url_of_finding.unwrap_or_default();
+            if Self::versions_agree(&chosen_version, &version) {
+                agreeing_sources.push(format!("{} ({})", url, version));
+            } else {
+                disagreeing_sources.push(format!("{} ({})", url, version));
+            }
+        }
+
+        if !agreeing_sources.is_empty() {
+            chosen.evidence_text.push_str(&format!(
+                ". The version is corroborated by: {}",
+                agreeing_sources.join(", ")
+            ));
+        }
+        if !disagreeing_sources.is_empty() {
+            debug!(
+                "WordPress sources disagree on the version: {}",
+                disagreeing_sources.join(", ")
+            );
+            chosen.evidence_text.push_str(&format!(
+                ". Note that other sources disagree: {}",
+                disagreeing_sources.join(", ")
+            ));
+        }
+        Some(chosen)
+    }
+
     /// Checks in HTTP response body.
+    ///
+    /// The sources are checked from the most reliable one (meta generator)
+    /// to the least reliable one (version of the assets on the login page).
     fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
         trace!(
             "Running WordPressChecker::check_http_body() on {}",
@@ -60,7 +140,49 @@ impl<'a> WordPressChecker<'a> {
 		keep_right_meta.to_owned(),
 		Technology::WordPress,
 		"$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$"
-	    ));
+	    ).with_confidence(Confidence::Certain));
+        }
+
+        // The RSS feed contains the version in its generator tag
+        if url_response.url.contains("/feed") {
+            let (regex_feed, keep_left_feed, keep_right_feed) = self
+                .regexes
+                .get("http-body-feed")
+                .expect("Regex WordPress/http-body-feed not found");
+
+            // The regex matches
+            if let Some(caps) = regex_feed.captures(&url_response.body) {
+                info!("Regex WordPress/http-body-feed matches");
+                return Some(self.extract_finding_from_captures(
+		    caps,
+		    Some(url_response),
+		    keep_left_feed.to_owned(),
+		    keep_right_feed.to_owned(),
+		    Technology::WordPress,
+		    "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$"
+		).with_confidence(Confidence::Certain));
+            }
+        }
+
+        // Old versions of WordPress give the version in the readme
+        if url_response.url.contains("/readme.html") {
+            let (regex_readme, keep_left_readme, keep_right_readme) = self
+                .regexes
+                .get("http-body-readme")
+                .expect("Regex WordPress/http-body-readme not found");
+
+            // The regex matches
+            if let Some(caps) = regex_readme.captures(&url_response.body) {
+                info!("Regex WordPress/http-body-readme matches");
+                return Some(self.extract_finding_from_captures(
+		    caps,
+		    Some(url_response),
+		    keep_left_readme.to_owned(),
+		    keep_right_readme.to_owned(),
+		    Technology::WordPress,
+		    "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$"
+		).with_confidence(Confidence::Medium));
+            }
         }
 
         // Checking only on the wp-login.php page to avoid false positive
@@ -85,7 +207,7 @@ impl<'a> WordPressChecker<'a> {
 		    keep_right_login.to_owned(),
 		    Technology::WordPress,
 		    "$techno_name$$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$"
-		));
+		).with_confidence(Confidence::Medium));
             }
         }
         None
@@ -96,6 +218,10 @@ impl<'a> Checker for WordPressChecker<'a> {}
 
 impl<'a> HttpChecker for WordPressChecker<'a> {
     /// Check for a HTTP scan.
+    ///
+    /// The version can be found in several places, which can disagree
+    /// (e.g. a hardened install hiding the meta generator). All of them are
+    /// checked and merged into only one finding.
     fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
         trace!("Running WordPressChecker::check_http()");
 
@@ -103,6 +229,7 @@ impl<'a> HttpChecker for WordPressChecker<'a> {
         let mut datas = data.to_vec();
         datas.sort_by(|a, b| a.url.partial_cmp(&b.url).unwrap());
 
+        let mut findings: Vec<(usize, Finding)> = Vec::new();
         for url_response in datas {
             // JavaScript files could be hosted on a different server
             // Don't check the JavaScript files to avoid false positive,
@@ -115,12 +242,24 @@ impl<'a> HttpChecker for WordPressChecker<'a> {
                 continue;
             }
 
-            let response = self.check_http_body(&url_response);
-            if response.is_some() {
-                return vec![response.unwrap()];
+            if let Some(finding) = self.check_http_body(&url_response) {
+                // The lower the rank, the more reliable the source
+                let rank = if finding.evidence.starts_with("?ver=") {
+                    3
+                } else if url_response.url.contains("/readme.html") {
+                    2
+                } else if url_response.url.contains("/feed") {
+                    1
+                } else {
+                    0
+                };
+                findings.push((rank, finding));
             }
         }
-        return Vec::new();
+
+        findings.sort_by_key(|(rank, _)| *rank);
+        let findings = findings.into_iter().map(|(_, f)| f).collect();
+        self.merge_findings(findings).into_iter().collect()
     }
 
     /// The technology supported by the checker
@@ -141,10 +280,10 @@ mod tests {
         let url1 = "https://www.example.com/index.php";
         let mut url_response_valid =
             UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
-        let finding = checker.check_http_body(&url_response_valid);
-        assert!(finding.is_some());
+        let finding = checker.check_http_body(&url_response_valid).unwrap();
+        assert_eq!(Confidence::Certain, finding.confidence);
         check_finding_fields(
-            &finding.unwrap(),
+            &finding,
             "WordPress 6.1.2",
             Technology::WordPress,
             Some("6.1.2"),
@@ -155,10 +294,10 @@ mod tests {
         let url2 = "https://www.example.com/wp-login.php";
         url_response_valid.body = body2.to_string();
         url_response_valid.url = url2.to_string();
-        let finding = checker.check_http_body(&url_response_valid);
-        assert!(finding.is_some());
+        let finding = checker.check_http_body(&url_response_valid).unwrap();
+        assert_eq!(Confidence::Medium, finding.confidence);
         check_finding_fields(
-            &finding.unwrap(),
+            &finding,
             "ver=6.1.2",
             Technology::WordPress,
             Some("6.1.2"),
@@ -227,6 +366,107 @@ mod tests {
         );
     }
 
+    #[test]
+    fn feed_and_readme_match() {
+        let checker = WordPressChecker::new();
+        let body1 = r#"<lastBuildDate>Mon, 08 Jan 2024 10:00:00 +0000</lastBuildDate>
+	<generator>https://wordpress.org/?v=6.4.2</generator>"#;
+        let url1 = "https://www.example.com/blog/feed/";
+        let url_response_valid =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "?v=6.4.2",
+            Technology::WordPress,
+            Some("6.4.2"),
+            Some(url1),
+        );
+
+        let body2 = r#"<h1 id="logo"><a href="https://wordpress.org/"><img alt="WordPress" src="wp-admin/images/wordpress-logo.png" /></a><br /> Version 4.9.8</h1>"#;
+        let url2 = "https://www.example.com/blog/readme.html";
+        let url_response_valid =
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response_valid);
+        assert!(finding.is_some());
+        check_finding_fields(
+            &finding.unwrap(),
+            "Version 4.9.8",
+            Technology::WordPress,
+            Some("4.9.8"),
+            Some(url2),
+        );
+    }
+
+    #[test]
+    fn merges_corroborating_sources() {
+        let checker = WordPressChecker::new();
+        let url1 = "https://www.example.com/";
+        let url_response_meta = UrlResponse::new(
+            url1,
+            HashMap::new(),
+            r#"<meta name="generator" content="WordPress 6.4" />"#,
+            UrlRequestType::Default,
+            200,
+        );
+        let url2 = "https://www.example.com/feed/";
+        let url_response_feed = UrlResponse::new(
+            url2,
+            HashMap::new(),
+            "<generator>https://wordpress.org/?v=6.4.2</generator>",
+            UrlRequestType::Default,
+            200,
+        );
+        // The most specific version is preferred when sources agree
+        let findings = checker.check_http(&[url_response_meta, url_response_feed]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "?v=6.4.2",
+            Technology::WordPress,
+            Some("6.4.2"),
+            Some(url2),
+        );
+        assert!(findings[0]
+            .evidence_text
+            .contains("corroborated by: https://www.example.com/ (6.4)"));
+    }
+
+    #[test]
+    fn notes_conflicting_sources() {
+        let checker = WordPressChecker::new();
+        let url1 = "https://www.example.com/wp-login.php";
+        let url_response_login = UrlResponse::new(
+            url1,
+            HashMap::new(),
+            r#"<link href="style.min.css?ver=6.3.1" rel="stylesheet""#,
+            UrlRequestType::Default,
+            200,
+        );
+        let url2 = "https://www.example.com/feed/";
+        let url_response_feed = UrlResponse::new(
+            url2,
+            HashMap::new(),
+            "<generator>https://wordpress.org/?v=6.4.2</generator>",
+            UrlRequestType::Default,
+            200,
+        );
+        // The feed is more reliable than the login page
+        let findings = checker.check_http(&[url_response_login, url_response_feed]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "?v=6.4.2",
+            Technology::WordPress,
+            Some("6.4.2"),
+            Some(url2),
+        );
+        assert!(findings[0]
+            .evidence_text
+            .contains("other sources disagree: https://www.example.com/wp-login.php (6.3.1)"));
+    }
+
     #[test]
     fn doesnt_find_match_in_url_responses() {
         let checker = WordPressChecker::new();
@@ -0,0 +1,236 @@
+//! The WordPress APIs checker.
+//! This module contains the checker used to report the WordPress APIs
+//! reachable on the asset: the REST API and XML-RPC.
+//! https://developer.wordpress.org/rest-api/
+//! https://codex.wordpress.org/XML-RPC_Support
+//!
+//! They widen the attack surface: the REST API lists the users and the
+//! content, XML-RPC allows to try many passwords in one request and its
+//! pingbacks can be used to scan or flood other hosts.
+
+use std::collections::HashMap;
+
+use super::{Checker, HttpChecker};
+use crate::models::reqres::{UrlRequestType, UrlResponse};
+use crate::models::{technology::Technology, Finding};
+use log::{info, trace};
+use regex::Regex;
+
+/// The checker
+pub struct WordPressApiChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+}
+
+impl<'a> WordPressApiChecker<'a> {
+    /// Creates the checker.
+    /// By doing so, the regex is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+        // Example: "namespaces":["oembed\/1.0","wp\/v2","wp-site-health\/v1"]
+        let rest_regex = Regex::new(r#"(?P<wholematch>"namespaces"\s*:\s*\[[^\]]*\])"#).unwrap();
+        // Example: <methodResponse><params><param><value><array><data>
+        //   <value><string>system.multicall</string></value>
+        let xmlrpc_regex = Regex::new(
+            r#"(?s)(?P<wholematch><methodResponse>\s*<params>\s*<param>\s*<value>\s*<array>)"#,
+        )
+        .unwrap();
+        regexes.insert("http-body-rest", (rest_regex, 60, 20));
+        regexes.insert("http-body-xmlrpc", (xmlrpc_regex, 60, 0));
+        Self { regexes }
+    }
+
+    /// Checks whether the index of the REST API is reachable.
+    fn check_rest_api(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running WordPressApiChecker::check_rest_api() on {}",
+            url_response.url
+        );
+        if !url_response.url.contains("/wp-json") {
+            return None;
+        }
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get("http-body-rest")
+            .expect("Regex WordPressApi/http-body-rest not found");
+        let caps = regex.captures(&url_response.body)?;
+        info!("Regex WordPressApi/http-body-rest matches");
+        // The routes are the keys of the "routes" object
+        let routes_count = url_response.body.matches("\"methods\":").count();
+        let users = if url_response.body.contains("\\/wp\\/v2\\/users")
+            || url_response.body.contains("/wp/v2/users")
+        {
+            " The users route is exposed."
+        } else {
+            ""
+        };
+        let template = format!(
+            "$techno_name$: the WordPress REST API is enabled and lists {} routes, it can leak the users and the content.{} We found \"$evidence$\" at this url: $url_of_finding$",
+            routes_count, users
+        );
+        Some(self.extract_finding_from_captures(
+            caps,
+            Some(url_response),
+            keep_left.to_owned(),
+            keep_right.to_owned(),
+            Technology::Misconfiguration,
+            &template,
+        ))
+    }
+
+    /// Checks whether XML-RPC answers to system.listMethods, and whether
+    /// the pingbacks are available.
+    fn check_xmlrpc(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running WordPressApiChecker::check_xmlrpc() on {}",
+            url_response.url
+        );
+        if !url_response.url.contains("/xmlrpc.php") {
+            return None;
+        }
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get("http-body-xmlrpc")
+            .expect("Regex WordPressApi/http-body-xmlrpc not found");
+        let caps = regex.captures(&url_response.body)?;
+        info!("Regex WordPressApi/http-body-xmlrpc matches");
+        let methods_count = url_response.body.matches("<string>").count();
+        let pingback = if url_response.body.contains("<string>pingback.ping</string>") {
+            "pingback.ping is available, the pingbacks can be used to scan or flood other hosts"
+        } else {
+            "pingback.ping is not available"
+        };
+        let template = format!(
+            "$techno_name$: WordPress XML-RPC is enabled with {} methods, it allows to try many passwords in one request and {}. We found \"$evidence$\" at this url: $url_of_finding$",
+            methods_count, pingback
+        );
+        Some(self.extract_finding_from_captures(
+            caps,
+            Some(url_response),
+            keep_left.to_owned(),
+            keep_right.to_owned(),
+            Technology::Misconfiguration,
+            &template,
+        ))
+    }
+}
+
+impl<'a> Checker for WordPressApiChecker<'a> {}
+
+impl<'a> HttpChecker for WordPressApiChecker<'a> {
+    /// Check for a HTTP scan.
+    /// Each API gives at most one finding.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running WordPressApiChecker::check_http()");
+        let url_responses: Vec<&UrlResponse> = data
+            .iter()
+            .filter(|url_response| {
+                url_response.request_type == UrlRequestType::Default
+                    && url_response.status_code == 200
+            })
+            .collect();
+        let rest_finding = url_responses
+            .iter()
+            .find_map(|url_response| self.check_rest_api(url_response));
+        let xmlrpc_finding = url_responses
+            .iter()
+            .find_map(|url_response| self.check_xmlrpc(url_response));
+        rest_finding.into_iter().chain(xmlrpc_finding).collect()
+    }
+
+    /// The APIs are checked along with WordPress
+    fn get_technology(&self) -> Technology {
+        Technology::WordPress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn apis_match() {
+        let checker = WordPressApiChecker::new();
+        let url1 = "https://www.example.com/wp-json/";
+        let body1 = r#"{"name":"Example","namespaces":["oembed\/1.0","wp\/v2","wp-site-health\/v1"],"routes":{"\/":{"namespace":"","methods":["GET"]},"\/wp\/v2\/posts":{"namespace":"wp\/v2","methods":["GET","POST"]},"\/wp\/v2\/users":{"namespace":"wp\/v2","methods":["GET","POST"]}}}"#;
+        let url_response1 =
+            UrlResponse::new(url1, HashMap::new(), body1, UrlRequestType::Default, 200);
+        let url2 = "https://www.example.com/xmlrpc.php";
+        let body2 = r#"<?xml version="1.0" encoding="UTF-8"?>
+<methodResponse>
+  <params>
+    <param>
+      <value>
+      <array><data>
+  <value><string>system.multicall</string></value>
+  <value><string>system.listMethods</string></value>
+  <value><string>pingback.ping</string></value>
+  <value><string>wp.getUsersBlogs</string></value>
+</data></array>
+      </value>
+    </param>
+  </params>
+</methodResponse>"#;
+        let url_response2 =
+            UrlResponse::new(url2, HashMap::new(), body2, UrlRequestType::Default, 200);
+
+        let findings = checker.check_http(&[url_response1, url_response2]);
+        assert_eq!(2, findings.len());
+        check_finding_fields(
+            &findings[0],
+            r#""namespaces":["oembed\/1.0""#,
+            Technology::Misconfiguration,
+            None,
+            Some(url1),
+        );
+        assert!(findings[0]
+            .evidence_text
+            .contains("lists 3 routes, it can leak the users and the content. The users route"));
+        check_finding_fields(
+            &findings[1],
+            "<methodResponse>",
+            Technology::Misconfiguration,
+            None,
+            Some(url2),
+        );
+        assert!(findings[1]
+            .evidence_text
+            .contains("with 4 methods, it allows to try many passwords in one request and pingback.ping is available"));
+    }
+
+    #[test]
+    fn apis_dont_match() {
+        let checker = WordPressApiChecker::new();
+        let url_responses = [
+            // The REST API is disabled
+            UrlResponse::new(
+                "https://www.example.com/wp-json/",
+                HashMap::new(),
+                r#"{"code":"rest_disabled","message":"The REST API is disabled on this site.","data":{"status":401}}"#,
+                UrlRequestType::Default,
+                401,
+            ),
+            // XML-RPC is disabled
+            UrlResponse::new(
+                "https://www.example.com/xmlrpc.php",
+                HashMap::new(),
+                r#"<?xml version="1.0" encoding="UTF-8"?><methodResponse><fault><value><struct><member><name>faultCode</name><value><int>405</int></value></member></struct></value></fault></methodResponse>"#,
+                UrlRequestType::Default,
+                200,
+            ),
+            UrlResponse::new(
+                "https://www.example.com/",
+                HashMap::new(),
+                r#"{"namespaces":["wp\/v2"]}"#,
+                UrlRequestType::Default,
+                200,
+            ),
+        ];
+        assert!(checker.check_http(&url_responses).is_empty());
+    }
+}
@@ -0,0 +1,233 @@
+//! The generic WordPress theme checker.
+//! This module contains the checker used to read the name and the version
+//! of any theme from the header of its style.css, for the themes without
+//! a checker of their own.
+//! https://developer.wordpress.org/themes/basics/main-stylesheet-style-css/
+
+use std::collections::HashMap;
+
+use crate::checkers::{Checker, HttpChecker};
+use crate::models::reqres::UrlRequestType;
+use crate::models::{reqres::UrlResponse, technology::Technology, Finding};
+use log::{debug, info, trace};
+use regex::Regex;
+
+/// The themes having a checker of their own, with their CVEs
+const DEDICATED_THEMES: [&str; 1] = ["Divi"];
+
+/// The checker
+pub struct GenericThemeChecker<'a> {
+    /// The regexes and their parameters used to recognize the technology
+    /// The left-side usize represent the number of chars to keep in the
+    /// evidence, from the left, if the regex matches. The right-side is
+    /// similar but it's about the number of chars to keep from the right.
+    regexes: HashMap<&'a str, (Regex, usize, usize)>,
+    /// The regex of the URLs of the main stylesheets of the themes
+    style_url_regex: Regex,
+}
+
+impl<'a> GenericThemeChecker<'a> {
+    /// Creates the checker.
+    /// By doing so, the regex will is compiled once and the checker can be
+    /// reused.
+    pub fn new() -> Self {
+        let mut regexes = HashMap::new();
+
+        // Example: /*
+        //      Theme Name: Twenty Twenty-Four
+        //      Theme URI: https://wordpress.org/themes/twentytwentyfour/
+        //      Version: 1.0
+        //
+        // (?s) means . matches also newline
+        let header_regex = Regex::new(
+            r#"(?s)^.{0,200}?Theme\s+Name:[ \t]*(?P<name>[^\r\n*]+?)[ \t]*[\r\n].*?(?P<wholematch>Version:[ \t]*(?P<version1>\d+(\.\d+)+))"#,
+        )
+        .unwrap();
+
+        // Example: /wp-content/themes/twentytwentyfour/style.css?ver=1.0
+        let style_url_regex = Regex::new(r#"/themes/[^/?#]+/style\.css([?#]|$)"#).unwrap();
+
+        regexes.insert("http-body-header", (header_regex, 30, 30));
+        Self {
+            regexes,
+            style_url_regex,
+        }
+    }
+
+    /// Checks in HTTP response body.
+    fn check_http_body(&self, url_response: &UrlResponse) -> Option<Finding> {
+        trace!(
+            "Running GenericThemeChecker::check_http_body() on {}",
+            url_response.url
+        );
+
+        if !self.style_url_regex.is_match(&url_response.url) {
+            return None;
+        }
+        let (regex, keep_left, keep_right) = self
+            .regexes
+            .get("http-body-header")
+            .expect("Regex GenericTheme/http-body-header not found");
+        let caps = regex.captures(&url_response.body)?;
+        let name = caps["name"].to_string();
+        if DEDICATED_THEMES.contains(&name.as_str()) {
+            debug!("The theme {} has a checker of its own", name);
+            return None;
+        }
+        info!("Regex GenericTheme/http-body-header matches for {}", name);
+        let template = format!(
+            "$techno_name$ {}$techno_version$ has been identified because we found \"$evidence$\" at this url: $url_of_finding$",
+            name
+        );
+        Some(self.extract_finding_from_captures(
+            caps,
+            Some(url_response),
+            keep_left.to_owned(),
+            keep_right.to_owned(),
+            Technology::WPTheme,
+            &template,
+        ))
+    }
+}
+
+impl<'a> Checker for GenericThemeChecker<'a> {}
+
+impl<'a> HttpChecker for GenericThemeChecker<'a> {
+    /// Check for a HTTP scan.
+    /// The stylesheets are the ones found in the pages, or requested
+    /// directly.
+    fn check_http(&self, data: &[UrlResponse]) -> Vec<Finding> {
+        trace!("Running GenericThemeChecker::check_http()");
+        let mut findings: Vec<Finding> = Vec::new();
+        for url_response in data {
+            if url_response.request_type == UrlRequestType::JavaScript {
+                continue;
+            }
+            if let Some(finding) = self.check_http_body(url_response) {
+                // A theme can be included by several pages. Several themes
+                // can have the same version, their name is at the beginning
+                // of the evidence text.
+                let theme = |finding: &Finding| {
+                    finding
+                        .evidence_text
+                        .split(" has been identified")
+                        .next()
+                        .map(str::to_string)
+                };
+                if !findings
+                    .iter()
+                    .any(|existing| theme(existing) == theme(&finding))
+                {
+                    findings.push(finding);
+                }
+            }
+        }
+        findings
+    }
+
+    /// The themes are checked along with WordPress
+    fn get_technology(&self) -> Technology {
+        Technology::WordPress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::check_finding_fields;
+
+    #[test]
+    fn theme_header_matches() {
+        let checker = GenericThemeChecker::new();
+        let body = "/*\nTheme Name: Twenty Twenty-Four\nTheme URI: https://wordpress.org/themes/twentytwentyfour/\nAuthor: the WordPress team\nRequires at least: 6.4\nVersion: 1.0\nLicense: GNU General Public License v2 or later\n*/\n";
+        let url = "https://www.example.com/wp-content/themes/twentytwentyfour/style.css?ver=1.0";
+        let url_response = UrlResponse::new(url, HashMap::new(), body, UrlRequestType::Css, 200);
+        let findings = checker.check_http(&[url_response]);
+        assert_eq!(1, findings.len());
+        check_finding_fields(
+            &findings[0],
+            "Version: 1.0",
+            Technology::WPTheme,
+            Some("1.0"),
+            Some(url),
+        );
+        assert!(findings[0]
+            .evidence_text
+            .starts_with("WordPress theme Twenty Twenty-Four 1.0 has been"));
+
+        let body = "/*!\r\n * Theme Name:  Astra\r\n * Version:     4.6.4\r\n */";
+        let url = "https://www.example.com/blog/wp-content/themes/astra/style.css";
+        let url_response =
+            UrlResponse::new(url, HashMap::new(), body, UrlRequestType::Default, 200);
+        let finding = checker.check_http_body(&url_response).unwrap();
+        assert_eq!(Some("4.6.4".to_string()), finding.version);
+        assert!(finding
+            .evidence_text
+            .starts_with("WordPress theme Astra 4.6.4"));
+    }
+
+    #[test]
+    fn themes_with_the_same_version_are_kept() {
+        let checker = GenericThemeChecker::new();
+        let url_response1 = UrlResponse::new(
+            "https://www.example.com/wp-content/themes/astra/style.css",
+            HashMap::new(),
+            "/*\nTheme Name: Astra\nVersion: 4.6.4\n*/",
+            UrlRequestType::Css,
+            200,
+        );
+        let url_response2 = UrlResponse::new(
+            "https://www.example.com/wp-content/themes/astra-child/style.css",
+            HashMap::new(),
+            "/*\nTheme Name: Astra Child\nVersion: 4.6.4\n*/",
+            UrlRequestType::Css,
+            200,
+        );
+        let url_response3 = UrlResponse::new(
+            "https://www.example.com/wp-content/themes/astra/style.css?ver=4.6.4",
+            HashMap::new(),
+            "/*\nTheme Name: Astra\nVersion: 4.6.4\n*/",
+            UrlRequestType::Css,
+            200,
+        );
+        let findings = checker.check_http(&[url_response1, url_response2, url_response3]);
+        assert_eq!(2, findings.len());
+        assert!(findings[1]
+            .evidence_text
+            .starts_with("WordPress theme Astra Child 4.6.4"));
+    }
+
+    #[test]
+    fn theme_header_doesnt_match() {
+        let checker = GenericThemeChecker::new();
+        // Divi has a checker of its own
+        let url_response1 = UrlResponse::new(
+            "https://www.example.com/wp-content/themes/Divi/style.css",
+            HashMap::new(),
+            "/*!\nTheme Name: Divi\nVersion: 4.23.0\n*/",
+            UrlRequestType::Css,
+            200,
+        );
+        // Not the main stylesheet of a theme
+        let url_response2 = UrlResponse::new(
+            "https://www.example.com/wp-content/themes/astra/assets/css/main.css",
+            HashMap::new(),
+            "/*\nTheme Name: Astra\nVersion: 4.6.4\n*/",
+            UrlRequestType::Css,
+            200,
+        );
+        // The header must be at the beginning
+        let url_response3 = UrlResponse::new(
+            "https://www.example.com/wp-content/themes/custom/style.css",
+            HashMap::new(),
+            &format!(
+                "{}/* Theme Name: Custom\nVersion: 1.2 */",
+                "body{margin:0}".repeat(20)
+            ),
+            UrlRequestType::Css,
+            200,
+        );
+        let findings = checker.check_http(&[url_response1, url_response2, url_response3]);
+        assert!(findings.is_empty());
+    }
+}
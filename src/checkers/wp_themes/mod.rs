@@ -3,3 +3,4 @@
 //! similar and grouping them in a module helps keeping a clean architecture.
 
 pub mod divi;
+pub mod generic;
@@ -1,5 +1,6 @@
 pub mod application;
 pub mod checkers;
+pub mod config;
 pub mod models;
 pub mod readers;
 pub mod vulnerabilities;
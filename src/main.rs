@@ -2,9 +2,9 @@ use log::debug;
 use sanca_software::application::Application;
 
 fn main() {
-    //simple_logger::init_with_level(log::Level::Debug).unwrap();
-    debug!("Starting application");
     let mut application = Application::new();
     application.read_argv();
+    application.init_logger();
+    debug!("Starting application");
     application.run();
 }
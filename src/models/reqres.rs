@@ -4,76 +4,257 @@
 //! They provide an interface between [`Technology`] and [`crate::readers::http::HttpReader`].
 
 use super::technology::Technology;
-use log::{error, trace};
+use log::{error, trace, warn};
 use regex::Regex;
+use reqwest::{Method, Url};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// The separator of the Set-Cookie headers of a response, joined in a
+/// single value. They can't be joined with commas like the other headers,
+/// the attributes contain some (e.g. Expires=Wed, 21 Oct 2026 07:28:00 GMT).
+pub const SET_COOKIE_SEPARATOR: char = '\n';
+
+/// The maximum numbers of requests generated for a target, to bound the
+/// footprint of a scan. The main URL is always requested, it's not
+/// counted.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestBudget {
+    /// The maximum number of requests of each technology, if any
+    pub per_technology: Option<usize>,
+    /// The maximum number of requests of the target, if any
+    pub total: Option<usize>,
+}
+
+impl RequestBudget {
+    /// Checks whether the total budget allows one more request, when
+    /// count requests (without the main URL) are already planned.
+    pub fn allows(&self, count: usize) -> bool {
+        self.total.is_none_or(|total| count < total)
+    }
+}
+
 /// Represents a request that an HTTP reader will have to handle
 /// It contains a URL, and a `fetch_js` field. If `fetch_js` is set
 /// to true, the reader will look for JavaScript URLs in the response
-/// body, and fetch all of them.
+/// body, and fetch all of them. The `fetch_css` field does the same for
+/// the stylesheets.
 #[derive(Debug)]
 pub struct UrlRequest {
     /// The URL where to send the HTTP request
     pub url: String,
     /// Whether to fetch the JavaScript files found in the response from url
     pub fetch_js: bool,
+    /// Whether to fetch the stylesheets found in the response from url
+    pub fetch_css: bool,
+    /// The HTTP method of the request, GET by default
+    pub method: Method,
+    /// The body of the request, if any
+    pub body: Option<String>,
+    /// The content type of the body (e.g. application/json), if any
+    pub content_type: Option<String>,
 }
 
 impl UrlRequest {
     /// Creates a list of UrlRequests based on a list of technologies
-    pub fn from_technologies(main_url: &str, technologies: &[Technology]) -> Vec<UrlRequest> {
-        trace!("Running UrlRequest::from_technologies()");
+    ///
+    /// If a path prefix is given, all the generated URLs except the main
+    /// one are moved under this prefix. It allows to scan applications
+    /// installed in a non-standard directory (e.g. WordPress in /cms/).
+    ///
+    /// The URLs are normalized, so that the absolute and relative forms
+    /// of a same path are requested only once. The main URL is always
+    /// first, the other ones keep the order of the technologies.
+    pub fn from_technologies(
+        main_url: &str,
+        technologies: &[Technology],
+        path_prefix: Option<&str>,
+    ) -> Vec<UrlRequest> {
+        Self::from_technologies_with_budget(
+            main_url,
+            technologies,
+            path_prefix,
+            &RequestBudget::default(),
+        )
+    }
+
+    /// Creates a list of UrlRequests based on a list of technologies,
+    /// like [`from_technologies`](UrlRequest::from_technologies), within
+    /// the budget. When a technology or the target reaches its budget,
+    /// its remaining requests are skipped. The requests already planned
+    /// by another technology are free.
+    pub fn from_technologies_with_budget(
+        main_url: &str,
+        technologies: &[Technology],
+        path_prefix: Option<&str>,
+        budget: &RequestBudget,
+    ) -> Vec<UrlRequest> {
+        trace!("Running UrlRequest::from_technologies_with_budget()");
+        let main_url = Self::normalize_url(main_url);
         // Helps to avoid duplicated when building the list of UrlRequests
-        // key is URL, value is fetch_js
-        let mut url_requests_map: HashMap<String, bool> = HashMap::new();
+        // key is URL, value is the position in url_requests
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        let mut url_requests: Vec<UrlRequest> = Vec::new();
+        // The number of requests, without the main URL
+        let mut count = 0;
         trace!("Looping over provided technologies");
         // For each technology, add its UrlRequests to the list, while avoiding
         // duplicates
         for technology in technologies {
             trace!("Checking {:?}", technology);
-            for url_request in technology.get_url_requests(main_url) {
+            let mut technology_count = 0;
+            let mut skipped = 0;
+            for mut url_request in technology.get_url_requests(&main_url) {
+                url_request.url = Self::normalize_url(&url_request.url);
+                if let Some(prefix) = path_prefix {
+                    if url_request.url != main_url {
+                        url_request.add_path_prefix(prefix);
+                    }
+                }
                 trace!("Handling UrlRequest {:?}", url_request);
-                // If the URL is not stored already
-                if !url_requests_map.contains_key(&url_request.url) {
-                    trace!("UrlRequest is not already in the list, add it");
-                    url_requests_map.insert(url_request.url, url_request.fetch_js);
-                } else if url_request.fetch_js == true {
-                    trace!("UrlRequest already in the list, but this time fetch_js is true, update the already stored value");
-                    // If the URL was already in the list but the new one has
-                    // fetch_js to true, set fetch_js to true also in the list.
-                    // It might be already set to true, but that's not important.
-                    url_requests_map.insert(url_request.url, true);
+                match positions.get(&url_request.get_key()) {
+                    Some(position) => {
+                        // If the URL was already in the list but the new one has
+                        // fetch_js to true, set fetch_js to true also in the list.
+                        if url_request.fetch_js {
+                            trace!("UrlRequest already in the list, but this time fetch_js is true, update the already stored value");
+                            url_requests[*position].fetch_js = true;
+                        }
+                        if url_request.fetch_css {
+                            url_requests[*position].fetch_css = true;
+                        }
+                    }
+                    None => {
+                        if url_request.url != main_url {
+                            let within_budget = budget
+                                .per_technology
+                                .is_none_or(|max| technology_count < max)
+                                && budget.allows(count);
+                            if !within_budget {
+                                skipped += 1;
+                                continue;
+                            }
+                            technology_count += 1;
+                            count += 1;
+                        }
+                        trace!("UrlRequest is not already in the list, add it");
+                        positions.insert(url_request.get_key(), url_requests.len());
+                        url_requests.push(url_request);
+                    }
                 }
             }
+            if skipped > 0 {
+                warn!(
+                    "The request budget is reached, {} requests of {:?} are skipped",
+                    skipped, technology
+                );
+            }
         }
 
-        trace!("Converting the HashMap of UrlRequests to a Vec");
-        // Convert the HashMap to a list of UrlRequest
-        let mut url_requests: Vec<UrlRequest> = Vec::new();
-        for (url, fetch_js) in url_requests_map.iter() {
-            // The objective is to keep the main URL always in first position.
-            // When possible, it's better to manage the main URL first, it will
-            // be clearer for the user.
-            if url == main_url {
-                trace!("The URL is the main, add it in the first position");
-                let mut tmp = vec![UrlRequest::new(url, *fetch_js)];
-                tmp.extend(url_requests);
-                url_requests = tmp;
-            } else {
-                trace!("Pushing UrlRequest {} / {} to the list", url, fetch_js);
-                url_requests.push(UrlRequest::new(url, *fetch_js));
-            }
+        // The objective is to keep the main URL always in first position.
+        // When possible, it's better to manage the main URL first, it will
+        // be clearer for the user.
+        if let Some(position) = positions.get(&main_url) {
+            trace!("Moving the main URL in the first position");
+            let main_url_request = url_requests.remove(*position);
+            url_requests.insert(0, main_url_request);
         }
 
         return url_requests;
     }
 
+    /// Normalizes the URL.
+    ///
+    /// An empty path becomes /, the duplicated slashes are collapsed and
+    /// the . and .. segments are resolved. The query string is kept as is.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sanca_software::models::reqres::UrlRequest;
+    /// assert_eq!("https://example.com/", UrlRequest::normalize_url("https://example.com"));
+    /// assert_eq!(
+    ///     "https://example.com/b/c.php?x=1",
+    ///     UrlRequest::normalize_url("https://example.com//a/../b/./c.php?x=1")
+    /// );
+    /// ```
+    pub fn normalize_url(url: &str) -> String {
+        let (scheme, rest) = match url.split_once("://") {
+            Some(parts) => parts,
+            None => return url.to_string(),
+        };
+        let (authority, path_query) = match rest.find('/') {
+            Some(position) => rest.split_at(position),
+            None => match rest.find('?') {
+                Some(position) => rest.split_at(position),
+                None => (rest, ""),
+            },
+        };
+        let (path, query) = match path_query.find('?') {
+            Some(position) => path_query.split_at(position),
+            None => (path_query, ""),
+        };
+
+        let mut segments: Vec<&str> = Vec::new();
+        for segment in path.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => {
+                    segments.pop();
+                }
+                _ => segments.push(segment),
+            }
+        }
+        let mut new_path = format!("/{}", segments.join("/"));
+        // Keep the trailing slash of directories
+        let last_segment = path.rsplit('/').next().unwrap_or("");
+        if !segments.is_empty()
+            && (path.ends_with('/') || last_segment == "." || last_segment == "..")
+        {
+            new_path.push('/');
+        }
+
+        format!("{}://{}{}{}", scheme, authority, new_path, query)
+    }
+
     /// Creates a new UrlRequest
     pub fn new(url: &str, fetch_js: bool) -> Self {
         UrlRequest {
             url: url.to_string(),
             fetch_js: fetch_js,
+            fetch_css: false,
+            method: Method::GET,
+            body: None,
+            content_type: None,
+        }
+    }
+
+    /// Fetches the stylesheets found in the response too
+    pub fn with_fetch_css(mut self) -> Self {
+        self.fetch_css = true;
+        self
+    }
+
+    /// Sends the request with the given HTTP method instead of GET
+    pub fn with_method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Sends the given body with the request, with its content type
+    pub fn with_body(mut self, body: &str, content_type: &str) -> Self {
+        self.body = Some(body.to_string());
+        self.content_type = Some(content_type.to_string());
+        self
+    }
+
+    /// Returns the key identifying the request, to avoid duplicates.
+    /// It's the URL for a GET request without body, the requests with
+    /// another method or a body are only duplicates of the same ones.
+    pub fn get_key(&self) -> String {
+        match (&self.method, &self.body) {
+            (&Method::GET, None) => self.url.clone(),
+            (method, body) => format!("{} {} {}", method, self.url, body.as_deref().unwrap_or("")),
         }
     }
 
@@ -99,10 +280,21 @@ impl UrlRequest {
         // Note: this regex is not exhaustive. It doesn't support the
         // user:pass@hostname form, and it ignores the hash (#ancher1)
         // But it should enough for what we have to do with it.
-        let url_regex = Regex::new(r"(?P<protocol>[a-z0-9]+):\/\/(?P<hostname>[^\/:]+)(:(?P<port>\d{1,5}))?(?P<path>\/[^\?]*)?(?P<querystring>\?[^#]*)?(#.*)?").unwrap();
-        let caps = url_regex
-            .captures(main_url)
-            .expect(&format!("Unable to parse the provided URL: {}", main_url));
+        let url_regex = Regex::new(r"^(?i)(?P<protocol>[a-z0-9+.-]+):\/\/(?P<hostname>\[[^\]\/]*\]|[^\/:?#]+)(:(?P<port>\d{1,5}))?(?P<path>\/[^\?#]*)?(?P<querystring>\?[^#]*)?(#.*)?$").unwrap();
+        let caps = match url_regex.captures(main_url.trim()) {
+            Some(caps) => caps,
+            None => {
+                // The URL given by the user has already been requested, it
+                // shouldn't happen. Don't abort the whole scan for it.
+                warn!("Unable to parse the URL {}, appending the path", main_url);
+                let url = format!(
+                    "{}/{}",
+                    main_url.trim_end_matches('/'),
+                    path_to.trim_start_matches('/')
+                );
+                return Self::new(&url, fetch_js);
+            }
+        };
         let protocol: String = caps["protocol"].to_string();
         let hostname: String = caps["hostname"].to_string();
         trace!(
@@ -111,23 +303,26 @@ impl UrlRequest {
             hostname
         );
         // If the port is not provided, use the default for http / https
-        let port: String = if caps.name("port").is_some() {
-            trace!("Port = {}", caps.name("port").unwrap().as_str());
-            format!(":{}", caps.name("port").unwrap().as_str().to_string())
-        } else {
-            trace!("Port not provided. 80 is default for HTTP & 443 default for HTTPS");
-            "".to_string()
+        let port: String = match caps.name("port") {
+            Some(port) => {
+                trace!("Port = {}", port.as_str());
+                format!(":{}", port.as_str())
+            }
+            None => {
+                trace!("Port not provided. 80 is default for HTTP & 443 default for HTTPS");
+                "".to_string()
+            }
         };
         // If no path is provided, uses /
-        let path_from: String = if caps.name("path").is_some() {
-            trace!(
-                "Found a path in the main URL: {}",
-                caps.name("path").unwrap().as_str()
-            );
-            caps.name("path").unwrap().as_str().to_string()
-        } else {
-            trace!("No path found in the main URL, use /");
-            "/".to_string()
+        let path_from: String = match caps.name("path") {
+            Some(path) => {
+                trace!("Found a path in the main URL: {}", path.as_str());
+                path.as_str().to_string()
+            }
+            None => {
+                trace!("No path found in the main URL, use /");
+                "/".to_string()
+            }
         };
 
         let new_path: String = if path_to.starts_with("/") {
@@ -136,48 +331,29 @@ impl UrlRequest {
         } else {
             trace!("The path found in the main URL is relative, compute the new path");
             // Here handle the relative path in path_to
-            let path_parts: Vec<String> = path_from.split("/").map(|i| i.to_string()).collect();
-
-            if path_from.chars().nth(path_from.len() - 1).unwrap() == '/' {
+            if path_from.ends_with('/') {
                 trace!("The last char of the main URL path is a /");
                 // If the last char of the original path is a /, concatenate the paths
                 // Example: https://example.com/something/that/
                 // In this case, a path of "test.php" would produce the URL
                 // https://example.com/something/that/test.php
                 format!("{}{}", path_from, path_to)
-            } else if path_parts.len() <= 1 {
-                trace!("The main URL path has only one part (/something), just use the new one");
-                // If we were at the top of the tree, just use path_to
-                // Example: https://example.com/something
-                // In this case, a path_to of "other/index.php" would produce the URL
-                // https://example.com/other/index.php
-                path_to.to_string()
             } else {
-                trace!(
-                    "The main URL path has several parts and doesn't end with a / (/some/thing)"
-                );
-                // If we have an original path with several parts and not ending with a /,
-                // just remove the last part.
+                trace!("The main URL path doesn't end with a / (/some/thing)");
+                // If the original path doesn't end with a /, just remove
+                // the last part. The path starts with a /, so there's
+                // always one.
                 // Example: https://example.com/this/that.php
                 // In this case, a path of "index.php" would produce the URL
                 // https://example.com/this/index.php
-                let mut np = "".to_string();
-                let mut part_counter = 0;
-
-                trace!("Loop over each path part to remove the last one");
-                for part in path_parts.iter() {
-                    trace!("Checking path part {}", part);
-                    // We take all parts except the last one
-                    if part_counter < path_parts.len() - 1 && !part.is_empty() {
-                        trace!("Part is not the last one, use it");
-                        np.push_str(&format!("/{}", part));
-                    }
-                    part_counter += 1;
-                }
-
-                trace!("The last part of the original path has been removed, add the new one");
-                np.push_str(&format!("/{}", path_to));
-                np
+                // And with https://example.com/something, a path of
+                // "other/index.php" would produce the URL
+                // https://example.com/other/index.php
+                let directory = match path_from.rfind('/') {
+                    Some(position) => &path_from[..=position],
+                    None => "/",
+                };
+                format!("{}{}", directory, path_to)
             }
         };
 
@@ -185,6 +361,41 @@ impl UrlRequest {
         Self::new(&url, fetch_js)
     }
 
+    /// Moves the URL under the given path prefix.
+    ///
+    /// The path of the URL is prepended with the prefix, unless it's already
+    /// under it. The query string is kept as is.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut url_request = sanca_software::models::reqres::UrlRequest::new("https://example.com/wp-login.php", false);
+    /// url_request.add_path_prefix("/cms/");
+    /// assert_eq!("https://example.com/cms/wp-login.php", url_request.url);
+    /// ```
+    pub fn add_path_prefix(&mut self, path_prefix: &str) {
+        trace!("Running UrlRequest::add_path_prefix()");
+        let prefix = format!("/{}", path_prefix.trim_matches('/'));
+        if prefix == "/" {
+            return;
+        }
+
+        // Example: https://example.com:8443/
+        let root_url = Self::from_path(&self.url, "/", false).url;
+        let root_url_no_slash = &root_url[..root_url.len() - 1];
+        let path = match self.url.strip_prefix(root_url_no_slash) {
+            Some(p) if p.starts_with('/') => p.to_string(),
+            _ => "/".to_string(),
+        };
+
+        if path == prefix || path.starts_with(&format!("{}/", prefix)) {
+            trace!("The URL {} is already under {}", self.url, prefix);
+            return;
+        }
+
+        self.url = format!("{}{}{}", root_url_no_slash, prefix, path);
+    }
+
     /// Get the IP or hostname & the port of the URL
     ///
     /// # Examples
@@ -227,7 +438,7 @@ impl UrlRequest {
 ///
 /// Contains all the data needed by a checker: the URL of the request,
 /// the HTTP headers, the response body, and the [`UrlRequestType`].
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct UrlResponse {
     /// The URL where the request was sent
     pub url: String,
@@ -241,6 +452,10 @@ pub struct UrlResponse {
     /// Was it a "main" request, of a subsequent one to download
     /// a JavaScript file?
     pub request_type: UrlRequestType,
+    /// The HTTP version of the response (e.g. HTTP/1.1), if known
+    pub http_version: Option<String>,
+    /// The redirections followed to get the response, in order
+    pub redirects: Vec<Redirect>,
 }
 
 impl UrlResponse {
@@ -258,9 +473,49 @@ impl UrlResponse {
             body: body.to_string(),
             request_type,
             status_code: status_code,
+            http_version: None,
+            redirects: Vec::new(),
+        }
+    }
+
+    /// Sets the HTTP version of the response
+    pub fn with_http_version(mut self, http_version: &str) -> Self {
+        self.http_version = Some(http_version.to_string());
+        self
+    }
+
+    /// Sets the redirections followed to get the response
+    pub fn with_redirects(mut self, redirects: Vec<Redirect>) -> Self {
+        self.redirects = redirects;
+        self
+    }
+
+    /// Whether the redirections led to another hostname than the one of
+    /// the request. The response is then the one of another asset.
+    pub fn is_redirected_to_another_host(&self) -> bool {
+        match self.redirects.first() {
+            Some(first_redirect) => {
+                let get_host = |url: &str| {
+                    Url::parse(url)
+                        .ok()
+                        .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+                };
+                get_host(&first_redirect.url) != get_host(&self.url)
+            }
+            None => false,
         }
     }
 
+    /// Whether the request succeeded, with a 2xx status code
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status_code)
+    }
+
+    /// Whether the status code is one of the given ones
+    pub fn has_status(&self, status_codes: &[u16]) -> bool {
+        status_codes.contains(&self.status_code)
+    }
+
     /// Return a HashMap with only the headers given in parameter.
     /// Any non-existing header is ignored.
     pub fn get_headers(&self, header_names: &[String]) -> HashMap<String, String> {
@@ -273,10 +528,88 @@ impl UrlResponse {
         }
         headers
     }
+
+    /// Returns the cookies set by the response, in the order of the
+    /// Set-Cookie headers. The invalid ones are ignored.
+    pub fn get_set_cookies(&self) -> Vec<SetCookie> {
+        match self.headers.get("Set-cookie") {
+            Some(set_cookies) => set_cookies
+                .split(SET_COOKIE_SEPARATOR)
+                .filter_map(SetCookie::parse)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// A cookie set by a response, with its attributes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetCookie {
+    /// The name of the cookie (e.g. PHPSESSID)
+    pub name: String,
+    /// The value of the cookie
+    pub value: String,
+    /// The attributes (e.g. Path, HttpOnly), with their value if any
+    pub attributes: Vec<(String, Option<String>)>,
+}
+
+impl SetCookie {
+    /// Parses the value of a Set-Cookie header.
+    /// Example: id=a3fWa; Path=/; Secure; HttpOnly
+    ///
+    /// Returns None if there is no cookie name.
+    pub fn parse(set_cookie: &str) -> Option<Self> {
+        let mut parts = set_cookie.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let attributes = parts
+            .map(str::trim)
+            .filter(|attribute| !attribute.is_empty())
+            .map(|attribute| match attribute.split_once('=') {
+                Some((name, value)) => (name.trim().to_string(), Some(value.trim().to_string())),
+                None => (attribute.to_string(), None),
+            })
+            .collect();
+        Some(Self {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            attributes,
+        })
+    }
+
+    /// Whether the attribute is given, its name is case-insensitive.
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.attributes
+            .iter()
+            .any(|(attribute, _)| attribute.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the value of the attribute, if it's given with one.
+    /// Its name is case-insensitive.
+    pub fn get_attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute.eq_ignore_ascii_case(name))
+            .and_then(|(_, value)| value.as_deref())
+    }
+}
+
+/// A redirection followed by the HTTP reader.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Redirect {
+    /// The URL which answered with the redirection
+    pub url: String,
+    /// The status code of the redirection (e.g. 301)
+    pub status_code: u16,
+    /// The URL the redirection points to
+    pub location: String,
 }
 
 /// Represents the type of a UrlRequest.
-/// It is about a main URL, or a JavaScript one.
+/// It is about a main URL, a JavaScript one or a stylesheet.
 #[derive(Clone, Debug, PartialEq)]
 pub enum UrlRequestType {
     /// The default UrlRequest type.
@@ -285,14 +618,138 @@ pub enum UrlRequestType {
     Default,
     /// The UrlRequest is the one of a JavaScript file.
     JavaScript,
+    /// The UrlRequest is the one of a stylesheet.
+    Css,
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{UrlRequest, UrlRequestType, UrlResponse};
+    use super::{
+        Method, Redirect, RequestBudget, SetCookie, UrlRequest, UrlRequestType, UrlResponse,
+    };
     use crate::models::technology::Technology;
+    use proptest::prelude::*;
     use std::collections::HashMap;
 
+    #[test]
+    fn status_codes_are_checked() {
+        let url_response = |status_code| {
+            UrlResponse::new(
+                "https://www.example.com/",
+                HashMap::new(),
+                "",
+                UrlRequestType::Default,
+                status_code,
+            )
+        };
+        assert!(url_response(200).is_success());
+        assert!(url_response(204).is_success());
+        assert!(!url_response(301).is_success());
+        assert!(!url_response(404).is_success());
+        assert!(url_response(401).has_status(&[401, 403]));
+        assert!(!url_response(404).has_status(&[401, 403]));
+    }
+
+    #[test]
+    fn redirects_to_another_host_are_detected() {
+        let redirect = |url: &str, location: &str| Redirect {
+            url: url.to_string(),
+            status_code: 301,
+            location: location.to_string(),
+        };
+        let url_response = |url: &str, redirects| {
+            UrlResponse::new(url, HashMap::new(), "", UrlRequestType::Default, 200)
+                .with_redirects(redirects)
+        };
+        assert!(
+            !url_response("https://www.example.com/", Vec::new()).is_redirected_to_another_host()
+        );
+        assert!(!url_response(
+            "https://www.example.com/blog/",
+            vec![redirect(
+                "http://www.example.com/blog",
+                "https://www.example.com/blog/"
+            )]
+        )
+        .is_redirected_to_another_host());
+        assert!(url_response(
+            "https://login.example.net/",
+            vec![
+                redirect(
+                    "https://www.example.com/admin",
+                    "https://www.example.com/admin/"
+                ),
+                redirect(
+                    "https://www.example.com/admin/",
+                    "https://login.example.net/"
+                ),
+            ]
+        )
+        .is_redirected_to_another_host());
+    }
+
+    #[test]
+    fn from_technologies_with_budget_works() {
+        let technologies = vec![Technology::PhpMyAdmin, Technology::WordPress];
+        let main_url = "https://example.com/";
+        let budget = RequestBudget {
+            per_technology: Some(2),
+            total: None,
+        };
+        let urls: Vec<String> =
+            UrlRequest::from_technologies_with_budget(main_url, &technologies, None, &budget)
+                .into_iter()
+                .map(|url_request| url_request.url)
+                .collect();
+        assert_eq!(
+            vec![
+                "https://example.com/",
+                "https://example.com/doc/html/index.html",
+                "https://example.com/phpmyadmin/doc/html/index.html",
+                "https://example.com/wp-admin/install.php",
+                "https://example.com/wp-login.php",
+            ],
+            urls
+        );
+
+        let budget = RequestBudget {
+            per_technology: None,
+            total: Some(3),
+        };
+        let urls: Vec<String> =
+            UrlRequest::from_technologies_with_budget(main_url, &technologies, None, &budget)
+                .into_iter()
+                .map(|url_request| url_request.url)
+                .collect();
+        // The main URL is always requested
+        assert_eq!(4, urls.len());
+        assert_eq!("https://example.com/", urls[0]);
+        assert!(!urls.contains(&"https://example.com/wp-login.php".to_string()));
+    }
+
+    #[test]
+    fn get_key_works() {
+        let url = "https://example.com/graphql";
+        let get_request = UrlRequest::new(url, false);
+        let options_request = UrlRequest::new(url, false).with_method(Method::OPTIONS);
+        let post_request = UrlRequest::new(url, false)
+            .with_method(Method::POST)
+            .with_body("{\"query\":\"{__typename}\"}", "application/json");
+        assert_eq!(url, get_request.get_key());
+        assert_eq!(
+            "OPTIONS https://example.com/graphql ",
+            options_request.get_key()
+        );
+        assert_eq!(
+            "POST https://example.com/graphql {\"query\":\"{__typename}\"}",
+            post_request.get_key()
+        );
+        assert_eq!(
+            Some("application/json"),
+            post_request.content_type.as_deref()
+        );
+    }
+
     #[test]
     fn from_technologies_remove_duplicate_urls() {
         // We want to ensure that when calling from_technologies(),
@@ -300,7 +757,8 @@ mod tests {
         let technologies: Vec<Technology> =
             vec![Technology::JQuery, Technology::PHP, Technology::JQuery];
         let main_url = "https://example.com/";
-        let url_requests_from_technologies = UrlRequest::from_technologies(main_url, &technologies);
+        let url_requests_from_technologies =
+            UrlRequest::from_technologies(main_url, &technologies, None);
         let mut found_urls: Vec<String> = Vec::new();
 
         for url_request in url_requests_from_technologies {
@@ -313,6 +771,106 @@ mod tests {
         }
     }
 
+    #[test]
+    fn from_technologies_is_deterministic() {
+        let technologies: Vec<Technology> = vec![
+            Technology::WordPress,
+            Technology::JQuery,
+            Technology::PHP,
+            Technology::Plesk,
+        ];
+        let main_url = "https://example.com";
+        let urls: Vec<String> = UrlRequest::from_technologies(main_url, &technologies, None)
+            .into_iter()
+            .map(|u| u.url)
+            .collect();
+        assert_eq!("https://example.com/", urls[0]);
+        for _ in 0..10 {
+            let other_urls: Vec<String> =
+                UrlRequest::from_technologies(main_url, &technologies, None)
+                    .into_iter()
+                    .map(|u| u.url)
+                    .collect();
+            assert_eq!(urls, other_urls);
+        }
+    }
+
+    #[test]
+    fn from_technologies_collapses_paths() {
+        // The absolute and relative forms of a same path must be requested
+        // only once when the main URL is the root
+        let technologies: Vec<Technology> = vec![Technology::Matomo];
+        let url_requests =
+            UrlRequest::from_technologies("https://example.com/", &technologies, None);
+        let count = url_requests
+            .iter()
+            .filter(|u| u.url == "https://example.com/CHANGELOG.md")
+            .count();
+        assert_eq!(1, count);
+    }
+
+    #[test]
+    fn normalize_url_works() {
+        let urls = [
+            ("https://example.com", "https://example.com/"),
+            ("https://example.com?a=b", "https://example.com/?a=b"),
+            (
+                "http://example.com:8080//a//b/",
+                "http://example.com:8080/a/b/",
+            ),
+            (
+                "https://example.com/a/../../b.php",
+                "https://example.com/b.php",
+            ),
+            ("https://example.com/a/./b/..", "https://example.com/a/"),
+            (
+                "https://example.com/a/?x=../y",
+                "https://example.com/a/?x=../y",
+            ),
+        ];
+        for (url, expected) in urls {
+            assert_eq!(expected, UrlRequest::normalize_url(url));
+        }
+    }
+
+    #[test]
+    fn from_technologies_uses_path_prefix() {
+        let technologies: Vec<Technology> = vec![Technology::WordPress, Technology::Plesk];
+        let main_url = "https://example.com/";
+        let url_requests = UrlRequest::from_technologies(main_url, &technologies, Some("/cms/"));
+        let urls: Vec<String> = url_requests.into_iter().map(|u| u.url).collect();
+        assert!(urls.contains(&main_url.to_string()));
+        assert!(urls.contains(&"https://example.com/cms/wp-login.php".to_string()));
+        assert!(urls.contains(&"https://example.com/cms/login_up.php".to_string()));
+        assert!(!urls.contains(&"https://example.com/wp-login.php".to_string()));
+    }
+
+    #[test]
+    fn add_path_prefix_works() {
+        let mut url_request_1 = UrlRequest::new("https://example.com:8443/a/b.php?c=d", false);
+        url_request_1.add_path_prefix("cms");
+        assert_eq!(
+            "https://example.com:8443/cms/a/b.php?c=d",
+            url_request_1.url
+        );
+
+        // Already under the prefix
+        let mut url_request_2 = UrlRequest::new("https://example.com/cms/wp-login.php", false);
+        url_request_2.add_path_prefix("/cms/");
+        assert_eq!("https://example.com/cms/wp-login.php", url_request_2.url);
+
+        let mut url_request_3 = UrlRequest::new("https://example.com", false);
+        url_request_3.add_path_prefix("/cms");
+        assert_eq!("https://example.com/cms/", url_request_3.url);
+
+        // An empty prefix changes nothing
+        let mut url_request_4 = UrlRequest::new("https://example.com/cmsx/index.php", false);
+        url_request_4.add_path_prefix("/");
+        assert_eq!("https://example.com/cmsx/index.php", url_request_4.url);
+        url_request_4.add_path_prefix("/cms");
+        assert_eq!("https://example.com/cms/cmsx/index.php", url_request_4.url);
+    }
+
     #[test]
     fn get_hostname_port_works() {
         let url_request1 = UrlRequest::new("https://www.this.that.example.com", false);
@@ -335,6 +893,18 @@ mod tests {
         let (hostname3, port3) = url_request3.get_hostname_port();
         assert_eq!("that.this.example.com", hostname3);
         assert_eq!(8080, port3);
+
+        // Only parsed, never resolved, so that it works through a proxy
+        let url_request4 = UrlRequest::new(
+            "http://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion/",
+            false,
+        );
+        let (hostname4, port4) = url_request4.get_hostname_port();
+        assert_eq!(
+            "2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion",
+            hostname4
+        );
+        assert_eq!(80, port4);
     }
 
     #[test]
@@ -387,6 +957,83 @@ mod tests {
         );
     }
 
+    #[test]
+    fn from_path_handles_edge_cases() {
+        let cases = [
+            ("https://example.com/", "", "https://example.com/"),
+            (
+                "https://example.com",
+                "wp-login.php",
+                "https://example.com/wp-login.php",
+            ),
+            (
+                "https://example.com/blog",
+                "wp-login.php",
+                "https://example.com/wp-login.php",
+            ),
+            ("https://example.com/blog", "/", "https://example.com/"),
+            (
+                "HTTPS://Example.com/Blog/",
+                "feed/",
+                "HTTPS://Example.com/Blog/feed/",
+            ),
+            (
+                "http://[::1]:8080/app/index.php",
+                "login",
+                "http://[::1]:8080/app/login",
+            ),
+            ("http://[::1]/", "login", "http://[::1]/login"),
+            (
+                "https://example.com/blog/#top",
+                "feed/",
+                "https://example.com/blog/feed/",
+            ),
+            ("https://example.com/a?b=/c/d", "e", "https://example.com/e"),
+            // Not a URL, the path is appended
+            ("example.com/blog/", "/feed/", "example.com/blog/feed/"),
+            ("", "", "/"),
+        ];
+        for (main_url, path, expected) in cases {
+            assert_eq!(
+                expected,
+                UrlRequest::from_path(main_url, path, false).url,
+                "from_path({:?}, {:?})",
+                main_url,
+                path
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn from_path_never_panics(main_url in "\\PC*", path in "\\PC*") {
+            UrlRequest::from_path(&main_url, &path, false);
+        }
+
+        #[test]
+        fn from_path_keeps_the_origin(
+            protocol in "(?i)https?",
+            hostname in "[a-z0-9-]{1,20}(\\.[a-z0-9-]{1,10}){0,3}",
+            port in proptest::option::of(1u16..),
+            path_from in "(/[a-zA-Z0-9._~%-]{0,10}){0,5}/?",
+            querystring in "(\\?[a-z0-9=&/]{0,20})?",
+            path_to in "/?([a-zA-Z0-9._~%-]{0,10}/?){0,5}",
+        ) {
+            let origin = match port {
+                Some(port) => format!("{}://{}:{}", protocol, hostname, port),
+                None => format!("{}://{}", protocol, hostname),
+            };
+            let main_url = format!("{}{}{}", origin, path_from, querystring);
+            let url = UrlRequest::from_path(&main_url, &path_to, false).url;
+
+            prop_assert!(url.starts_with(&format!("{}/", origin)), "{} from {}", url, main_url);
+            prop_assert!(url.ends_with(&path_to), "{} from {}", url, main_url);
+            if path_to.starts_with('/') {
+                prop_assert_eq!(format!("{}{}", origin, path_to), url);
+            }
+        }
+    }
+
     #[test]
     fn url_response_get_headers_works() {
         let mut headers_1 = HashMap::new();
@@ -410,4 +1057,45 @@ mod tests {
         assert!(extracted_headers.contains_key("X-powered-by"));
         assert_eq!("PHP/8.2", extracted_headers["X-powered-by"]);
     }
+
+    #[test]
+    fn set_cookies_are_parsed() {
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Set-cookie".to_string(),
+            "XSRF-TOKEN=eyJpdiI6; expires=Wed, 21 Oct 2026 07:28:00 GMT; Max-Age=7200; path=/; samesite=lax\nlaravel_session=eyJpdiI7; path=/; secure; HttpOnly\ninvalid".to_string(),
+        );
+        let url_response = UrlResponse::new(
+            "https://www.example.com/",
+            headers,
+            "",
+            UrlRequestType::Default,
+            200,
+        );
+        let cookies = url_response.get_set_cookies();
+        assert_eq!(2, cookies.len());
+        assert_eq!("XSRF-TOKEN", cookies[0].name);
+        assert_eq!("eyJpdiI6", cookies[0].value);
+        assert_eq!(
+            Some("Wed, 21 Oct 2026 07:28:00 GMT"),
+            cookies[0].get_attribute("Expires")
+        );
+        assert_eq!(Some("lax"), cookies[0].get_attribute("SameSite"));
+        assert!(!cookies[0].has_attribute("HttpOnly"));
+        assert_eq!("laravel_session", cookies[1].name);
+        assert!(cookies[1].has_attribute("Secure"));
+        assert!(cookies[1].has_attribute("httponly"));
+        assert_eq!(None, cookies[1].get_attribute("HttpOnly"));
+
+        assert_eq!(None, SetCookie::parse("=value; Path=/"));
+        assert!(UrlResponse::new(
+            "https://www.example.com/",
+            HashMap::new(),
+            "",
+            UrlRequestType::Default,
+            200
+        )
+        .get_set_cookies()
+        .is_empty());
+    }
 }
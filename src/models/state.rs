@@ -0,0 +1,153 @@
+//! The state of a scan of several targets
+//!
+//! With `--state-file`, the targets scanned without error are recorded as
+//! soon as they are finished, along with their findings. If the scan is
+//! interrupted, running it again with the same state file skips them and
+//! outputs their findings with the ones of the other targets. The targets
+//! having scan errors aren't recorded, so they're scanned again.
+//!
+//! The state file is kept after the scan, it has to be removed to scan
+//! all the targets again.
+
+use super::Finding;
+use log::{debug, trace};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+
+/// A target whose scan is finished
+#[derive(Deserialize, Serialize)]
+struct CompletedTarget {
+    /// The URL of the target
+    url: String,
+    /// The findings of the target
+    findings: Vec<Finding>,
+}
+
+/// The state of a scan, stored in a JSON file
+#[derive(Default, Deserialize, Serialize)]
+pub struct ScanState {
+    /// The path of the state file
+    #[serde(skip)]
+    path: String,
+    /// The targets whose scan is finished, in the order of completion
+    completed: Vec<CompletedTarget>,
+}
+
+impl ScanState {
+    /// Reads the state file at the given path.
+    /// The state is empty if the file doesn't exist yet.
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        trace!("Running ScanState::from_file() on {}", path);
+        let mut state: Self = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("Invalid state file {}: {}", path, e))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(format!("Unable to read the state file {}: {}", path, e)),
+        };
+        debug!("{} targets already scanned", state.completed.len());
+        state.path = path.to_string();
+        // The full evidence isn't stored, the stored one is kept as is
+        for finding in state
+            .completed
+            .iter_mut()
+            .flat_map(|completed_target| completed_target.findings.iter_mut())
+        {
+            finding.full_evidence = finding.evidence.clone();
+        }
+        Ok(state)
+    }
+
+    /// Checks whether the scan of the target is finished.
+    pub fn is_completed(&self, url: &str) -> bool {
+        self.completed
+            .iter()
+            .any(|completed_target| completed_target.url == url)
+    }
+
+    /// Records the target as finished, and writes the state file.
+    /// The file is replaced at once, so an interruption never leaves it
+    /// half written.
+    pub fn complete(&mut self, url: &str, findings: Vec<Finding>) -> Result<(), String> {
+        trace!("Running ScanState::complete() for {}", url);
+        self.completed.push(CompletedTarget {
+            url: url.to_string(),
+            findings,
+        });
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        let temporary_path = format!("{}.tmp", self.path);
+        fs::write(&temporary_path, json)
+            .and_then(|_| fs::rename(&temporary_path, &self.path))
+            .map_err(|e| format!("Unable to write the state file {}: {}", self.path, e))
+    }
+
+    /// Returns the number of finished targets.
+    pub fn completed_count(&self) -> usize {
+        self.completed.len()
+    }
+
+    /// Returns the findings of the finished targets, split between the
+    /// first `count` targets finished and the next ones.
+    pub fn into_findings(self, count: usize) -> (Vec<Finding>, Vec<Finding>) {
+        let mut completed = self.completed;
+        let next_completed = completed.split_off(count.min(completed.len()));
+        let into_findings = |completed: Vec<CompletedTarget>| -> Vec<Finding> {
+            completed
+                .into_iter()
+                .flat_map(|completed_target| completed_target.findings)
+                .collect()
+        };
+        (into_findings(completed), into_findings(next_completed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::technology::Technology;
+
+    #[test]
+    fn state_is_resumed() {
+        let path = std::env::temp_dir()
+            .join(format!("sanca-state-{}.json", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let mut state = ScanState::from_file(&path).unwrap();
+        assert!(!state.is_completed("https://www.example.com/"));
+        let finding = Finding::new(
+            Technology::Nginx,
+            Some("1.25.3"),
+            "nginx/1.25.3",
+            "Nginx 1.25.3 has been identified",
+            Some("https://www.example.com/"),
+        );
+        state
+            .complete("https://www.example.com/", vec![finding])
+            .unwrap();
+        state
+            .complete("https://blog.example.com/", Vec::new())
+            .unwrap();
+
+        let state = ScanState::from_file(&path).unwrap();
+        assert!(state.is_completed("https://www.example.com/"));
+        assert!(state.is_completed("https://blog.example.com/"));
+        assert!(!state.is_completed("https://shop.example.com/"));
+        assert_eq!(2, state.completed_count());
+        let (findings, next_findings) = state.into_findings(1);
+        assert!(next_findings.is_empty());
+        assert_eq!(1, findings.len());
+        assert_eq!(Technology::Nginx, findings[0].technology);
+        assert_eq!("nginx/1.25.3", findings[0].full_evidence);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn from_file_reports_errors() {
+        let path = std::env::temp_dir().join(format!("sanca-state-{}.txt", std::process::id()));
+        fs::write(&path, "not json").unwrap();
+        let result = ScanState::from_file(&path.to_string_lossy());
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err_and(|e| e.starts_with("Invalid state file")));
+    }
+}
@@ -0,0 +1,279 @@
+//! The targets of a scan
+//!
+//! A scan can be run on several targets listed in a file given with
+//! `--targets-file`, one per line: a URL for the HTTP scans, a host:port
+//! for the TCP and UDP scans. Each target can be followed by the
+//! technologies to check for it (its profile), separated by a `|`.
+//! The empty lines and the lines starting with `#` are ignored.
+//!
+//! Example of targets file:
+//!
+//! ```text
+//! # The shop only needs a few checks
+//! https://shop.example.com | prestashop,php
+//! https://blog.example.com/ | wordpress,wp-plugins
+//! https://www.example.com
+//! ```
+//!
+//! Example of targets file for a TCP scan:
+//!
+//! ```text
+//! mail.example.com:25 | exim
+//! 203.0.113.10:22
+//! [2001:db8::7]:21
+//! ```
+
+use super::technology::Technology;
+use log::trace;
+use std::collections::HashSet;
+use std::fs;
+
+/// Represents a target of a scan
+#[derive(Clone, Debug, PartialEq)]
+pub struct Target {
+    /// The URL of the target, or its host:port for a TCP or UDP scan
+    pub url: String,
+    /// The technologies to check for this target.
+    /// None to check the technologies of the scan.
+    pub technologies: Option<Vec<Technology>>,
+}
+
+impl Target {
+    /// Creates a new Target
+    pub fn new(url: &str, technologies: Option<Vec<Technology>>) -> Self {
+        Target {
+            url: url.to_string(),
+            technologies,
+        }
+    }
+
+    /// Creates a new Target of a TCP or UDP scan.
+    /// The IPv6 addresses are put between brackets.
+    pub fn from_host_port(ip_hostname: &str, port: u16) -> Self {
+        let url = if ip_hostname.contains(':') && !ip_hostname.starts_with('[') {
+            format!("[{}]:{}", ip_hostname, port)
+        } else {
+            format!("{}:{}", ip_hostname, port)
+        };
+        Self::new(&url, None)
+    }
+
+    /// Returns the host and the port of a target of a TCP or UDP scan.
+    /// The brackets of the IPv6 addresses are removed.
+    pub fn get_host_port(&self) -> Result<(String, u16), String> {
+        let invalid = || format!("{} is not a host:port", self.url);
+        let (host, port) = self.url.rsplit_once(':').ok_or_else(invalid)?;
+        let port = port.parse::<u16>().map_err(|_| invalid())?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.is_empty() || host.contains('/') {
+            return Err(invalid());
+        }
+        Ok((host.to_string(), port))
+    }
+
+    /// Returns the host of the target, a URL or a host:port, without its
+    /// scheme, its port and its path.
+    /// The brackets of the IPv6 addresses are removed.
+    pub fn get_host(&self) -> String {
+        let rest = self
+            .url
+            .split_once("://")
+            .map_or(self.url.as_str(), |(_, rest)| rest);
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = match authority.rsplit_once(':') {
+            // A bare IPv6 address has no port
+            Some((host, port))
+                if port.chars().all(|c| c.is_ascii_digit())
+                    && (!host.contains(':') || host.ends_with(']')) =>
+            {
+                host
+            }
+            _ => authority,
+        };
+        host.trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_lowercase()
+    }
+
+    /// Reads the targets file at the given path
+    pub fn from_file(path: &str) -> Result<Vec<Self>, String> {
+        trace!("Running Target::from_file() on {}", path);
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read the targets file {}: {}", path, e))?;
+        Self::parse(&content).map_err(|e| format!("Invalid targets file {}: {}", path, e))
+    }
+
+    /// Parses the content of a targets file.
+    /// The technologies of a profile are given as on the command line,
+    /// the groups (e.g. wp-plugins) are accepted too.
+    pub fn parse(content: &str) -> Result<Vec<Self>, String> {
+        let mut targets = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (url, profile) = match line.split_once('|') {
+                Some((url, profile)) => (url.trim(), Some(profile)),
+                None => (line, None),
+            };
+            if url.is_empty() {
+                return Err(format!("Line {}: the URL is missing", index + 1));
+            }
+
+            let technologies = match profile {
+                Some(profile) => {
+                    let mut technologies = Vec::new();
+                    for selector in profile.split(',').map(str::trim) {
+                        let selected = Technology::from_selector(selector)
+                            .map_err(|e| format!("Line {}: {}", index + 1, e))?;
+                        for technology in selected {
+                            if !technologies.contains(&technology) {
+                                technologies.push(technology);
+                            }
+                        }
+                    }
+                    Some(technologies)
+                }
+                None => None,
+            };
+            targets.push(Self::new(url, technologies));
+        }
+        Ok(targets)
+    }
+}
+
+/// Parses the ports given on the command line: a port, or a list of
+/// ports and ranges separated by commas (e.g. 21,22,80-90).
+/// The ports are returned once each, in the given order.
+pub fn parse_ports(spec: &str) -> Result<Vec<u16>, String> {
+    let parse_port = |port: &str| match port.trim().parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(format!("Invalid port {} in {}", port.trim(), spec)),
+    };
+    let mut ports = Vec::new();
+    let mut seen = HashSet::new();
+    for part in spec.split(',') {
+        let range = match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (parse_port(start)?, parse_port(end)?);
+                if start > end {
+                    return Err(format!(
+                        "Invalid port range {} in {}, the start is after the end",
+                        part.trim(),
+                        spec
+                    ));
+                }
+                start..=end
+            }
+            None => {
+                let port = parse_port(part)?;
+                port..=port
+            }
+        };
+        ports.extend(range.filter(|port| seen.insert(*port)));
+    }
+    Ok(ports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ports_works() {
+        assert_eq!(Ok(vec![22]), parse_ports("22"));
+        assert_eq!(Ok(vec![21, 22, 25]), parse_ports("21,22, 25"));
+        assert_eq!(Ok(vec![80, 81, 82, 22]), parse_ports("80-82,22"));
+        assert_eq!(Ok(vec![8080]), parse_ports("8080-8080"));
+        // The ports given twice are scanned once
+        assert_eq!(Ok(vec![21, 22, 23]), parse_ports("21-23,22"));
+
+        assert!(parse_ports("90-80").is_err());
+        assert!(parse_ports("").is_err());
+        assert!(parse_ports("22,").is_err());
+        assert!(parse_ports("0").is_err());
+        assert!(parse_ports("65536").is_err());
+        assert!(parse_ports("80-").is_err());
+        assert!(parse_ports("ssh").is_err());
+    }
+
+    #[test]
+    fn parse_works() {
+        let content = "# The targets\n\
+            \n\
+            https://shop.example.com | prestashop, PHP\n\
+            https://blog.example.com/|wordpress,wp-themes\n  \
+            https://www.example.com  \n";
+        let targets = Target::parse(content).unwrap();
+        assert_eq!(3, targets.len());
+        assert_eq!(
+            Target::new(
+                "https://shop.example.com",
+                Some(vec![Technology::Prestashop, Technology::PHP])
+            ),
+            targets[0]
+        );
+        assert_eq!("https://blog.example.com/", targets[1].url);
+        let technologies = targets[1].technologies.as_ref().unwrap();
+        assert_eq!(Technology::WordPress, technologies[0]);
+        assert!(technologies.contains(&Technology::WPTDivi));
+        assert_eq!(Target::new("https://www.example.com", None), targets[2]);
+    }
+
+    #[test]
+    fn parse_reports_errors() {
+        assert!(Target::parse("https://www.example.com | notatechnology")
+            .unwrap_err()
+            .contains("Line 1"));
+        assert!(Target::parse("# Comment\n | php")
+            .unwrap_err()
+            .contains("Line 2: the URL is missing"));
+        assert!(Target::from_file("/sanca/does/not/exist.txt").is_err());
+    }
+
+    #[test]
+    fn get_host_port_works() {
+        let targets = Target::parse("mail.example.com:25\n[2001:db8::7]:21\n").unwrap();
+        assert_eq!(
+            Ok(("mail.example.com".to_string(), 25)),
+            targets[0].get_host_port()
+        );
+        assert_eq!(
+            Ok(("2001:db8::7".to_string(), 21)),
+            targets[1].get_host_port()
+        );
+        assert_eq!(
+            "[2001:db8::7]:21",
+            Target::from_host_port("2001:db8::7", 21).url
+        );
+        assert_eq!(
+            Ok(("203.0.113.10".to_string(), 22)),
+            Target::from_host_port("203.0.113.10", 22).get_host_port()
+        );
+        assert!(Target::new("https://www.example.com/", None)
+            .get_host_port()
+            .is_err());
+        assert!(Target::new("www.example.com", None)
+            .get_host_port()
+            .is_err());
+        assert!(Target::new("www.example.com:99999", None)
+            .get_host_port()
+            .is_err());
+    }
+
+    #[test]
+    fn get_host_works() {
+        let hosts = [
+            ("mail.example.com:25", "mail.example.com"),
+            ("[2001:db8::7]:21", "2001:db8::7"),
+            ("2001:db8::7", "2001:db8::7"),
+            ("https://WWW.example.com:8443/shop/?a=b", "www.example.com"),
+            ("http://[2001:db8::7]/", "2001:db8::7"),
+            ("https://www.example.com", "www.example.com"),
+        ];
+        for (url, host) in hosts {
+            assert_eq!(host, Target::new(url, None).get_host(), "{}", url);
+        }
+    }
+}
@@ -10,16 +10,20 @@
 use super::reqres::UrlRequest;
 use super::ScanType;
 use clap::{builder::PossibleValue, ValueEnum};
-use serde::Serialize;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::string::ToString;
 
+/// The groups of technologies, to select several technologies at once
+pub const TECHNOLOGY_GROUPS: &[&str] = &["wp-plugins", "wp-themes", "js-libraries", "os"];
+
 /// An enumeration to represent the technologies that Sanca can tried to identify.
 /// In practice it is useful mainly for the web technologies to send only
 /// HTTP requests needed to identify the given technologies.
 /// As an example, it's not needed to send a request at /phpinfo.php
 /// if we want to identify only the JavaScript libraries.
-#[derive(Clone, Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Technology {
     Dovecot,
     Exim,
@@ -108,6 +112,40 @@ pub enum Technology {
     WPPAdvancedCustomFields,
     WPPHealthCheck,
     JQueryMobile,
+    Confluence,
+    Matomo,
+    Moodle,
+    MSSQL,
+    Snmp,
+    Telnet,
+    GraphQL,
+    Infrastructure,
+    Hint,
+    Lighttpd,
+    Caddy,
+    HAProxy,
+    AspNet,
+    Ntp,
+    DnsServer,
+    Bind,
+    PowerDNS,
+    PowerDNSRecursor,
+    Dnsmasq,
+    Magento,
+    TlsCertificate,
+    IIS,
+    Waf,
+    Cloudflare,
+    Akamai,
+    Sucuri,
+    Laravel,
+    CodeIgniter,
+    Express,
+    JavaServlet,
+    Typo3Extension,
+    WPTheme,
+    Misconfiguration,
+    DirectoryListing,
 }
 
 impl Technology {
@@ -117,13 +155,183 @@ impl Technology {
     pub fn get_scans(&self) -> Vec<ScanType> {
         match self {
             Self::Dovecot | Self::Exim => vec![ScanType::Tcp],
-            Self::MariaDB | Self::MySQL => vec![ScanType::Tcp],
+            Self::MariaDB | Self::MSSQL | Self::MySQL => vec![ScanType::Tcp],
+            Self::Telnet => vec![ScanType::Tcp],
             Self::OpenSSH | Self::ProFTPD | Self::PureFTPd => vec![ScanType::Tcp],
             Self::OS => vec![ScanType::Tcp, ScanType::Http],
+            Self::Ntp | Self::Snmp => vec![ScanType::Udp],
+            Self::DnsServer
+            | Self::Bind
+            | Self::PowerDNS
+            | Self::PowerDNSRecursor
+            | Self::Dnsmasq => vec![ScanType::Udp],
             _ => vec![ScanType::Http],
         }
     }
 
+    /// Returns the name of the npm package of the technology, for the
+    /// JavaScript libraries.
+    pub fn get_npm_package(&self) -> Option<&'static str> {
+        match self {
+            Self::JQuery => Some("jquery"),
+            Self::JQueryUI => Some("jquery-ui"),
+            Self::Lodash => Some("lodash"),
+            Self::Bootstrap => Some("bootstrap"),
+            Self::AngularJS => Some("angular"),
+            Self::Angular => Some("@angular/core"),
+            Self::ReactJS => Some("react"),
+            Self::Handlebars => Some("handlebars"),
+            Self::Knockout => Some("knockout"),
+            Self::TinyMCE => Some("tinymce"),
+            Self::Highcharts => Some("highcharts"),
+            Self::Gsap => Some("gsap"),
+            _ => None,
+        }
+    }
+
+    /// Returns the ecosystem and the name of the package of the technology
+    /// on OSV.dev: the npm packages of the JavaScript libraries, and the
+    /// Packagist ones of the PHP applications.
+    pub fn get_osv_package(&self) -> Option<(&'static str, &'static str)> {
+        if let Some(package) = self.get_npm_package() {
+            return Some(("npm", package));
+        }
+        match self {
+            Self::Drupal => Some(("Packagist", "drupal/core")),
+            Self::Symfony => Some(("Packagist", "symfony/symfony")),
+            Self::Typo3 => Some(("Packagist", "typo3/cms-core")),
+            Self::PhpMyAdmin => Some(("Packagist", "phpmyadmin/phpmyadmin")),
+            Self::Moodle => Some(("Packagist", "moodle/moodle")),
+            Self::Prestashop => Some(("Packagist", "prestashop/prestashop")),
+            Self::Magento => Some(("Packagist", "magento/community-edition")),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the product on endoflife.date, for the
+    /// technologies in the bundled end-of-life dataset.
+    pub fn get_eol_product(&self) -> Option<&'static str> {
+        match self {
+            Self::PHP => Some("php"),
+            Self::WordPress => Some("wordpress"),
+            Self::OpenSSL => Some("openssl"),
+            Self::Httpd => Some("apache-http-server"),
+            Self::Nginx => Some("nginx"),
+            Self::Tomcat => Some("tomcat"),
+            Self::Drupal => Some("drupal"),
+            Self::JQuery => Some("jquery"),
+            Self::AngularJS => Some("angularjs"),
+            Self::Angular => Some("angular"),
+            Self::Bootstrap => Some("bootstrap"),
+            Self::MySQL => Some("mysql"),
+            Self::MariaDB => Some("mariadb"),
+            Self::Ubuntu => Some("ubuntu"),
+            Self::Debian => Some("debian"),
+            Self::CentOS => Some("centos"),
+            _ => None,
+        }
+    }
+
+    /// Checks whether the technology is a WordPress plugin.
+    pub fn is_wordpress_plugin(&self) -> bool {
+        matches!(
+            self,
+            Self::WPPYoastSEO
+                | Self::WPPRevSlider
+                | Self::WPPJSComposer
+                | Self::WPPContactForm
+                | Self::WPPElementor
+                | Self::WPPElementsReadyLite
+                | Self::WPPGTranslate
+                | Self::WPPWooCommerce
+                | Self::WPPClassicEditor
+                | Self::WPPAkismet
+                | Self::WPPWpformsLite
+                | Self::WPPAllInOneWpMigration
+                | Self::WPPReallySimpleSSL
+                | Self::WPPJetpack
+                | Self::WPPLiteSpeedCache
+                | Self::WPPAllInOneSEO
+                | Self::WPPWordfence
+                | Self::WPPWpMailSmtp
+                | Self::WPPMc4wp
+                | Self::WPPSpectra
+                | Self::WPPLayerSlider
+                | Self::WPPWpMembers
+                | Self::WPPForminator
+                | Self::WPPWpSuperCache
+                | Self::WPPEmailSubscribers
+                | Self::WPPBetterSearchReplace
+                | Self::WPPAdvancedCustomFields
+                | Self::WPPHealthCheck
+        )
+    }
+
+    /// Checks whether the technology is a JavaScript library.
+    pub fn is_javascript_library(&self) -> bool {
+        self.get_npm_package().is_some() || matches!(self, Self::CKEditor | Self::JQueryMobile)
+    }
+
+    /// Checks whether the technology belongs to the group.
+    /// The groups are listed in [`TECHNOLOGY_GROUPS`].
+    pub fn is_in_group(&self, group: &str) -> bool {
+        match group {
+            "wp-plugins" => self.is_wordpress_plugin(),
+            "wp-themes" => matches!(self, Self::WPTDivi),
+            "js-libraries" => self.is_javascript_library(),
+            "os" => self.is_os(),
+            _ => false,
+        }
+    }
+
+    /// Returns the technologies selected by the selector, which is either
+    /// the name of a technology (as given with -t) or a group.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sanca_software::models::technology::Technology;
+    /// assert_eq!(vec![Technology::PHP], Technology::from_selector("php").unwrap());
+    /// assert!(Technology::from_selector("wp-plugins").unwrap().contains(&Technology::WPPAkismet));
+    /// assert!(Technology::from_selector("notatechnology").is_err());
+    /// ```
+    pub fn from_selector(selector: &str) -> Result<Vec<Self>, String> {
+        if TECHNOLOGY_GROUPS.contains(&selector) {
+            return Ok(Self::value_variants()
+                .iter()
+                .filter(|technology| technology.is_in_group(selector))
+                .cloned()
+                .collect());
+        }
+        Self::from_str(selector, true)
+            .map(|technology| vec![technology])
+            .map_err(|_| {
+                format!(
+                    "Invalid technology or group: {}. The groups are {}",
+                    selector,
+                    TECHNOLOGY_GROUPS.join(", ")
+                )
+            })
+    }
+
+    /// Checks whether the technology is an OS.
+    pub fn is_os(&self) -> bool {
+        matches!(
+            self,
+            Self::OS
+                | Self::Ubuntu
+                | Self::Debian
+                | Self::CentOS
+                | Self::Fedora
+                | Self::Unix
+                | Self::OracleLinux
+                | Self::FreeBSD
+                | Self::OpenBSD
+                | Self::NetBSD
+                | Self::AlmaLinux
+        )
+    }
+
     /// Returns the CPE part, vendor and product as a tuple.
     /// Example: ("a", "jquery", "jquery")
     pub fn get_cpe_part_vendor_product(&self) -> (String, String, String) {
@@ -436,6 +644,108 @@ impl Technology {
                 "jquery".to_string(),
                 "jquery_mobile".to_string(),
             ),
+            Self::Confluence => (
+                "a".to_string(),
+                "atlassian".to_string(),
+                "confluence".to_string(),
+            ),
+            Self::Matomo => ("a".to_string(), "matomo".to_string(), "matomo".to_string()),
+            Self::Moodle => ("a".to_string(), "moodle".to_string(), "moodle".to_string()),
+            Self::MSSQL => (
+                "a".to_string(),
+                "microsoft".to_string(),
+                "sql_server".to_string(),
+            ),
+            Self::Snmp => (
+                "a".to_string(),
+                "net-snmp".to_string(),
+                "net-snmp".to_string(),
+            ),
+            Self::Telnet => ("a".to_string(), "telnet".to_string(), "telnet".to_string()),
+            Self::GraphQL => (
+                "a".to_string(),
+                "graphql".to_string(),
+                "graphql".to_string(),
+            ),
+            Self::Infrastructure => ("".to_string(), "".to_string(), "".to_string()),
+            Self::Hint => ("".to_string(), "".to_string(), "".to_string()),
+            Self::Lighttpd => (
+                "a".to_string(),
+                "lighttpd".to_string(),
+                "lighttpd".to_string(),
+            ),
+            Self::Caddy => (
+                "a".to_string(),
+                "caddyserver".to_string(),
+                "caddy".to_string(),
+            ),
+            Self::HAProxy => (
+                "a".to_string(),
+                "haproxy".to_string(),
+                "haproxy".to_string(),
+            ),
+            Self::AspNet => (
+                "a".to_string(),
+                "microsoft".to_string(),
+                "asp.net".to_string(),
+            ),
+            Self::Ntp => ("a".to_string(), "ntp".to_string(), "ntp".to_string()),
+            Self::DnsServer => ("".to_string(), "".to_string(), "".to_string()),
+            Self::Bind => ("a".to_string(), "isc".to_string(), "bind".to_string()),
+            Self::PowerDNS => (
+                "a".to_string(),
+                "powerdns".to_string(),
+                "authoritative".to_string(),
+            ),
+            Self::PowerDNSRecursor => (
+                "a".to_string(),
+                "powerdns".to_string(),
+                "recursor".to_string(),
+            ),
+            Self::Dnsmasq => (
+                "a".to_string(),
+                "thekelleys".to_string(),
+                "dnsmasq".to_string(),
+            ),
+            Self::Magento => (
+                "a".to_string(),
+                "magento".to_string(),
+                "magento".to_string(),
+            ),
+            // The certificates have no CPE
+            Self::TlsCertificate => ("".to_string(), "".to_string(), "".to_string()),
+            Self::IIS => (
+                "a".to_string(),
+                "microsoft".to_string(),
+                "internet_information_services".to_string(),
+            ),
+            // The WAF and CDN services have no CPE
+            Self::Waf | Self::Cloudflare | Self::Akamai | Self::Sucuri => {
+                ("".to_string(), "".to_string(), "".to_string())
+            }
+            Self::Laravel => (
+                "a".to_string(),
+                "laravel".to_string(),
+                "laravel".to_string(),
+            ),
+            Self::CodeIgniter => (
+                "a".to_string(),
+                "codeigniter".to_string(),
+                "codeigniter".to_string(),
+            ),
+            Self::Express => (
+                "a".to_string(),
+                "expressjs".to_string(),
+                "express".to_string(),
+            ),
+            // Several products implement the servlets (Tomcat, Jetty...)
+            Self::JavaServlet => ("".to_string(), "".to_string(), "".to_string()),
+            // The extensions have no common CPE
+            Self::Typo3Extension => ("".to_string(), "".to_string(), "".to_string()),
+            // The themes without a checker of their own have no CPE
+            Self::WPTheme => ("".to_string(), "".to_string(), "".to_string()),
+            Self::Misconfiguration => ("".to_string(), "".to_string(), "".to_string()),
+            Self::DirectoryListing => ("".to_string(), "".to_string(), "".to_string()),
         }
     }
 
@@ -474,7 +784,13 @@ impl Technology {
                     UrlRequest::from_path(main_url, "_profiler/phpinfo", false),
                 ]
             }
-            Self::Httpd | Self::Nginx | Self::OpenSSL => {
+            Self::Httpd
+            | Self::Nginx
+            | Self::OpenSSL
+            | Self::Lighttpd
+            | Self::Caddy
+            | Self::HAProxy
+            | Self::IIS => {
                 vec![
                     UrlRequest::new(main_url, false),
                     UrlRequest::from_path(main_url, "/pageNotFoundNotFound", false),
@@ -486,6 +802,9 @@ impl Technology {
                     UrlRequest::from_path(main_url, "/pageNotFoundNotFound", false),
                     UrlRequest::from_path(main_url, "/..;/..;/", false),
                     UrlRequest::from_path(main_url, "/..;/status.html", false),
+                    UrlRequest::from_path(main_url, "/manager/html", false),
+                    UrlRequest::from_path(main_url, "/host-manager/html", false),
+                    UrlRequest::from_path(main_url, "/examples/", false),
                 ]
             }
             Self::PhpMyAdmin => {
@@ -500,20 +819,41 @@ impl Technology {
                 ]
             }
             Self::Typo3 => {
-                vec![
+                let mut url_requests = vec![
+                    UrlRequest::new(main_url, false),
                     UrlRequest::from_path(main_url, "typo3/sysext/install/composer.json", false),
                     UrlRequest::from_path(
                         main_url,
                         "typo3/sysext/linkvalidator/composer.json",
                         false,
                     ),
-                ]
+                ];
+                // The most installed third-party extensions
+                for extension in ["news", "powermail", "mask", "container", "gridelements"] {
+                    url_requests.push(UrlRequest::from_path(
+                        main_url,
+                        &format!("typo3conf/ext/{}/composer.json", extension),
+                        false,
+                    ));
+                }
+                url_requests
             }
             Self::WordPress => {
                 vec![
-                    UrlRequest::new(main_url, false),
+                    // The stylesheets give the theme and its version
+                    UrlRequest::new(main_url, false).with_fetch_css(),
                     UrlRequest::from_path(main_url, "wp-admin/install.php", false),
                     UrlRequest::from_path(main_url, "wp-login.php", false),
+                    UrlRequest::from_path(main_url, "feed/", false),
+                    UrlRequest::from_path(main_url, "readme.html", false),
+                    // The REST API and XML-RPC, with the methods it offers
+                    UrlRequest::from_path(main_url, "wp-json/", false),
+                    UrlRequest::from_path(main_url, "xmlrpc.php", false)
+                        .with_method(Method::POST)
+                        .with_body(
+                            "<?xml version=\"1.0\"?><methodCall><methodName>system.listMethods</methodName><params></params></methodCall>",
+                            "text/xml",
+                        ),
                 ]
             }
             Self::Plesk => {
@@ -918,6 +1258,86 @@ impl Technology {
                     ),
                 ]
             }
+            Self::Confluence => {
+                vec![
+                    UrlRequest::new(main_url, false),
+                    UrlRequest::from_path(main_url, "/login.action", false),
+                    UrlRequest::from_path(main_url, "login.action", false),
+                ]
+            }
+            Self::Matomo => {
+                vec![
+                    UrlRequest::from_path(
+                        main_url,
+                        "/index.php?module=API&method=API.getMatomoVersion&format=json",
+                        false,
+                    ),
+                    UrlRequest::from_path(
+                        main_url,
+                        "index.php?module=API&method=API.getMatomoVersion&format=json",
+                        false,
+                    ),
+                    UrlRequest::from_path(main_url, "/CHANGELOG.md", false),
+                    UrlRequest::from_path(main_url, "CHANGELOG.md", false),
+                    UrlRequest::from_path(main_url, "/matomo.js", false),
+                    UrlRequest::from_path(main_url, "matomo.js", false),
+                    UrlRequest::from_path(main_url, "/piwik.js", false),
+                    UrlRequest::from_path(main_url, "piwik.js", false),
+                ]
+            }
+            Self::Moodle => {
+                vec![
+                    UrlRequest::new(main_url, true),
+                    UrlRequest::from_path(main_url, "/login/index.php", false),
+                    UrlRequest::from_path(main_url, "login/index.php", false),
+                    UrlRequest::from_path(main_url, "/lib/upgrade.txt", false),
+                    UrlRequest::from_path(main_url, "lib/upgrade.txt", false),
+                ]
+            }
+            Self::Drupal => {
+                vec![
+                    UrlRequest::new(main_url, false),
+                    // Drupal 7
+                    UrlRequest::from_path(main_url, "CHANGELOG.txt", false),
+                    UrlRequest::from_path(main_url, "/CHANGELOG.txt", false),
+                    // Drupal 8+, until it was removed in 8.8
+                    UrlRequest::from_path(main_url, "core/CHANGELOG.txt", false),
+                    UrlRequest::from_path(main_url, "/core/CHANGELOG.txt", false),
+                ]
+            }
+            Self::Magento => {
+                vec![
+                    // The static assets, the headers and the cookies
+                    UrlRequest::new(main_url, false),
+                    UrlRequest::from_path(main_url, "magento_version", false),
+                    UrlRequest::from_path(main_url, "/magento_version", false),
+                ]
+            }
+            Self::GraphQL => {
+                let mut url_requests = Vec::new();
+                for path in ["/graphql", "/api/graphql", "/v1/graphql"] {
+                    url_requests.push(UrlRequest::from_path(main_url, path, false));
+                    // The introspection query {__schema{queryType{name}}}
+                    url_requests.push(UrlRequest::from_path(
+                        main_url,
+                        &format!("{}?query=%7B__schema%7BqueryType%7Bname%7D%7D%7D", path),
+                        false,
+                    ));
+                }
+                url_requests
+            }
+            // The responses of the other technologies are checked too
+            Self::DirectoryListing => {
+                let mut url_requests = vec![UrlRequest::new(main_url, false)];
+                for path in ["/icons/", "/images/", "/assets/", "/uploads/"] {
+                    url_requests.push(UrlRequest::from_path(main_url, path, false));
+                }
+                url_requests
+            }
+            // The certificate is read by the TLS reader, without request
+            Self::TlsCertificate => Vec::new(),
+            // The version is in the banner of the stylesheets too
+            Self::Bootstrap => vec![UrlRequest::new(main_url, true).with_fetch_css()],
             _ => vec![UrlRequest::new(main_url, true)],
         }
     }
@@ -1007,6 +1427,40 @@ impl Display for Technology {
             Technology::WPPAdvancedCustomFields => "AdvancedCustomFields".to_string(),
             Technology::WPPHealthCheck => "HealthCheck".to_string(),
             Technology::JQueryMobile => "jQueryMobile".to_string(),
+            Technology::Confluence => "Confluence".to_string(),
+            Technology::Matomo => "Matomo".to_string(),
+            Technology::Moodle => "Moodle".to_string(),
+            Technology::MSSQL => "MSSQL".to_string(),
+            Technology::Snmp => "SNMP".to_string(),
+            Technology::Telnet => "Telnet".to_string(),
+            Technology::GraphQL => "GraphQL".to_string(),
+            Technology::Infrastructure => "Infrastructure".to_string(),
+            Technology::Hint => "Technology hint".to_string(),
+            Technology::Lighttpd => "Lighttpd".to_string(),
+            Technology::Caddy => "Caddy".to_string(),
+            Technology::HAProxy => "HAProxy".to_string(),
+            Technology::AspNet => "ASP.NET".to_string(),
+            Technology::Ntp => "NTP".to_string(),
+            Technology::DnsServer => "DNS server".to_string(),
+            Technology::Bind => "BIND".to_string(),
+            Technology::PowerDNS => "PowerDNS Authoritative Server".to_string(),
+            Technology::PowerDNSRecursor => "PowerDNS Recursor".to_string(),
+            Technology::Dnsmasq => "Dnsmasq".to_string(),
+            Technology::Magento => "Magento".to_string(),
+            Technology::TlsCertificate => "TLS certificate".to_string(),
+            Technology::IIS => "Microsoft IIS".to_string(),
+            Technology::Waf => "WAF/CDN".to_string(),
+            Technology::Cloudflare => "Cloudflare".to_string(),
+            Technology::Akamai => "Akamai".to_string(),
+            Technology::Sucuri => "Sucuri".to_string(),
+            Technology::Laravel => "Laravel".to_string(),
+            Technology::CodeIgniter => "CodeIgniter".to_string(),
+            Technology::Express => "Express".to_string(),
+            Technology::JavaServlet => "Java servlet container".to_string(),
+            Technology::Typo3Extension => "TYPO3 extension".to_string(),
+            Technology::WPTheme => "WordPress theme".to_string(),
+            Technology::Misconfiguration => "Misconfiguration".to_string(),
+            Technology::DirectoryListing => "Directory listing".to_string(),
         };
         write!(f, "{}", s)
     }
@@ -1086,6 +1540,29 @@ impl ValueEnum for Technology {
             Technology::WPPAdvancedCustomFields,
             Technology::WPPHealthCheck,
             Technology::JQueryMobile,
+            Technology::Confluence,
+            Technology::Matomo,
+            Technology::Moodle,
+            Technology::MSSQL,
+            Technology::Snmp,
+            Technology::Telnet,
+            Technology::GraphQL,
+            Technology::Infrastructure,
+            Technology::Lighttpd,
+            Technology::Caddy,
+            Technology::HAProxy,
+            Technology::AspNet,
+            Technology::Ntp,
+            Technology::DnsServer,
+            Technology::Magento,
+            Technology::TlsCertificate,
+            Technology::IIS,
+            Technology::Waf,
+            Technology::Laravel,
+            Technology::CodeIgniter,
+            Technology::Express,
+            Technology::JavaServlet,
+            Technology::DirectoryListing,
         ]
     }
 
@@ -1162,6 +1639,29 @@ impl ValueEnum for Technology {
             Technology::WPPAdvancedCustomFields => Some(PossibleValue::new("advancedcustomfields")),
             Technology::WPPHealthCheck => Some(PossibleValue::new("healthcheck")),
             Technology::JQueryMobile => Some(PossibleValue::new("jquerymobile")),
+            Technology::Confluence => Some(PossibleValue::new("confluence")),
+            Technology::Matomo => Some(PossibleValue::new("matomo")),
+            Technology::Moodle => Some(PossibleValue::new("moodle")),
+            Technology::MSSQL => Some(PossibleValue::new("mssql")),
+            Technology::Snmp => Some(PossibleValue::new("snmp")),
+            Technology::Telnet => Some(PossibleValue::new("telnet")),
+            Technology::GraphQL => Some(PossibleValue::new("graphql")),
+            Technology::Infrastructure => Some(PossibleValue::new("infrastructure")),
+            Technology::Lighttpd => Some(PossibleValue::new("lighttpd")),
+            Technology::Caddy => Some(PossibleValue::new("caddy")),
+            Technology::HAProxy => Some(PossibleValue::new("haproxy")),
+            Technology::AspNet => Some(PossibleValue::new("aspnet")),
+            Technology::Ntp => Some(PossibleValue::new("ntp")),
+            Technology::DnsServer => Some(PossibleValue::new("dnsserver")),
+            Technology::Magento => Some(PossibleValue::new("magento")),
+            Technology::TlsCertificate => Some(PossibleValue::new("tls-certificate")),
+            Technology::IIS => Some(PossibleValue::new("iis")),
+            Technology::Waf => Some(PossibleValue::new("waf")),
+            Technology::Laravel => Some(PossibleValue::new("laravel")),
+            Technology::CodeIgniter => Some(PossibleValue::new("codeigniter")),
+            Technology::Express => Some(PossibleValue::new("express")),
+            Technology::JavaServlet => Some(PossibleValue::new("java-servlet")),
+            Technology::DirectoryListing => Some(PossibleValue::new("directory-listing")),
             // Ignore the specific OS since they cannot be given as CLI argument. Use OS instead.
             _ => None,
         }
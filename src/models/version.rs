@@ -0,0 +1,363 @@
+//! Versions of the technologies
+//!
+//! The [`Version`] parses the various formats of versions found by the
+//! checkers (`1.0.2k-fips`, `7.4`, `2~bpo10+1`, `4.5.1.1`, `1:2.4.29`)
+//! and gives them a total ordering.
+//!
+//! The [`VersionRange`] gives the versions affected by a CVE.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+/// The words of the pre-releases, which sort before the release.
+/// Example: 2.0.0-beta < 2.0.0
+const PRE_RELEASE_WORDS: &[&str] = &["alpha", "beta", "dev", "pre", "rc"];
+
+/// A part of a version, the variants are in ascending order.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum VersionPart {
+    /// A ~, which sorts before anything.
+    /// Example: 2.0~rc1 < 2.0
+    Tilde,
+    /// A pre-release word, lowercased.
+    /// Example: the beta in 2.0.0-beta
+    PreRelease(String),
+    /// A number.
+    Number(u64),
+    /// Other letters, lowercased.
+    /// Example: the k in 1.0.2k
+    Text(String),
+}
+
+/// A version, which can be compared to another one.
+///
+/// The ordering follows the Debian one, the most common among the
+/// formats found by Sanca:
+/// - the numbers are compared numerically (1.10 > 1.9)
+/// - the missing parts are zeros (7.4 == 7.4.0)
+/// - the pre-releases come before the zeros (2.0.0-beta < 2.0.0)
+/// - the other letters come after the numbers (1.0.2k > 1.0.2)
+/// - a ~ comes before anything (2~bpo10 < 2)
+/// - the epoch (1:2.4.29) takes precedence over everything else
+///
+/// # Examples
+///
+/// ```rust
+/// use sanca_software::models::version::Version;
+/// assert!(Version::parse("1.0.2k-fips") > Version::parse("1.0.2"));
+/// assert!(Version::parse("2.4.10") > Version::parse("2.4.9"));
+/// assert!(Version::parse("2.0.0-beta") < Version::parse("2.0.0"));
+/// assert_eq!(Version::parse("7.4"), Version::parse("7.4.0"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Version {
+    /// The version as it has been given
+    original: String,
+    /// The epoch, 0 if not given
+    epoch: u64,
+    /// The parts of the version, without the trailing zeros
+    parts: Vec<VersionPart>,
+}
+
+impl Version {
+    /// Parses a version.
+    /// Any string is accepted, the chars other than digits, letters and
+    /// ~ are considered as separators.
+    pub fn parse(version: &str) -> Self {
+        let trimmed = version.trim();
+        // Example: 1:2.4.29
+        let (epoch, rest) = match trimmed.split_once(':') {
+            Some((epoch, rest))
+                if !epoch.is_empty() && epoch.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                (epoch.parse().unwrap_or(u64::MAX), rest)
+            }
+            _ => (0, trimmed),
+        };
+
+        let mut parts = Vec::new();
+        let mut chars = rest.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_ascii_digit() {
+                let mut number = c.to_string();
+                while let Some(next) = chars.next_if(|next| next.is_ascii_digit()) {
+                    number.push(next);
+                }
+                parts.push(VersionPart::Number(number.parse().unwrap_or(u64::MAX)));
+            } else if c.is_alphabetic() {
+                let mut text = c.to_lowercase().to_string();
+                while let Some(next) = chars.next_if(|next| next.is_alphabetic()) {
+                    text.extend(next.to_lowercase());
+                }
+                if PRE_RELEASE_WORDS.contains(&text.as_str()) {
+                    parts.push(VersionPart::PreRelease(text));
+                } else {
+                    parts.push(VersionPart::Text(text));
+                }
+            } else if c == '~' {
+                parts.push(VersionPart::Tilde);
+            }
+        }
+        // 7.4.0 is the same as 7.4
+        while parts.last() == Some(&VersionPart::Number(0)) {
+            parts.pop();
+        }
+
+        Self {
+            original: version.to_string(),
+            epoch,
+            parts,
+        }
+    }
+}
+
+impl Display for Version {
+    /// Formats the version as it has been given
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.original)
+    }
+}
+
+impl Ord for Version {
+    /// Compares the epochs, then the parts one by one.
+    /// A missing part is a zero, whatever it's compared to.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch.cmp(&other.epoch).then_with(|| {
+            let length = self.parts.len().max(other.parts.len());
+            let zero = VersionPart::Number(0);
+            for i in 0..length {
+                let part = self.parts.get(i).unwrap_or(&zero);
+                let other_part = other.parts.get(i).unwrap_or(&zero);
+                let ordering = part.cmp(other_part);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            Ordering::Equal
+        })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    /// Two versions are equal if they are the same once parsed.
+    /// Example: 7.4 == 7.4.0
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl From<&str> for Version {
+    fn from(version: &str) -> Self {
+        Self::parse(version)
+    }
+}
+
+/// The versions affected by a CVE, as given by the CPE matches of the NVD.
+/// Either an exact version, or bounds. A missing bound is not checked.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct VersionRange {
+    /// The exact version, None or * for any version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The first version affected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_including: Option<String>,
+    /// The last version not affected, before the first one affected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_excluding: Option<String>,
+    /// The last version affected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_including: Option<String>,
+    /// The first version fixed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_excluding: Option<String>,
+}
+
+impl VersionRange {
+    /// Checks whether the version is in the range.
+    pub fn contains(&self, version: &str) -> bool {
+        let version = Version::parse(version);
+        // The CPE use * for any version and - for no version
+        if let Some(exact) = self.version.as_deref().filter(|v| *v != "*" && *v != "-") {
+            return version == Version::parse(exact);
+        }
+        let bound = |bound: &Option<String>, accepted: &[Ordering]| {
+            bound
+                .as_deref()
+                .is_none_or(|bound| accepted.contains(&version.cmp(&Version::parse(bound))))
+        };
+        bound(&self.start_including, &[Ordering::Greater, Ordering::Equal])
+            && bound(&self.start_excluding, &[Ordering::Greater])
+            && bound(&self.end_including, &[Ordering::Less, Ordering::Equal])
+            && bound(&self.end_excluding, &[Ordering::Less])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Checks that each version is lower than the next one
+    fn assert_ascending(versions: &[&str]) {
+        for pair in versions.windows(2) {
+            assert!(
+                Version::parse(pair[0]) < Version::parse(pair[1]),
+                "{} should be lower than {}",
+                pair[0],
+                pair[1]
+            );
+            assert!(Version::parse(pair[1]) > Version::parse(pair[0]));
+        }
+    }
+
+    #[test]
+    fn numbers_are_compared_numerically() {
+        assert_ascending(&["1.2", "1.9.2", "1.10.0", "1.12.4", "2", "10.0"]);
+        assert_ascending(&["4.5", "4.5.1", "4.5.1.1", "4.5.1.2", "4.5.2"]);
+        assert_ascending(&["8.9", "9.0", "9.2", "9.5", "10"]);
+    }
+
+    #[test]
+    fn missing_parts_are_zeros() {
+        assert_eq!(Version::parse("7.4"), Version::parse("7.4.0"));
+        assert_eq!(Version::parse("9"), Version::parse("9.0.0.0"));
+        assert_eq!(Version::parse("0"), Version::parse(""));
+        assert_ne!(Version::parse("7.4"), Version::parse("7.4.1"));
+        assert_ne!(Version::parse("1.0"), Version::parse("1.0.2k"));
+    }
+
+    #[test]
+    fn openssl_letters_are_later() {
+        assert_ascending(&[
+            "1.0.2",
+            "1.0.2a",
+            "1.0.2k",
+            "1.0.2k-fips",
+            "1.0.2u",
+            "1.1.0",
+        ]);
+        assert_eq!(Version::parse("1.0.2K"), Version::parse("1.0.2k"));
+    }
+
+    #[test]
+    fn debian_versions_are_handled() {
+        // Backports come before the release
+        assert_ascending(&["2~bpo10+1", "2", "2+deb12u1", "2+deb12u2"]);
+        assert_ascending(&["2.0~rc1", "2.0~rc2", "2.0", "2.0.1"]);
+        // The epoch takes precedence
+        assert_ascending(&["2.4.58", "1:2.4.29", "2:1.0"]);
+        assert_eq!(Version::parse("0:2.4.29"), Version::parse("2.4.29"));
+    }
+
+    #[test]
+    fn pre_releases_are_earlier() {
+        assert_ascending(&[
+            "2.0.0-alpha",
+            "2.0.0-beta",
+            "2.0.0-beta2",
+            "2.0.0-rc1",
+            "2.0.0",
+            "2.0.0a",
+            "2.0.1",
+        ]);
+        assert_ascending(&["1.rc1", "1.0~beta", "1"]);
+        assert_eq!(Version::parse("5.3-RC1"), Version::parse("5.3-rc1"));
+    }
+
+    #[test]
+    fn various_formats_are_parsed() {
+        assert_ascending(&["8.2p1", "8.9p1", "9.2p1"]);
+        assert_ascending(&["10.3.39-MariaDB", "10.11.6-MariaDB-0+deb12u1"]);
+        assert_eq!(Version::parse(" 4.15.1 "), Version::parse("4.15.1"));
+        assert_eq!("1.0.2k-fips", Version::parse("1.0.2k-fips").to_string());
+    }
+
+    #[test]
+    fn ordering_is_total() {
+        let mut versions: Vec<Version> =
+            ["2", "1.0.2k", "2~bpo10+1", "1:0.1", "7.4.0", "1.0.2", "7.4"]
+                .iter()
+                .map(|version| Version::parse(version))
+                .collect();
+        versions.sort();
+        let sorted: Vec<String> = versions.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            vec!["1.0.2", "1.0.2k", "2~bpo10+1", "2", "7.4.0", "7.4", "1:0.1"],
+            sorted
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn ordering_is_transitive(
+            versions in proptest::collection::vec("((0|1|2|k|rc|beta|~)[.+-]?){0,5}", 3)
+        ) {
+            let versions: Vec<Version> = versions.iter().map(|v| Version::parse(v)).collect();
+            for a in &versions {
+                for b in &versions {
+                    prop_assert_eq!(a.cmp(b), b.cmp(a).reverse(), "{} and {}", a, b);
+                    for c in &versions {
+                        if a <= b && b <= c {
+                            prop_assert!(a <= c, "{} <= {} <= {}", a, b, c);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn version_range_bounds_are_checked() {
+        // jQuery before 3.5.0
+        let range = VersionRange {
+            version: Some("*".to_string()),
+            start_including: Some("1.2".to_string()),
+            end_excluding: Some("3.5.0".to_string()),
+            ..Default::default()
+        };
+        assert!(range.contains("1.2.0"));
+        assert!(range.contains("1.12.4"));
+        assert!(range.contains("3.4.1"));
+        assert!(!range.contains("3.5.0"));
+        assert!(!range.contains("1.1.9"));
+
+        let range = VersionRange {
+            start_excluding: Some("2.4.0".to_string()),
+            end_including: Some("2.4.58".to_string()),
+            ..Default::default()
+        };
+        assert!(!range.contains("2.4.0"));
+        assert!(range.contains("2.4.1"));
+        assert!(range.contains("2.4.58"));
+        assert!(!range.contains("2.4.59"));
+        // No bound, any version
+        assert!(VersionRange::default().contains("1.0"));
+    }
+
+    #[test]
+    fn version_range_exact_version_is_checked() {
+        let range = VersionRange {
+            version: Some("1.0.2k".to_string()),
+            // Ignored with an exact version
+            end_excluding: Some("0.1".to_string()),
+            ..Default::default()
+        };
+        assert!(range.contains("1.0.2K"));
+        assert!(!range.contains("1.0.2"));
+        let range = VersionRange {
+            version: Some("-".to_string()),
+            ..Default::default()
+        };
+        assert!(range.contains("7.4"));
+    }
+}
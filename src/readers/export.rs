@@ -0,0 +1,463 @@
+//! Read the banners of a Shodan or Censys export
+//!
+//! Instead of fetching the data from the remote hosts, the [`ExportReader`]
+//! reads the banners and the HTTP responses already collected by Shodan or
+//! Censys, so the checkers can run offline on existing recon data.
+//!
+//! The supported formats are:
+//! - the Shodan host information (`shodan host --format json` or the
+//!   `/shodan/host/{ip}` API), with its banners in `data`
+//! - the Shodan banners, one per line (`shodan download`)
+//! - the Censys hosts (`censys view` or the `/v2/hosts/{ip}` API), with
+//!   their `services`, optionally wrapped in `result`
+//!
+//! Several hosts can be given, either in a JSON array or one per line.
+
+use std::collections::HashMap;
+use std::fs;
+
+use log::{debug, trace, warn};
+use serde_json::Value;
+
+use crate::models::reqres::{UrlRequestType, UrlResponse, SET_COOKIE_SEPARATOR};
+
+/// A service found in an export
+#[derive(Debug, PartialEq)]
+pub enum ExportedService {
+    /// The banner of a TCP service
+    Banner {
+        /// The IP or hostname of the host
+        ip_hostname: String,
+        /// The port of the service
+        port: u16,
+        /// The banner sent by the service
+        banner: Vec<u8>,
+    },
+    /// The response of a HTTP service
+    Http(UrlResponse),
+}
+
+/// A reader used to get the services of a Shodan or Censys export.
+pub struct ExportReader {
+    /// The path of the export
+    path: String,
+}
+
+impl ExportReader {
+    /// Creates a new ExportReader
+    pub fn new(path: &str) -> Self {
+        ExportReader {
+            path: path.to_string(),
+        }
+    }
+
+    /// Reads the services of the export.
+    pub fn read(&self) -> Result<Vec<ExportedService>, String> {
+        trace!("Running ExportReader::read()");
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Unable to read the export {}: {}", self.path, e))?;
+        parse_export(&content)
+    }
+}
+
+/// Parses the content of an export, either a JSON document or a JSON
+/// document per line.
+pub fn parse_export(content: &str) -> Result<Vec<ExportedService>, String> {
+    let values = match serde_json::from_str::<Value>(content) {
+        Ok(value) => vec![value],
+        Err(_) => {
+            let mut values = Vec::new();
+            for (index, line) in content.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let value = serde_json::from_str(line)
+                    .map_err(|e| format!("Line {}: invalid JSON: {}", index + 1, e))?;
+                values.push(value);
+            }
+            values
+        }
+    };
+
+    let mut services = Vec::new();
+    for value in &values {
+        add_services(&mut services, value);
+    }
+    debug!("{} services found in the export", services.len());
+    Ok(services)
+}
+
+/// Adds the services of the JSON value, whatever the format it follows.
+fn add_services(services: &mut Vec<ExportedService>, value: &Value) {
+    if let Some(items) = value.as_array() {
+        for item in items {
+            add_services(services, item);
+        }
+    } else if let Some(result) = value.get("result") {
+        // Censys API response
+        add_services(services, result);
+    } else if let Some(censys_services) = value.get("services").and_then(Value::as_array) {
+        // Censys host
+        let ip = value.get("ip").and_then(Value::as_str).unwrap_or_default();
+        for service in censys_services {
+            services.extend(parse_censys_service(ip, service));
+        }
+    } else if let Some(banners) = value.get("data").and_then(Value::as_array) {
+        // Shodan host, its banners are in data
+        for banner in banners {
+            services.extend(parse_shodan_banner(banner));
+        }
+    } else if value.get("port").is_some() {
+        services.extend(parse_shodan_banner(value));
+    } else {
+        warn!("Unknown item in the export, it's ignored");
+    }
+}
+
+/// Parses a Shodan banner.
+/// The data of the HTTP services is the head of the response, the body
+/// is in http.html.
+fn parse_shodan_banner(banner: &Value) -> Option<ExportedService> {
+    let ip = banner.get("ip_str").and_then(Value::as_str)?;
+    let port = get_port(banner.get("port")?)?;
+    let data = banner
+        .get("data")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if banner.get("transport").and_then(Value::as_str) == Some("udp") {
+        debug!("The UDP service {}:{} is ignored", ip, port);
+        return None;
+    }
+
+    match banner.get("http") {
+        Some(http) => {
+            let (status_code, headers) = parse_response_head(data);
+            let status_code = http
+                .get("status")
+                .and_then(Value::as_u64)
+                .map(|status| status as u16)
+                .unwrap_or(status_code);
+            let body = http.get("html").and_then(Value::as_str).unwrap_or_default();
+            let scheme = if banner.get("ssl").is_some() {
+                "https"
+            } else {
+                "http"
+            };
+            let host = http.get("host").and_then(Value::as_str).unwrap_or(ip);
+            let location = http.get("location").and_then(Value::as_str).unwrap_or("/");
+            let url = format!("{}://{}:{}{}", scheme, host, port, location);
+            Some(ExportedService::Http(UrlResponse::new(
+                &url,
+                headers,
+                body,
+                UrlRequestType::Default,
+                status_code,
+            )))
+        }
+        None => Some(ExportedService::Banner {
+            ip_hostname: ip.to_string(),
+            port,
+            banner: data.as_bytes().to_vec(),
+        }),
+    }
+}
+
+/// Parses a service of a Censys host.
+/// The headers of the HTTP responses are lists of values.
+fn parse_censys_service(ip: &str, service: &Value) -> Option<ExportedService> {
+    let port = get_port(service.get("port")?)?;
+    let transport = service
+        .get("transport_protocol")
+        .and_then(Value::as_str)
+        .unwrap_or("TCP");
+    if !transport.eq_ignore_ascii_case("tcp") {
+        debug!("The {} service {}:{} is ignored", transport, ip, port);
+        return None;
+    }
+
+    match service.get("http").and_then(|http| http.get("response")) {
+        Some(response) => {
+            let mut headers = HashMap::new();
+            if let Some(header_map) = response.get("headers").and_then(Value::as_object) {
+                for (header_name, header_values) in header_map {
+                    // Censys adds its own fields, e.g. _encoding
+                    if header_name.starts_with('_') {
+                        continue;
+                    }
+                    match header_values {
+                        // A header given several times
+                        Value::Array(values) => {
+                            for header_value in values.iter().filter_map(Value::as_str) {
+                                add_header(&mut headers, header_name, header_value);
+                            }
+                        }
+                        Value::String(value) => add_header(&mut headers, header_name, value),
+                        _ => {}
+                    }
+                }
+            }
+            let body = response
+                .get("body")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let status_code = response
+                .get("status_code")
+                .and_then(Value::as_u64)
+                .unwrap_or(0) as u16;
+            let url = service
+                .get("http")
+                .and_then(|http| http.get("request"))
+                .and_then(|request| request.get("uri"))
+                .and_then(Value::as_str)
+                .map(|uri| uri.to_string())
+                .unwrap_or_else(|| {
+                    let scheme = if service.get("tls").is_some() {
+                        "https"
+                    } else {
+                        "http"
+                    };
+                    format!("{}://{}:{}/", scheme, ip, port)
+                });
+            Some(ExportedService::Http(UrlResponse::new(
+                &url,
+                headers,
+                body,
+                UrlRequestType::Default,
+                status_code,
+            )))
+        }
+        None => Some(ExportedService::Banner {
+            ip_hostname: ip.to_string(),
+            port,
+            banner: service
+                .get("banner")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .as_bytes()
+                .to_vec(),
+        }),
+    }
+}
+
+/// Returns the port, given as a number or a string.
+fn get_port(value: &Value) -> Option<u16> {
+    match value {
+        Value::Number(number) => number.as_u64().and_then(|port| u16::try_from(port).ok()),
+        Value::String(port) => port.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Parses the head of a HTTP response (status line and headers).
+/// Returns the status code and the headers.
+fn parse_response_head(head: &str) -> (u16, HashMap<String, String>) {
+    let mut lines = head.lines();
+    // Example: HTTP/1.1 200 OK
+    let status_code = lines
+        .next()
+        .and_then(|status_line| status_line.split_whitespace().nth(1))
+        .and_then(|status_code| status_code.parse().ok())
+        .unwrap_or(0);
+    let mut headers = HashMap::new();
+    for line in lines {
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((header_name, header_value)) = line.split_once(':') {
+            add_header(&mut headers, header_name.trim(), header_value.trim());
+        }
+    }
+    (status_code, headers)
+}
+
+/// Adds a header to the map, the same way as the HttpReader: only the
+/// first letter of the name is in uppercase, and the values of a header
+/// given several times are concatenated. The cookies are kept apart.
+fn add_header(headers: &mut HashMap<String, String>, header_name: &str, header_value: &str) {
+    let mut header_name_text = header_name.to_lowercase();
+    if let Some(first_letter) = header_name_text.get_mut(0..1) {
+        first_letter.make_ascii_uppercase();
+    }
+    match headers.get_mut(&header_name_text) {
+        Some(value) if header_name_text == "Set-cookie" => {
+            value.push(SET_COOKIE_SEPARATOR);
+            value.push_str(header_value);
+        }
+        Some(value) => {
+            value.push_str(", ");
+            value.push_str(header_value);
+        }
+        None => {
+            headers.insert(header_name_text, header_value.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shodan_host_is_parsed() {
+        let content = r#"{
+            "ip_str": "203.0.113.10",
+            "ports": [22, 443, 161],
+            "data": [
+                {
+                    "ip_str": "203.0.113.10",
+                    "port": 22,
+                    "transport": "tcp",
+                    "data": "SSH-2.0-OpenSSH_7.4\r\nKey type: ssh-rsa\n"
+                },
+                {
+                    "ip_str": "203.0.113.10",
+                    "port": 443,
+                    "transport": "tcp",
+                    "data": "HTTP/1.1 200 OK\r\nServer: Apache/2.4.6 (CentOS)\r\nX-Powered-By: PHP/7.2.34\r\nX-Powered-By: PleskLin\r\n\r\n",
+                    "http": {"status": 200, "host": "www.example.com", "location": "/", "html": "<html></html>"},
+                    "ssl": {"versions": ["TLSv1.2"]}
+                },
+                {
+                    "ip_str": "203.0.113.10",
+                    "port": 161,
+                    "transport": "udp",
+                    "data": "SNMP:\n  Versions:\n    1"
+                }
+            ]
+        }"#;
+        let services = parse_export(content).unwrap();
+        assert_eq!(2, services.len());
+        assert_eq!(
+            ExportedService::Banner {
+                ip_hostname: "203.0.113.10".to_string(),
+                port: 22,
+                banner: b"SSH-2.0-OpenSSH_7.4\r\nKey type: ssh-rsa\n".to_vec(),
+            },
+            services[0]
+        );
+        match &services[1] {
+            ExportedService::Http(url_response) => {
+                assert_eq!("https://www.example.com:443/", url_response.url);
+                assert_eq!(200, url_response.status_code);
+                assert_eq!("<html></html>", url_response.body);
+                assert_eq!(
+                    Some(&"PHP/7.2.34, PleskLin".to_string()),
+                    url_response.headers.get("X-powered-by")
+                );
+                assert_eq!(
+                    Some(&"Apache/2.4.6 (CentOS)".to_string()),
+                    url_response.headers.get("Server")
+                );
+            }
+            service => panic!("Unexpected service {:?}", service),
+        }
+    }
+
+    #[test]
+    fn shodan_banners_are_parsed_by_line() {
+        let content = "{\"ip_str\": \"203.0.113.10\", \"port\": 21, \"data\": \"220 ProFTPD 1.3.5b Server (ProFTPD)\\r\\n\"}\n\
+            \n\
+            {\"ip_str\": \"203.0.113.11\", \"port\": 3306, \"transport\": \"tcp\", \"data\": \"5.7.34-log\"}\n";
+        let services = parse_export(content).unwrap();
+        assert_eq!(2, services.len());
+        assert!(parse_export("{\"port\": 21}\n{invalid")
+            .unwrap_err()
+            .starts_with("Line 2:"));
+    }
+
+    #[test]
+    fn censys_host_is_parsed() {
+        let content = r#"{
+            "code": 200,
+            "status": "OK",
+            "result": {
+                "ip": "198.51.100.7",
+                "services": [
+                    {
+                        "port": 21,
+                        "service_name": "FTP",
+                        "transport_protocol": "TCP",
+                        "banner": "220 (vsFTPd 3.0.3)"
+                    },
+                    {
+                        "port": 80,
+                        "service_name": "HTTP",
+                        "transport_protocol": "TCP",
+                        "http": {
+                            "request": {"method": "GET", "uri": "http://198.51.100.7/"},
+                            "response": {
+                                "status_code": 200,
+                                "headers": {"Server": ["nginx/1.18.0"], "_encoding": {"Server": "DISPLAY_UTF8"}},
+                                "body": "<title>Welcome to nginx!</title>"
+                            }
+                        }
+                    },
+                    {"port": "123", "transport_protocol": "UDP", "service_name": "NTP"}
+                ]
+            }
+        }"#;
+        let services = parse_export(content).unwrap();
+        assert_eq!(2, services.len());
+        assert_eq!(
+            ExportedService::Banner {
+                ip_hostname: "198.51.100.7".to_string(),
+                port: 21,
+                banner: b"220 (vsFTPd 3.0.3)".to_vec(),
+            },
+            services[0]
+        );
+        match &services[1] {
+            ExportedService::Http(url_response) => {
+                assert_eq!("http://198.51.100.7/", url_response.url);
+                assert_eq!(1, url_response.headers.len());
+                assert_eq!(
+                    Some(&"nginx/1.18.0".to_string()),
+                    url_response.headers.get("Server")
+                );
+            }
+            service => panic!("Unexpected service {:?}", service),
+        }
+    }
+
+    #[test]
+    fn set_cookie_headers_are_kept_apart() {
+        let content = r#"[
+            {
+                "ip_str": "203.0.113.10",
+                "port": 80,
+                "data": "HTTP/1.1 200 OK\r\nSet-Cookie: lang=en; Expires=Wed, 21 Oct 2026 07:28:00 GMT\r\nSet-Cookie: laravel_session=eyJpdiI7; path=/\r\n\r\n",
+                "http": {"status": 200, "host": "203.0.113.10", "location": "/", "html": ""}
+            },
+            {
+                "ip": "198.51.100.7",
+                "services": [
+                    {
+                        "port": 80,
+                        "transport_protocol": "TCP",
+                        "http": {
+                            "response": {
+                                "status_code": 200,
+                                "headers": {"Set-Cookie": ["lang=en; Expires=Wed, 21 Oct 2026 07:28:00 GMT", "laravel_session=eyJpdiI7; path=/"]}
+                            }
+                        }
+                    }
+                ]
+            }
+        ]"#;
+        let services = parse_export(content).unwrap();
+        assert_eq!(2, services.len());
+        for service in services {
+            match service {
+                ExportedService::Http(url_response) => {
+                    let names: Vec<String> = url_response
+                        .get_set_cookies()
+                        .into_iter()
+                        .map(|cookie| cookie.name)
+                        .collect();
+                    assert_eq!(vec!["lang", "laravel_session"], names);
+                }
+                service => panic!("Unexpected service {:?}", service),
+            }
+        }
+    }
+}
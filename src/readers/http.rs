@@ -4,13 +4,26 @@
 //! technologies being used by the remote host.
 
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use futures::future::join_all;
-use log::{debug, error, info, trace};
+use http_body_util::BodyExt;
+use log::{debug, error, info, trace, warn};
 use regex::Regex;
-use reqwest::Client;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::redirect::Policy;
+use reqwest::{cookie::Jar, Client, ClientBuilder, Method, Proxy, RequestBuilder, Response, Url};
 
-use crate::models::reqres::{UrlRequest, UrlRequestType, UrlResponse};
+use super::http_cache::{CachedResponse, HttpCache};
+use super::resolver::Resolver;
+use crate::models::reqres::{
+    Redirect, UrlRequest, UrlRequestType, UrlResponse, SET_COOKIE_SEPARATOR,
+};
+use crate::models::{ScanError, ScanErrorKind};
+
+/// The delay before the first retry of a request, doubled at each retry
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
 
 /// A reader used to fetch HTTP(S) resources.
 ///
@@ -23,6 +36,25 @@ use crate::models::reqres::{UrlRequest, UrlRequestType, UrlResponse};
 pub struct HttpReader<'a> {
     /// The regex to find URLs
     url_regexes: HashMap<&'a str, Regex>,
+    /// The cache used to send conditional requests, if any
+    http_cache: Option<HttpCache>,
+    /// The proxy to send the requests through, if any
+    proxy: Option<Proxy>,
+    /// The cookies shared by all the requests, to keep a session
+    cookie_jar: Option<Arc<Jar>>,
+    /// The resolver of the hostnames, the one of reqwest is used if none
+    resolver: Option<Resolver>,
+    /// The timeout of each request, none by default
+    timeout: Option<Duration>,
+    /// The headers added to all the requests, e.g. to authenticate
+    headers: HeaderMap,
+    /// The maximum number of redirections followed for each request
+    max_redirects: usize,
+    /// The redirections followed, by URL of the first request
+    redirects: Arc<Mutex<HashMap<String, Vec<Redirect>>>>,
+    /// The number of times a request is sent again after a transient
+    /// failure
+    retries: usize,
 }
 
 impl HttpReader<'_> {
@@ -32,6 +64,13 @@ impl HttpReader<'_> {
             r#"<script[^>]+src\s*=\s*["']?\s*(?P<url>(((?P<protocol>[a-z0-9]+):)?\/\/(?P<hostname>[^\/:]+)(:(?P<port>\d{1,5}))?)?(?P<path>\/?[a-zA-Z0-9\/._ %;=@-]*(?P<extension>\.[a-zA-Z0-9_-]+)?)?(?P<querystring>\?[^#\s'">]*)?(#[^'">\s]*)?)\s*["']?"#
         ).unwrap();
 
+        // Only the links to .css files are stylesheets, the other ones
+        // (e.g. icons) are ignored
+        // Example: <link rel="stylesheet" href="/css/bootstrap.min.css?v=5">
+        let css_regex = Regex::new(
+            r#"<link[^>]+href\s*=\s*["']?\s*(?P<url>(((?P<protocol>[a-z0-9]+):)?\/\/(?P<hostname>[^\/:]+)(:(?P<port>\d{1,5}))?)?(?P<path>\/?[a-zA-Z0-9\/._ %;=@-]*(?P<extension>\.css))(?P<querystring>\?[^#\s'">]*)?(#[^'">\s]*)?)\s*["']?"#
+        ).unwrap();
+
         // Example: Sfjs.loadToolbar('c32ea2')
         let symfony_debug_toolbar_regex = Regex::new(
             r#"<script[^>]*>.*Sfjs.loadToolbar\(['"](?P<profilertoken>[a-f0-9]+)['"]\)"#,
@@ -44,22 +83,234 @@ impl HttpReader<'_> {
         let symfony_old_debug_toolbar_regex =
             Regex::new(r#"Sfjs\.load\(\s*['"]sfwdt(?P<profilertoken>[a-f0-9]+)['"]"#).unwrap();
 
+        // The directory of a WordPress theme, in the URL of any asset
+        // Example: src="https://www.example.com/wp-content/themes/astra/assets/js/frontend.min.js"
+        let wp_theme_regex = Regex::new(
+            r#"["'(=]\s*(?P<url>((https?:)?//[^/"'\s]+)?[^"'\s()<>]*?/wp-content/themes/[a-zA-Z0-9_.-]+/)"#,
+        )
+        .unwrap();
+
         let mut url_regexes = HashMap::new();
         url_regexes.insert("scripts", script_regex);
+        url_regexes.insert("wp_themes", wp_theme_regex);
+        url_regexes.insert("css", css_regex);
         url_regexes.insert("symfony_debug_toolbar", symfony_debug_toolbar_regex);
         url_regexes.insert("symfony_old_debug_toolbar", symfony_old_debug_toolbar_regex);
         HttpReader {
             url_regexes: url_regexes,
+            http_cache: None,
+            proxy: None,
+            cookie_jar: None,
+            resolver: None,
+            timeout: None,
+            headers: HeaderMap::new(),
+            max_redirects: 10,
+            redirects: Arc::new(Mutex::new(HashMap::new())),
+            retries: 2,
+        }
+    }
+
+    /// Parses the URL of a proxy, to be given to with_proxy().
+    /// Returns an error if the URL can't be used as a proxy.
+    pub fn parse_proxy(proxy: &str) -> Result<Proxy, String> {
+        Proxy::all(proxy).map_err(|e| format!("Invalid proxy URL {}: {}", proxy, e))
+    }
+
+    /// Sends the requests through the given proxy.
+    /// With a socks5h:// proxy, the hostnames are resolved by the proxy.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Uses the given cache to store the responses and send conditional
+    /// requests for the URLs already in cache.
+    pub fn with_http_cache(mut self, http_cache: HttpCache) -> Self {
+        self.http_cache = Some(http_cache);
+        self
+    }
+
+    /// Resolves the hostnames with the given resolver, to use its DNS
+    /// server and share its cache.
+    pub fn with_resolver(mut self, resolver: Resolver) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// Gives up the requests not finished within the given duration.
+    /// A zero duration waits indefinitely, as without timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout).filter(|timeout| !timeout.is_zero());
+        self
+    }
+
+    /// Adds the headers to all the requests. They replace the default ones
+    /// with the same name (e.g. User-Agent), the invalid ones are ignored.
+    pub fn with_headers(mut self, headers: &[(String, String)]) -> Self {
+        for (name, value) in headers {
+            match (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                (Ok(name), Ok(value)) => {
+                    self.headers.append(name, value);
+                }
+                _ => warn!("Ignoring the invalid header {}: {}", name, value),
+            }
+        }
+        self
+    }
+
+    /// Follows up to the given number of redirections for each request,
+    /// 10 by default. Beyond, the response of the last redirection is
+    /// returned.
+    pub fn with_max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    /// Sends a request again up to the given number of times, 2 by
+    /// default, when the connection failed or timed out.
+    /// The responses with an error status code aren't retried.
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Keeps the cookies set by the responses and sends them in the next
+    /// requests, so a session opened by [`login`](HttpReader::login) is
+    /// reused by the scan.
+    pub fn with_cookie_jar(mut self) -> Self {
+        self.cookie_jar = Some(Arc::new(Jar::default()));
+        self
+    }
+
+    /// Logs in before the scan, by posting the form data to the login URL.
+    /// The form data is URL-encoded (e.g. user=admin&password=secret).
+    ///
+    /// If a CSRF field is given, the login page is fetched first to
+    /// extract the hidden token of the field, which is added to the form
+    /// data.
+    ///
+    /// The cookie jar MUST be enabled with
+    /// [`with_cookie_jar`](HttpReader::with_cookie_jar) to keep the session.
+    /// Returns the status code of the response to the login request.
+    pub async fn login(
+        &self,
+        login_url: &str,
+        login_data: &str,
+        csrf_field: Option<&str>,
+        user_agent: &str,
+    ) -> Result<u16, String> {
+        trace!("Running HttpReader::login()");
+        if self.cookie_jar.is_none() {
+            warn!("Logging in without a cookie jar, the session won't be kept");
         }
+        let http_client = self
+            .get_client_builder()
+            .http1_only()
+            .build()
+            .expect("Unable to create a HTTP client.");
+
+        let mut form_data = login_data.to_string();
+        if let Some(csrf_field) = csrf_field {
+            debug!("Fetching the CSRF token from {}", login_url);
+            let login_page = http_client
+                .get(login_url)
+                .header("User-Agent", user_agent)
+                .headers(self.headers.clone())
+                .send()
+                .await
+                .map_err(|e| format!("Unable to fetch the login page {}: {:?}", login_url, e))?
+                .text()
+                .await
+                .map_err(|e| format!("Unable to read the login page {}: {:?}", login_url, e))?;
+            let csrf_token = extract_input_value(&login_page, csrf_field).ok_or_else(|| {
+                format!(
+                    "The field {} has not been found in the login page {}",
+                    csrf_field, login_url
+                )
+            })?;
+            debug!("CSRF token found: {}", csrf_token);
+            if !form_data.is_empty() {
+                form_data.push('&');
+            }
+            form_data.push_str(&format!(
+                "{}={}",
+                encode_form_component(csrf_field),
+                encode_form_component(&csrf_token)
+            ));
+        }
+
+        debug!("Logging in on {}", login_url);
+        let response = http_client
+            .post(login_url)
+            .header("User-Agent", user_agent)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .headers(self.headers.clone())
+            .body(form_data)
+            .send()
+            .await
+            .map_err(|e| format!("Unable to log in on {}: {:?}", login_url, e))?;
+        let status_code = response.status().as_u16();
+        info!("Login on {}: status code {}", login_url, status_code);
+        Ok(status_code)
+    }
+
+    /// Requests a random path that doesn't exist, to detect the soft 404:
+    /// a missing page answered with a success status code.
+    /// Returns the response to the random path, and its fingerprint if
+    /// it's a soft 404. None if the request failed.
+    pub async fn probe_soft_404(
+        &self,
+        main_url: &str,
+        user_agent: &str,
+    ) -> Option<(UrlResponse, Option<Soft404Fingerprint>)> {
+        trace!("Running HttpReader::probe_soft_404()");
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.subsec_nanos())
+            .unwrap_or(0);
+        let path = format!("/sanca-{:08x}{:04x}", nanos, std::process::id() & 0xFFFF);
+        let url_request = UrlRequest::from_path(main_url, &path, false);
+        debug!("Probing the soft 404 with {}", url_request.url);
+        let http_client = self
+            .get_client_builder()
+            .http1_only()
+            .build()
+            .expect("Unable to create a HTTP client.");
+        let url_response = self
+            .http_request(
+                &url_request,
+                &http_client,
+                UrlRequestType::Default,
+                user_agent,
+            )
+            .await
+            .ok()?;
+        let fingerprint = Soft404Fingerprint::from_response(&url_response);
+        info!(
+            "Response to the missing page {}: {}, soft 404: {:?}",
+            url_response.url, url_response.status_code, fingerprint
+        );
+        Some((url_response, fingerprint))
     }
 
     /// Reads via HTTP(S)
     /// Sends HTTP requests to each URL to fetch the response, and
     /// optionally requests the JavaScript files found in the response body.
-    pub async fn read(&self, url_requests: &[UrlRequest], user_agent: &str) -> Vec<UrlResponse> {
+    /// Returns the responses, and the errors of the failed requests.
+    pub async fn read(
+        &self,
+        url_requests: &[UrlRequest],
+        user_agent: &str,
+    ) -> (Vec<UrlResponse>, Vec<ScanError>) {
         trace!("Running HttpReader::read()");
-        let http_client = Client::builder()
-            .danger_accept_invalid_certs(true)
+        // HTTP/2 is only used by probe_http2(), to send the same requests
+        // whatever the server supports
+        let http_client = self
+            .get_client_builder()
+            .http1_only()
             .build()
             .expect("Unable to create a HTTP client.");
 
@@ -81,31 +332,170 @@ impl HttpReader<'_> {
         let responses_results = join_all(url_responses_futures).await;
         trace!("All UrlRequests handled");
         let mut responses_clean: Vec<UrlResponse> = Vec::new();
-        // Add each successfull response to the list
-        // responses_results is a Vec<Result<Vec<UrlResponse>, String>>
-        // so we have to unpack each Result and concatenate all the
-        // Vec<UrlResponse>
-        for response_result in responses_results {
-            if response_result.is_ok() {
-                let mut response = response_result.unwrap();
-                responses_clean.append(&mut response);
+        let mut scan_errors: Vec<ScanError> = Vec::new();
+        // responses_results is a Vec<(Vec<UrlResponse>, Vec<ScanError>)>
+        // so we have to concatenate all the responses and all the errors
+        for (mut responses, mut errors) in responses_results {
+            responses_clean.append(&mut responses);
+            scan_errors.append(&mut errors);
+        }
+        (responses_clean, scan_errors)
+    }
+
+    /// Probes the support of HTTP/2 by the server of the URL.
+    /// Over HTTPS, HTTP/2 is offered with ALPN (h2) along with HTTP/1.1.
+    /// Over HTTP, the request is directly sent in HTTP/2 (h2c with prior
+    /// knowledge), so an error means that h2c isn't supported.
+    /// Returns the response with its HTTP version, None if the request
+    /// failed.
+    pub async fn probe_http2(&self, url: &str, user_agent: &str) -> Option<UrlResponse> {
+        trace!("Running HttpReader::probe_http2()");
+        let mut client_builder = self.get_client_builder();
+        if url.to_lowercase().starts_with("http://") {
+            client_builder = client_builder.http2_prior_knowledge();
+        }
+        let http_client = client_builder
+            .build()
+            .expect("Unable to create a HTTP client.");
+
+        debug!("Probing the support of HTTP/2 on {}", url);
+        let response = match http_client
+            .get(url)
+            .header("User-Agent", user_agent)
+            .headers(self.headers.clone())
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                info!("The HTTP/2 probe on {} failed: {:?}", url, e);
+                return None;
             }
+        };
+        let http_version = format!("{:?}", response.version());
+        let status_code = response.status().as_u16();
+        let response_url = response.url().to_string();
+        Some(
+            UrlResponse::new(
+                &response_url,
+                HashMap::new(),
+                "",
+                UrlRequestType::Default,
+                status_code,
+            )
+            .with_http_version(&http_version),
+        )
+    }
+
+    /// Returns the builder of the HTTP clients, with the common settings.
+    fn get_client_builder(&self) -> ClientBuilder {
+        let mut client_builder = Client::builder().danger_accept_invalid_certs(true);
+        if let Some(proxy) = &self.proxy {
+            debug!("Using the proxy {:?}", proxy);
+            client_builder = client_builder.proxy(proxy.clone());
+        }
+        if let Some(cookie_jar) = &self.cookie_jar {
+            client_builder = client_builder.cookie_provider(cookie_jar.clone());
+        }
+        if let Some(resolver) = &self.resolver {
+            client_builder = client_builder.dns_resolver(Arc::new(resolver.clone()));
+        }
+        if let Some(timeout) = self.timeout {
+            client_builder = client_builder.timeout(timeout);
+        }
+        client_builder.redirect(self.get_redirect_policy())
+    }
+
+    /// Returns the redirect policy of the HTTP clients.
+    /// It follows up to max_redirects redirections and records them, so
+    /// the chain can be attached to the response.
+    fn get_redirect_policy(&self) -> Policy {
+        let max_redirects = self.max_redirects;
+        let redirects = self.redirects.clone();
+        Policy::custom(move |attempt| {
+            // The previous URLs start with the one of the request
+            let previous = attempt.previous();
+            if previous.len() > max_redirects {
+                debug!(
+                    "Not following more than {} redirections, to {}",
+                    max_redirects,
+                    attempt.url()
+                );
+                return attempt.stop();
+            }
+            let redirect = Redirect {
+                url: previous[previous.len() - 1].to_string(),
+                status_code: attempt.status().as_u16(),
+                location: attempt.url().to_string(),
+            };
+            debug!(
+                "Following the redirection from {} to {}",
+                redirect.url, redirect.location
+            );
+            let mut redirects = redirects.lock().unwrap();
+            let chain = redirects.entry(previous[0].to_string()).or_default();
+            if previous.len() == 1 {
+                chain.clear();
+            }
+            chain.push(redirect);
+            attempt.follow()
+        })
+    }
+
+    /// Returns the redirections followed to get the response to the URL.
+    fn get_redirects(&self, request_url: &str, response_url: &Url) -> Vec<Redirect> {
+        let request_url = match Url::parse(request_url) {
+            Ok(request_url) => request_url,
+            Err(_) => return Vec::new(),
+        };
+        if &request_url == response_url {
+            return Vec::new();
         }
-        responses_clean
+        self.redirects
+            .lock()
+            .unwrap()
+            .get(request_url.as_str())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the builder of the request, with the headers of the reader.
+    fn get_request_builder(
+        &self,
+        url_request: &UrlRequest,
+        http_client: &Client,
+        user_agent: &str,
+    ) -> RequestBuilder {
+        let mime_type = "text/html,application/javascript,*/*;q=0.8";
+        let mut request_builder = http_client
+            .request(url_request.method.clone(), &url_request.url)
+            .header("User-Agent", user_agent)
+            .header("Accept", mime_type);
+        if let Some(body) = &url_request.body {
+            request_builder = request_builder.body(body.clone());
+        }
+        if let Some(content_type) = &url_request.content_type {
+            request_builder = request_builder.header("Content-Type", content_type);
+        }
+        // The headers given by the user take precedence
+        request_builder.headers(self.headers.clone())
     }
 
     /// Reads one page via HTTP(S)
     /// Sends an HTTP request to the url to fetch the response, and
-    /// optionally requests the JavaScript files found in the response
+    /// optionally requests the JavaScript files and the stylesheets found
+    /// in the response.
+    /// Returns the responses, and the errors of the failed requests.
     async fn read_one_page(
         &self,
         url_request: &UrlRequest,
         http_client: &Client,
         user_agent: &str,
-    ) -> Result<Vec<UrlResponse>, String> {
+    ) -> (Vec<UrlResponse>, Vec<ScanError>) {
         trace!("Running HttpChecker::read_one_page()");
         debug!("Sending HTTP request for URL {}", url_request.url);
         let mut responses: Vec<UrlResponse> = Vec::new();
+        let mut scan_errors: Vec<ScanError> = Vec::new();
         let main_response_result = self
             .http_request(
                 &url_request,
@@ -116,7 +506,7 @@ impl HttpReader<'_> {
             .await;
         if let Err(e) = main_response_result {
             error!("An error occured while reading one page: {:?}", e);
-            return Err(e);
+            return (responses, vec![e]);
         }
 
         trace!("Pushing the HTTP response to the list");
@@ -124,14 +514,51 @@ impl HttpReader<'_> {
         let main_response_body = main_response.body.clone();
         responses.push(main_response);
 
-        let mut next_urls_requests = Vec::new();
+        // The subsequent requests, with the type of their responses
+        let mut next_urls_requests: Vec<(UrlRequest, UrlRequestType)> = Vec::new();
         if url_request.fetch_js {
             debug!("Fetch JS is true for URL {}", url_request.url);
-            next_urls_requests =
+            let js_url_requests =
                 self.extract_urls(&url_request.url, &main_response_body, None, "scripts");
             info!(
                 "The following URLs have been found in the response body: {:?}",
-                next_urls_requests
+                js_url_requests
+            );
+            next_urls_requests.extend(
+                js_url_requests
+                    .into_iter()
+                    .map(|js_url_request| (js_url_request, UrlRequestType::JavaScript)),
+            );
+        }
+        if url_request.fetch_css {
+            debug!("Fetch CSS is true for URL {}", url_request.url);
+            let css_url_requests =
+                self.extract_urls(&url_request.url, &main_response_body, Some(".css"), "css");
+            info!(
+                "The following stylesheets have been found in the response body: {:?}",
+                css_url_requests
+            );
+            // The main stylesheets of the WordPress themes aren't always
+            // linked (e.g. a child theme), their path is a convention
+            let style_url_requests: Vec<UrlRequest> = self
+                .extract_wp_themes(&url_request.url, &main_response_body)
+                .into_iter()
+                .filter(|style_url_request| {
+                    !css_url_requests.iter().any(|css_url_request| {
+                        css_url_request.url.split(['?', '#']).next()
+                            == Some(style_url_request.url.as_str())
+                    })
+                })
+                .collect();
+            debug!(
+                "The following theme stylesheets are requested: {:?}",
+                style_url_requests
+            );
+            next_urls_requests.extend(
+                css_url_requests
+                    .into_iter()
+                    .chain(style_url_requests)
+                    .map(|css_url_request| (css_url_request, UrlRequestType::Css)),
             );
         }
 
@@ -139,38 +566,36 @@ impl HttpReader<'_> {
         // a UrlRequest for the profiler
         let url_requests_symfony = self.extract_symfony(&url_request.url, &main_response_body);
         if url_requests_symfony.is_some() {
-            next_urls_requests.push(url_requests_symfony.unwrap());
+            next_urls_requests.push((url_requests_symfony.unwrap(), UrlRequestType::Default));
         }
 
         // Here we store all the Futures of the http requests
         // They will be handled all together in parallel
-        let url_responses_futures = next_urls_requests.iter().map(|i| {
-            let request_type = if i.fetch_js {
-                UrlRequestType::JavaScript
-            } else {
-                UrlRequestType::Default
-            };
-
-            let response_future = self.http_request(&i, &http_client, request_type, user_agent);
-            response_future
-        });
+        let url_responses_futures =
+            next_urls_requests
+                .iter()
+                .map(|(next_url_request, request_type)| {
+                    self.http_request(
+                        next_url_request,
+                        &http_client,
+                        request_type.clone(),
+                        user_agent,
+                    )
+                });
 
         trace!("Waiting for all the subsequent HTTP requests to be handled");
         // Send all the HTTP requests, and wait for the result
         let responses_results = join_all(url_responses_futures).await;
         trace!("Subsequent HTTP requests handled");
-        // Add each successfull response to the list
-        // responses_results is a Vec<Result<Vec<UrlResponse>, String>>
-        // so we have to unpack each Result and concatenate all the
-        // Vec<UrlResponse>
+        // Add each successfull response to the list, and keep the errors
         for response_result in responses_results {
-            if response_result.is_ok() {
-                let response = response_result.unwrap();
-                responses.push(response);
+            match response_result {
+                Ok(response) => responses.push(response),
+                Err(e) => scan_errors.push(e),
             }
         }
 
-        Ok(responses)
+        (responses, scan_errors)
     }
 
     /// Sends one HTTP request and get the response.
@@ -180,14 +605,27 @@ impl HttpReader<'_> {
         http_client: &Client,
         request_type: UrlRequestType,
         user_agent: &str,
-    ) -> Result<UrlResponse, String> {
+    ) -> Result<UrlResponse, ScanError> {
         trace!("Running HttpReader::http_request()");
-        let mime_type = "text/html,application/javascript,*/*;q=0.8";
-        let response_result = http_client
-            .get(&url_request.url)
-            .header("User-Agent", user_agent)
-            .header("Accept", mime_type)
-            .send()
+        // Only the responses to the GET requests are cached
+        let cacheable = url_request.method == Method::GET;
+        let cached_response = self
+            .http_cache
+            .as_ref()
+            .filter(|_| cacheable)
+            .and_then(|c| c.read(&url_request.url));
+        let mut request_builder = self.get_request_builder(url_request, http_client, user_agent);
+        // Send a conditional request if the response is in cache
+        if let Some(cached) = &cached_response {
+            if let Some(etag) = &cached.etag {
+                request_builder = request_builder.header("If-None-Match", etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request_builder = request_builder.header("If-Modified-Since", last_modified);
+            }
+        }
+        let response_result = self
+            .send_with_retries(request_builder, &url_request.url)
             .await;
 
         if let Err(e) = response_result {
@@ -195,53 +633,111 @@ impl HttpReader<'_> {
                 "An error occured in the HTTP request to {}: {:?}",
                 url_request.url, e
             );
-            return Err(format!(
-                "Error while sending an HTTP request to {}: {:?}",
-                url_request.url, e
-            ));
+            return Err(get_scan_error(&url_request.url, &e));
         }
 
         let response = response_result.unwrap();
         let mut headers: HashMap<String, String> = HashMap::new();
         trace!("Extracting HTTP headers");
-        for (header_name, header_value) in response.headers().iter() {
-            // Only the first letter of the header name is in uppercase
-            // It will avoid struggling with the case later
-            let mut header_name_text = header_name.to_string().to_lowercase();
-            header_name_text
-                .get_mut(0..1)
-                .unwrap()
-                .make_ascii_uppercase();
-
-            // When a header is given several times (e.g. x-powered-by), concatenate
-            if headers.contains_key(&header_name_text) {
-                let concat_header = format!(
-                    "{}, {}",
-                    headers.get(&header_name_text).unwrap(),
-                    header_value.to_str().unwrap_or("").to_string(),
-                );
+        add_headers(&mut headers, response.headers());
 
-                headers.insert(header_name_text, concat_header);
-            } else {
-                headers.insert(
-                    header_name_text,
-                    header_value.to_str().unwrap_or("").to_string(),
-                );
+        let status_code = response.status().as_u16();
+        // The resource didn't change since it has been cached
+        if status_code == 304 {
+            if let Some(cached) = cached_response {
+                debug!("Using the cached response for {}", url_request.url);
+                return Ok(UrlResponse::new(
+                    &cached.url,
+                    cached.headers,
+                    &cached.body,
+                    request_type,
+                    cached.status_code,
+                )
+                .with_redirects(cached.redirects));
             }
         }
 
-        let status_code = response.status().as_u16();
+        let http_version = format!("{:?}", response.version());
         // In case of redirection, the final URL will be stored & printed
         let response_url = response.url().to_string();
-        let body = response.text().await.unwrap_or("".to_string());
+        let redirects = self.get_redirects(&url_request.url, response.url());
+        // The body is collected frame by frame to keep the trailers of
+        // the chunked responses, response.text() would drop them
+        let collected = http::Response::from(response).into_body().collect().await;
+        let body = match collected {
+            Ok(collected) => {
+                if let Some(trailers) = collected.trailers() {
+                    debug!("Trailers received from {}: {:?}", response_url, trailers);
+                    add_headers(&mut headers, trailers);
+                }
+                String::from_utf8_lossy(&collected.to_bytes()).to_string()
+            }
+            Err(e) => {
+                error!("Unable to read the body of {}: {:?}", response_url, e);
+                String::new()
+            }
+        };
 
-        Ok(UrlResponse::new(
-            &response_url,
-            headers,
-            &body,
-            request_type,
-            status_code,
-        ))
+        if let Some(http_cache) = self.http_cache.as_ref().filter(|_| cacheable) {
+            http_cache.store(
+                &url_request.url,
+                &CachedResponse {
+                    url: response_url.clone(),
+                    status_code,
+                    etag: headers.get("Etag").cloned(),
+                    last_modified: headers.get("Last-modified").cloned(),
+                    headers: headers.clone(),
+                    body: body.clone(),
+                    redirects: redirects.clone(),
+                },
+            );
+        }
+
+        Ok(
+            UrlResponse::new(&response_url, headers, &body, request_type, status_code)
+                .with_http_version(&http_version)
+                .with_redirects(redirects),
+        )
+    }
+
+    /// Sends the request, and sends it again after a transient failure
+    /// with an exponential backoff (100 ms, 200 ms, 400 ms...).
+    /// The timeout bounds the request with its retries: each attempt only
+    /// has the time left, and no retry is done beyond.
+    async fn send_with_retries(
+        &self,
+        request_builder: RequestBuilder,
+        url: &str,
+    ) -> Result<Response, reqwest::Error> {
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let mut attempt = 0;
+        loop {
+            // The bodies are strings, so the request can always be cloned
+            let mut attempt_builder = match request_builder.try_clone() {
+                Some(attempt_builder) => attempt_builder,
+                None => return request_builder.send().await,
+            };
+            if let Some(deadline) = deadline {
+                attempt_builder =
+                    attempt_builder.timeout(deadline.saturating_duration_since(Instant::now()));
+            }
+            let error = match attempt_builder.send().await {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+
+            let backoff = RETRY_BACKOFF.saturating_mul(2u32.saturating_pow(attempt as u32));
+            let in_time = deadline.is_none_or(|deadline| Instant::now() + backoff < deadline);
+            if attempt >= self.retries || !in_time || !is_transient(url, &error) {
+                return Err(error);
+            }
+            attempt += 1;
+            warn!(
+                "The request to {} failed, retry {}/{} in {:?}: {}",
+                url, attempt, self.retries, backoff, error
+            );
+            tokio::time::sleep(backoff).await;
+        }
     }
 
     /// Extract all URLs from a given string, and return them optionnally
@@ -296,6 +792,41 @@ impl HttpReader<'_> {
         url_requests
     }
 
+    /// Search the WordPress themes in the URLs of the assets, and return
+    /// the UrlRequests of their main stylesheets (style.css), once per
+    /// theme.
+    pub fn extract_wp_themes(&self, request_url: &str, data: &str) -> Vec<UrlRequest> {
+        let mut url_requests: Vec<UrlRequest> = Vec::new();
+        let caps = self
+            .url_regexes
+            .get("wp_themes")
+            .unwrap()
+            .captures_iter(data);
+        for rmatch in caps {
+            let theme_url = rmatch.name("url").unwrap().as_str();
+            let style_url = format!("{}style.css", theme_url);
+            let url_request = if style_url.starts_with("//") {
+                let protocol = if request_url.starts_with("http://") {
+                    "http:"
+                } else {
+                    "https:"
+                };
+                UrlRequest::new(&format!("{}{}", protocol, style_url), false)
+            } else if style_url.starts_with("https://") || style_url.starts_with("http://") {
+                UrlRequest::new(&style_url, false)
+            } else {
+                UrlRequest::from_path(request_url, &style_url, false)
+            };
+            if !url_requests
+                .iter()
+                .any(|existing| existing.url == url_request.url)
+            {
+                url_requests.push(url_request);
+            }
+        }
+        url_requests
+    }
+
     /// Search the Symfony toolbar and if found, return a UrlRequest to
     /// find the Symfony version.
     pub fn extract_symfony(&self, url: &str, data: &str) -> Option<UrlRequest> {
@@ -331,9 +862,247 @@ impl HttpReader<'_> {
     }
 }
 
+/// Adds the headers (or the trailers) of a response to the map.
+/// Only the first letter of the header names is in uppercase, it will
+/// avoid struggling with the case later.
+fn add_headers(headers: &mut HashMap<String, String>, header_map: &HeaderMap) {
+    for (header_name, header_value) in header_map.iter() {
+        let mut header_name_text = header_name.to_string().to_lowercase();
+        header_name_text
+            .get_mut(0..1)
+            .unwrap()
+            .make_ascii_uppercase();
+        let header_value_text = header_value.to_str().unwrap_or("").to_string();
+
+        // When a header is given several times (e.g. x-powered-by), concatenate
+        // The cookies are kept apart, their attributes contain commas
+        match headers.get_mut(&header_name_text) {
+            Some(value) if header_name_text == "Set-cookie" => {
+                value.push(SET_COOKIE_SEPARATOR);
+                value.push_str(&header_value_text);
+            }
+            Some(value) => {
+                value.push_str(", ");
+                value.push_str(&header_value_text);
+            }
+            None => {
+                headers.insert(header_name_text, header_value_text);
+            }
+        }
+    }
+}
+
+/// Returns the error of a failed request to the URL, with its kind.
+/// The DNS failures are connection errors for reqwest, they are told
+/// apart by the messages of the underlying errors.
+fn get_scan_error(url: &str, error: &reqwest::Error) -> ScanError {
+    let mut messages = vec![error.to_string()];
+    let mut source = std::error::Error::source(error);
+    while let Some(e) = source {
+        messages.push(e.to_string());
+        source = e.source();
+    }
+    let kind = if error.is_timeout() {
+        ScanErrorKind::Timeout
+    } else if messages.iter().any(|message| message.contains("dns error")) {
+        ScanErrorKind::Dns
+    } else if error.is_connect() {
+        ScanErrorKind::Connection
+    } else {
+        ScanErrorKind::Other
+    };
+    ScanError::new(url, kind, &messages.join(": "))
+}
+
+/// Whether the request failed because of the network, so sending it
+/// again can succeed (e.g. a connection refused or reset, a timeout).
+/// The DNS failures and the invalid requests aren't transient.
+fn is_transient(url: &str, error: &reqwest::Error) -> bool {
+    match get_scan_error(url, error).kind {
+        ScanErrorKind::Timeout | ScanErrorKind::Connection => true,
+        ScanErrorKind::Dns => false,
+        // The connection closed before the response
+        ScanErrorKind::Other => error.is_request(),
+    }
+}
+
+/// The fingerprint of the response to a missing page, when it's a soft
+/// 404 (a success status code). The responses matching it are missing
+/// pages too, even if the status code says otherwise.
+#[derive(Debug, PartialEq)]
+pub struct Soft404Fingerprint {
+    /// The status code of the missing page
+    status_code: u16,
+    /// The title of the missing page, if any
+    title: Option<String>,
+    /// The length of the body, without the path that can be reflected
+    length: usize,
+}
+
+impl Soft404Fingerprint {
+    /// Creates the fingerprint of the response to a missing page.
+    /// The real 404 don't need a fingerprint, None is returned.
+    pub fn from_response(url_response: &UrlResponse) -> Option<Self> {
+        if !(200..300).contains(&url_response.status_code) {
+            return None;
+        }
+        Some(Soft404Fingerprint {
+            status_code: url_response.status_code,
+            title: get_title(&url_response.body),
+            length: get_length_without_path(url_response),
+        })
+    }
+
+    /// Checks whether the response is the missing page.
+    /// The pages often contain a token or a date, so the length can vary
+    /// a little.
+    pub fn matches(&self, url_response: &UrlResponse) -> bool {
+        let length = get_length_without_path(url_response);
+        let tolerance = (self.length / 50).max(20);
+        url_response.status_code == self.status_code
+            && get_title(&url_response.body) == self.title
+            && length.abs_diff(self.length) <= tolerance
+    }
+}
+
+/// The minimum number of probed paths to tell a catch-all
+const MIN_CATCH_ALL_RESPONSES: usize = 5;
+
+/// Checks whether the host answers the same response to all the probed
+/// paths, as a catch-all or a honeypot does. The probes then say nothing
+/// about the technologies, the matching ones would be bogus findings.
+/// The main page and the JavaScript files are not probes, they are
+/// ignored.
+pub fn is_catch_all(main_url: &str, url_responses: &[UrlResponse]) -> bool {
+    let probes: Vec<&UrlResponse> = url_responses
+        .iter()
+        .filter(|url_response| {
+            url_response.url != main_url && url_response.request_type == UrlRequestType::Default
+        })
+        .collect();
+    if probes.len() < MIN_CATCH_ALL_RESPONSES {
+        return false;
+    }
+    // The fingerprint of the first probe, whatever its status code
+    let first = probes[0];
+    let length = get_length_without_path(first);
+    let tolerance = (length / 50).max(20);
+    let title = get_title(&first.body);
+    probes.iter().all(|url_response| {
+        url_response.status_code == first.status_code
+            && get_title(&url_response.body) == title
+            && get_length_without_path(url_response).abs_diff(length) <= tolerance
+    })
+}
+
+/// Returns the title of the HTML page, if any.
+fn get_title(body: &str) -> Option<String> {
+    let title_regex = Regex::new(r"(?is)<title[^>]*>(?P<title>.*?)</title>").unwrap();
+    title_regex
+        .captures(body)
+        .map(|caps| caps["title"].trim().to_string())
+}
+
+/// Returns the length of the body, without the path of the URL that the
+/// missing pages often reflect.
+fn get_length_without_path(url_response: &UrlResponse) -> usize {
+    let path = url_response
+        .url
+        .split_once("://")
+        .and_then(|(_, rest)| rest.find('/').map(|position| &rest[position..]))
+        .unwrap_or("/");
+    if path == "/" {
+        return url_response.body.len();
+    }
+    url_response.body.replace(path, "").len()
+}
+
+/// Extracts the value of the input named field in the HTML page.
+/// It's used to get the hidden CSRF tokens of the login forms.
+fn extract_input_value(html: &str, field: &str) -> Option<String> {
+    let input_regex = Regex::new(r"(?is)<input\b[^>]*>").unwrap();
+    let name_regex = Regex::new(&format!(
+        r#"(?i)\bname\s*=\s*["']?{}["'\s/>]"#,
+        regex::escape(field)
+    ))
+    .unwrap();
+    let value_regex = Regex::new(r#"(?i)\bvalue\s*=\s*(["'])(?P<value>.*?)(["'])"#).unwrap();
+    let value = input_regex
+        .find_iter(html)
+        .filter(|input| name_regex.is_match(input.as_str()))
+        .find_map(|input| value_regex.captures(input.as_str()))
+        .map(|caps| caps["value"].replace("&amp;", "&"));
+    value
+}
+
+/// Encodes a name or a value of URL-encoded form data.
+fn encode_form_component(component: &str) -> String {
+    component
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'*' => {
+                (byte as char).to_string()
+            }
+            b' ' => "+".to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::future::Future;
+    use std::thread::JoinHandle;
+
+    /// Starts a server answering the given number of connections with
+    /// the response built from each request. The thread returns the
+    /// requests received.
+    fn start_server<F>(connections: usize, respond: F) -> (u16, JoinHandle<Vec<String>>)
+    where
+        F: Fn(&str) -> Vec<u8> + Send + 'static,
+    {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for _ in 0..connections {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buffer = [0; 2048];
+                let read = stream.read(&mut buffer).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                let _ = stream.write_all(&respond(&request));
+                requests.push(request);
+            }
+            requests
+        });
+        (port, server)
+    }
+
+    /// Returns a response with the given status, headers and body, which
+    /// closes the connection. Each header ends with \r\n.
+    fn http_response(status: &str, headers: &str, body: &str) -> Vec<u8> {
+        format!(
+            "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            headers,
+            body.len(),
+            body
+        )
+        .into_bytes()
+    }
+
+    /// Runs the future on a current-thread runtime, as the scans do.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
 
     #[test]
     fn extract_script_urls() {
@@ -350,4 +1119,666 @@ mod tests {
         assert_eq!(url_requests.len(), 1);
         assert_eq!(url_requests[0].url, "https://www.example.org/login/javax.faces.resource/jquery/jquery.js.jsf;jsessionid=pYKnBiK_o8OyDlcQakjdpd2xmR_8mMal_lhg5js8.uldspt02?ln=primefaces&amp;v=8.0");
     }
+
+    #[test]
+    fn extract_css_urls() {
+        let hr = HttpReader::new();
+        let data = r#"<link rel="icon" href="/favicon.ico">
+            <link rel="stylesheet" href="/css/bootstrap.min.css?v=5.3.0">
+            <link href='//cdn.example.net/theme/style.css' rel='stylesheet' type='text/css'>"#;
+        let url_requests = hr.extract_urls(
+            "https://www.example.org/index.html",
+            data,
+            Some(".css"),
+            "css",
+        );
+        let urls: Vec<&str> = url_requests
+            .iter()
+            .map(|url_request| url_request.url.as_str())
+            .collect();
+        assert_eq!(
+            vec![
+                "https://www.example.org/css/bootstrap.min.css?v=5.3.0",
+                "https://cdn.example.net/theme/style.css",
+            ],
+            urls
+        );
+    }
+
+    #[test]
+    fn extract_wp_themes_works() {
+        let hr = HttpReader::new();
+        let html = r#"<link rel="stylesheet" href="/wp-content/themes/astra/assets/css/minified/main.min.css?ver=4.6.4">
+        <script src="https://www.example.com/wp-content/themes/astra/assets/js/frontend.min.js"></script>
+        <img src="//cdn.example.com/blog/wp-content/themes/astra-child/images/logo.png">
+        <div style="background: url(/wp-content/plugins/elementor/bg.png)"></div>"#;
+        let urls: Vec<String> = hr
+            .extract_wp_themes("https://www.example.com/", html)
+            .into_iter()
+            .map(|url_request| url_request.url)
+            .collect();
+        assert_eq!(
+            vec![
+                "https://www.example.com/wp-content/themes/astra/style.css",
+                "https://cdn.example.com/blog/wp-content/themes/astra-child/style.css",
+            ],
+            urls
+        );
+    }
+
+    #[test]
+    fn stylesheets_are_fetched() {
+        let (port, server) = start_server(2, |request| {
+            let body = if request.starts_with("GET /style.css ") {
+                "/*! Bootstrap v5.3.0 */"
+            } else {
+                "<link rel=\"stylesheet\" href=\"/style.css\">"
+            };
+            http_response("200 OK", "", body)
+        });
+
+        let hr = HttpReader::new();
+        let url = format!("http://127.0.0.1:{}/", port);
+        let url_request = UrlRequest::new(&url, false).with_fetch_css();
+        let (url_responses, scan_errors) = block_on(hr.read(&[url_request], "Sanca"));
+        server.join().unwrap();
+
+        assert!(scan_errors.is_empty());
+        assert_eq!(2, url_responses.len());
+        assert_eq!(UrlRequestType::Default, url_responses[0].request_type);
+        assert_eq!(format!("{}style.css", url), url_responses[1].url);
+        assert_eq!(UrlRequestType::Css, url_responses[1].request_type);
+        assert_eq!("/*! Bootstrap v5.3.0 */", url_responses[1].body);
+    }
+
+    #[test]
+    fn method_and_body_are_sent() {
+        let (port, server) = start_server(2, |request| {
+            // Echo the request line and the body
+            let (head, body) = request.split_once("\r\n\r\n").unwrap_or((request, ""));
+            let content_type = head
+                .lines()
+                .find(|line| line.to_lowercase().starts_with("content-type:"))
+                .unwrap_or("");
+            let echo = format!(
+                "{}|{}|{}",
+                head.lines().next().unwrap_or(""),
+                content_type,
+                body
+            );
+            http_response("200 OK", "", &echo)
+        });
+
+        let hr = HttpReader::new();
+        let url = format!("http://127.0.0.1:{}/graphql", port);
+        let get_request = UrlRequest::new(&url, false);
+        let post_request = UrlRequest::new(&url, false)
+            .with_method(Method::POST)
+            .with_body(r#"{"query":"{__typename}"}"#, "application/json");
+        for (url_request, expected) in [
+            (get_request, "GET /graphql HTTP/1.1||"),
+            (
+                post_request,
+                r#"POST /graphql HTTP/1.1|content-type: application/json|{"query":"{__typename}"}"#,
+            ),
+        ] {
+            let (url_responses, scan_errors) = block_on(hr.read(&[url_request], "Sanca"));
+            assert!(scan_errors.is_empty());
+            assert_eq!(expected, url_responses[0].body);
+        }
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn method_is_sent() {
+        let (port, server) = start_server(1, |request| {
+            let allow = if request.starts_with("OPTIONS / ") {
+                "GET, HEAD, OPTIONS, TRACE"
+            } else {
+                "GET"
+            };
+            http_response("200 OK", &format!("Allow: {}\r\n", allow), "")
+        });
+
+        let hr = HttpReader::new();
+        let url = format!("http://127.0.0.1:{}/", port);
+        let url_request = UrlRequest::new(&url, false).with_method(Method::OPTIONS);
+        let (url_responses, scan_errors) = block_on(hr.read(&[url_request], "Sanca"));
+        server.join().unwrap();
+
+        assert!(scan_errors.is_empty());
+        assert_eq!(
+            Some(&"GET, HEAD, OPTIONS, TRACE".to_string()),
+            url_responses[0].headers.get("Allow")
+        );
+    }
+
+    #[test]
+    fn resolver_overrides_are_used() {
+        let (port, server) = start_server(1, |_| http_response("200 OK", "", "staging"));
+
+        let resolver = Resolver::new(None);
+        resolver.add_override("staging.example.com", "127.0.0.1".parse().unwrap());
+        let hr = HttpReader::new().with_resolver(resolver);
+        let url = format!("http://staging.example.com:{}/", port);
+        let (url_responses, scan_errors) =
+            block_on(hr.read(&[UrlRequest::new(&url, false)], "Sanca"));
+        let requests = server.join().unwrap();
+
+        assert!(scan_errors.is_empty());
+        assert_eq!("staging", url_responses[0].body);
+        // The Host header is still the hostname
+        assert!(requests[0].contains(&format!("host: staging.example.com:{}\r\n", port)));
+    }
+
+    #[test]
+    fn timeout_is_used() {
+        // A server which never answers
+        let (port, server) = start_server(1, |_| {
+            std::thread::sleep(Duration::from_millis(500));
+            Vec::new()
+        });
+
+        let hr = HttpReader::new().with_timeout(Duration::from_millis(100));
+        let url = format!("http://127.0.0.1:{}/", port);
+        let (url_responses, scan_errors) =
+            block_on(hr.read(&[UrlRequest::new(&url, false)], "Sanca"));
+        server.join().unwrap();
+
+        assert!(url_responses.is_empty());
+        assert_eq!(1, scan_errors.len());
+        assert_eq!(ScanErrorKind::Timeout, scan_errors[0].kind);
+    }
+
+    #[test]
+    fn trailers_are_added_to_headers() {
+        let (port, server) = start_server(1, |_| {
+            b"HTTP/1.1 200 OK\r\n\
+            Content-Type: text/html\r\n\
+            Transfer-Encoding: chunked\r\n\
+            Trailer: Server\r\n\
+            Connection: close\r\n\r\n\
+            6\r\n<html>\r\n\
+            7\r\n</html>\r\n\
+            0\r\n\
+            Server: nginx/1.25.3\r\n\r\n"
+                .to_vec()
+        });
+
+        let hr = HttpReader::new();
+        let url = format!("http://127.0.0.1:{}/", port);
+        let (url_responses, scan_errors) =
+            block_on(hr.read(&[UrlRequest::new(&url, false)], "Sanca"));
+        server.join().unwrap();
+
+        assert!(scan_errors.is_empty());
+        assert_eq!(1, url_responses.len());
+        assert_eq!("<html></html>", url_responses[0].body);
+        assert_eq!(
+            Some(&"nginx/1.25.3".to_string()),
+            url_responses[0].headers.get("Server")
+        );
+        assert_eq!(
+            Some(&"text/html".to_string()),
+            url_responses[0].headers.get("Content-type")
+        );
+    }
+
+    #[test]
+    fn extract_input_value_works() {
+        let html = r#"<form method="post">
+            <input type="text" name="username">
+            <input type="hidden" value="a1b2+c3/d4==" name="_csrf_token" />
+            <input type='hidden' name='token' value='x&amp;y'>
+        </form>"#;
+        assert_eq!(
+            Some("a1b2+c3/d4==".to_string()),
+            extract_input_value(html, "_csrf_token")
+        );
+        assert_eq!(Some("x&y".to_string()), extract_input_value(html, "token"));
+        assert_eq!(None, extract_input_value(html, "username"));
+        assert_eq!(None, extract_input_value(html, "csrf"));
+        assert_eq!(
+            "a1b2%2Bc3%2Fd4%3D%3D",
+            encode_form_component("a1b2+c3/d4==")
+        );
+    }
+
+    #[test]
+    fn login_session_is_reused() {
+        let (port, server) = start_server(4, |request| {
+            if request.starts_with("GET /login ") {
+                http_response(
+                    "200 OK",
+                    "",
+                    "<input type=\"hidden\" name=\"csrf\" value=\"t0k+n\"><form>",
+                )
+            } else if request.starts_with("POST /login ") {
+                http_response(
+                    "302 Found",
+                    "Location: /\r\nSet-Cookie: session=s3cr3t; Path=/\r\n",
+                    "",
+                )
+            } else if request.starts_with("GET / ") {
+                http_response("200 OK", "", "Welcome")
+            } else {
+                http_response("200 OK", "", "Dashboard")
+            }
+        });
+
+        let hr = HttpReader::new().with_cookie_jar();
+        let login_url = format!("http://127.0.0.1:{}/login", port);
+        let status_code = block_on(hr.login(
+            &login_url,
+            "user=admin&password=p%40ss",
+            Some("csrf"),
+            "Sanca",
+        ))
+        .unwrap();
+        // The redirection after the login is followed
+        assert_eq!(200, status_code);
+        let url = format!("http://127.0.0.1:{}/admin", port);
+        let (url_responses, scan_errors) =
+            block_on(hr.read(&[UrlRequest::new(&url, false)], "Sanca"));
+        let requests = server.join().unwrap();
+
+        assert!(requests[0].starts_with("GET /login "));
+        assert!(requests[1].starts_with("POST /login "));
+        assert!(requests[1].ends_with("user=admin&password=p%40ss&csrf=t0k%2Bn"));
+        assert!(requests[2].starts_with("GET / "));
+        assert!(requests[3].starts_with("GET /admin "));
+        assert!(requests[3]
+            .to_lowercase()
+            .contains("cookie: session=s3cr3t"));
+        assert_eq!("Dashboard", url_responses[0].body);
+        assert!(scan_errors.is_empty());
+    }
+
+    #[test]
+    fn read_reports_errors() {
+        use std::net::TcpListener;
+
+        // The port is closed once the listener is dropped
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let hr = HttpReader::new();
+        let url = format!("http://127.0.0.1:{}/", port);
+        let (url_responses, scan_errors) =
+            block_on(hr.read(&[UrlRequest::new(&url, true)], "Sanca"));
+
+        assert!(url_responses.is_empty());
+        assert_eq!(1, scan_errors.len());
+        assert_eq!(url, scan_errors[0].target);
+        assert_eq!(ScanErrorKind::Connection, scan_errors[0].kind);
+        assert!(!scan_errors[0].message.is_empty());
+    }
+
+    #[test]
+    fn is_catch_all_works() {
+        let main_url = "https://www.example.com/";
+        let paths = [
+            "wp-login.php",
+            "readme.html",
+            "CHANGELOG.txt",
+            "typo3/sysext/install/composer.json",
+            "phpmyadmin/ChangeLog",
+        ];
+        let responses = |body: &dyn Fn(&str) -> String| -> Vec<UrlResponse> {
+            let mut url_responses = vec![UrlResponse::new(
+                main_url,
+                HashMap::new(),
+                "<html><title>Home</title><body>Welcome</body></html>",
+                UrlRequestType::Default,
+                200,
+            )];
+            for path in paths {
+                url_responses.push(UrlResponse::new(
+                    &format!("{}{}", main_url, path),
+                    HashMap::new(),
+                    &body(path),
+                    UrlRequestType::Default,
+                    200,
+                ));
+            }
+            url_responses
+        };
+
+        // The same page, with the path reflected
+        let url_responses =
+            responses(&|path| format!("<html><title>Login</title><body>/{}</body></html>", path));
+        assert!(is_catch_all(main_url, &url_responses));
+
+        // Distinct pages
+        let url_responses = responses(&|path| {
+            format!(
+                "<html><title>{}</title><body>{}</body></html>",
+                path,
+                "x".repeat(path.len() * 20)
+            )
+        });
+        assert!(!is_catch_all(main_url, &url_responses));
+
+        // Too few probes
+        let url_responses = responses(&|_| "Version 1.2.3".to_string());
+        assert!(!is_catch_all(main_url, &url_responses[..4]));
+    }
+
+    #[test]
+    fn soft_404_fingerprint_works() {
+        let page = |url: &str, status_code: u16, extra: &str| {
+            let path = &url[url.find("/sanca").or(url.find("/wp")).unwrap_or(0)..];
+            let body = format!(
+                "<html><head><title>Page not found</title></head><body>{} doesn't exist. {}</body></html>",
+                path, extra
+            );
+            UrlResponse::new(
+                url,
+                HashMap::new(),
+                &body,
+                UrlRequestType::Default,
+                status_code,
+            )
+        };
+        let missing_page = page(
+            "https://www.example.com/sanca-0011223344",
+            200,
+            "Token: 1a2b",
+        );
+        let fingerprint = Soft404Fingerprint::from_response(&missing_page).unwrap();
+
+        // The path and the token change, it's still the missing page
+        let readme = page(
+            "https://www.example.com/wp-content/plugins/akismet/readme.txt",
+            200,
+            "Token: 9f8e7d",
+        );
+        assert!(fingerprint.matches(&readme));
+
+        let readme = UrlResponse::new(
+            "https://www.example.com/wp-content/plugins/akismet/readme.txt",
+            HashMap::new(),
+            "=== Akismet Anti-spam: Spam Protection ===\nStable tag: 5.3.1\n",
+            UrlRequestType::Default,
+            200,
+        );
+        assert!(!fingerprint.matches(&readme));
+        let readme = page(
+            "https://www.example.com/wp-content/plugins/akismet/readme.txt",
+            404,
+            "Token: 9f8e7d",
+        );
+        assert!(!fingerprint.matches(&readme));
+
+        // A real 404 has no fingerprint
+        let missing_page = page("https://www.example.com/sanca-0011223344", 404, "");
+        assert_eq!(None, Soft404Fingerprint::from_response(&missing_page));
+    }
+
+    #[test]
+    fn client_is_built_with_a_proxy() {
+        // Intercepting proxies such as Burp or ZAP listen on HTTP
+        let proxy = HttpReader::parse_proxy("http://127.0.0.1:8080").unwrap();
+        let http_reader = HttpReader::new().with_proxy(proxy);
+        assert!(http_reader.get_client_builder().build().is_ok());
+    }
+
+    #[test]
+    fn invalid_proxy_is_reported() {
+        assert!(HttpReader::parse_proxy("http://127.0.0.1:8080").is_ok());
+        assert!(HttpReader::parse_proxy("socks5h://127.0.0.1:9050").is_ok());
+        assert!(HttpReader::parse_proxy("ftp://127.0.0.1:21").is_err());
+        assert!(HttpReader::parse_proxy("not a proxy").is_err());
+    }
+
+    #[test]
+    fn custom_headers_are_sent() {
+        let http_client = Client::new();
+        let url_request = UrlRequest::new("https://www.example.com/", false);
+
+        let http_reader = HttpReader::new().with_headers(&[
+            ("Authorization".to_string(), "Bearer 0a1b2c".to_string()),
+            ("Cookie".to_string(), "session=3d4e5f".to_string()),
+        ]);
+        let request = http_reader
+            .get_request_builder(&url_request, &http_client, "sanca")
+            .build()
+            .unwrap();
+        assert_eq!("Bearer 0a1b2c", request.headers()["Authorization"]);
+        assert_eq!("session=3d4e5f", request.headers()["Cookie"]);
+        assert_eq!("sanca", request.headers()["User-Agent"]);
+
+        // The User-Agent is replaced only if it's given explicitly
+        let http_reader = HttpReader::new().with_headers(&[
+            ("User-Agent".to_string(), "Mozilla/5.0".to_string()),
+            ("Invalid header".to_string(), "ignored".to_string()),
+        ]);
+        let request = http_reader
+            .get_request_builder(&url_request, &http_client, "sanca")
+            .build()
+            .unwrap();
+        assert_eq!(1, request.headers().get_all("User-Agent").iter().count());
+        assert_eq!("Mozilla/5.0", request.headers()["User-Agent"]);
+        assert!(request.headers().get("Invalid header").is_none());
+    }
+
+    /// Starts a server redirecting /old to /new, and /away to the other
+    /// host, with a response in HTTP/1.1 for each connection.
+    fn start_redirecting_server() -> u16 {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buffer = [0; 2048];
+                let read = stream.read(&mut buffer).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                let response = if request.starts_with("GET /old ") {
+                    "HTTP/1.1 301 Moved Permanently\r\nLocation: /new\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+                } else if request.starts_with("GET /away ") {
+                    format!("HTTP/1.1 302 Found\r\nLocation: http://other.example.net:{}/new\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", port)
+                } else {
+                    "HTTP/1.1 200 OK\r\nServer: nginx\r\nContent-Length: 3\r\nConnection: close\r\n\r\nnew".to_string()
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        port
+    }
+
+    #[test]
+    fn redirects_are_recorded() {
+        let port = start_redirecting_server();
+        let resolver = Resolver::new(None);
+        resolver.add_override("www.example.com", "127.0.0.1".parse().unwrap());
+        resolver.add_override("other.example.net", "127.0.0.1".parse().unwrap());
+        let hr = HttpReader::new().with_resolver(resolver);
+
+        let url_old = format!("http://www.example.com:{}/old", port);
+        let url_new = format!("http://www.example.com:{}/new", port);
+        let (url_responses, scan_errors) =
+            block_on(hr.read(&[UrlRequest::new(&url_old, false)], "Sanca"));
+        assert!(scan_errors.is_empty());
+        assert_eq!(url_new, url_responses[0].url);
+        assert_eq!(200, url_responses[0].status_code);
+        assert_eq!(
+            vec![Redirect {
+                url: url_old,
+                status_code: 301,
+                location: url_new.clone(),
+            }],
+            url_responses[0].redirects
+        );
+        assert!(!url_responses[0].is_redirected_to_another_host());
+
+        // The redirection leaves the hostname
+        let url_away = format!("http://www.example.com:{}/away", port);
+        let (url_responses, scan_errors) =
+            block_on(hr.read(&[UrlRequest::new(&url_away, false)], "Sanca"));
+        assert!(scan_errors.is_empty());
+        assert_eq!(
+            format!("http://other.example.net:{}/new", port),
+            url_responses[0].url
+        );
+        assert_eq!(302, url_responses[0].redirects[0].status_code);
+        assert!(url_responses[0].is_redirected_to_another_host());
+
+        // Not redirected
+        let (url_responses, _) = block_on(hr.read(&[UrlRequest::new(&url_new, false)], "Sanca"));
+        assert!(url_responses[0].redirects.is_empty());
+    }
+
+    #[test]
+    fn max_redirects_is_used() {
+        let port = start_redirecting_server();
+        let hr = HttpReader::new().with_max_redirects(0);
+        let url_old = format!("http://127.0.0.1:{}/old", port);
+        let (url_responses, scan_errors) =
+            block_on(hr.read(&[UrlRequest::new(&url_old, false)], "Sanca"));
+
+        // The redirection itself is returned
+        assert!(scan_errors.is_empty());
+        assert_eq!(url_old, url_responses[0].url);
+        assert_eq!(301, url_responses[0].status_code);
+        assert!(url_responses[0].redirects.is_empty());
+    }
+
+    /// Starts a server closing the first connections without response,
+    /// then answering with the status code. The number of connections
+    /// accepted is returned with the port.
+    fn start_failing_server(failures: usize, status: &'static str) -> (u16, Arc<Mutex<usize>>) {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let connections = Arc::new(Mutex::new(0));
+        let server_connections = connections.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buffer = [0; 2048];
+                let _ = stream.read(&mut buffer);
+                let mut connections = server_connections.lock().unwrap();
+                *connections += 1;
+                if *connections > failures {
+                    let response = format!(
+                        "HTTP/1.1 {}\r\nServer: nginx\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                        status
+                    );
+                    let _ = stream.write_all(response.as_bytes());
+                }
+            }
+        });
+        (port, connections)
+    }
+
+    #[test]
+    fn transient_failure_is_retried() {
+        let (port, connections) = start_failing_server(1, "200 OK");
+        let hr = HttpReader::new();
+        let url = format!("http://127.0.0.1:{}/", port);
+        let (url_responses, scan_errors) =
+            block_on(hr.read(&[UrlRequest::new(&url, false)], "Sanca"));
+
+        assert!(scan_errors.is_empty());
+        assert_eq!(1, url_responses.len());
+        assert_eq!(200, url_responses[0].status_code);
+        assert_eq!("ok", url_responses[0].body);
+        assert_eq!(2, *connections.lock().unwrap());
+
+        // Without retry, the failure is returned
+        let (port, connections) = start_failing_server(1, "200 OK");
+        let hr = HttpReader::new().with_retries(0);
+        let url = format!("http://127.0.0.1:{}/", port);
+        let (url_responses, scan_errors) =
+            block_on(hr.read(&[UrlRequest::new(&url, false)], "Sanca"));
+        assert!(url_responses.is_empty());
+        assert_eq!(1, scan_errors.len());
+        assert_eq!(1, *connections.lock().unwrap());
+    }
+
+    #[test]
+    fn error_status_is_not_retried() {
+        let (port, connections) = start_failing_server(0, "503 Service Unavailable");
+        let hr = HttpReader::new();
+        let url = format!("http://127.0.0.1:{}/", port);
+        let (url_responses, scan_errors) =
+            block_on(hr.read(&[UrlRequest::new(&url, false)], "Sanca"));
+
+        assert!(scan_errors.is_empty());
+        assert_eq!(503, url_responses[0].status_code);
+        assert_eq!(1, *connections.lock().unwrap());
+    }
+
+    #[test]
+    fn compressed_body_is_decoded() {
+        use crate::checkers::{wordpress::WordPressChecker, HttpChecker};
+        use crate::models::technology::Technology;
+
+        const GZIP_BODY: &[u8] = include_bytes!("../../tests/fixtures/wordpress-page.html.gz");
+        // The server forces the compression of the body
+        let (port, server) = start_server(1, |_| {
+            let mut response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                GZIP_BODY.len()
+            )
+            .into_bytes();
+            response.extend_from_slice(GZIP_BODY);
+            response
+        });
+
+        let hr = HttpReader::new();
+        let url = format!("http://127.0.0.1:{}/", port);
+        let (url_responses, scan_errors) =
+            block_on(hr.read(&[UrlRequest::new(&url, false)], "Sanca"));
+        let requests = server.join().unwrap();
+
+        assert!(requests[0]
+            .to_lowercase()
+            .contains("accept-encoding: gzip, br, deflate"));
+        assert!(scan_errors.is_empty());
+        assert!(url_responses[0]
+            .body
+            .contains(r#"<meta name="generator" content="WordPress 6.4.2" />"#));
+        let findings = WordPressChecker::new().check_http(&url_responses);
+        assert_eq!(1, findings.len());
+        assert_eq!(Technology::WordPress, findings[0].technology);
+        assert_eq!(Some("6.4.2".to_string()), findings[0].version);
+    }
+
+    #[test]
+    fn set_cookie_headers_are_kept_apart() {
+        let (port, server) = start_server(1, |_| {
+            b"HTTP/1.1 200 OK\r\n\
+            X-Powered-By: PHP/8.2.12\r\nX-Powered-By: Laravel\r\n\
+            Set-Cookie: XSRF-TOKEN=eyJpdiI6; expires=Wed, 21 Oct 2026 07:28:00 GMT; path=/\r\n\
+            Set-Cookie: laravel_session=eyJpdiI7; expires=Wed, 21 Oct 2026 07:28:00 GMT; path=/; httponly\r\n\
+            Content-Length: 2\r\nConnection: close\r\n\r\nok"
+                .to_vec()
+        });
+
+        let hr = HttpReader::new();
+        let url = format!("http://127.0.0.1:{}/", port);
+        let (url_responses, _) = block_on(hr.read(&[UrlRequest::new(&url, false)], "Sanca"));
+        server.join().unwrap();
+
+        // The other headers are still concatenated
+        assert_eq!(
+            "PHP/8.2.12, Laravel",
+            url_responses[0].headers["X-powered-by"]
+        );
+        let cookies = url_responses[0].get_set_cookies();
+        assert_eq!(2, cookies.len());
+        assert_eq!("XSRF-TOKEN", cookies[0].name);
+        assert_eq!(
+            Some("Wed, 21 Oct 2026 07:28:00 GMT"),
+            cookies[0].get_attribute("expires")
+        );
+        assert_eq!("laravel_session", cookies[1].name);
+        assert!(cookies[1].has_attribute("HttpOnly"));
+    }
 }
@@ -0,0 +1,167 @@
+//! Cache the HTTP responses on disk
+//!
+//! The [`HttpCache`] stores the HTTP responses having an ETag or a
+//! Last-Modified header, so that subsequent scans of the same target can
+//! send conditional requests (If-None-Match / If-Modified-Since) and reuse
+//! the cached response when the server answers 304 Not Modified.
+//!
+//! The cache has the following structure:
+//! http_cache/
+//!   \__<hash of the URL>.json
+
+use std::collections::HashMap;
+use std::env;
+use std::fs::{create_dir_all, File};
+use std::io::prelude::*;
+use std::path::PathBuf;
+
+use log::{error, trace};
+use serde::{Deserialize, Serialize};
+
+use crate::models::reqres::Redirect;
+
+/// A cached HTTP response.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CachedResponse {
+    /// The URL of the response, after the redirections
+    pub url: String,
+    /// The status code of the response
+    pub status_code: u16,
+    /// The response headers
+    pub headers: HashMap<String, String>,
+    /// The response body
+    pub body: String,
+    /// The value of the ETag header, if any
+    pub etag: Option<String>,
+    /// The value of the Last-Modified header, if any
+    pub last_modified: Option<String>,
+    /// The redirections followed to get the response
+    #[serde(default)]
+    pub redirects: Vec<Redirect>,
+}
+
+/// A cache storing the HTTP responses in files.
+pub struct HttpCache {
+    /// The directory where the responses are stored
+    root_dir: PathBuf,
+}
+
+impl HttpCache {
+    /// Creates a cache stored in a "http_cache" directory next to the
+    /// executable, or in the current working directory if the executable
+    /// path is unknown.
+    pub fn new() -> Self {
+        let root_dir = if let Ok(mut p) = env::current_exe() {
+            p.pop();
+            p.join("http_cache")
+        } else {
+            PathBuf::new().join("http_cache")
+        };
+        Self::from_dir(root_dir)
+    }
+
+    /// Creates a cache stored in the given directory.
+    pub fn from_dir(root_dir: PathBuf) -> Self {
+        Self { root_dir }
+    }
+
+    /// Returns the file used to store the response of the given URL.
+    /// The URL is hashed with FNV-1a, which is stable across executions,
+    /// to get a valid filename.
+    fn get_filename(&self, url: &str) -> PathBuf {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in url.as_bytes() {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        self.root_dir.join(format!("{:016x}.json", hash))
+    }
+
+    /// Reads the cached response of the given URL.
+    pub fn read(&self, url: &str) -> Option<CachedResponse> {
+        trace!("Running HttpCache::read() for {}", url);
+        let filename = self.get_filename(url);
+        if !filename.exists() {
+            return None;
+        }
+
+        let mut json = String::new();
+        let read_result = File::open(&filename).and_then(|mut f| f.read_to_string(&mut json));
+        if read_result.is_err() {
+            error!("Unable to read the file {}", filename.to_string_lossy());
+            return None;
+        }
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Stores the response of the given URL.
+    /// Only the responses having an ETag or a Last-Modified header are
+    /// stored, the others couldn't be revalidated.
+    pub fn store(&self, url: &str, cached_response: &CachedResponse) {
+        trace!("Running HttpCache::store() for {}", url);
+        if cached_response.etag.is_none() && cached_response.last_modified.is_none() {
+            return;
+        }
+
+        if !self.root_dir.exists() && create_dir_all(&self.root_dir).is_err() {
+            error!(
+                "Unable to create the directory {}",
+                self.root_dir.to_string_lossy()
+            );
+            return;
+        }
+
+        let json = match serde_json::to_string(cached_response) {
+            Ok(j) => j,
+            Err(_) => {
+                error!("Error while serializing a cached response to JSON");
+                return;
+            }
+        };
+        let filename = self.get_filename(url);
+        let write_result = File::create(&filename).and_then(|mut f| f.write_all(json.as_bytes()));
+        if write_result.is_err() {
+            error!("Unable to write the file {}", filename.to_string_lossy());
+        }
+    }
+}
+
+impl Default for HttpCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_and_reads_responses() {
+        let root_dir = env::temp_dir().join(format!("sanca-http-cache-{}", std::process::id()));
+        let http_cache = HttpCache::from_dir(root_dir.clone());
+        let url = "https://www.example.com/wp-login.php";
+        assert!(http_cache.read(url).is_none());
+
+        let mut cached_response = CachedResponse {
+            url: url.to_string(),
+            status_code: 200,
+            headers: HashMap::new(),
+            body: "the body".to_string(),
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+            redirects: Vec::new(),
+        };
+        http_cache.store(url, &cached_response);
+        assert_eq!(Some(cached_response.clone()), http_cache.read(url));
+        assert!(http_cache.read("https://www.example.com/").is_none());
+
+        // Responses without validator aren't stored
+        cached_response.etag = None;
+        let other_url = "https://www.example.com/other";
+        http_cache.store(other_url, &cached_response);
+        assert!(http_cache.read(other_url).is_none());
+
+        std::fs::remove_dir_all(&root_dir).unwrap();
+    }
+}
@@ -3,5 +3,11 @@
 //! Main readers are TCP, UDP and HTTP. Their objective is to make easier
 //! fetching data, to focus on the identification of the technology.
 
+pub mod export;
 pub mod http;
+pub mod http_cache;
+pub mod resolver;
+pub mod socks;
 pub mod tcp;
+pub mod tls;
+pub mod udp;
@@ -0,0 +1,418 @@
+//! Resolve the hostnames
+//!
+//! The [`Resolver`] resolves the hostnames for the TCP and HTTP readers.
+//! The addresses are kept in a cache shared by all the clones of the
+//! resolver, so a hostname is resolved only once per scan even if many
+//! URLs are requested.
+//!
+//! By default the system resolver is used. A DNS server can be given to
+//! send the A and AAAA queries to it directly, and the address of a
+//! hostname can be forced (e.g. to scan a virtual host on a given IP).
+//!
+//! The IPv4 addresses are tried first, unless the IPv6 ones are preferred
+//! or the IPv6 addresses are excluded.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use log::{debug, trace, warn};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+/// The length of the header of a DNS message
+const HEADER_LENGTH: usize = 12;
+/// The type of the A records
+const TYPE_A: u16 = 1;
+/// The type of the AAAA records
+const TYPE_AAAA: u16 = 28;
+/// The IN class
+const CLASS_IN: u16 = 1;
+/// The port of the DNS servers
+const DNS_PORT: u16 = 53;
+
+/// The addresses to connect to, and their order
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AddressFamily {
+    /// The IPv4 addresses first, then the IPv6 ones
+    #[default]
+    Any,
+    /// The IPv6 addresses first, then the IPv4 ones
+    PreferIpv6,
+    /// Only the IPv4 addresses
+    Ipv4Only,
+}
+
+/// A resolver with a cache
+#[derive(Clone, Debug)]
+pub struct Resolver {
+    /// The DNS server to query, the system resolver is used if none
+    server: Option<SocketAddr>,
+    /// The addresses to return, and their order
+    address_family: AddressFamily,
+    /// The addresses already resolved, by hostname
+    cache: Arc<Mutex<HashMap<String, Vec<IpAddr>>>>,
+}
+
+impl Resolver {
+    /// Creates a new Resolver, using the given DNS server if any
+    pub fn new(server: Option<IpAddr>) -> Self {
+        Self {
+            server: server.map(|ip| SocketAddr::new(ip, DNS_PORT)),
+            address_family: AddressFamily::Any,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the addresses of the given family only, in its order.
+    pub fn with_address_family(mut self, address_family: AddressFamily) -> Self {
+        self.address_family = address_family;
+        self
+    }
+
+    /// Forces the address of the hostname, it's never resolved.
+    /// The Host header and the SNI are still the hostname, which allows
+    /// to scan a virtual host of a given IP.
+    pub fn add_override(&self, hostname: &str, ip: IpAddr) {
+        debug!("{} is forced to {}", hostname, ip);
+        self.cache
+            .lock()
+            .unwrap()
+            .entry(hostname.trim_end_matches('.').to_lowercase())
+            .or_default()
+            .push(ip);
+    }
+
+    /// Resolves the hostname.
+    /// The IP addresses are returned as is, the hostnames are looked up
+    /// in the cache before being resolved. The addresses are then sorted
+    /// or filtered according to the address family.
+    pub fn resolve(&self, hostname: &str) -> IoResult<Vec<IpAddr>> {
+        trace!("Running Resolver::resolve() on {}", hostname);
+        let ips = self.resolve_all(hostname)?;
+        let ips = match self.address_family {
+            AddressFamily::Any => {
+                let (ipv4, ipv6): (Vec<IpAddr>, Vec<IpAddr>) =
+                    ips.into_iter().partition(|ip| ip.is_ipv4());
+                [ipv4, ipv6].concat()
+            }
+            AddressFamily::PreferIpv6 => {
+                let (ipv6, ipv4): (Vec<IpAddr>, Vec<IpAddr>) =
+                    ips.into_iter().partition(|ip| ip.is_ipv6());
+                [ipv6, ipv4].concat()
+            }
+            AddressFamily::Ipv4Only => ips.into_iter().filter(|ip| ip.is_ipv4()).collect(),
+        };
+        if ips.is_empty() {
+            return Err(IoError::new(
+                ErrorKind::NotFound,
+                format!("No usable address found for {}", hostname),
+            ));
+        }
+        Ok(ips)
+    }
+
+    /// Resolves the hostname, returning the addresses of both families.
+    fn resolve_all(&self, hostname: &str) -> IoResult<Vec<IpAddr>> {
+        // The IPv6 addresses of the URLs are between brackets
+        let ip = hostname.trim_start_matches('[').trim_end_matches(']');
+        if let Ok(ip) = ip.parse::<IpAddr>() {
+            return Ok(vec![ip]);
+        }
+        let hostname = hostname.trim_end_matches('.').to_lowercase();
+        if let Some(ips) = self.cache.lock().unwrap().get(&hostname) {
+            debug!("{} found in the DNS cache", hostname);
+            return Ok(ips.clone());
+        }
+        let ips = match self.server {
+            Some(server) => Self::query_server(server, &hostname)?,
+            None => (hostname.as_str(), 0)
+                .to_socket_addrs()?
+                .map(|socket_addr| socket_addr.ip())
+                .collect(),
+        };
+        if ips.is_empty() {
+            return Err(IoError::new(
+                ErrorKind::NotFound,
+                format!("No address found for {}", hostname),
+            ));
+        }
+        debug!("{} resolved to {:?}", hostname, ips);
+        self.cache.lock().unwrap().insert(hostname, ips.clone());
+        Ok(ips)
+    }
+
+    /// Sends the A and AAAA queries to the DNS server.
+    /// The IPv4 addresses come first.
+    fn query_server(server: SocketAddr, hostname: &str) -> IoResult<Vec<IpAddr>> {
+        debug!("Resolving {} with the DNS server {}", hostname, server);
+        let bind_address = match server {
+            SocketAddr::V4(_) => "0.0.0.0:0",
+            SocketAddr::V6(_) => "[::]:0",
+        };
+        let socket = UdpSocket::bind(bind_address)?;
+        socket.set_read_timeout(Some(Duration::new(2, 0)))?;
+        socket.connect(server)?;
+
+        let mut ips = Vec::new();
+        for record_type in [TYPE_A, TYPE_AAAA] {
+            // A new ID for each query, a forged response has to guess it
+            let query_id = Self::get_query_id();
+            socket.send(&Self::build_query(query_id, hostname, record_type)?)?;
+            let mut buffer = [0; 1500];
+            let read = match socket.recv(&mut buffer) {
+                Ok(read) => read,
+                // A missing AAAA answer isn't fatal if there are IPv4
+                Err(e) if !ips.is_empty() => {
+                    warn!("No AAAA answer for {}: {:?}", hostname, e);
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+            match Self::parse_response(query_id, &buffer[..read]) {
+                Some(answers) => ips.extend(answers),
+                None => warn!("Invalid DNS response for {}", hostname),
+            }
+        }
+        Ok(ips)
+    }
+
+    /// Returns a random ID for a query, to recognize its response.
+    fn get_query_id() -> u16 {
+        let mut hasher = RandomState::new().build_hasher();
+        if let Ok(elapsed) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            hasher.write_u128(elapsed.as_nanos());
+        }
+        hasher.finish() as u16
+    }
+
+    /// Builds a query of the given type for the hostname, with the given ID.
+    fn build_query(query_id: u16, hostname: &str, record_type: u16) -> IoResult<Vec<u8>> {
+        let mut query = query_id.to_be_bytes().to_vec();
+        // Recursion desired, one question
+        query.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        for label in hostname.split('.') {
+            if label.is_empty() || label.len() > 63 {
+                return Err(IoError::new(
+                    ErrorKind::InvalidInput,
+                    format!("Invalid hostname {}", hostname),
+                ));
+            }
+            query.push(label.len() as u8);
+            query.extend_from_slice(label.as_bytes());
+        }
+        query.push(0);
+        query.extend_from_slice(&record_type.to_be_bytes());
+        query.extend_from_slice(&CLASS_IN.to_be_bytes());
+        Ok(query)
+    }
+
+    /// Returns the position following the name starting at pos.
+    /// The name is either a list of labels ending with an empty one, or
+    /// ends with a compression pointer.
+    fn skip_name(data: &[u8], mut pos: usize) -> Option<usize> {
+        loop {
+            let length = *data.get(pos)? as usize;
+            match length {
+                0 => return Some(pos + 1),
+                // A pointer takes 2 bytes and ends the name
+                length if length & 0xC0 == 0xC0 => return Some(pos + 2),
+                length => pos += 1 + length,
+            }
+        }
+    }
+
+    /// Parses a response to the query with the given ID.
+    /// Returns the addresses of the A and AAAA records, the other records
+    /// (e.g. CNAME) are skipped.
+    fn parse_response(query_id: u16, data: &[u8]) -> Option<Vec<IpAddr>> {
+        let header = data.get(..HEADER_LENGTH)?;
+        let id = u16::from_be_bytes([header[0], header[1]]);
+        let flags = u16::from_be_bytes([header[2], header[3]]);
+        // The QR bit is set in the responses
+        if id != query_id || flags & 0x8000 == 0 {
+            return None;
+        }
+        let question_count = u16::from_be_bytes([header[4], header[5]]);
+        let answer_count = u16::from_be_bytes([header[6], header[7]]);
+
+        let mut pos = HEADER_LENGTH;
+        for _ in 0..question_count {
+            // The name, then the type and the class
+            pos = Self::skip_name(data, pos)? + 4;
+        }
+        let mut ips = Vec::new();
+        for _ in 0..answer_count {
+            pos = Self::skip_name(data, pos)?;
+            let record = data.get(pos..pos + 10)?;
+            let record_type = u16::from_be_bytes([record[0], record[1]]);
+            let data_length = u16::from_be_bytes([record[8], record[9]]) as usize;
+            let record_data = data.get(pos + 10..pos + 10 + data_length)?;
+            pos += 10 + data_length;
+            match (record_type, data_length) {
+                (TYPE_A, 4) => {
+                    let octets: [u8; 4] = record_data.try_into().ok()?;
+                    ips.push(IpAddr::V4(Ipv4Addr::from(octets)));
+                }
+                (TYPE_AAAA, 16) => {
+                    let octets: [u8; 16] = record_data.try_into().ok()?;
+                    ips.push(IpAddr::V6(Ipv6Addr::from(octets)));
+                }
+                _ => continue,
+            }
+        }
+        Some(ips)
+    }
+}
+
+impl Resolve for Resolver {
+    /// Resolves the hostname for reqwest.
+    /// The resolution is blocking, so it's done outside of the runtime
+    /// threads.
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let hostname = name.as_str().to_string();
+            let ips = tokio::task::spawn_blocking(move || resolver.resolve(&hostname)).await??;
+            // The port is replaced by the one of the URL
+            let addrs: Addrs = Box::new(
+                ips.into_iter()
+                    .map(|ip| SocketAddr::new(ip, 0))
+                    .collect::<Vec<SocketAddr>>()
+                    .into_iter(),
+            );
+            Ok(addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ips_are_not_resolved() {
+        let resolver = Resolver::new(None);
+        assert_eq!(
+            vec!["192.168.1.10".parse::<IpAddr>().unwrap()],
+            resolver.resolve("192.168.1.10").unwrap()
+        );
+        assert_eq!(
+            vec!["::1".parse::<IpAddr>().unwrap()],
+            resolver.resolve("[::1]").unwrap()
+        );
+        assert!(resolver.cache.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn cache_is_shared() {
+        let resolver = Resolver::new(None);
+        let ips = vec!["203.0.113.7".parse::<IpAddr>().unwrap()];
+        resolver
+            .clone()
+            .cache
+            .lock()
+            .unwrap()
+            .insert("www.example.com".to_string(), ips.clone());
+        assert_eq!(ips, resolver.resolve("WWW.example.com.").unwrap());
+    }
+
+    #[test]
+    fn overrides_are_used() {
+        // The server isn't reachable, it must not be queried
+        let resolver = Resolver::new(Some("192.0.2.1".parse().unwrap()));
+        let ip = "10.0.0.5".parse::<IpAddr>().unwrap();
+        resolver.add_override("Staging.example.com", ip);
+        assert_eq!(vec![ip], resolver.resolve("staging.example.com").unwrap());
+    }
+
+    #[test]
+    fn address_family_is_applied() {
+        let ipv4 = "203.0.113.7".parse::<IpAddr>().unwrap();
+        let ipv6 = "2001:db8::7".parse::<IpAddr>().unwrap();
+        let resolver = Resolver::new(None);
+        resolver.add_override("www.example.com", ipv6);
+        resolver.add_override("www.example.com", ipv4);
+        resolver.add_override("ipv6.example.com", ipv6);
+        assert_eq!(
+            vec![ipv4, ipv6],
+            resolver.resolve("www.example.com").unwrap()
+        );
+
+        let resolver = resolver.with_address_family(AddressFamily::PreferIpv6);
+        assert_eq!(
+            vec![ipv6, ipv4],
+            resolver.resolve("www.example.com").unwrap()
+        );
+
+        let resolver = resolver.with_address_family(AddressFamily::Ipv4Only);
+        assert_eq!(vec![ipv4], resolver.resolve("www.example.com").unwrap());
+        assert!(resolver.resolve("ipv6.example.com").is_err());
+        assert!(resolver.resolve("[::1]").is_err());
+    }
+
+    #[test]
+    fn build_query_works() {
+        let query = Resolver::build_query(0x5342, "www.example.com", TYPE_AAAA).unwrap();
+        assert_eq!(&[0x53, 0x42, 0x01, 0x00, 0x00, 0x01], &query[..6]);
+        assert_eq!(
+            b"\x03www\x07example\x03com\x00\x00\x1c\x00\x01",
+            &query[12..]
+        );
+        assert!(Resolver::build_query(0x5342, "www..com", TYPE_A).is_err());
+    }
+
+    #[test]
+    fn query_ids_are_random() {
+        let query_ids: Vec<u16> = (0..8).map(|_| Resolver::get_query_id()).collect();
+        assert!(query_ids.iter().any(|query_id| *query_id != query_ids[0]));
+    }
+
+    #[test]
+    fn parse_response_works() {
+        let mut response = vec![
+            0x53, 0x42, 0x81, 0x80, 0x00, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00,
+        ];
+        response.extend_from_slice(b"\x03www\x07example\x03com\x00\x00\x01\x00\x01");
+        // A CNAME to example.com, with a compression pointer
+        response.extend_from_slice(&[0xC0, 0x0C, 0x00, 0x05, 0x00, 0x01, 0, 0, 0x0E, 0x10]);
+        response.extend_from_slice(&[0x00, 0x02, 0xC0, 0x10]);
+        response.extend_from_slice(&[0xC0, 0x10, 0x00, 0x01, 0x00, 0x01, 0, 0, 0x0E, 0x10]);
+        response.extend_from_slice(&[0x00, 0x04, 93, 184, 215, 14]);
+        assert_eq!(
+            Some(vec!["93.184.215.14".parse::<IpAddr>().unwrap()]),
+            Resolver::parse_response(0x5342, &response)
+        );
+        // The response to another query
+        assert_eq!(None, Resolver::parse_response(0x5300, &response));
+        response[1] = 0x00;
+        assert_eq!(None, Resolver::parse_response(0x5342, &response));
+    }
+
+    #[test]
+    fn query_server_works() {
+        // A DNS server answering every query with 127.0.0.2
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server_address = server.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for _ in 0..2 {
+                let mut buffer = [0; 512];
+                let (read, peer) = server.recv_from(&mut buffer).unwrap();
+                let mut response = buffer[..read].to_vec();
+                response[2] = 0x81;
+                response[3] = 0x80;
+                if response[read - 3] == TYPE_A as u8 {
+                    response[7] = 0x01;
+                    response.extend_from_slice(&[0xC0, 0x0C, 0x00, 0x01, 0x00, 0x01]);
+                    response.extend_from_slice(&[0, 0, 0x0E, 0x10, 0x00, 0x04, 127, 0, 0, 2]);
+                }
+                server.send_to(&response, peer).unwrap();
+            }
+        });
+        let ips = Resolver::query_server(server_address, "backend.example.com").unwrap();
+        assert_eq!(vec!["127.0.0.2".parse::<IpAddr>().unwrap()], ips);
+    }
+}
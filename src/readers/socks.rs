@@ -0,0 +1,288 @@
+//! Connect through a SOCKS5 proxy
+//!
+//! The [`SocksProxy`] opens TCP connections through a SOCKS5 proxy
+//! (RFC 1928), for the readers which don't rely on the HTTP client.
+//! With the `socks5h://` scheme, the hostname is resolved by the proxy,
+//! which is required to reach the Tor hidden services (.onion).
+
+use log::{debug, trace};
+
+use super::tcp::connect_timeout;
+
+use std::io::prelude::*;
+use std::io::{Error, ErrorKind, Result as IoResult};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// The version of the protocol
+const SOCKS_VERSION: u8 = 0x05;
+/// The authentication method "no authentication required"
+const NO_AUTHENTICATION: u8 = 0x00;
+/// The CONNECT command
+const CONNECT: u8 = 0x01;
+/// The address types
+const ATYP_IPV4: u8 = 0x01;
+const ATYP_DOMAIN_NAME: u8 = 0x03;
+const ATYP_IPV6: u8 = 0x04;
+
+/// A SOCKS5 proxy
+#[derive(Clone, Debug, PartialEq)]
+pub struct SocksProxy {
+    /// The IP address or hostname of the proxy
+    pub ip_hostname: String,
+    /// The port of the proxy
+    pub port: u16,
+    /// Whether the hostnames are resolved by the proxy (socks5h)
+    pub remote_dns: bool,
+}
+
+impl SocksProxy {
+    /// Creates a SocksProxy from a URL like socks5h://127.0.0.1:9050.
+    /// Returns None if the URL is not a SOCKS5 one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sanca_software::readers::socks::SocksProxy;
+    /// let proxy = SocksProxy::from_url("socks5h://127.0.0.1:9050").unwrap();
+    /// assert_eq!("127.0.0.1", proxy.ip_hostname);
+    /// assert_eq!(9050, proxy.port);
+    /// assert!(proxy.remote_dns);
+    /// assert!(SocksProxy::from_url("http://127.0.0.1:8080").is_none());
+    /// ```
+    pub fn from_url(url: &str) -> Option<Self> {
+        let (scheme, address) = url.split_once("://")?;
+        let remote_dns = match scheme.to_lowercase().as_str() {
+            "socks5" => false,
+            "socks5h" => true,
+            _ => return None,
+        };
+        let address = address.trim_end_matches('/');
+        let (ip_hostname, port) = match address.rsplit_once(':') {
+            // An IPv6 address without port (e.g. [::1])
+            Some(_) if address.ends_with(']') => (address, 1080),
+            Some((ip_hostname, port)) => (ip_hostname, port.parse().ok()?),
+            // The default port of SOCKS
+            None => (address, 1080),
+        };
+        let ip_hostname = ip_hostname.trim_start_matches('[').trim_end_matches(']');
+        if ip_hostname.is_empty() {
+            return None;
+        }
+        Some(Self {
+            ip_hostname: ip_hostname.to_string(),
+            port,
+            remote_dns,
+        })
+    }
+
+    /// Opens a TCP connection to the target through the proxy.
+    /// The timeout applies to the connection to the proxy, the reads and
+    /// the writes, they wait indefinitely with None.
+    pub fn connect(
+        &self,
+        ip_hostname: &str,
+        port: u16,
+        timeout: Option<Duration>,
+    ) -> IoResult<TcpStream> {
+        trace!("Running SocksProxy::connect()");
+        debug!(
+            "Connecting to {}:{} through the SOCKS proxy {}:{}",
+            ip_hostname, port, self.ip_hostname, self.port
+        );
+        let proxy_addrs: Vec<SocketAddr> = (self.ip_hostname.as_str(), self.port)
+            .to_socket_addrs()?
+            .collect();
+        let mut stream = connect_timeout(&proxy_addrs, timeout)?;
+        stream.set_read_timeout(timeout)?;
+        stream.set_write_timeout(timeout)?;
+
+        trace!("Sending the SOCKS greeting");
+        stream.write_all(&[SOCKS_VERSION, 1, NO_AUTHENTICATION])?;
+        let mut reply = [0; 2];
+        stream.read_exact(&mut reply)?;
+        if reply != [SOCKS_VERSION, NO_AUTHENTICATION] {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "The SOCKS proxy requires an authentication",
+            ));
+        }
+
+        trace!("Sending the SOCKS CONNECT request");
+        let mut request = vec![SOCKS_VERSION, CONNECT, 0x00];
+        request.extend_from_slice(&self.encode_address(ip_hostname, port)?);
+        request.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&request)?;
+
+        let mut reply = [0; 4];
+        stream.read_exact(&mut reply)?;
+        if reply[0] != SOCKS_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Invalid response from the SOCKS proxy",
+            ));
+        }
+        if reply[1] != 0x00 {
+            return Err(Error::new(
+                ErrorKind::ConnectionRefused,
+                format!("The SOCKS proxy refused the connection (code {})", reply[1]),
+            ));
+        }
+
+        // Skip the bound address and port
+        let address_length = match reply[3] {
+            ATYP_IPV4 => 4,
+            ATYP_IPV6 => 16,
+            ATYP_DOMAIN_NAME => {
+                let mut length = [0; 1];
+                stream.read_exact(&mut length)?;
+                length[0] as usize
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "Invalid address type from the SOCKS proxy",
+                ))
+            }
+        };
+        let mut bound_address = vec![0; address_length + 2];
+        stream.read_exact(&mut bound_address)?;
+        debug!("Connected through the SOCKS proxy");
+        Ok(stream)
+    }
+
+    /// Encodes the target address of the CONNECT request.
+    /// The hostname is sent as is to the proxy with socks5h, otherwise it's
+    /// resolved locally.
+    fn encode_address(&self, ip_hostname: &str, port: u16) -> IoResult<Vec<u8>> {
+        let ip = match ip_hostname
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse()
+        {
+            Ok(ip) => ip,
+            Err(_) if self.remote_dns => {
+                if ip_hostname.len() > 255 {
+                    return Err(Error::new(ErrorKind::InvalidInput, "Hostname too long"));
+                }
+                let mut address = vec![ATYP_DOMAIN_NAME, ip_hostname.len() as u8];
+                address.extend_from_slice(ip_hostname.as_bytes());
+                return Ok(address);
+            }
+            Err(_) => (ip_hostname, port)
+                .to_socket_addrs()?
+                .next()
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::NotFound,
+                        format!("Unable to resolve {}", ip_hostname),
+                    )
+                })?
+                .ip(),
+        };
+        Ok(match ip {
+            IpAddr::V4(ip) => [&[ATYP_IPV4], &ip.octets()[..]].concat(),
+            IpAddr::V6(ip) => [&[ATYP_IPV6], &ip.octets()[..]].concat(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn from_url_works() {
+        let proxy = SocksProxy::from_url("socks5://proxy.example.com").unwrap();
+        assert_eq!("proxy.example.com", proxy.ip_hostname);
+        assert_eq!(1080, proxy.port);
+        assert!(!proxy.remote_dns);
+
+        let proxy = SocksProxy::from_url("SOCKS5H://[::1]:9050/").unwrap();
+        assert_eq!("::1", proxy.ip_hostname);
+        assert_eq!(9050, proxy.port);
+        assert!(proxy.remote_dns);
+        let proxy = SocksProxy::from_url("socks5h://[::1]").unwrap();
+        assert_eq!("::1", proxy.ip_hostname);
+        assert_eq!(1080, proxy.port);
+
+        assert!(SocksProxy::from_url("https://proxy.example.com:3128").is_none());
+        assert!(SocksProxy::from_url("socks5h://127.0.0.1:notaport").is_none());
+        assert!(SocksProxy::from_url("127.0.0.1:9050").is_none());
+    }
+
+    #[test]
+    fn connect_sends_the_hostname() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut greeting = [0; 3];
+            stream.read_exact(&mut greeting).unwrap();
+            assert_eq!([SOCKS_VERSION, 1, NO_AUTHENTICATION], greeting);
+            stream
+                .write_all(&[SOCKS_VERSION, NO_AUTHENTICATION])
+                .unwrap();
+
+            let hostname = b"example2345678.onion";
+            let mut request = vec![0; 5 + hostname.len() + 2];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!(
+                &[SOCKS_VERSION, CONNECT, 0x00, ATYP_DOMAIN_NAME],
+                &request[..4]
+            );
+            assert_eq!(hostname, &request[5..5 + hostname.len()]);
+            assert_eq!([0x00, 0x16], request[5 + hostname.len()..]);
+            stream
+                .write_all(&[SOCKS_VERSION, 0x00, 0x00, ATYP_IPV4, 0, 0, 0, 0, 0, 0])
+                .unwrap();
+            stream.write_all(b"SSH-2.0-OpenSSH_9.2p1\r\n").unwrap();
+        });
+
+        let proxy = SocksProxy::from_url(&format!("socks5h://127.0.0.1:{}", port)).unwrap();
+        let mut stream = proxy
+            .connect("example2345678.onion", 22, Some(Duration::new(1, 0)))
+            .unwrap();
+        let mut banner = String::new();
+        stream.read_to_string(&mut banner).unwrap();
+        assert_eq!("SSH-2.0-OpenSSH_9.2p1\r\n", banner);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn connect_reports_refusals() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut greeting = [0; 3];
+            stream.read_exact(&mut greeting).unwrap();
+            stream
+                .write_all(&[SOCKS_VERSION, NO_AUTHENTICATION])
+                .unwrap();
+            let mut request = [0; 10];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!(&[ATYP_IPV4, 10, 0, 0, 1], &request[3..8]);
+            // Connection refused
+            stream
+                .write_all(&[SOCKS_VERSION, 0x05, 0x00, ATYP_IPV4, 0, 0, 0, 0, 0, 0])
+                .unwrap();
+        });
+
+        let proxy = SocksProxy::from_url(&format!("socks5://127.0.0.1:{}", port)).unwrap();
+        let result = proxy.connect("10.0.0.1", 22, Some(Duration::new(1, 0)));
+        assert_eq!(ErrorKind::ConnectionRefused, result.unwrap_err().kind());
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn connect_to_the_proxy_is_bounded() {
+        // The documentation range is not routed
+        let start = std::time::Instant::now();
+        let proxy = SocksProxy::from_url("socks5h://192.0.2.1:1080").unwrap();
+        let result = proxy.connect("www.example.com", 22, Some(Duration::from_millis(200)));
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -1,13 +1,21 @@
 //! Fetch data over TCP
 //!
 //! The [`TcpReader`] fetches the banner of services running on TCP.
+//! For services which don't send a banner by themselves, it can also
+//! send a probe and read the raw response.
+//! The connections can go through a SOCKS5 proxy.
+//! Otherwise the hostname is resolved by the [`Resolver`], and its
+//! addresses are tried in turn, IPv4 or IPv6.
 
 use log::{debug, warn};
 use log::{error, trace};
 
+use super::resolver::Resolver;
+use super::socks::SocksProxy;
+
 use std::io::prelude::*;
 use std::io::Result as IoResult;
-use std::net::TcpStream;
+use std::net::{SocketAddr, TcpStream};
 use std::time::Duration;
 
 /// A TCP reader
@@ -16,6 +24,13 @@ pub struct TcpReader {
     pub ip_hostname: String,
     /// The port for connection
     pub port: u16,
+    /// The SOCKS proxy to connect through, if any
+    proxy: Option<SocksProxy>,
+    /// The resolver of the hostname, one without cache is used if none
+    resolver: Option<Resolver>,
+    /// The timeout of the connection, the reads and the writes, none if
+    /// zero
+    timeout: Duration,
 }
 
 impl TcpReader {
@@ -24,16 +39,85 @@ impl TcpReader {
         TcpReader {
             ip_hostname: ip_hostname.to_string(),
             port,
+            proxy: None,
+            resolver: None,
+            timeout: Duration::new(1, 0),
         }
     }
 
+    /// Waits up to the given duration for the data, 1 second by default.
+    /// A zero duration waits indefinitely.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Connects through the given SOCKS proxy.
+    pub fn with_proxy(mut self, proxy: SocksProxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Resolves the hostname with the given resolver.
+    /// It's not used with a proxy, which resolves the hostname itself.
+    pub fn with_resolver(mut self, resolver: Resolver) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
     /// Reads the given number of bytes.
     pub fn read(&self, bytes_to_read: usize) -> IoResult<String> {
         trace!("Running TcpReader::read()");
+        let data = self.read_raw(bytes_to_read)?;
+
+        let from_utf8 = String::from_utf8_lossy(&data);
+        return Ok(from_utf8.to_string());
+    }
+
+    /// Reads the given number of bytes, without converting them.
+    /// Some banners are binary (e.g. the MySQL handshake).
+    pub fn read_raw(&self, bytes_to_read: usize) -> IoResult<Vec<u8>> {
+        trace!("Running TcpReader::read_raw()");
+        let mut stream = self.connect()?;
+        self.read_stream(&mut stream, bytes_to_read)
+    }
+
+    /// Sends the probe and reads up to the given number of bytes of the
+    /// response.
+    /// The raw bytes are returned since the responses to probes are
+    /// usually binary.
+    pub fn probe(&self, payload: &[u8], bytes_to_read: usize) -> IoResult<Vec<u8>> {
+        trace!("Running TcpReader::probe()");
+        let mut stream = self.connect()?;
+        trace!("Sending a probe of {} bytes", payload.len());
+        stream.write_all(payload)?;
+        self.read_stream(&mut stream, bytes_to_read)
+    }
+
+    /// Connects to the remote host.
+    pub(crate) fn connect(&self) -> IoResult<TcpStream> {
+        let timeout = Some(self.timeout).filter(|timeout| !timeout.is_zero());
+        if let Some(proxy) = &self.proxy {
+            return proxy.connect(&self.ip_hostname, self.port, timeout);
+        }
+        // The IPv6 addresses can't be joined to the port with a colon
+        let socket_addrs: Vec<SocketAddr> = match &self.resolver {
+            Some(resolver) => resolver.resolve(&self.ip_hostname)?,
+            None => Resolver::new(None).resolve(&self.ip_hostname)?,
+        }
+        .into_iter()
+        .map(|ip| SocketAddr::new(ip, self.port))
+        .collect();
+        debug!("Connecting to {:?}", socket_addrs);
+        let stream = connect_timeout(&socket_addrs, timeout)?;
+        stream.set_read_timeout(timeout)?;
+        stream.set_write_timeout(timeout)?;
+        Ok(stream)
+    }
+
+    /// Reads the given number of bytes from the stream.
+    fn read_stream(&self, stream: &mut TcpStream, bytes_to_read: usize) -> IoResult<Vec<u8>> {
         trace!("Want to read {} bytes", bytes_to_read);
-        let mut stream = TcpStream::connect(format!("{}:{}", self.ip_hostname, self.port))?;
-        let timeout = Duration::new(1, 0);
-        stream.set_read_timeout(Some(timeout))?;
         // Store bytes in data, buffer is only temporary
         let mut data: Vec<u8> = Vec::new();
 
@@ -47,18 +131,28 @@ impl TcpReader {
             // If an error occures during reading, it is returned only
             // if no data has been read at all. If at least something was
             // read, stop reading and return what we already have.
-            if let Err(e) = read_result {
-                warn!("Got an error while reading over TCP: {:?}", e);
-                if data.len() == 0 {
-                    error!("No data has been read at all");
-                    return Err(e);
-                } else {
-                    debug!("Data have been read, use it and ignore the error");
+            match read_result {
+                Err(e) => {
+                    if data.is_empty() {
+                        warn!("Got an error while reading over TCP: {:?}", e);
+                        error!("No data has been read at all");
+                        return Err(e);
+                    } else {
+                        // Usually the read timeout, once the service sent
+                        // everything it had to send
+                        debug!("Got an error while reading over TCP: {:?}", e);
+                        debug!("Data have been read, use it and ignore the error");
+                        break;
+                    }
+                }
+                Ok(0) => {
+                    debug!("The connection has been closed by the remote host");
                     break;
                 }
-            } else {
-                trace!("Store the data read");
-                data.append(&mut buffer.to_vec());
+                Ok(read) => {
+                    trace!("Store the data read");
+                    data.extend_from_slice(&buffer[..read]);
+                }
             }
         }
         if data.len() > bytes_to_read {
@@ -66,7 +160,114 @@ impl TcpReader {
             data.truncate(bytes_to_read);
         }
 
-        let from_utf8 = String::from_utf8_lossy(&data);
-        return Ok(from_utf8.to_string());
+        Ok(data)
+    }
+}
+
+/// Connects to the first address accepting the connection, in turn.
+/// Each connection waits up to the timeout, or indefinitely with None.
+pub(crate) fn connect_timeout(
+    socket_addrs: &[SocketAddr],
+    timeout: Option<Duration>,
+) -> IoResult<TcpStream> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return TcpStream::connect(socket_addrs),
+    };
+    let mut last_error = None;
+    for socket_addr in socket_addrs {
+        match TcpStream::connect_timeout(socket_addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => {
+                debug!("Unable to connect to {}: {}", socket_addr, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "No address to connect to")
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::readers::resolver::AddressFamily;
+    use std::net::TcpListener;
+
+    /// Starts a server sending the banner to the first client.
+    fn start_server(address: &str, banner: &'static [u8]) -> u16 {
+        start_slow_server(address, banner, Duration::ZERO)
+    }
+
+    /// Starts a server sending the banner to the first client after the
+    /// delay.
+    fn start_slow_server(address: &str, banner: &'static [u8], delay: Duration) -> u16 {
+        let listener = TcpListener::bind(address).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            std::thread::sleep(delay);
+            // The client may be gone after its timeout
+            let _ = stream.write_all(banner);
+        });
+        port
+    }
+
+    #[test]
+    fn reads_over_ipv6() {
+        let port = start_server("[::1]:0", b"SSH-2.0-OpenSSH_9.6\r\n");
+        let tcp_reader = TcpReader::new("::1", port);
+        assert_eq!("SSH-2.0-OpenSSH_9.6\r\n", tcp_reader.read(64).unwrap());
+
+        let port = start_server("[::1]:0", b"220 ProFTPD Server\r\n");
+        let tcp_reader = TcpReader::new("[::1]", port).with_resolver(Resolver::new(None));
+        assert_eq!("220 ProFTPD Server\r\n", tcp_reader.read(64).unwrap());
+    }
+
+    #[test]
+    fn address_family_is_used() {
+        let port = start_server("[::1]:0", b"banner");
+        let resolver = Resolver::new(None);
+        resolver.add_override("ipv6.example.com", "::1".parse().unwrap());
+        resolver.add_override("ipv6.example.com", "192.0.2.1".parse().unwrap());
+        // The IPv4 address isn't reachable, the IPv6 one must be tried first
+        let tcp_reader = TcpReader::new("ipv6.example.com", port).with_resolver(
+            resolver
+                .clone()
+                .with_address_family(AddressFamily::PreferIpv6),
+        );
+        assert_eq!("banner", tcp_reader.read(64).unwrap());
+
+        // Without IPv4 address, nothing is tried
+        resolver.add_override("ipv6only.example.com", "::1".parse().unwrap());
+        let tcp_reader = TcpReader::new("ipv6only.example.com", port)
+            .with_resolver(resolver.with_address_family(AddressFamily::Ipv4Only));
+        assert!(tcp_reader.read(64).is_err());
+    }
+
+    #[test]
+    fn timeout_is_used() {
+        let delay = Duration::from_millis(300);
+        let port = start_slow_server("127.0.0.1:0", b"220 mail.example.com ESMTP", delay);
+        let tcp_reader = TcpReader::new("127.0.0.1", port).with_timeout(Duration::from_millis(50));
+        assert!(tcp_reader.read(64).is_err());
+
+        // Without timeout, the slow banner is read
+        let port = start_slow_server("127.0.0.1:0", b"220 mail.example.com ESMTP", delay);
+        let tcp_reader = TcpReader::new("127.0.0.1", port).with_timeout(Duration::ZERO);
+        assert_eq!("220 mail.example.com ESMTP", tcp_reader.read(26).unwrap());
+    }
+
+    #[test]
+    fn connect_timeout_is_used() {
+        // The documentation range is not routed, the connection hangs or
+        // fails at once, but never waits for the system timeout
+        let start = std::time::Instant::now();
+        let tcp_reader = TcpReader::new("192.0.2.1", 22).with_timeout(Duration::from_millis(200));
+        assert!(tcp_reader.read(64).is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        assert!(connect_timeout(&[], Some(Duration::from_millis(200))).is_err());
     }
 }
@@ -0,0 +1,284 @@
+//! Fetch the certificate of a TLS server
+//!
+//! The [`TlsReader`] performs a TLS handshake with the remote host and
+//! returns the attributes of its certificate. The certificate isn't
+//! verified: the self-signed and expired ones are read like the others,
+//! it's up to the checkers to report them.
+//! The connection is opened by a [`TcpReader`], so it uses its proxy,
+//! resolver and timeout.
+
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use log::{debug, trace};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, ClientConnection, DigitallySignedStruct, SignatureScheme};
+use x509_parser::prelude::{ASN1Time, FromDer, GeneralName, X509Certificate};
+
+use super::tcp::TcpReader;
+
+/// The attributes of a TLS certificate
+#[derive(Clone, Debug, PartialEq)]
+pub struct TlsCertificate {
+    /// The subject, e.g. CN=www.example.com, O=Example
+    pub subject: String,
+    /// The common name of the subject, if any
+    pub common_name: Option<String>,
+    /// The DNS names and IP addresses of the Subject Alternative Name
+    pub subject_alt_names: Vec<String>,
+    /// The issuer, e.g. C=US, O=Let's Encrypt, CN=R3
+    pub issuer: String,
+    /// The end of the validity, e.g. Jan  1 00:00:00 2030 +00:00
+    pub not_after: String,
+    /// Whether the validity ended
+    pub expired: bool,
+    /// Whether the certificate is issued by its subject
+    pub self_signed: bool,
+}
+
+impl TlsCertificate {
+    /// Parses a DER-encoded certificate.
+    pub fn from_der(der: &[u8]) -> Result<Self, String> {
+        let (_, certificate) = X509Certificate::from_der(der)
+            .map_err(|e| format!("Unable to parse the certificate: {}", e))?;
+
+        let common_name = certificate
+            .subject()
+            .iter_common_name()
+            .next()
+            .and_then(|common_name| common_name.as_str().ok())
+            .map(|common_name| common_name.to_string());
+        let subject_alt_names = match certificate.subject_alternative_name() {
+            Ok(Some(extension)) => extension
+                .value
+                .general_names
+                .iter()
+                .filter_map(|general_name| match general_name {
+                    GeneralName::DNSName(dns_name) => Some(dns_name.to_string()),
+                    GeneralName::IPAddress(bytes) => ip_from_bytes(bytes),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let not_after = certificate.validity().not_after;
+
+        Ok(Self {
+            subject: certificate.subject().to_string(),
+            common_name,
+            subject_alt_names,
+            issuer: certificate.issuer().to_string(),
+            not_after: not_after.to_string(),
+            expired: not_after < ASN1Time::now(),
+            self_signed: certificate.subject().as_raw() == certificate.issuer().as_raw(),
+        })
+    }
+}
+
+/// A TLS reader
+pub struct TlsReader {
+    /// The reader opening the TCP connection
+    tcp_reader: TcpReader,
+}
+
+impl TlsReader {
+    /// Creates a new TlsReader, connecting with the given TcpReader.
+    pub fn new(tcp_reader: TcpReader) -> Self {
+        TlsReader { tcp_reader }
+    }
+
+    /// Performs the TLS handshake and returns the certificate of the
+    /// server.
+    /// The hostname is sent with SNI, to get the certificate of the
+    /// virtual host.
+    pub fn read_certificate(&self) -> IoResult<TlsCertificate> {
+        trace!("Running TlsReader::read_certificate()");
+        let provider = Arc::new(ring::default_provider());
+        let config = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(IoError::other)?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)))
+            .with_no_client_auth();
+        let server_name = ServerName::try_from(self.tcp_reader.ip_hostname.as_str())
+            .map_err(|e| IoError::new(ErrorKind::InvalidInput, e))?
+            .to_owned();
+        let mut connection =
+            ClientConnection::new(Arc::new(config), server_name).map_err(IoError::other)?;
+
+        let mut stream = self.tcp_reader.connect()?;
+        debug!(
+            "TLS handshake with {}:{}",
+            self.tcp_reader.ip_hostname, self.tcp_reader.port
+        );
+        while connection.is_handshaking() {
+            connection.complete_io(&mut stream)?;
+        }
+
+        let certificate = connection
+            .peer_certificates()
+            .and_then(|certificates| certificates.first())
+            .ok_or_else(|| IoError::new(ErrorKind::InvalidData, "No certificate received"))?;
+        TlsCertificate::from_der(certificate.as_ref())
+            .map_err(|e| IoError::new(ErrorKind::InvalidData, e))
+    }
+}
+
+/// Accepts any certificate, to read the self-signed and the expired ones.
+/// The signatures of the handshake are still verified.
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Converts the bytes of an IP address of a Subject Alternative Name.
+fn ip_from_bytes(bytes: &[u8]) -> Option<String> {
+    match bytes.len() {
+        4 => Some(IpAddr::from(<[u8; 4]>::try_from(bytes).ok()?).to_string()),
+        16 => Some(IpAddr::from(<[u8; 16]>::try_from(bytes).ok()?).to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustls::pki_types::PrivatePkcs8KeyDer;
+    use rustls::{ServerConfig, ServerConnection};
+    use std::net::TcpListener;
+
+    const SELF_SIGNED: &[u8] = include_bytes!("../../tests/fixtures/tls-self-signed.der");
+    const EXPIRED: &[u8] = include_bytes!("../../tests/fixtures/tls-expired.der");
+    const KEY: &[u8] = include_bytes!("../../tests/fixtures/tls-key.der");
+
+    /// Starts a TLS server sending the certificate, for one handshake.
+    fn start_server(certificate: &'static [u8]) -> u16 {
+        let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![CertificateDer::from(certificate)],
+                PrivatePkcs8KeyDer::from(KEY).into(),
+            )
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut connection = ServerConnection::new(Arc::new(config)).unwrap();
+            while connection.is_handshaking() {
+                if connection.complete_io(&mut stream).is_err() {
+                    break;
+                }
+            }
+        });
+        port
+    }
+
+    #[test]
+    fn certificate_is_parsed() {
+        let certificate = TlsCertificate::from_der(SELF_SIGNED).unwrap();
+        assert_eq!("O=Sanca tests, CN=www.example.com", certificate.subject);
+        assert_eq!(Some("www.example.com".to_string()), certificate.common_name);
+        assert_eq!(
+            vec!["www.example.com", "example.com", "127.0.0.1"],
+            certificate.subject_alt_names
+        );
+        assert_eq!(certificate.subject, certificate.issuer);
+        assert!(certificate.not_after.contains("2126"));
+        assert!(!certificate.expired);
+        assert!(certificate.self_signed);
+
+        let certificate = TlsCertificate::from_der(EXPIRED).unwrap();
+        assert_eq!(
+            Some("expired.example.com".to_string()),
+            certificate.common_name
+        );
+        assert!(certificate.subject_alt_names.is_empty());
+        assert!(certificate.expired);
+
+        assert!(TlsCertificate::from_der(b"not a certificate").is_err());
+    }
+
+    #[test]
+    fn self_signed_certificate_is_read() {
+        let port = start_server(SELF_SIGNED);
+        let tls_reader = TlsReader::new(TcpReader::new("127.0.0.1", port));
+        let certificate = tls_reader.read_certificate().unwrap();
+        assert_eq!(Some("www.example.com".to_string()), certificate.common_name);
+        assert!(certificate.self_signed);
+    }
+
+    #[test]
+    fn expired_certificate_is_read() {
+        let port = start_server(EXPIRED);
+        let tls_reader = TlsReader::new(TcpReader::new("localhost", port));
+        let certificate = tls_reader.read_certificate().unwrap();
+        assert_eq!(
+            Some("expired.example.com".to_string()),
+            certificate.common_name
+        );
+        assert!(certificate.expired);
+    }
+
+    #[test]
+    fn read_certificate_reports_errors() {
+        // A server which isn't a TLS one
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            use std::io::Write;
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.write_all(b"SSH-2.0-OpenSSH_9.2p1\r\n");
+        });
+        let tls_reader = TlsReader::new(TcpReader::new("127.0.0.1", port));
+        assert!(tls_reader.read_certificate().is_err());
+    }
+}
@@ -0,0 +1,107 @@
+//! Fetch data over UDP
+//!
+//! The [`UdpReader`] sends a probe to services running on UDP and reads
+//! their response. Unlike most TCP services, UDP services never send
+//! anything by themselves, so a probe is always needed.
+
+use log::{debug, trace};
+
+use std::io::{Error, ErrorKind, Result as IoResult};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+/// A UDP reader
+pub struct UdpReader {
+    /// The IP address or hostname to send the probe to
+    pub ip_hostname: String,
+    /// The port to send the probe to
+    pub port: u16,
+    /// The time to wait for the response, indefinitely if zero
+    timeout: Duration,
+}
+
+impl UdpReader {
+    /// Creates a new UdpReader
+    pub fn new(ip_hostname: &str, port: u16) -> Self {
+        UdpReader {
+            ip_hostname: ip_hostname.to_string(),
+            port,
+            timeout: Duration::new(2, 0),
+        }
+    }
+
+    /// Waits up to the given duration for the response, 2 seconds by
+    /// default. A zero duration waits indefinitely.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends the probe and reads up to the given number of bytes of the
+    /// response.
+    /// An error is returned if no response is received within the timeout.
+    pub fn read(&self, payload: &[u8], bytes_to_read: usize) -> IoResult<Vec<u8>> {
+        trace!("Running UdpReader::read()");
+        let remote_addr: SocketAddr = (self.ip_hostname.as_str(), self.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("Unable to resolve {}", self.ip_hostname),
+                )
+            })?;
+
+        // Bind on the same IP version as the remote address
+        let local_addr = if remote_addr.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(local_addr)?;
+        socket.set_read_timeout(Some(self.timeout).filter(|timeout| !timeout.is_zero()))?;
+        socket.connect(remote_addr)?;
+
+        trace!("Sending a probe of {} bytes", payload.len());
+        socket.send(payload)?;
+
+        let mut buffer = vec![0; bytes_to_read];
+        let read = socket.recv(&mut buffer)?;
+        debug!("Received {} bytes over UDP", read);
+        buffer.truncate(read);
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_response() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = server.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let mut buffer = [0; 16];
+            let (read, from) = server.recv_from(&mut buffer).unwrap();
+            assert_eq!(b"probe", &buffer[..read]);
+            server.send_to(b"response", from).unwrap();
+        });
+
+        let udp_reader = UdpReader::new("127.0.0.1", port);
+        assert_eq!(
+            b"response".to_vec(),
+            udp_reader.read(b"probe", 1500).unwrap()
+        );
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn no_response_is_an_error() {
+        // Nothing answers on this socket
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = server.local_addr().unwrap().port();
+        let udp_reader = UdpReader::new("127.0.0.1", port).with_timeout(Duration::from_millis(100));
+        assert!(udp_reader.read(b"probe", 1500).is_err());
+    }
+}
@@ -1,18 +1,67 @@
 /// This module contains the FileCacheManager struct.
 use crate::models::{technology::Technology, Finding, CVE};
 use crate::vulnerabilities::cache_managers::CacheManager;
-use log::{error, trace};
+use log::{debug, error, trace};
 use std::env;
-use std::fs::{create_dir_all, File};
+use std::fs::{create_dir_all, metadata, File};
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 /// Represents a cache manager which stores the data in files.
-pub struct FileCacheManager {}
+pub struct FileCacheManager {
+    /// The directory where the CVEs are stored
+    root_dir: PathBuf,
+    /// The time after which the stored CVEs are downloaded again, if any
+    ttl: Option<Duration>,
+}
 
 impl FileCacheManager {
-    pub fn new() -> Self {
-        Self {}
+    /// Creates a cache stored in a "cves" directory next to the
+    /// executable, or in the current working directory if the executable
+    /// path is unknown.
+    /// Each source of vulnerabilities has its own directory (e.g.
+    /// cves/nvd), the CVEs of a source aren't the ones of another source.
+    pub fn new(source: &str) -> Self {
+        let root_dir = if let Ok(mut p) = env::current_exe() {
+            p.pop();
+            p.join("cves")
+        } else {
+            // By default, a "cves" directory is created in the current working directory
+            PathBuf::new().join("cves")
+        };
+        Self::from_dir(root_dir.join(source))
+    }
+
+    /// Creates a cache stored in the given directory.
+    pub fn from_dir(root_dir: PathBuf) -> Self {
+        Self {
+            root_dir,
+            ttl: None,
+        }
+    }
+
+    /// Ignores the CVEs stored for longer than the given time, so the
+    /// newly published CVEs are downloaded.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Checks whether the file has been stored for longer than the TTL.
+    /// The date of the last modification is the date it was stored.
+    fn is_stale(&self, filename: &Path) -> bool {
+        let ttl = match self.ttl {
+            Some(ttl) => ttl,
+            None => return false,
+        };
+        let stored_at = match metadata(filename).and_then(|m| m.modified()) {
+            Ok(stored_at) => stored_at,
+            Err(_) => return true,
+        };
+        SystemTime::now()
+            .duration_since(stored_at)
+            .is_ok_and(|age| age >= ttl)
     }
 }
 
@@ -24,10 +73,9 @@ impl CacheManager for FileCacheManager {
         if finding.version.is_none() {
             return false;
         }
-        if let Some(cves) = self.read(
-            finding.technology.clone(),
-            &finding.version.as_ref().unwrap(),
-        ) {
+        let version = finding.version.as_ref().unwrap();
+        if let Some(mut cves) = self.read(finding.technology.clone(), version) {
+            cves.retain(|cve| cve.version_matches(version));
             finding.vulnerabilities = cves;
             return true;
         } else {
@@ -38,16 +86,8 @@ impl CacheManager for FileCacheManager {
     /// Read the CVEs associated with a Technology & version in cache.
     fn read(&self, technology: Technology, version: &str) -> Option<Vec<CVE>> {
         trace!("Running FileCacheManager::read");
-        let root_dir = if let Ok(mut p) = env::current_exe() {
-            p.pop();
-            p.join("cves")
-        } else {
-            // By default, a "cves" directory is created in the current working directory
-            PathBuf::new().join("cves")
-        };
-
         let (_part, vendor, product) = technology.get_cpe_part_vendor_product();
-        let dirname = Path::new(&root_dir).join(vendor).join(product);
+        let dirname = self.root_dir.join(vendor).join(product);
         if !dirname.exists() {
             return None;
         }
@@ -56,6 +96,13 @@ impl CacheManager for FileCacheManager {
         if !filename.exists() {
             return None;
         }
+        if self.is_stale(&filename) {
+            debug!(
+                "The CVEs in {} are stale, download them again",
+                filename.to_string_lossy()
+            );
+            return None;
+        }
 
         let mut file = if let Ok(f) = File::open(filename.clone()) {
             f
@@ -88,21 +135,15 @@ impl CacheManager for FileCacheManager {
     ///
     /// The cache has the following structure:
     /// cves/
-    ///   \__<cpe_vendor>/
-    ///        \__<cpe_product>
-    ///              \__<version>.json
+    ///   \__<source>/
+    ///        \__<cpe_vendor>/
+    ///              \__<cpe_product>
+    ///                    \__<version>.json
     ///
-    /// The JSON files contain an array of CVEs.
+    /// The JSON files contain an array of CVEs. A file already stored is
+    /// replaced, since it's only downloaded again when stale.
     fn store(&self, vulns: Vec<CVE>, technology: Technology, version: &str) {
         trace!("Running FileCacheManager::store");
-        let root_dir = if let Ok(mut p) = env::current_exe() {
-            p.pop();
-            p.join("cves")
-        } else {
-            // By default, a "cves" directory will be created in the current working directory
-            PathBuf::new().join("cves")
-        };
-
         let vulns_json = if let Ok(j) = serde_json::value::to_value(vulns) {
             format!("{:#}", j)
         } else {
@@ -111,7 +152,7 @@ impl CacheManager for FileCacheManager {
         };
 
         let (_part, vendor, product) = technology.get_cpe_part_vendor_product();
-        let dirname = Path::new(&root_dir).join(vendor).join(product);
+        let dirname = self.root_dir.join(vendor).join(product);
         if !dirname.exists() {
             let create_result = create_dir_all(&dirname);
             if create_result.is_err() {
@@ -123,17 +164,75 @@ impl CacheManager for FileCacheManager {
             }
         }
         let filename = dirname.join(&format!("{}.json", version));
-        if !filename.exists() {
-            let mut file = if let Ok(f) = File::create(filename.clone()) {
-                f
-            } else {
-                error!("Unable to create the file {}", filename.to_string_lossy());
-                return;
-            };
+        let mut file = if let Ok(f) = File::create(filename.clone()) {
+            f
+        } else {
+            error!("Unable to create the file {}", filename.to_string_lossy());
+            return;
+        };
 
-            if file.write_all(vulns_json.as_bytes()).is_err() {
-                error!("Unable to write the file {}", filename.to_string_lossy());
-            }
+        if file.write_all(vulns_json.as_bytes()).is_err() {
+            error!("Unable to write the file {}", filename.to_string_lossy());
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_and_reads_cves() {
+        let root_dir = env::temp_dir().join(format!("sanca-cves-{}", std::process::id()));
+        let cache_manager = FileCacheManager::from_dir(root_dir.clone());
+        assert!(cache_manager.read(Technology::JQuery, "1.12.4").is_none());
+
+        let cves = vec![CVE {
+            cve_id: "CVE-2020-11022".to_string(),
+            base_score: 6.1,
+            cvss_version: "3.1".to_string(),
+            version_ranges: Vec::new(),
+        }];
+        cache_manager.store(cves, Technology::JQuery, "1.12.4");
+        let read_cves = cache_manager.read(Technology::JQuery, "1.12.4").unwrap();
+        assert_eq!(1, read_cves.len());
+        assert_eq!("CVE-2020-11022", read_cves[0].cve_id);
+
+        // The stored CVEs are fresh for a day, and stale with a zero TTL
+        let fresh =
+            FileCacheManager::from_dir(root_dir.clone()).with_ttl(Duration::from_secs(86400));
+        assert!(fresh.read(Technology::JQuery, "1.12.4").is_some());
+        let stale = FileCacheManager::from_dir(root_dir.clone()).with_ttl(Duration::ZERO);
+        assert!(stale.read(Technology::JQuery, "1.12.4").is_none());
+
+        // A stale entry is replaced when downloaded again
+        stale.store(Vec::new(), Technology::JQuery, "1.12.4");
+        assert!(fresh
+            .read(Technology::JQuery, "1.12.4")
+            .is_some_and(|cves| cves.is_empty()));
+
+        std::fs::remove_dir_all(&root_dir).unwrap();
+    }
+
+    #[test]
+    fn sources_are_kept_apart() {
+        let root_dir = env::temp_dir().join(format!("sanca-cves-sources-{}", std::process::id()));
+        let nvd = FileCacheManager::from_dir(root_dir.join("nvd"));
+        let ghsa = FileCacheManager::from_dir(root_dir.join("ghsa"));
+        let cves = vec![CVE {
+            cve_id: "CVE-2020-11022".to_string(),
+            base_score: 6.1,
+            cvss_version: "3.1".to_string(),
+            version_ranges: Vec::new(),
+        }];
+        nvd.store(cves, Technology::JQuery, "1.12.4");
+        assert!(ghsa.read(Technology::JQuery, "1.12.4").is_none());
+        ghsa.store(Vec::new(), Technology::JQuery, "1.12.4");
+        assert!(nvd
+            .read(Technology::JQuery, "1.12.4")
+            .is_some_and(|cves| cves.len() == 1));
+
+        std::fs::remove_dir_all(&root_dir).unwrap();
+        assert!(FileCacheManager::new("nvd").root_dir.ends_with("cves/nvd"));
+    }
+}
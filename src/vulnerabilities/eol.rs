@@ -0,0 +1,221 @@
+//! Flag the end-of-life versions
+//!
+//! The versions past their end of life don't receive security fixes
+//! anymore, even if no CVE is known for them yet. The dataset is bundled
+//! in the endoflife.date format, keyed by the product names given by
+//! [`Technology::get_eol_product`]. Since it's a snapshot, the versions
+//! released after it are left unknown.
+
+use crate::models::{get_timestamp, technology::Technology, version::Version, Finding};
+use log::{debug, trace};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The bundled end-of-life dataset
+const EOL_DATASET: &str = include_str!("eol.json");
+
+/// The end of life of a release cycle, as given by endoflife.date.
+/// It's either a date (YYYY-MM-DD) or a flag.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum EolValue {
+    /// The date of the end of life
+    Date(String),
+    /// Whether the cycle is past its end of life
+    Flag(bool),
+}
+
+/// A release cycle of a product.
+#[derive(Debug, Deserialize)]
+struct Cycle {
+    /// The release cycle. Example: 7.4
+    cycle: String,
+    /// The end of life of the cycle
+    eol: EolValue,
+}
+
+/// Flags the findings whose version is past its end of life.
+pub fn complete_findings(findings: &mut [Finding]) {
+    trace!("Running eol::complete_findings()");
+    // The dataset is bundled, it's always valid
+    let dataset: HashMap<String, Vec<Cycle>> =
+        serde_json::from_str(EOL_DATASET).expect("Invalid bundled end-of-life dataset");
+    // YYYY-MM-DD
+    let today = get_timestamp()[..10].to_string();
+
+    for finding in findings.iter_mut() {
+        if let Some(version) = &finding.version {
+            finding.eol = is_eol(&dataset, &finding.technology, version, &today);
+            debug!(
+                "End of life of {} {}: {:?}",
+                finding.technology, version, finding.eol
+            );
+        }
+    }
+}
+
+/// Checks whether the version of the technology is past its end of life
+/// at the given date (YYYY-MM-DD).
+/// Some(true) if it is, Some(false) if it isn't, None if it's unknown.
+fn is_eol(
+    dataset: &HashMap<String, Vec<Cycle>>,
+    technology: &Technology,
+    version: &str,
+    today: &str,
+) -> Option<bool> {
+    let cycles = dataset.get(technology.get_eol_product()?)?;
+
+    // The OS versions can be several candidates (e.g. 20.04|22.04),
+    // they must all agree
+    let results: Vec<Option<bool>> = version
+        .split('|')
+        .map(|candidate| is_cycle_eol(cycles, candidate, today))
+        .collect();
+    if results.iter().all(|result| *result == Some(true)) {
+        Some(true)
+    } else if results.iter().all(|result| *result == Some(false)) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Checks whether the version is past its end of life, according to the
+/// cycle it belongs to.
+fn is_cycle_eol(cycles: &[Cycle], version: &str, today: &str) -> Option<bool> {
+    let components = get_numeric_components(version);
+    if components.is_empty() {
+        return None;
+    }
+
+    // The most specific cycle is used (e.g. 1.1.1 rather than 1)
+    let cycle = cycles
+        .iter()
+        .filter(|cycle| components.starts_with(&get_numeric_components(&cycle.cycle)))
+        .max_by_key(|cycle| get_numeric_components(&cycle.cycle).len());
+
+    match cycle {
+        Some(cycle) => Some(match &cycle.eol {
+            EolValue::Flag(flag) => *flag,
+            EolValue::Date(date) => date.as_str() <= today,
+        }),
+        None => {
+            // Older than all the cycles of the dataset
+            let version = Version::parse(version);
+            let oldest = cycles
+                .iter()
+                .map(|cycle| Version::parse(&cycle.cycle))
+                .min()?;
+            if version < oldest {
+                Some(true)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Returns the leading numeric components of the version.
+/// Example: 1.0.2k-fips gives [1, 0, 2]
+fn get_numeric_components(version: &str) -> Vec<u64> {
+    let mut components = Vec::new();
+    for component in version.trim().split('.') {
+        let digits: String = component
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        match digits.parse() {
+            Ok(number) => components.push(number),
+            Err(_) => break,
+        }
+        // A suffix ends the numeric part (e.g. 2k-fips)
+        if digits.len() != component.len() {
+            break;
+        }
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_dataset() -> HashMap<String, Vec<Cycle>> {
+        serde_json::from_str(EOL_DATASET).unwrap()
+    }
+
+    #[test]
+    fn is_eol_works() {
+        let dataset = get_dataset();
+        let today = "2025-06-01";
+        assert_eq!(
+            Some(true),
+            is_eol(&dataset, &Technology::PHP, "7.4.33", today)
+        );
+        assert_eq!(
+            Some(false),
+            is_eol(&dataset, &Technology::PHP, "8.3.6", today)
+        );
+        // Older than the dataset
+        assert_eq!(
+            Some(true),
+            is_eol(&dataset, &Technology::PHP, "5.2.17", today)
+        );
+        // Newer than the dataset
+        assert_eq!(None, is_eol(&dataset, &Technology::PHP, "9.0.1", today));
+        assert_eq!(
+            Some(true),
+            is_eol(&dataset, &Technology::OpenSSL, "1.0.2k-fips", today)
+        );
+        assert_eq!(
+            Some(false),
+            is_eol(&dataset, &Technology::OpenSSL, "3.0.13", today)
+        );
+        assert_eq!(
+            Some(true),
+            is_eol(&dataset, &Technology::JQuery, "1.12.4", today)
+        );
+        assert_eq!(
+            Some(false),
+            is_eol(&dataset, &Technology::Httpd, "2.4.62", today)
+        );
+        assert_eq!(None, is_eol(&dataset, &Technology::OpenSSH, "9.2", today));
+    }
+
+    #[test]
+    fn os_candidates_must_agree() {
+        let dataset = get_dataset();
+        let today = "2025-06-01";
+        assert_eq!(
+            Some(true),
+            is_eol(&dataset, &Technology::Ubuntu, "18.04", today)
+        );
+        assert_eq!(
+            None,
+            is_eol(&dataset, &Technology::Ubuntu, "20.04|22.04", today)
+        );
+        assert_eq!(
+            Some(true),
+            is_eol(&dataset, &Technology::Ubuntu, "22.10|23.04", today)
+        );
+    }
+
+    #[test]
+    fn complete_findings_works() {
+        let mut findings = vec![
+            Finding::new(Technology::PHP, Some("5.6.40"), "PHP/5.6.40", "PHP", None),
+            Finding::new(Technology::PHP, None, "PHP", "PHP", None),
+        ];
+        complete_findings(&mut findings);
+        assert_eq!(Some(true), findings[0].eol);
+        assert_eq!(None, findings[1].eol);
+    }
+
+    #[test]
+    fn get_numeric_components_works() {
+        assert_eq!(vec![1, 0, 2], get_numeric_components("1.0.2k-fips"));
+        assert_eq!(vec![2, 4, 29], get_numeric_components("2.4.29"));
+        assert_eq!(vec![10, 11, 6], get_numeric_components("10.11.6-MariaDB"));
+        assert!(get_numeric_components("unknown").is_empty());
+    }
+}
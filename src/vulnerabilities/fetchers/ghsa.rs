@@ -0,0 +1,394 @@
+use crate::models::{version::Version, Finding, CVE as CVEModel};
+use crate::vulnerabilities::cache_managers::CacheManager;
+use crate::vulnerabilities::fetchers::VulnFetcher;
+/// This module implements a vulnerability fetcher which downloads
+/// the advisories from the GitHub Security Advisory database (GHSA).
+/// https://docs.github.com/en/graphql/reference/queries#securityvulnerabilities
+///
+/// Only the JavaScript libraries published on npm are supported, the
+/// NVD has a weak coverage for them.
+use log::{debug, error, trace, warn};
+use serde::Deserialize;
+use serde_json::json;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::thread;
+use std::time::Duration;
+
+/// The endpoint of the GitHub GraphQL API
+const GITHUB_GRAPHQL_URL: &str = "https://api.github.com/graphql";
+/// The query listing the vulnerabilities of a package
+const VULNERABILITIES_QUERY: &str = "query($package: String!, $after: String) {
+  securityVulnerabilities(ecosystem: NPM, package: $package, first: 100, after: $after) {
+    nodes {
+      vulnerableVersionRange
+      advisory { ghsaId withdrawnAt identifiers { type value } cvss { score vectorString } }
+    }
+    pageInfo { hasNextPage endCursor }
+  }
+}";
+/// The longest time to wait when the rate limit is reached, in seconds
+const MAX_RETRY_AFTER: u64 = 60;
+
+/// This structs represents the GHSA vulnerabilities fetcher.
+pub struct GHSAFetcher {
+    /// An optional cache manager to reduce API calls.
+    cache: Option<Box<dyn CacheManager>>,
+    /// The GitHub token, the API can't be used without it.
+    token: Option<String>,
+    /// Whether the rate limit has been reached, no more requests are
+    /// sent in this case.
+    rate_limited: Cell<bool>,
+}
+
+impl GHSAFetcher {
+    /// Uses the given GitHub token for the requests to the API.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Fetches the vulnerabilities for a finding
+    pub fn fetch_vulns(&self, finding: &mut Finding) {
+        trace!("Running GHSAFetcher::fetch_vulns()");
+        let package = match finding.technology.get_npm_package() {
+            Some(package) => package,
+            None => {
+                debug!("The technology is not published on npm, it's ignored.");
+                return;
+            }
+        };
+        let version = match &finding.version {
+            Some(version) => version.clone(),
+            None => {
+                debug!("Version is empty, technology is ignored.");
+                return;
+            }
+        };
+        let token = match &self.token {
+            Some(token) => token,
+            None => {
+                error!("A GitHub token is required to use the GHSA.");
+                return;
+            }
+        };
+
+        let mut after: Option<String> = None;
+        loop {
+            if self.rate_limited.get() {
+                warn!("GitHub rate limit reached, {} is ignored.", package);
+                return;
+            }
+            let response = match self.send_query(token, package, after.as_deref()) {
+                Some(response) => response,
+                None => return,
+            };
+
+            let vulnerabilities = response.data.security_vulnerabilities;
+            for node in vulnerabilities.nodes {
+                if node.advisory.withdrawn_at.is_some()
+                    || !version_matches_range(&version, &node.vulnerable_version_range)
+                {
+                    continue;
+                }
+                let cve: CVEModel = node.advisory.into();
+                if !finding.vulnerabilities.contains(&cve) {
+                    finding.vulnerabilities.push(cve);
+                }
+            }
+
+            if !vulnerabilities.page_info.has_next_page {
+                break;
+            }
+            after = vulnerabilities.page_info.end_cursor;
+        }
+
+        if let Some(c) = self.cache.as_ref() {
+            c.store(
+                finding.vulnerabilities.clone(),
+                finding.technology.clone(),
+                &version,
+            );
+        }
+    }
+
+    /// Sends the query for a page of vulnerabilities.
+    /// When the rate limit is reached, the request is sent again once if
+    /// GitHub asks to wait for a short time.
+    fn send_query(&self, token: &str, package: &str, after: Option<&str>) -> Option<APIResponse> {
+        let body = json!({
+            "query": VULNERABILITIES_QUERY,
+            "variables": { "package": package, "after": after },
+        });
+        let client = reqwest::blocking::Client::new();
+        for attempt in 0..2 {
+            let response_opt = client
+                .post(GITHUB_GRAPHQL_URL)
+                .bearer_auth(token)
+                .header("User-Agent", "Sanca")
+                .json(&body)
+                .send();
+            let response = match response_opt {
+                Ok(response) => response,
+                Err(e) => {
+                    error!("Error while communicating with GitHub. {:?}", e);
+                    return None;
+                }
+            };
+
+            let status = response.status().as_u16();
+            let remaining = response
+                .headers()
+                .get("x-ratelimit-remaining")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok());
+            if status == 429 || (status == 403 && remaining == Some(0)) {
+                let retry_after = response
+                    .headers()
+                    .get("retry-after")
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse::<u64>().ok());
+                match retry_after {
+                    Some(seconds) if attempt == 0 && seconds <= MAX_RETRY_AFTER => {
+                        warn!("GitHub rate limit reached, retrying in {}s", seconds);
+                        thread::sleep(Duration::from_secs(seconds));
+                        continue;
+                    }
+                    _ => {
+                        error!("GitHub rate limit reached, the next advisories are ignored.");
+                        self.rate_limited.set(true);
+                        return None;
+                    }
+                }
+            }
+            if !response.status().is_success() {
+                error!("Invalid HTTP response code: {}", response.status());
+                return None;
+            }
+
+            return match response.json::<APIResponse>() {
+                Ok(api_response) => Some(api_response),
+                Err(e) => {
+                    error!("Invalid JSON returned by GitHub API. {:?}", e);
+                    None
+                }
+            };
+        }
+        None
+    }
+}
+
+impl VulnFetcher for GHSAFetcher {
+    /// Creates a new GHSAFetcher.
+    fn new(cache: Option<Box<dyn CacheManager>>) -> Self {
+        Self {
+            cache,
+            token: None,
+            rate_limited: Cell::new(false),
+        }
+    }
+
+    /// Complete the findings with vulnerabilities coming from the GHSA.
+    fn complete_findings(&self, findings: &mut Vec<Finding>) {
+        for finding in findings {
+            let cache_found = match &self.cache {
+                Some(cache) => cache.complete_finding(finding),
+                None => false,
+            };
+            if !cache_found {
+                self.fetch_vulns(finding);
+            }
+        }
+    }
+}
+
+/// Checks whether the version is in the range given by the GHSA.
+/// Example of range: ">= 1.2.0, < 3.5.0"
+fn version_matches_range(version: &str, range: &str) -> bool {
+    range.split(',').all(|constraint| {
+        let constraint = constraint.trim();
+        let (operator, bound) = match constraint.find(|c: char| c.is_ascii_alphanumeric()) {
+            Some(position) => constraint.split_at(position),
+            None => return false,
+        };
+        let ordering = Version::parse(version).cmp(&Version::parse(bound.trim()));
+        match operator.trim() {
+            "<" => ordering == Ordering::Less,
+            "<=" => ordering != Ordering::Greater,
+            ">" => ordering == Ordering::Greater,
+            ">=" => ordering != Ordering::Less,
+            "=" | "" => ordering == Ordering::Equal,
+            _ => false,
+        }
+    })
+}
+
+/// Represents the main JSON object returned by the GitHub GraphQL API.
+#[derive(Debug, Deserialize)]
+pub struct APIResponse {
+    /// The data of the response.
+    pub data: APIData,
+}
+
+/// Represents the data returned as part of the APIResponse.
+#[derive(Debug, Deserialize)]
+pub struct APIData {
+    /// The vulnerabilities of the package.
+    #[serde(rename = "securityVulnerabilities")]
+    pub security_vulnerabilities: SecurityVulnerabilities,
+}
+
+/// Represents a page of vulnerabilities.
+#[derive(Debug, Deserialize)]
+pub struct SecurityVulnerabilities {
+    /// The vulnerabilities.
+    pub nodes: Vec<SecurityVulnerability>,
+    /// The pagination data.
+    #[serde(rename = "pageInfo")]
+    pub page_info: PageInfo,
+}
+
+/// Represents the pagination data of a page of vulnerabilities.
+#[derive(Debug, Deserialize)]
+pub struct PageInfo {
+    /// Whether there are more vulnerabilities.
+    #[serde(rename = "hasNextPage")]
+    pub has_next_page: bool,
+    /// The cursor to use to get the next page.
+    #[serde(rename = "endCursor")]
+    pub end_cursor: Option<String>,
+}
+
+/// Represents a vulnerability of a package.
+#[derive(Debug, Deserialize)]
+pub struct SecurityVulnerability {
+    /// The range of the vulnerable versions.
+    /// Example: >= 1.2.0, < 3.5.0
+    #[serde(rename = "vulnerableVersionRange")]
+    pub vulnerable_version_range: String,
+    /// The advisory of the vulnerability.
+    pub advisory: SecurityAdvisory,
+}
+
+/// Represents an advisory.
+#[derive(Debug, Deserialize)]
+pub struct SecurityAdvisory {
+    /// The GHSA identifier.
+    /// Example: GHSA-gxr4-xjj5-5px2
+    #[serde(rename = "ghsaId")]
+    pub ghsa_id: String,
+    /// When the advisory was withdrawn, if it was.
+    #[serde(rename = "withdrawnAt")]
+    pub withdrawn_at: Option<String>,
+    /// The identifiers of the advisory (GHSA and CVE).
+    pub identifiers: Vec<AdvisoryIdentifier>,
+    /// The CVSS of the advisory.
+    pub cvss: Option<AdvisoryCVSS>,
+}
+
+/// Represents an identifier of an advisory.
+#[derive(Debug, Deserialize)]
+pub struct AdvisoryIdentifier {
+    /// The type of identifier.
+    /// Example: CVE
+    #[serde(rename = "type")]
+    pub identifier_type: String,
+    /// The identifier.
+    /// Example: CVE-2020-11022
+    pub value: String,
+}
+
+/// Represents the CVSS of an advisory.
+#[derive(Debug, Deserialize)]
+pub struct AdvisoryCVSS {
+    /// The base score.
+    /// Example: 6.9
+    pub score: f64,
+    /// The vector string.
+    /// Example: CVSS:3.1/AV:N/AC:H/PR:N/UI:R/S:C/C:H/I:L/N:N
+    #[serde(rename = "vectorString")]
+    pub vector_string: Option<String>,
+}
+
+/// The CVE identifier is preferred, the GHSA one is used for the
+/// advisories without CVE.
+impl From<SecurityAdvisory> for CVEModel {
+    fn from(advisory: SecurityAdvisory) -> Self {
+        let cve_id = advisory
+            .identifiers
+            .iter()
+            .find(|identifier| identifier.identifier_type == "CVE")
+            .map(|identifier| identifier.value.clone())
+            .unwrap_or(advisory.ghsa_id);
+        let (base_score, cvss_version) = match advisory.cvss {
+            Some(cvss) => {
+                // Example: CVSS:3.1/AV:N[...] gives 3.1
+                let cvss_version = cvss
+                    .vector_string
+                    .as_deref()
+                    .and_then(|vector| vector.strip_prefix("CVSS:"))
+                    .and_then(|vector| vector.split('/').next())
+                    .unwrap_or("")
+                    .to_string();
+                (cvss.score, cvss_version)
+            }
+            None => (0.0, String::new()),
+        };
+
+        Self {
+            cve_id,
+            base_score,
+            cvss_version,
+            version_ranges: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::technology::Technology;
+
+    #[test]
+    fn version_matches_range_works() {
+        assert!(version_matches_range("3.4.1", "< 3.5.0"));
+        assert!(!version_matches_range("3.5.0", "< 3.5.0"));
+        assert!(version_matches_range("1.12.4", ">= 1.2.0, < 3.5.0"));
+        assert!(!version_matches_range("1.1.9", ">= 1.2.0, < 3.5.0"));
+        assert!(version_matches_range("4.17.20", "<= 4.17.20"));
+        assert!(version_matches_range("1.0.0", "= 1.0.0"));
+        assert!(!version_matches_range("1.0.1", "= 1.0.0"));
+        assert!(version_matches_range("1.10.0", "> 1.9.2"));
+        assert!(!version_matches_range("1.0.0", ""));
+    }
+
+    #[test]
+    fn response_is_parsed() {
+        let json = r#"{"data":{"securityVulnerabilities":{"nodes":[
+            {"vulnerableVersionRange":">= 1.2.0, < 3.5.0","advisory":{"ghsaId":"GHSA-gxr4-xjj5-5px2","withdrawnAt":null,
+                "identifiers":[{"type":"GHSA","value":"GHSA-gxr4-xjj5-5px2"},{"type":"CVE","value":"CVE-2020-11022"}],
+                "cvss":{"score":6.9,"vectorString":"CVSS:3.1/AV:N/AC:H/PR:N/UI:R/S:C/C:H/I:L/A:N"}}},
+            {"vulnerableVersionRange":"< 1.9.0","advisory":{"ghsaId":"GHSA-2pqj-h3vj-pqgw","withdrawnAt":null,
+                "identifiers":[{"type":"GHSA","value":"GHSA-2pqj-h3vj-pqgw"}],"cvss":{"score":0.0,"vectorString":null}}}
+        ],"pageInfo":{"hasNextPage":false,"endCursor":"Y3Vyc29yOjI="}}}}"#;
+        let response: APIResponse = serde_json::from_str(json).unwrap();
+        let mut nodes = response.data.security_vulnerabilities.nodes.into_iter();
+
+        let cve: CVEModel = nodes.next().unwrap().advisory.into();
+        assert_eq!("CVE-2020-11022", cve.cve_id);
+        assert_eq!(6.9, cve.base_score);
+        assert_eq!("3.1", cve.cvss_version);
+
+        let cve: CVEModel = nodes.next().unwrap().advisory.into();
+        assert_eq!("GHSA-2pqj-h3vj-pqgw", cve.cve_id);
+        assert_eq!("", cve.cvss_version);
+    }
+
+    #[test]
+    fn technologies_without_package_are_ignored() {
+        let fetcher = GHSAFetcher::new(None).with_token("token");
+        let mut finding = Finding::new(Technology::OpenSSH, Some("8.9"), "", "", None);
+        fetcher.fetch_vulns(&mut finding);
+        assert!(finding.vulnerabilities.is_empty());
+    }
+}
@@ -1,5 +1,7 @@
 /// The module fetchers defines the vulnerabilities fetchers.
+pub mod ghsa;
 pub mod nvd;
+pub mod osv;
 
 use crate::models::Finding;
 use crate::vulnerabilities::cache_managers::CacheManager;
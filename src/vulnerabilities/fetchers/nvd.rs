@@ -1,4 +1,4 @@
-use crate::models::{Finding, CVE as CVEModel};
+use crate::models::{version::VersionRange, Finding, CVE as CVEModel};
 use crate::vulnerabilities::cache_managers::CacheManager;
 use crate::vulnerabilities::fetchers::VulnFetcher;
 /// This module implements a vulnerability fetcher which downloads
@@ -11,9 +11,17 @@ use serde::Deserialize;
 pub struct NVDFetcher {
     /// An optional cache manager to reduce API calls.
     cache: Option<Box<dyn CacheManager>>,
+    /// An optional API key, raising the rate limit of the NVD.
+    api_key: Option<String>,
 }
 
 impl NVDFetcher {
+    /// Uses the given API key for the requests to the NVD.
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
     /// Fetches the vulnerabilities for a finding
     pub fn fetch_vulns(&self, finding: &mut Finding) {
         trace!("Running NVDFetcher::fetch_vulns()");
@@ -24,7 +32,11 @@ impl NVDFetcher {
         }
         // Example: https://services.nvd.nist.gov/rest/json/cves/2.0?noRejected&cpeName=cpe:2.3:a:jquery:jquery:1.8.3
         let nvd_url = format!("https://services.nvd.nist.gov/rest/json/cves/2.0?noRejected&cpeName=cpe:2.3:{}:{}:{}:{}", part, vendor, product, finding.version.as_ref().unwrap());
-        let response_opt = reqwest::blocking::get(nvd_url);
+        let mut request = reqwest::blocking::Client::new().get(nvd_url);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("apiKey", api_key);
+        }
+        let response_opt = request.send();
         let response = if let Err(e) = response_opt {
             error!("Error while communicating with NVD. {:?}", e);
             return;
@@ -44,8 +56,18 @@ impl NVDFetcher {
             return;
         };
 
+        let version = finding.version.as_ref().unwrap();
         for vulnerability in nvd_response.vulnerabilities {
-            let cve: CVEModel = vulnerability.into();
+            let version_ranges = vulnerability
+                .cve
+                .get_version_ranges(&part, &vendor, &product);
+            let mut cve: CVEModel = vulnerability.into();
+            cve.version_ranges = version_ranges;
+            // The patched versions can match the CPE of a vulnerable one
+            if !cve.version_matches(version) {
+                debug!("{} doesn't affect the version {}", cve.cve_id, version);
+                continue;
+            }
             if cve.base_score > 0.0 && !finding.vulnerabilities.contains(&cve) {
                 finding.vulnerabilities.push(cve);
             }
@@ -64,7 +86,10 @@ impl NVDFetcher {
 impl VulnFetcher for NVDFetcher {
     /// Creates a new NVDFetcher.
     fn new(cache: Option<Box<dyn CacheManager>>) -> Self {
-        Self { cache }
+        Self {
+            cache,
+            api_key: None,
+        }
     }
 
     /// Complete the findings with vulnerabilities coming from the NVD.
@@ -133,6 +158,71 @@ pub struct CVE {
     pub descriptions: Vec<CVEDescription>,
     /// The metrics related to the CVE.
     pub metrics: CVEMetrics,
+    /// The configurations affected by the CVE.
+    #[serde(default)]
+    pub configurations: Vec<CVEConfiguration>,
+}
+
+impl CVE {
+    /// Returns the ranges of the vulnerable versions of the product.
+    /// The CPE matches of the other products are ignored.
+    pub fn get_version_ranges(&self, part: &str, vendor: &str, product: &str) -> Vec<VersionRange> {
+        let prefix = format!("cpe:2.3:{}:{}:{}:", part, vendor, product);
+        self.configurations
+            .iter()
+            .flat_map(|configuration| configuration.nodes.iter())
+            .flat_map(|node| node.cpe_match.iter())
+            .filter(|cpe_match| cpe_match.vulnerable && cpe_match.criteria.starts_with(&prefix))
+            .map(|cpe_match| VersionRange {
+                // Example: cpe:2.3:a:jquery:jquery:1.8.3:*:*:*:*:*:*:*
+                version: cpe_match.criteria[prefix.len()..]
+                    .split(':')
+                    .next()
+                    .map(|version| version.to_string()),
+                start_including: cpe_match.version_start_including.clone(),
+                start_excluding: cpe_match.version_start_excluding.clone(),
+                end_including: cpe_match.version_end_including.clone(),
+                end_excluding: cpe_match.version_end_excluding.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Represents a configuration affected by a CVE.
+#[derive(Debug, Deserialize)]
+pub struct CVEConfiguration {
+    /// The nodes of the configuration.
+    pub nodes: Vec<CVEConfigurationNode>,
+}
+
+/// Represents a node of a configuration.
+#[derive(Debug, Deserialize)]
+pub struct CVEConfigurationNode {
+    /// The CPE matched by the node.
+    #[serde(rename = "cpeMatch", default)]
+    pub cpe_match: Vec<CPEMatch>,
+}
+
+/// Represents a CPE match, the products and versions it covers.
+#[derive(Debug, Deserialize)]
+pub struct CPEMatch {
+    /// Whether the matched products are vulnerable.
+    pub vulnerable: bool,
+    /// The CPE, with the exact version or *.
+    /// Example: cpe:2.3:a:jquery:jquery:*:*:*:*:*:*:*:*
+    pub criteria: String,
+    /// The first version affected.
+    #[serde(rename = "versionStartIncluding")]
+    pub version_start_including: Option<String>,
+    /// The last version not affected, before the first one affected.
+    #[serde(rename = "versionStartExcluding")]
+    pub version_start_excluding: Option<String>,
+    /// The last version affected.
+    #[serde(rename = "versionEndIncluding")]
+    pub version_end_including: Option<String>,
+    /// The first version fixed.
+    #[serde(rename = "versionEndExcluding")]
+    pub version_end_excluding: Option<String>,
 }
 
 /// Represents a description as part of a CVE.
@@ -258,3 +348,67 @@ pub struct CVSS2Data {
 }
 
 impl CVSSData for CVSS2Data {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_ranges_are_read() {
+        let json = r#"{
+            "cve": {
+                "id": "CVE-2020-11022",
+                "sourceIdentifier": "security-advisories@github.com",
+                "published": "2020-04-29T22:15:11.903",
+                "lastModified": "2023-11-07T03:14:31.577",
+                "vulnStatus": "Modified",
+                "descriptions": [{"lang": "en", "value": "In jQuery versions [...]"}],
+                "metrics": {},
+                "configurations": [{
+                    "nodes": [{
+                        "operator": "OR",
+                        "negate": false,
+                        "cpeMatch": [
+                            {
+                                "vulnerable": true,
+                                "criteria": "cpe:2.3:a:jquery:jquery:*:*:*:*:*:*:*:*",
+                                "versionStartIncluding": "1.2",
+                                "versionEndExcluding": "3.5.0",
+                                "matchCriteriaId": "8D7E7C1E-4A3C-4B5C-9F64-6E7F2C2B7C1D"
+                            },
+                            {
+                                "vulnerable": true,
+                                "criteria": "cpe:2.3:a:drupal:drupal:*:*:*:*:*:*:*:*",
+                                "versionEndExcluding": "7.70",
+                                "matchCriteriaId": "1B2C3D4E-5F60-7182-93A4-B5C6D7E8F901"
+                            },
+                            {
+                                "vulnerable": false,
+                                "criteria": "cpe:2.3:a:jquery:jquery:4.0.0:*:*:*:*:*:*:*",
+                                "matchCriteriaId": "0A1B2C3D-4E5F-6071-8293-A4B5C6D7E8F9"
+                            }
+                        ]
+                    }]
+                }]
+            }
+        }"#;
+        let vulnerability: Vulnerability = serde_json::from_str(json).unwrap();
+        let version_ranges = vulnerability
+            .cve
+            .get_version_ranges("a", "jquery", "jquery");
+        assert_eq!(
+            vec![VersionRange {
+                version: Some("*".to_string()),
+                start_including: Some("1.2".to_string()),
+                end_excluding: Some("3.5.0".to_string()),
+                ..Default::default()
+            }],
+            version_ranges
+        );
+
+        let mut cve: CVEModel = vulnerability.into();
+        cve.version_ranges = version_ranges;
+        assert!(cve.version_matches("1.12.4"));
+        assert!(!cve.version_matches("3.5.0"));
+    }
+}
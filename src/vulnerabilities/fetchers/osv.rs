@@ -0,0 +1,352 @@
+use crate::models::{Finding, CVE as CVEModel};
+use crate::vulnerabilities::cache_managers::CacheManager;
+use crate::vulnerabilities::fetchers::VulnFetcher;
+/// This module implements a vulnerability fetcher which downloads
+/// the vulnerabilities from OSV.dev.
+/// https://google.github.io/osv.dev/post-v1-query/
+///
+/// The packages are looked up by ecosystem and name, which covers the
+/// JavaScript libraries (npm) and the PHP applications (Packagist) far
+/// better than the CPE of the NVD. OSV.dev checks the version itself.
+use log::{debug, error, trace};
+use serde::Deserialize;
+use serde_json::json;
+
+/// The endpoint of the OSV.dev API
+const OSV_QUERY_URL: &str = "https://api.osv.dev/v1/query";
+
+/// This structs represents the OSV.dev vulnerabilities fetcher.
+pub struct OSVFetcher {
+    /// An optional cache manager to reduce API calls.
+    cache: Option<Box<dyn CacheManager>>,
+}
+
+impl OSVFetcher {
+    /// Fetches the vulnerabilities for a finding
+    pub fn fetch_vulns(&self, finding: &mut Finding) {
+        trace!("Running OSVFetcher::fetch_vulns()");
+        let (ecosystem, package) = match finding.technology.get_osv_package() {
+            Some(osv_package) => osv_package,
+            None => {
+                debug!("The technology has no package on OSV.dev, it's ignored.");
+                return;
+            }
+        };
+        let version = match &finding.version {
+            Some(version) => version.clone(),
+            None => {
+                debug!("Version is empty, technology is ignored.");
+                return;
+            }
+        };
+
+        let client = reqwest::blocking::Client::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut body = json!({
+                "version": version,
+                "package": { "name": package, "ecosystem": ecosystem },
+            });
+            if let Some(page_token) = &page_token {
+                body["page_token"] = json!(page_token);
+            }
+            let response = match client.post(OSV_QUERY_URL).json(&body).send() {
+                Ok(response) => response,
+                Err(e) => {
+                    error!("Error while communicating with OSV.dev. {:?}", e);
+                    return;
+                }
+            };
+            if !response.status().is_success() {
+                error!("Invalid HTTP response code: {}", response.status());
+                return;
+            }
+            let osv_response: APIResponse = match response.json() {
+                Ok(osv_response) => osv_response,
+                Err(e) => {
+                    error!("Invalid JSON returned by OSV.dev API. {:?}", e);
+                    return;
+                }
+            };
+
+            for vulnerability in osv_response.vulns {
+                if vulnerability.withdrawn.is_some() {
+                    continue;
+                }
+                let cve: CVEModel = vulnerability.into();
+                if !finding.vulnerabilities.contains(&cve) {
+                    finding.vulnerabilities.push(cve);
+                }
+            }
+
+            match osv_response.next_page_token {
+                Some(next_page_token) => page_token = Some(next_page_token),
+                None => break,
+            }
+        }
+
+        if let Some(c) = self.cache.as_ref() {
+            c.store(
+                finding.vulnerabilities.clone(),
+                finding.technology.clone(),
+                &version,
+            );
+        }
+    }
+}
+
+impl VulnFetcher for OSVFetcher {
+    /// Creates a new OSVFetcher.
+    fn new(cache: Option<Box<dyn CacheManager>>) -> Self {
+        Self { cache }
+    }
+
+    /// Complete the findings with vulnerabilities coming from OSV.dev.
+    fn complete_findings(&self, findings: &mut Vec<Finding>) {
+        for finding in findings {
+            let cache_found = match &self.cache {
+                Some(cache) => cache.complete_finding(finding),
+                None => false,
+            };
+            if !cache_found {
+                self.fetch_vulns(finding);
+            }
+        }
+    }
+}
+
+/// Computes the base score of a CVSS 3.0 or 3.1 vector.
+/// Example of vector: CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H
+/// https://www.first.org/cvss/v3.1/specification-document#7-1-Base-Metrics-Equations
+fn get_cvss3_base_score(vector: &str) -> Option<f64> {
+    let metrics: Vec<(&str, &str)> = vector
+        .split('/')
+        .skip(1)
+        .filter_map(|metric| metric.split_once(':'))
+        .collect();
+    let get = |name: &str| {
+        metrics
+            .iter()
+            .find(|(metric, _)| *metric == name)
+            .map(|(_, value)| *value)
+    };
+    let scope_changed = get("S")? == "C";
+    let attack_vector = match get("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => return None,
+    };
+    let attack_complexity = match get("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => return None,
+    };
+    let privileges_required = match (get("PR")?, scope_changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let user_interaction = match get("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => return None,
+    };
+    let impact = |name: &str| match get(name)? {
+        "H" => Some(0.56),
+        "L" => Some(0.22),
+        "N" => Some(0.0),
+        _ => None,
+    };
+    let impact_sub_score: f64 =
+        1.0 - (1.0 - impact("C")?) * (1.0 - impact("I")?) * (1.0 - impact("A")?);
+
+    let impact = if scope_changed {
+        7.52 * (impact_sub_score - 0.029) - 3.25 * (impact_sub_score - 0.02).powi(15)
+    } else {
+        6.42 * impact_sub_score
+    };
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let exploitability =
+        8.22 * attack_vector * attack_complexity * privileges_required * user_interaction;
+    let base_score: f64 = if scope_changed {
+        1.08 * (impact + exploitability)
+    } else {
+        impact + exploitability
+    };
+    // The roundup of CVSS 3.1, avoiding the floating point errors
+    let integer = (base_score.min(10.0) * 100000.0).round() as u64;
+    if integer.is_multiple_of(10000) {
+        Some(integer as f64 / 100000.0)
+    } else {
+        Some((integer / 10000 + 1) as f64 / 10.0)
+    }
+}
+
+/// Represents the main JSON object returned by the OSV.dev query API.
+#[derive(Debug, Deserialize)]
+pub struct APIResponse {
+    /// The vulnerabilities of the version, none if it's not vulnerable.
+    #[serde(default)]
+    pub vulns: Vec<Vulnerability>,
+    /// The token to get the next page, if any.
+    pub next_page_token: Option<String>,
+}
+
+/// Represents a vulnerability returned as part of the APIResponse.
+#[derive(Debug, Deserialize)]
+pub struct Vulnerability {
+    /// The OSV identifier.
+    /// Example: GHSA-gxr4-xjj5-5px2
+    pub id: String,
+    /// The other identifiers of the vulnerability.
+    /// Example: ["CVE-2020-11022"]
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// When the vulnerability has been withdrawn, if it has.
+    pub withdrawn: Option<String>,
+    /// The severities of the vulnerability.
+    #[serde(default)]
+    pub severity: Vec<Severity>,
+}
+
+/// Represents a severity of a vulnerability.
+#[derive(Debug, Deserialize)]
+pub struct Severity {
+    /// The type of severity.
+    /// Example: CVSS_V3
+    #[serde(rename = "type")]
+    pub severity_type: String,
+    /// The CVSS vector.
+    /// Example: CVSS:3.1/AV:N/AC:H/PR:N/UI:R/S:C/C:L/I:L/A:N
+    pub score: String,
+}
+
+/// The CVE identifier is preferred, the OSV one is used for the
+/// vulnerabilities without CVE. Only the CVSS 3 vectors give a base
+/// score.
+impl From<Vulnerability> for CVEModel {
+    fn from(vulnerability: Vulnerability) -> Self {
+        let cve_id = vulnerability
+            .aliases
+            .iter()
+            .find(|alias| alias.starts_with("CVE-"))
+            .cloned()
+            .unwrap_or(vulnerability.id);
+        let cvss3 = vulnerability
+            .severity
+            .iter()
+            .find(|severity| severity.severity_type == "CVSS_V3");
+        let (base_score, cvss_version) = match cvss3.map(|severity| severity.score.as_str()) {
+            Some(vector) => (
+                get_cvss3_base_score(vector).unwrap_or(0.0),
+                // Example: CVSS:3.1/AV:N[...] gives 3.1
+                vector
+                    .strip_prefix("CVSS:")
+                    .and_then(|vector| vector.split('/').next())
+                    .unwrap_or("")
+                    .to_string(),
+            ),
+            None => (0.0, String::new()),
+        };
+
+        Self {
+            cve_id,
+            base_score,
+            cvss_version,
+            version_ranges: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::technology::Technology;
+    use crate::vulnerabilities::cache_managers::files::FileCacheManager;
+
+    #[test]
+    fn cvss3_base_score_is_computed() {
+        let score = |vector| get_cvss3_base_score(vector).unwrap();
+        assert_eq!(9.8, score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"));
+        assert_eq!(6.1, score("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"));
+        assert_eq!(6.9, score("CVSS:3.1/AV:N/AC:H/PR:N/UI:R/S:C/C:H/I:L/A:N"));
+        assert_eq!(5.3, score("CVSS:3.0/AV:N/AC:L/PR:N/UI:N/S:U/C:L/I:N/A:N"));
+        assert_eq!(0.0, score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N"));
+        assert!(get_cvss3_base_score("CVSS:3.1/AV:N/AC:L").is_none());
+    }
+
+    #[test]
+    fn response_is_parsed() {
+        let json = r#"{"vulns":[
+            {"id":"GHSA-gxr4-xjj5-5px2","aliases":["CVE-2020-11022"],"modified":"2024-02-16T08:15:04Z",
+                "severity":[{"type":"CVSS_V3","score":"CVSS:3.1/AV:N/AC:H/PR:N/UI:R/S:C/C:H/I:L/A:N"}]},
+            {"id":"GHSA-2pqj-h3vj-pqgw","modified":"2023-01-10T05:01:48Z",
+                "severity":[{"type":"CVSS_V4","score":"CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:P/VC:N/VI:N/VA:N/SC:L/SI:L/SA:N"}]}
+        ]}"#;
+        let response: APIResponse = serde_json::from_str(json).unwrap();
+        assert!(response.next_page_token.is_none());
+        let mut vulnerabilities = response.vulns.into_iter();
+
+        let cve: CVEModel = vulnerabilities.next().unwrap().into();
+        assert_eq!("CVE-2020-11022", cve.cve_id);
+        assert_eq!(6.9, cve.base_score);
+        assert_eq!("3.1", cve.cvss_version);
+
+        let cve: CVEModel = vulnerabilities.next().unwrap().into();
+        assert_eq!("GHSA-2pqj-h3vj-pqgw", cve.cve_id);
+        assert_eq!(0.0, cve.base_score);
+        assert_eq!("", cve.cvss_version);
+
+        // A version without vulnerability
+        let response: APIResponse = serde_json::from_str("{}").unwrap();
+        assert!(response.vulns.is_empty());
+    }
+
+    #[test]
+    fn technologies_without_package_are_ignored() {
+        assert_eq!(
+            Some(("npm", "jquery")),
+            Technology::JQuery.get_osv_package()
+        );
+        assert_eq!(
+            Some(("Packagist", "drupal/core")),
+            Technology::Drupal.get_osv_package()
+        );
+        let fetcher = OSVFetcher::new(None);
+        let mut finding = Finding::new(Technology::OpenSSH, Some("8.9"), "", "", None);
+        fetcher.fetch_vulns(&mut finding);
+        assert!(finding.vulnerabilities.is_empty());
+    }
+
+    #[test]
+    fn cache_of_the_nvd_is_not_read() {
+        let root_dir = std::env::temp_dir().join(format!("sanca-osv-cves-{}", std::process::id()));
+        let nvd_cve = CVEModel {
+            cve_id: "CVE-2023-38408".to_string(),
+            base_score: 9.8,
+            cvss_version: "3.1".to_string(),
+            version_ranges: Vec::new(),
+        };
+        FileCacheManager::from_dir(root_dir.join("nvd")).store(
+            vec![nvd_cve],
+            Technology::OpenSSH,
+            "8.9",
+        );
+
+        // OpenSSH has no OSV package, nothing is downloaded
+        let fetcher = OSVFetcher::new(Some(Box::new(FileCacheManager::from_dir(
+            root_dir.join("osv"),
+        ))));
+        let mut findings = vec![Finding::new(Technology::OpenSSH, Some("8.9"), "", "", None)];
+        fetcher.complete_findings(&mut findings);
+        std::fs::remove_dir_all(&root_dir).unwrap();
+        assert!(findings[0].vulnerabilities.is_empty());
+    }
+}
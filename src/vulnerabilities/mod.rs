@@ -5,8 +5,12 @@
 //! A fetcher is there to fetch the vulnerabilities from a source (e.g. NVD, Mitre).
 //! A cache manager stores the vulnerabilities fetched by the fetcher
 //! (e.g. in files or database)
+//!
+//! The versions past their end of life are flagged as well, even when
+//! no vulnerability is known.
 
 pub mod cache_managers;
+pub mod eol;
 pub mod fetchers;
 
 use clap::{builder::PossibleValue, ValueEnum};
@@ -16,18 +20,26 @@ use clap::{builder::PossibleValue, ValueEnum};
 pub enum VulnSource {
     /// The vulnerabilities are downloaded from the NVD.
     NVD,
+    /// The vulnerabilities are downloaded from the GitHub Security
+    /// Advisory database. Only the JavaScript libraries are supported.
+    GHSA,
+    /// The vulnerabilities are downloaded from OSV.dev. Only the
+    /// JavaScript libraries and some PHP applications are supported.
+    OSV,
 }
 
 impl ValueEnum for VulnSource {
     /// Lists the variants available for clap
     fn value_variants<'a>() -> &'a [Self] {
-        &[VulnSource::NVD]
+        &[VulnSource::NVD, VulnSource::GHSA, VulnSource::OSV]
     }
 
     /// Map each value to a possible value in clap
     fn to_possible_value(&self) -> Option<PossibleValue> {
         match &self {
             VulnSource::NVD => Some(PossibleValue::new("nvd")),
+            VulnSource::GHSA => Some(PossibleValue::new("ghsa")),
+            VulnSource::OSV => Some(PossibleValue::new("osv")),
         }
     }
 }
@@ -0,0 +1,69 @@
+//! Write the CPE of the [`Finding`](crate::models::Finding)s
+//! It prints one CPE 2.3 per line on STDOUT, or writes them to the output
+//! file, to feed other vulnerability scanners.
+
+use super::{write_output, Writer};
+use crate::{application::Args, models::ScanResult};
+
+/// A writer to print the CPE of the findings.
+pub struct CpeWriter {
+    /// The file where to write the CPE, STDOUT if None
+    output: Option<String>,
+}
+
+impl Writer for CpeWriter {
+    /// Create a new CpeWriter
+    fn new(argv: &Args) -> Self {
+        Self {
+            output: argv.output.clone(),
+        }
+    }
+
+    /// Writes the CPE of the findings
+    fn write(&self, scan_result: ScanResult) -> Result<(), std::io::Error> {
+        write_output(self.output.as_deref(), &self.to_cpe_lines(scan_result))
+    }
+}
+
+impl CpeWriter {
+    /// Builds the lines of the CPE, once each.
+    /// The findings of technologies without CPE are skipped.
+    fn to_cpe_lines(&self, scan_result: ScanResult) -> String {
+        let mut cpes: Vec<String> = Vec::new();
+        for cpe in scan_result.findings.iter().filter_map(|f| f.to_cpe()) {
+            if !cpes.contains(&cpe) {
+                cpes.push(cpe);
+            }
+        }
+        cpes.iter().map(|cpe| format!("{}\n", cpe)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{technology::Technology, Finding, ScanMetadata, SCHEMA_VERSION};
+    use clap::Parser;
+
+    #[test]
+    fn one_cpe_per_line() {
+        let args = Args::parse_from(["sanca", "-s", "http", "-u", "https://www.example.com/"]);
+        let writer = CpeWriter::new(&args);
+        let scan_result = ScanResult {
+            schema_version: SCHEMA_VERSION,
+            metadata: ScanMetadata::new("1.6.1", &["sanca".to_string()]),
+            findings: vec![
+                Finding::new(Technology::JQuery, Some("1.8.3"), "", "", None),
+                Finding::new(Technology::Nginx, None, "", "", None),
+                Finding::new(Technology::Misconfiguration, None, "", "", None),
+                Finding::new(Technology::JQuery, Some("1.8.3"), "", "", None),
+            ],
+            scan_errors: Vec::new(),
+            absent_technologies: None,
+        };
+        assert_eq!(
+            "cpe:2.3:a:jquery:jquery:1.8.3:*:*:*:*:*:*:*\ncpe:2.3:a:nginx:nginx:*:*:*:*:*:*:*:*\n",
+            writer.to_cpe_lines(scan_result)
+        );
+    }
+}
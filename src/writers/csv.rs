@@ -1,10 +1,11 @@
-//! Write the [`Finding`]s as CSV
-//! It presents the findings in a CSV format and prints it on STDOUT.
+//! Write the [`Finding`](crate::models::Finding)s as CSV
+//! It presents the findings in a CSV format and prints it on STDOUT, or
+//! writes it to the output file.
 
-use super::Writer;
+use super::{write_output, Writer};
 use crate::{
     application::Args,
-    models::{reqres::UrlRequest, Finding},
+    models::{reqres::UrlRequest, ScanResult},
 };
 
 /// A writer to print the findings as CSV.
@@ -15,13 +16,15 @@ pub struct CsvWriter {
     port: Option<u16>,
     /// The URL scanned
     url: Option<String>,
+    /// The file where to write the findings, STDOUT if None
+    output: Option<String>,
 }
 
 impl Writer for CsvWriter {
     /// Create a new CsvWriter
     fn new(argv: &Args) -> Self {
         let mut new_ip_hostname = argv.ip_hostname.clone();
-        let mut new_port = argv.port;
+        let mut new_port = argv.get_port();
         let url = argv.url.clone();
 
         // If scan type is HTTP, we have the URL but no ip_hostname nor port.
@@ -37,24 +40,29 @@ impl Writer for CsvWriter {
             ip_hostname: new_ip_hostname,
             port: new_port,
             url: url,
+            output: argv.output.clone(),
         }
     }
 
     /// Writes the findings
-    fn write(&self, findings: Vec<Finding>) {
+    fn write(&self, scan_result: ScanResult) -> Result<(), std::io::Error> {
         let mut csv = "\"Technology\",\"Version\",".to_string();
 
         // TCP or UDP scan (could be set in HTTP scan based on the URL)
-        if self.ip_hostname.is_some() {
+        if self.ip_hostname.is_some() && self.port.is_some() {
             csv.push_str("\"IP / Hostname\",\"Port\",")
         }
         // HTTP scan
         if self.url.is_some() {
             csv.push_str("\"Main URL\",\"URL of finding\",");
         }
+        // Several targets, or several ports
+        if self.port.is_none() && self.url.is_none() {
+            csv.push_str("\"Target\",\"URL of finding\",");
+        }
 
-        csv.push_str("\"Evidence\",\"Evidence text\", \"CVEs\"\n");
-        for finding in findings {
+        csv.push_str("\"Evidence\",\"Evidence text\", \"CVEs\",\"End of life\",\"Detected at\",\"Confidence\"\n");
+        for finding in scan_result.findings {
             let mut version = "unknown";
             if finding.version.is_some() {
                 version = &finding.version.as_ref().unwrap();
@@ -68,11 +76,11 @@ impl Writer for CsvWriter {
                 version.replace("\"", "\"\"")
             );
             // TCP or UDP scan (could be set in HTTP scan based on the URL)
-            if self.ip_hostname.is_some() {
+            if let (Some(ip_hostname), Some(port)) = (&self.ip_hostname, self.port) {
                 csv_line.push_str(&format!(
                     "\"{}\",\"{}\",",
-                    self.ip_hostname.as_ref().unwrap().replace("\"", "\"\""),
-                    self.port.unwrap()
+                    ip_hostname.replace("\"", "\"\""),
+                    port
                 ));
             }
             // HTTP scan
@@ -80,7 +88,27 @@ impl Writer for CsvWriter {
                 csv_line.push_str(&format!(
                     "\"{}\",\"{}\",",
                     self.url.as_ref().unwrap().replace("\"", "\"\""),
-                    finding.url_of_finding.unwrap().replace("\"", "\"\"")
+                    finding
+                        .url_of_finding
+                        .as_ref()
+                        .unwrap()
+                        .replace("\"", "\"\"")
+                ));
+            }
+            // Several targets, or several ports
+            if self.port.is_none() && self.url.is_none() {
+                csv_line.push_str(&format!(
+                    "\"{}\",\"{}\",",
+                    finding
+                        .target
+                        .as_deref()
+                        .unwrap_or_default()
+                        .replace("\"", "\"\""),
+                    finding
+                        .url_of_finding
+                        .as_deref()
+                        .unwrap_or_default()
+                        .replace("\"", "\"\"")
                 ));
             }
 
@@ -90,14 +118,24 @@ impl Writer for CsvWriter {
                 cve_ids.push(vuln.cve_id);
             }
 
+            let eol = match finding.eol {
+                Some(true) => "yes",
+                Some(false) => "no",
+                None => "unknown",
+            };
+
             csv_line.push_str(&format!(
-                "\"{}\",\"{}\",\"{}\"\n",
+                "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
                 finding.evidence.replace("\"", "\"\""),
                 finding.evidence_text.replace("\"", "\"\""),
-                cve_ids.join(", ")
+                cve_ids.join(", "),
+                eol,
+                finding.detected_at,
+                finding.confidence
             ));
             csv.push_str(&csv_line);
         }
-        println!("{}", csv);
+        csv.push('\n');
+        write_output(self.output.as_deref(), &csv)
     }
 }
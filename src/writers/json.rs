@@ -1,10 +1,11 @@
-//! Write the [`Finding`]s as JSON
-//! It presents the findings in a JSON format and prints it on STDOUT.
+//! Write the [`Finding`](crate::models::Finding)s as JSON
+//! It presents the findings in a JSON format and prints it on STDOUT, or
+//! writes it to the output file.
 
-use super::Writer;
+use super::{write_output, Writer};
 use crate::{
     application::Args,
-    models::{reqres::UrlRequest, Finding},
+    models::{reqres::UrlRequest, ScanResult},
 };
 use serde_json::value::Value;
 use serde_json::Map;
@@ -17,13 +18,15 @@ pub struct JsonWriter {
     port: Option<u16>,
     /// The URL scanned
     url: Option<String>,
+    /// The file where to write the findings, STDOUT if None
+    output: Option<String>,
 }
 
 impl Writer for JsonWriter {
     /// Create a new JsonWriter
     fn new(argv: &Args) -> Self {
         let mut new_ip_hostname = argv.ip_hostname.clone();
-        let mut new_port = argv.port;
+        let mut new_port = argv.get_port();
         let url = argv.url.clone();
 
         // If scan type is HTTP, we have the URL but no ip_hostname nor port.
@@ -39,33 +42,92 @@ impl Writer for JsonWriter {
             ip_hostname: new_ip_hostname,
             port: new_port,
             url: url,
+            output: argv.output.clone(),
         }
     }
 
-    /// Writes the findings
-    fn write(&self, findings: Vec<Finding>) {
-        // TODO: Add an object to contain ip_hostname & port
-        // { ip_hostname: "example.org", port: 25, findings: [...]  }
+    /// Writes the findings, in an envelope with the metadata of the scan
+    fn write(&self, scan_result: ScanResult) -> Result<(), std::io::Error> {
+        let result = self.to_json(scan_result);
+        write_output(self.output.as_deref(), &format!("{:#}\n", result))
+    }
+}
 
-        let mut map = Map::new();
-        map.insert(
+impl JsonWriter {
+    /// Builds the JSON of the scan result.
+    /// Example: { "metadata": { "target": { "ip_hostname": "example.org",
+    /// "port": 25, "url": "" }, "started_at": ... }, "findings": [...] }
+    fn to_json(&self, scan_result: ScanResult) -> Value {
+        let mut target = Map::new();
+        target.insert(
             "ip_hostname".to_string(),
             Value::String(self.ip_hostname.clone().unwrap_or(String::new())),
         );
-        map.insert(
+        target.insert(
             "port".to_string(),
             Value::Number(self.port.unwrap_or(0).into()),
         );
-        map.insert(
+        target.insert(
             "url".to_string(),
             Value::String(self.url.clone().unwrap_or(String::new())),
         );
 
-        // serde_json::to_value() should never return Err, since Finding derives
-        // Serialize.
-        let findings_value = serde_json::to_value(findings).unwrap();
-        map.insert("findings".to_string(), findings_value);
-        let result = Value::Object(map);
-        println!("{:#}", result);
+        // serde_json::to_value() should never return Err, since ScanResult
+        // derives Serialize.
+        let mut result = serde_json::to_value(scan_result).unwrap();
+        if let Some(metadata) = result.get_mut("metadata").and_then(Value::as_object_mut) {
+            metadata.insert("target".to_string(), Value::Object(target));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        technology::Technology, Finding, ScanError, ScanErrorKind, ScanMetadata, SCHEMA_VERSION,
+    };
+    use clap::Parser;
+
+    #[test]
+    fn findings_are_wrapped() {
+        let args = Args::parse_from(["sanca", "-s", "tcp", "-i", "example.org", "-p", "25"]);
+        let writer = JsonWriter::new(&args);
+        let scan_result = ScanResult {
+            schema_version: SCHEMA_VERSION,
+            metadata: ScanMetadata::new("1.6.1", &["sanca".to_string()]),
+            findings: vec![Finding::new(
+                Technology::Exim,
+                Some("4.96"),
+                "220 example.org ESMTP Exim 4.96",
+                "Exim 4.96 has been identified",
+                None,
+            )],
+            scan_errors: vec![ScanError::new(
+                "example.org:25",
+                ScanErrorKind::Timeout,
+                "Unable to probe for Exim: timed out",
+            )],
+            absent_technologies: None,
+        };
+        let result = writer.to_json(scan_result);
+        assert_eq!("example.org", result["metadata"]["target"]["ip_hostname"]);
+        assert_eq!(25, result["metadata"]["target"]["port"]);
+        assert_eq!("1.6.1", result["metadata"]["sanca_version"]);
+        assert!(result["metadata"]["started_at"].is_string());
+        assert_eq!("Exim", result["findings"][0]["technology"]);
+        assert!(result["findings"][0]["detected_at"].is_string());
+        assert_eq!("example.org:25", result["scan_errors"][0]["target"]);
+        assert_eq!("timeout", result["scan_errors"][0]["kind"]);
+        assert_eq!(1, result["schema_version"]);
+        // Only given with --report-absent
+        assert!(result.get("absent_technologies").is_none());
+
+        // The output can be parsed back, the target is ignored
+        let parsed: ScanResult = serde_json::from_value(result).unwrap();
+        assert_eq!(SCHEMA_VERSION, parsed.schema_version);
+        assert_eq!(Technology::Exim, parsed.findings[0].technology);
+        assert!(parsed.absent_technologies.is_none());
     }
 }
@@ -1,17 +1,24 @@
 //! Writing Findings
 //!
 //! After checkers finish their work, it's up to a writer to handle the
-//! [`Finding`]s. It provides a common interface, allowing to work on the
+//! [`Finding`](crate::models::Finding)s. It provides a common interface, allowing to work on the
 //! findings without affecting the execution of the application.
 
+pub mod cpe;
 pub mod csv;
 pub mod json;
 pub mod textstdout;
 
-use crate::{application::Args, models::Finding};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+
+use crate::{
+    application::Args,
+    models::{Finding, ScanResult},
+};
 
 /// A trait to have a common interface between writers.
-/// A writer has the responsibility to write the [`Finding`]s in a way,
+/// A writer has the responsibility to write the [`Finding`](crate::models::Finding)s in a way,
 /// be it on standard output, in a file, or to an API.
 pub trait Writer {
     /// Create a new writer
@@ -22,7 +29,74 @@ pub trait Writer {
         Self: Sized;
 
     /// Write the findings
-    /// What is done with the [`Finding`]s is totally up to the writer.
+    /// What is done with the [`Finding`](crate::models::Finding)s is totally up to the writer.
     /// They could be written to stdout, to a file, sent to an API, etc.
-    fn write(&self, findings: Vec<Finding>);
+    /// The structured writers also give the metadata of the scan.
+    fn write(&self, scan_result: ScanResult) -> Result<(), std::io::Error>;
+
+    /// Whether the writer can write the findings as soon as they're found
+    /// (with --stream). The structured formats are written at once.
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    /// Write a finding as soon as it's found, with --stream.
+    /// The findings given to [`write`](Writer::write) at the end include
+    /// the ones already written, it's up to the writer to skip them.
+    fn write_one(&self, _finding: &Finding) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+}
+
+/// Writes the content generated by a writer to its destination.
+/// The content is written to the file at `output` if one is given, and
+/// printed on STDOUT otherwise.
+pub fn write_output(output: Option<&str>, content: &str) -> Result<(), std::io::Error> {
+    match output {
+        Some(path) => {
+            let mut file = File::create(path)?;
+            file.write_all(content.as_bytes())?;
+            file.flush()
+        }
+        None => {
+            print!("{}", content);
+            std::io::stdout().flush()
+        }
+    }
+}
+
+/// Appends the content generated by a writer to its destination, after
+/// the content already written with [`write_output`].
+pub fn append_output(output: Option<&str>, content: &str) -> Result<(), std::io::Error> {
+    match output {
+        Some(path) => {
+            let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+            file.write_all(content.as_bytes())?;
+            file.flush()
+        }
+        None => {
+            print!("{}", content);
+            std::io::stdout().flush()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_output_writes_to_file() {
+        let path = std::env::temp_dir().join(format!("sanca-output-{}.txt", std::process::id()));
+        let path_str = path.to_str().unwrap();
+        assert!(write_output(Some(path_str), "the findings\n").is_ok());
+        assert_eq!("the findings\n", std::fs::read_to_string(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn write_output_reports_errors() {
+        let path = std::env::temp_dir().join("sanca-does-not-exist/output.txt");
+        assert!(write_output(Some(path.to_str().unwrap()), "the findings").is_err());
+    }
 }
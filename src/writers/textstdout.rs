@@ -1,9 +1,30 @@
 //! Write the [`Finding`]s to standard output
 //! It is the default writer, it presents the findings in a text
-//! format and prints it on STDOUT.
+//! format and prints it on STDOUT, or writes it to the output file.
+//!
+//! The findings are sorted by severity, the most critical first. When
+//! printing in a terminal, the findings and their CVEs are colored
+//! according to their CVSS base score. Colors are disabled when the
+//! output is not a terminal or when the NO_COLOR environment variable is
+//! set.
+//!
+//! With several targets, the findings are grouped by target, in the order
+//! of the scan.
+//!
+//! With --stream, the findings are written as soon as they're found, so
+//! they're not sorted.
 
-use super::Writer;
-use crate::{application::Args, models::Finding};
+use std::cell::{Cell, RefCell};
+use std::io::IsTerminal;
+
+use super::{append_output, write_output, Writer};
+use crate::{
+    application::Args,
+    models::{Confidence, Finding, ScanResult},
+};
+
+/// The ANSI escape code to reset the color
+const COLOR_RESET: &str = "\x1b[0m";
 
 /// A writer to print the findings in the terminal.
 pub struct TextStdoutWriter {
@@ -13,57 +34,391 @@ pub struct TextStdoutWriter {
     port: Option<u16>,
     /// The URL scanned
     url: Option<String>,
+    /// The file where to write the findings, STDOUT if None
+    output: Option<String>,
+    /// Whether to use ANSI colors
+    colored: bool,
+    /// Whether the findings are written as soon as they're found
+    streaming: bool,
+    /// Whether something has been written, when streaming
+    output_started: Cell<bool>,
+    /// The title of the last streamed finding
+    last_title: RefCell<Option<String>>,
 }
 
-impl Writer for TextStdoutWriter {
-    /// Create a new TextStdoutWriter
-    fn new(argv: &Args) -> Self {
-        Self {
-            ip_hostname: argv.ip_hostname.clone(),
-            port: argv.port,
-            url: argv.url.clone(),
+impl TextStdoutWriter {
+    /// Returns the highest CVSS base score of the CVEs of the finding,
+    /// or 0.0 if it has no CVEs.
+    fn get_max_base_score(finding: &Finding) -> f64 {
+        finding
+            .vulnerabilities
+            .iter()
+            .map(|vuln| vuln.base_score)
+            .fold(0.0, f64::max)
+    }
+
+    /// Returns the ANSI escape code to use for a given CVSS base score.
+    /// Critical is bold red, high is red, medium is yellow and low is cyan.
+    fn get_color(base_score: f64) -> Option<&'static str> {
+        if base_score >= 9.0 {
+            Some("\x1b[1;31m")
+        } else if base_score >= 7.0 {
+            Some("\x1b[31m")
+        } else if base_score >= 4.0 {
+            Some("\x1b[33m")
+        } else if base_score > 0.0 {
+            Some("\x1b[36m")
+        } else {
+            None
         }
     }
 
-    /// Prints the findings on STDOUT
-    fn write(&self, findings: Vec<Finding>) {
-        let title;
+    /// Colors the text according to the CVSS base score, if colors are
+    /// enabled.
+    fn colorize(&self, text: &str, base_score: f64) -> String {
+        match Self::get_color(base_score) {
+            Some(color) if self.colored => format!("{}{}{}", color, text, COLOR_RESET),
+            _ => text.to_string(),
+        }
+    }
+
+    /// Returns the title of the output: the URL, or the IP or hostname
+    /// and the port. None when several targets are scanned.
+    fn get_title(&self) -> Option<String> {
         if self.url.is_some() {
-            title = self.url.as_ref().unwrap().to_string();
+            Some(self.url.as_ref().unwrap().to_string())
         } else if self.ip_hostname.is_some() && self.port.is_some() {
-            title = format!(
+            Some(format!(
                 "{}:{}",
                 self.ip_hostname.as_ref().unwrap(),
                 self.port.unwrap()
-            );
+            ))
         } else {
-            panic!("The text writer didn't receive valid parameters");
+            None
         }
+    }
+
+    /// Returns the title of the finding: the one of the output, or its
+    /// target when several targets are scanned.
+    fn get_finding_title(&self, finding: &Finding) -> String {
+        self.get_title()
+            .or_else(|| finding.target.clone())
+            .unwrap_or_else(|| "Unknown target".to_string())
+    }
+
+    /// Formats the title of a group of findings.
+    fn format_title(title: &str) -> String {
+        format!("----------{}----------\n\n", title)
+    }
 
-        println!("----------{}----------\n", title);
-        for finding in findings {
-            let mut version = "unknown";
-            if finding.version.is_some() {
-                version = &finding.version.as_ref().unwrap();
+    /// Writes the streamed text, after the text already written.
+    fn write_streamed(&self, text: &str) -> Result<(), std::io::Error> {
+        if self.output_started.replace(true) {
+            append_output(self.output.as_deref(), text)
+        } else {
+            write_output(self.output.as_deref(), text)
+        }
+    }
+
+    /// Formats a finding, with its CVEs, the most critical first.
+    fn format_finding(&self, mut finding: Finding) -> String {
+        let mut version = "unknown";
+        if finding.version.is_some() {
+            version = &finding.version.as_ref().unwrap();
+        }
+        let max_base_score = Self::get_max_base_score(&finding);
+
+        // Show the most critical CVEs first
+        finding
+            .vulnerabilities
+            .sort_by(|a, b| b.base_score.total_cmp(&a.base_score));
+        let mut cve_ids = Vec::new();
+        for vuln in &finding.vulnerabilities {
+            cve_ids.push(self.colorize(&vuln.cve_id, vuln.base_score));
+        }
+
+        let cve_str = if cve_ids.len() > 0 {
+            format!(" | CVE: {}", cve_ids.join(", "))
+        } else {
+            String::new()
+        };
+
+        // Only the findings less or more reliable than usual are flagged
+        let confidence_str = if finding.confidence != Confidence::High {
+            format!(" | Confidence: {}", finding.confidence)
+        } else {
+            String::new()
+        };
+
+        let eol_str = if finding.eol == Some(true) {
+            " | End of life"
+        } else {
+            ""
+        };
+
+        format!(
+            "{} {}{}{}{}\n\n",
+            self.colorize(
+                &format!("[{}/{}]", finding.technology.to_string(), version),
+                max_base_score
+            ),
+            finding.evidence_text,
+            confidence_str,
+            eol_str,
+            cve_str
+        )
+    }
+
+    /// Sorts the findings by severity, the most critical first.
+    /// The sort is stable, so findings with the same severity keep the
+    /// order given by the checkers.
+    fn sort_by_severity(findings: &mut [Finding]) {
+        findings
+            .sort_by(|a, b| Self::get_max_base_score(b).total_cmp(&Self::get_max_base_score(a)));
+    }
+}
+
+impl Writer for TextStdoutWriter {
+    /// Create a new TextStdoutWriter
+    fn new(argv: &Args) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        Self {
+            ip_hostname: argv.ip_hostname.clone(),
+            port: argv.get_port(),
+            url: argv.url.clone(),
+            output: argv.output.clone(),
+            colored: argv.output.is_none() && !no_color && std::io::stdout().is_terminal(),
+            streaming: argv.stream,
+            output_started: Cell::new(false),
+            last_title: RefCell::new(None),
+        }
+    }
+
+    /// Prints the findings on STDOUT, or writes them to the output file
+    /// The metadata of the scan are not shown, the failed requests are
+    /// listed after the findings.
+    fn write(&self, scan_result: ScanResult) -> Result<(), std::io::Error> {
+        let mut text = String::new();
+        if self.streaming {
+            // The findings have already been written
+            if self.last_title.borrow().is_none() {
+                if let Some(title) = self.get_title() {
+                    text.push_str(&Self::format_title(&title));
+                }
+            }
+        } else {
+            let mut findings = scan_result.findings;
+            // The targets keep the order of the scan
+            let mut titles: Vec<String> = Vec::new();
+            for finding in &findings {
+                let title = self.get_finding_title(finding);
+                if !titles.contains(&title) {
+                    titles.push(title);
+                }
             }
-            let mut cve_ids = Vec::new();
-            for vuln in finding.vulnerabilities {
-                cve_ids.push(vuln.cve_id);
+            Self::sort_by_severity(&mut findings);
+            findings.sort_by_key(|finding| {
+                let title = self.get_finding_title(finding);
+                titles.iter().position(|t| *t == title)
+            });
+            if let Some(title) = self.get_title() {
+                text.push_str(&Self::format_title(&title));
             }
+            let mut last_title = self.get_title();
+            for finding in findings {
+                let title = self.get_finding_title(&finding);
+                if last_title.as_ref() != Some(&title) {
+                    text.push_str(&Self::format_title(&title));
+                    last_title = Some(title);
+                }
+                text.push_str(&self.format_finding(finding));
+            }
+        }
+        // The findings can be incomplete if some requests failed
+        for scan_error in &scan_result.scan_errors {
+            text.push_str(&format!(
+                "[Error] {}: {}\n\n",
+                scan_error.target, scan_error.message
+            ));
+        }
+        if self.streaming {
+            self.write_streamed(&text)
+        } else {
+            write_output(self.output.as_deref(), &text)
+        }
+    }
 
-            let cve_str = if cve_ids.len() > 0 {
-                format!(" | CVE: {}", cve_ids.join(", "))
-            } else {
-                String::new()
-            };
-
-            println!(
-                "[{}/{}] {}{}\n",
-                finding.technology.to_string(),
-                version,
-                finding.evidence_text,
-                cve_str
-            );
+    /// The findings can be written one by one
+    fn supports_streaming(&self) -> bool {
+        self.streaming
+    }
+
+    /// Writes the finding, after the title if it's the first one of its
+    /// target
+    fn write_one(&self, finding: &Finding) -> Result<(), std::io::Error> {
+        let title = self.get_finding_title(finding);
+        if self.last_title.borrow().as_ref() != Some(&title) {
+            self.write_streamed(&Self::format_title(&title))?;
+            *self.last_title.borrow_mut() = Some(title);
+        }
+        // The CVEs are sorted on a copy
+        let mut copy = Finding::new(
+            finding.technology.clone(),
+            finding.version.as_deref(),
+            &finding.evidence,
+            &finding.evidence_text,
+            finding.url_of_finding.as_deref(),
+        )
+        .with_confidence(finding.confidence);
+        copy.vulnerabilities = finding.vulnerabilities.clone();
+        copy.eol = finding.eol;
+        self.write_streamed(&self.format_finding(copy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        technology::Technology, ScanError, ScanErrorKind, ScanMetadata, CVE, SCHEMA_VERSION,
+    };
+
+    fn finding_with_scores(technology: Technology, scores: &[f64]) -> Finding {
+        let mut finding =
+            Finding::new(technology, Some("1.0.0"), "evidence", "evidence text", None);
+        for (i, score) in scores.iter().enumerate() {
+            finding.vulnerabilities.push(CVE {
+                cve_id: format!("CVE-2024-000{}", i),
+                base_score: *score,
+                cvss_version: "3.1".to_string(),
+                version_ranges: Vec::new(),
+            });
+        }
+        finding
+    }
+
+    #[test]
+    fn sorts_findings_by_severity() {
+        let mut findings = vec![
+            finding_with_scores(Technology::JQuery, &[]),
+            finding_with_scores(Technology::Httpd, &[5.3, 7.5]),
+            finding_with_scores(Technology::PHP, &[9.8]),
+            finding_with_scores(Technology::Nginx, &[]),
+        ];
+        TextStdoutWriter::sort_by_severity(&mut findings);
+        let technologies: Vec<Technology> = findings.iter().map(|f| f.technology.clone()).collect();
+        assert_eq!(
+            vec![
+                Technology::PHP,
+                Technology::Httpd,
+                Technology::JQuery,
+                Technology::Nginx
+            ],
+            technologies
+        );
+    }
+
+    #[test]
+    fn colors_by_base_score() {
+        assert_eq!(Some("\x1b[1;31m"), TextStdoutWriter::get_color(9.8));
+        assert_eq!(Some("\x1b[31m"), TextStdoutWriter::get_color(7.0));
+        assert_eq!(Some("\x1b[33m"), TextStdoutWriter::get_color(5.3));
+        assert_eq!(Some("\x1b[36m"), TextStdoutWriter::get_color(2.1));
+        assert_eq!(None, TextStdoutWriter::get_color(0.0));
+    }
+
+    #[test]
+    fn streamed_findings_are_written_once() {
+        let path = std::env::temp_dir()
+            .join(format!("sanca-stream-{}.txt", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let writer = TextStdoutWriter {
+            ip_hostname: None,
+            port: None,
+            url: Some("https://www.example.com/".to_string()),
+            output: Some(path.clone()),
+            colored: false,
+            streaming: true,
+            output_started: Cell::new(false),
+            last_title: RefCell::new(None),
+        };
+        assert!(writer.supports_streaming());
+        let findings = vec![
+            finding_with_scores(Technology::JQuery, &[]).with_confidence(Confidence::Medium),
+            finding_with_scores(Technology::PHP, &[9.8]),
+        ];
+        for finding in &findings {
+            writer.write_one(finding).unwrap();
         }
+        let scan_result = ScanResult {
+            schema_version: SCHEMA_VERSION,
+            metadata: ScanMetadata::new("1.6.1", &["sanca".to_string()]),
+            findings,
+            scan_errors: vec![ScanError::new(
+                "https://www.example.com/",
+                ScanErrorKind::Dns,
+                "dns error",
+            )],
+            absent_technologies: None,
+        };
+        writer.write(scan_result).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(text.starts_with("----------https://www.example.com/----------\n\n[jQuery/1.0.0]"));
+        assert_eq!(1, text.matches("----------").count() / 2);
+        assert_eq!(1, text.matches("[PHP/1.0.0]").count());
+        assert!(text.contains("[jQuery/1.0.0] evidence text | Confidence: medium\n"));
+        assert!(text.contains("[PHP/1.0.0] evidence text | CVE:"));
+        assert!(text.trim_end().ends_with("dns error"));
+    }
+
+    #[test]
+    fn findings_are_grouped_by_target() {
+        let path = std::env::temp_dir()
+            .join(format!("sanca-targets-{}.txt", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let writer = TextStdoutWriter {
+            ip_hostname: None,
+            port: None,
+            url: None,
+            output: Some(path.clone()),
+            colored: false,
+            streaming: false,
+            output_started: Cell::new(false),
+            last_title: RefCell::new(None),
+        };
+        let mut findings = vec![
+            finding_with_scores(Technology::OpenSSH, &[]),
+            finding_with_scores(Technology::Exim, &[]),
+            finding_with_scores(Technology::PHP, &[9.8]),
+        ];
+        findings[0].target = Some("203.0.113.10:22".to_string());
+        findings[1].target = Some("mail.example.com:25".to_string());
+        findings[2].target = Some("203.0.113.10:22".to_string());
+        let scan_result = ScanResult {
+            schema_version: SCHEMA_VERSION,
+            metadata: ScanMetadata::new("1.6.1", &["sanca".to_string()]),
+            findings,
+            scan_errors: Vec::new(),
+            absent_technologies: None,
+        };
+        writer.write(scan_result).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = text.lines().filter(|line| !line.is_empty()).collect();
+        assert_eq!(
+            vec![
+                "----------203.0.113.10:22----------",
+                "[PHP/1.0.0] evidence text | CVE: CVE-2024-0000",
+                "[OpenSSH/1.0.0] evidence text",
+                "----------mail.example.com:25----------",
+                "[Exim/1.0.0] evidence text",
+            ],
+            lines
+        );
     }
 }
@@ -0,0 +1,76 @@
+//! Uses sanca as a library, without the command line and the writers
+use std::io::Write;
+use std::net::TcpListener;
+use std::thread;
+
+use clap::Parser;
+use sanca_software::application::{Application, Args};
+use sanca_software::models::technology::Technology;
+
+/// Starts a server sending the banner to all its clients.
+/// The banner is read once for each probe.
+fn start_server(banner: &'static [u8]) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let _ = stream.write_all(banner);
+        }
+    });
+    port
+}
+
+#[test]
+fn scan_returns_the_findings() {
+    let port = start_server(b"SSH-2.0-OpenSSH_9.2p1 Debian-2+deb12u2\r\n");
+
+    let args = Args::parse_from([
+        "sanca",
+        "-s",
+        "tcp",
+        "-i",
+        "127.0.0.1",
+        "-p",
+        &port.to_string(),
+        "-t",
+        "openssh",
+    ]);
+    let mut application = Application::new();
+    application.set_args(args);
+    let findings = application.scan();
+
+    assert_eq!(1, findings.len());
+    assert_eq!(Technology::OpenSSH, findings[0].technology);
+    assert_eq!(Some("9.2p1".to_string()), findings[0].version);
+    assert_eq!(None, findings[0].target);
+}
+
+#[test]
+fn ports_are_scanned_concurrently() {
+    let port1 = start_server(b"SSH-2.0-OpenSSH_9.2p1 Debian-2+deb12u2\r\n");
+    let port2 = start_server(b"SSH-2.0-OpenSSH_8.4p1 Debian-5+deb11u3\r\n");
+
+    let args = Args::parse_from([
+        "sanca",
+        "-s",
+        "tcp",
+        "-i",
+        "127.0.0.1",
+        "-p",
+        &format!("{},{}", port1, port2),
+        "--concurrency",
+        "2",
+        "-t",
+        "openssh",
+    ]);
+    let mut application = Application::new();
+    application.set_args(args);
+    let findings = application.scan();
+
+    // The findings keep the order of the ports, and their port
+    assert_eq!(2, findings.len());
+    assert_eq!(Some("9.2p1".to_string()), findings[0].version);
+    assert_eq!(Some(format!("127.0.0.1:{}", port1)), findings[0].target);
+    assert_eq!(Some("8.4p1".to_string()), findings[1].version);
+    assert_eq!(Some(format!("127.0.0.1:{}", port2)), findings[1].target);
+}